@@ -0,0 +1,112 @@
+//! Terminal output throughput: JSON `Output` messages vs binary frames.
+//!
+//! Replays the PTY output chunks of a recorded Claude Code session through
+//! both encodings, server-side encode plus client-side decode, the way
+//! `handle_multiplexed_ws` and its clients handle them.
+//!
+//! Run with `cargo bench -p crab_city --bench ws_frames`.
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+
+use crab_city::ws::{FrameKind, ServerMessage, TerminalFrame};
+use virtual_terminal::{VtEvent, VtRecording};
+
+const INSTANCE_ID: &str = "4f9c2a7e-8d1b-4c3e-9a6f-0b2d5e7c1a38";
+
+fn recorded_chunks() -> Vec<Vec<u8>> {
+    let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../virtual_terminal/fixtures/resize_dedup.vtr");
+    let recording = VtRecording::from_file(&fixture).expect("fixture recording");
+    recording
+        .events
+        .into_iter()
+        .filter_map(|event| match event {
+            VtEvent::Output { data, .. } => Some(data),
+            _ => None,
+        })
+        .collect()
+}
+
+fn json_round_trip(chunk: &[u8]) -> usize {
+    let msg = ServerMessage::Output {
+        instance_id: INSTANCE_ID.to_string(),
+        data: String::from_utf8_lossy(chunk).into_owned(),
+        cursor: Some((0, 0)),
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    match serde_json::from_str::<ServerMessage>(&json).unwrap() {
+        ServerMessage::Output { data, .. } => data.len(),
+        _ => unreachable!(),
+    }
+}
+
+fn frame_round_trip(chunk: &[u8]) -> usize {
+    let frame = TerminalFrame {
+        kind: FrameKind::Output,
+        instance_id: INSTANCE_ID.to_string(),
+        seq: 0,
+        data: chunk.to_vec(),
+    };
+    let encoded = frame.encode();
+    TerminalFrame::decode(&encoded).unwrap().data.len()
+}
+
+fn bench_output(c: &mut Criterion) {
+    let chunks = recorded_chunks();
+    let total: usize = chunks.iter().map(Vec::len).sum();
+
+    let mut group = c.benchmark_group("terminal_output");
+    group.throughput(Throughput::Bytes(total as u64));
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                black_box(json_round_trip(black_box(chunk)));
+            }
+        })
+    });
+    group.bench_function("binary_frame", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                black_box(frame_round_trip(black_box(chunk)));
+            }
+        })
+    });
+    group.finish();
+
+    // Wire size matters as much as CPU on slow links
+    let json_bytes: usize = chunks
+        .iter()
+        .map(|chunk| {
+            serde_json::to_string(&ServerMessage::Output {
+                instance_id: INSTANCE_ID.to_string(),
+                data: String::from_utf8_lossy(chunk).into_owned(),
+                cursor: Some((0, 0)),
+            })
+            .unwrap()
+            .len()
+        })
+        .sum();
+    let frame_bytes: usize = chunks
+        .iter()
+        .map(|chunk| {
+            TerminalFrame {
+                kind: FrameKind::Output,
+                instance_id: INSTANCE_ID.to_string(),
+                seq: 0,
+                data: chunk.clone(),
+            }
+            .encode()
+            .len()
+        })
+        .sum();
+    println!(
+        "wire bytes for {} chunks ({} PTY bytes): json={} binary_frame={}",
+        chunks.len(),
+        total,
+        json_bytes,
+        frame_bytes
+    );
+}
+
+criterion_group!(benches, bench_output);
+criterion_main!(benches);
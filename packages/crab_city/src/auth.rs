@@ -85,6 +85,14 @@ pub struct AuthUser {
     pub csrf_token: String,
 }
 
+impl AuthUser {
+    /// Whether this is the synthetic loopback identity, which has no row in
+    /// `users` and so can't be stored where a user id is referenced.
+    pub fn is_loopback(&self) -> bool {
+        self.user_id == LOOPBACK_USER_ID
+    }
+}
+
 /// Optional auth user (for endpoints that work with or without auth).
 #[derive(Debug, Clone)]
 pub struct MaybeAuthUser(pub Option<AuthUser>);
@@ -103,7 +111,19 @@ pub struct AuthState {
 // Cookie Helpers
 // =============================================================================
 
+/// The session token from an `Authorization: Bearer` header (how `crab` talks
+/// to a remote server) or else the `crab_session` cookie.
 fn extract_session_token(headers: &HeaderMap) -> Option<String> {
+    extract_bearer_token(headers).or_else(|| extract_session_cookie(headers))
+}
+
+fn extract_bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+fn extract_session_cookie(headers: &HeaderMap) -> Option<String> {
     let cookie_header = headers.get(header::COOKIE)?.to_str().ok()?;
     for cookie in cookie_header.split(';') {
         let cookie = cookie.trim();
@@ -137,13 +157,15 @@ fn make_clear_cookie(auth_config: &AuthConfig) -> String {
 // Auth Middleware
 // =============================================================================
 
+const LOOPBACK_USER_ID: &str = "loopback";
+
 /// Synthetic admin identity for trusted loopback connections without a real session.
 /// Used when auth is disabled (local-only mode) or when a loopback request has no
 /// valid session cookie. This lets admin endpoints (invites, config) work for
 /// local CLI/TUI/browser clients without requiring login.
-fn synthetic_loopback_admin() -> AuthUser {
+pub(crate) fn synthetic_loopback_admin() -> AuthUser {
     AuthUser {
-        user_id: "loopback".to_string(),
+        user_id: LOOPBACK_USER_ID.to_string(),
         display_name: "Local Admin".to_string(),
         is_admin: true,
         session_token: String::new(),
@@ -257,8 +279,10 @@ pub async fn auth_middleware(
             .and_then(|v| v.to_str().ok())
             .map(|v| v.eq_ignore_ascii_case("websocket"))
             .unwrap_or(false);
+        // Nor do bearer tokens: a browser never attaches them on its own
+        let is_bearer = extract_bearer_token(request.headers()).is_some();
 
-        if !is_ws {
+        if !is_ws && !is_bearer {
             let csrf_header = request
                 .headers()
                 .get("X-CSRF-Token")
@@ -997,6 +1021,28 @@ mod tests {
         // Test with no cookie header
         let headers = HeaderMap::new();
         assert!(extract_session_token(&headers).is_none());
+
+        // A bearer token wins over the cookie
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("crab_session=abc123"),
+        );
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer tok456"),
+        );
+        assert_eq!(extract_session_token(&headers), Some("tok456".to_string()));
+
+        // Other schemes and empty tokens are ignored
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic Zm9v"),
+        );
+        assert!(extract_session_token(&headers).is_none());
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer "));
+        assert!(extract_session_token(&headers).is_none());
     }
 
     // =========================================================================
@@ -2111,6 +2157,39 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn middleware_bearer_token_passes_without_csrf() {
+        let state = test_auth_state(true).await;
+        let (session_token, _csrf) = register_and_get_tokens(&state).await;
+        let app = middleware_router(state);
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/protected")
+                    .header("authorization", format!("Bearer {}", session_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/protected")
+                    .header("authorization", "Bearer invalid_token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[tokio::test]
     async fn middleware_post_with_wrong_csrf_returns_403() {
         let state = test_auth_state(true).await;
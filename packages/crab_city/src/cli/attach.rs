@@ -1,4 +1,6 @@
 use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use futures::{SinkExt, StreamExt};
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{
@@ -8,15 +10,21 @@ use ratatui::crossterm::event::{
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::Widget;
+use std::io::Write;
 use std::time::{Duration, Instant};
 use tokio_tungstenite::tungstenite;
 
+use crate::cli::copy_mode::{CopyAction, CopyMode, CopyModeWidget};
 use crate::cli::daemon::{DaemonError, DaemonInfo};
+use crate::cli::handshake::{WsStream, handshake};
+use crate::cli::keys::{Action, KeyBindings, KeySpec};
 use crate::cli::terminal::get_terminal_size;
 use crab_city::config::{MAX_SCROLLBACK_LINES, MIN_SCROLLBACK_LINES};
 use crab_city::inference::ClaudeState;
-use crab_city::ws::{ClientMessage, ServerMessage};
-use virtual_terminal::walk_row;
+use crab_city::ws::{
+    Capability, ClientMessage, FrameKind, OutputMode, ServerMessage, TerminalFrame,
+};
+use virtual_terminal::{VtCallbacks, render_hyperlink_overlay, walk_row};
 
 /// Default scrollback lines if config fetch fails.
 const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
@@ -24,29 +32,27 @@ const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
 /// Lines scrolled per mouse wheel tick.
 const MOUSE_SCROLL_LINES: usize = 3;
 
-/// Detect detach key (Ctrl-]).
-///
-/// Crossterm 0.28 maps bytes 0x1C-0x1F to Ctrl+'4'-'7' (not the real
-/// characters \, ], ^, _). Byte 0x1D (Ctrl-]) therefore arrives as
-/// `Char('5') + CONTROL`.  Match both representations for safety.
-fn is_detach_key(key: &KeyEvent) -> bool {
-    key.modifiers.contains(KeyModifiers::CONTROL)
-        && matches!(key.code, KeyCode::Char(']') | KeyCode::Char('5'))
+/// Local screen model. The callbacks track OSC 8 hyperlinks so they can be
+/// re-emitted over the ratatui frame.
+pub(super) type AttachParser = vt100::Parser<VtCallbacks>;
+
+pub(super) fn new_parser(rows: u16, cols: u16, scrollback: usize) -> AttachParser {
+    vt100::Parser::new_with_callbacks(rows, cols, scrollback, VtCallbacks::default())
 }
 
 /// Build status bar text based on current Claude state and elapsed time.
-fn status_bar_text(state: &ClaudeState, started_at: Instant) -> String {
+fn status_bar_text(state: &ClaudeState, started_at: Instant, detach: KeySpec) -> String {
     match state {
         ClaudeState::Initializing => {
             let elapsed = started_at.elapsed().as_secs();
             let msg = if elapsed < 10 {
-                "Waiting for first byte..."
+                "Waiting for first byte...".to_string()
             } else if elapsed < 30 {
-                "Process is starting \u{2014} no output yet"
+                "Process is starting \u{2014} no output yet".to_string()
             } else {
-                "No output received. Ctrl-] to switch instances"
+                format!("No output received. {} to switch instances", detach)
             };
-            format!(" INIT \u{2502} {} \u{2502} Ctrl-] switch ", msg)
+            format!(" INIT \u{2502} {} \u{2502} {} switch ", msg, detach)
         }
         ClaudeState::Starting => {
             let elapsed = started_at.elapsed().as_secs();
@@ -57,19 +63,21 @@ fn status_bar_text(state: &ClaudeState, started_at: Instant) -> String {
             } else {
                 "Startup is slower than usual \u{2014} network latency or API load"
             };
-            format!(" STARTING \u{2502} {} \u{2502} Ctrl-] switch ", msg)
+            format!(" STARTING \u{2502} {} \u{2502} {} switch ", msg, detach)
         }
         ClaudeState::Idle | ClaudeState::WaitingForInput { .. } => {
-            " READY \u{2502} Claude is idle \u{2502} Ctrl-] detach ".to_string()
+            format!(" READY \u{2502} Claude is idle \u{2502} {} detach ", detach)
+        }
+        ClaudeState::Thinking => {
+            format!(" ACTIVE \u{2502} Thinking... \u{2502} {} detach ", detach)
         }
-        ClaudeState::Thinking => " ACTIVE \u{2502} Thinking... \u{2502} Ctrl-] detach ".to_string(),
         ClaudeState::Responding => {
-            " ACTIVE \u{2502} Responding... \u{2502} Ctrl-] detach ".to_string()
+            format!(" ACTIVE \u{2502} Responding... \u{2502} {} detach ", detach)
         }
         ClaudeState::ToolExecuting { tool } => {
             format!(
-                " ACTIVE \u{2502} Running {}... \u{2502} Ctrl-] detach ",
-                tool
+                " ACTIVE \u{2502} Running {}... \u{2502} {} detach ",
+                tool, detach
             )
         }
     }
@@ -85,11 +93,39 @@ fn vt100_color_to_ratatui(color: vt100::Color) -> Color {
     }
 }
 
+/// Text modifiers for a cell. ratatui has a single underline modifier, so
+/// every underline style maps to it.
+fn cell_modifiers(cell: &virtual_terminal::CellInfo<'_>) -> Modifier {
+    let mut modifiers = Modifier::empty();
+    if cell.bold {
+        modifiers |= Modifier::BOLD;
+    }
+    if cell.dim {
+        modifiers |= Modifier::DIM;
+    }
+    if cell.italic {
+        modifiers |= Modifier::ITALIC;
+    }
+    if cell.underline != vt100::UnderlineStyle::None {
+        modifiers |= Modifier::UNDERLINED;
+    }
+    if cell.inverse {
+        modifiers |= Modifier::REVERSED;
+    }
+    if cell.strikethrough {
+        modifiers |= Modifier::CROSSED_OUT;
+    }
+    if cell.blink {
+        modifiers |= Modifier::SLOW_BLINK;
+    }
+    modifiers
+}
+
 // ── PtyWidget ───────────────────────────────────────────────────────
 
 /// Renders a `vt100::Screen` buffer into a ratatui `Buffer`.
-struct PtyWidget<'a> {
-    screen: &'a vt100::Screen,
+pub(super) struct PtyWidget<'a> {
+    pub(super) screen: &'a vt100::Screen,
 }
 
 impl Widget for PtyWidget<'_> {
@@ -100,20 +136,10 @@ impl Widget for PtyWidget<'_> {
                 let mut style = Style::default()
                     .fg(vt100_color_to_ratatui(cell.fg))
                     .bg(vt100_color_to_ratatui(cell.bg));
-                let mut modifiers = Modifier::empty();
-                if cell.bold {
-                    modifiers |= Modifier::BOLD;
-                }
-                if cell.italic {
-                    modifiers |= Modifier::ITALIC;
+                if cell.underline_color != vt100::Color::Default {
+                    style = style.underline_color(vt100_color_to_ratatui(cell.underline_color));
                 }
-                if cell.underline {
-                    modifiers |= Modifier::UNDERLINED;
-                }
-                if cell.inverse {
-                    modifiers |= Modifier::REVERSED;
-                }
-                style = style.add_modifier(modifiers);
+                style = style.add_modifier(cell_modifiers(&cell));
 
                 let x = area.x + cell.col;
                 let y = area.y + row;
@@ -128,8 +154,8 @@ impl Widget for PtyWidget<'_> {
 // ── StatusBarWidget ─────────────────────────────────────────────────
 
 /// Full-width reversed+bold status bar.
-struct StatusBarWidget<'a> {
-    text: &'a str,
+pub(super) struct StatusBarWidget<'a> {
+    pub(super) text: &'a str,
 }
 
 impl Widget for StatusBarWidget<'_> {
@@ -183,7 +209,7 @@ impl Widget for OverlayBadge<'_> {
 // ── key_to_bytes ────────────────────────────────────────────────────
 
 /// Convert a crossterm `KeyEvent` to the byte sequence a PTY expects.
-fn key_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
+pub(super) fn key_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
     let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
     match key.code {
         KeyCode::Char(c) if ctrl => {
@@ -235,12 +261,36 @@ fn key_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
 
 // ── Events from the WS reader task ─────────────────────────────────
 
-enum AttachEvent {
-    Output(String),
+pub(super) enum AttachEvent {
+    Output(Vec<u8>),
     StateChange(ClaudeState),
+    /// The server acknowledged binary frames; input may now be sent as frames
+    BinaryFramesEnabled,
+    /// OSC 52 write: selection and base64 data, passed through to the local terminal
+    Clipboard(String, String),
     Closed,
 }
 
+/// How an attach session asks for output and takes part in the terminal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AttachOptions {
+    /// Coalesced screen diffs instead of raw output (see
+    /// `crab_city::ws::OutputMode`)
+    pub screen_diff: bool,
+    /// Frame rate cap for screen diffs; `None` keeps the server default
+    pub max_fps: Option<u32>,
+    /// Watch read-only: the local window doesn't size the PTY and
+    /// keystrokes (other than detach) are not sent
+    pub spectate: bool,
+    /// Detach, scroll and copy mode keys
+    pub keys: KeyBindings,
+}
+
+/// The `client_type` this session registers its viewport as.
+fn viewport_client_type(spectate: bool) -> Option<String> {
+    Some(if spectate { "spectator" } else { "terminal" }.to_string())
+}
+
 /// What happened when an attach session ended.
 pub enum AttachOutcome {
     /// User pressed Ctrl-] to detach; instance is still running.
@@ -250,28 +300,32 @@ pub enum AttachOutcome {
 }
 
 /// Attach to an instance, forwarding terminal I/O over WebSocket.
-pub async fn attach(daemon: &DaemonInfo, instance_id: &str) -> Result<AttachOutcome, DaemonError> {
+pub async fn attach(
+    daemon: &DaemonInfo,
+    instance_id: &str,
+    options: AttachOptions,
+) -> Result<AttachOutcome, DaemonError> {
     // 1. Fetch scrollback_lines from server config (best-effort, fall back to default)
     let scrollback_lines = fetch_scrollback_lines(daemon).await;
 
     // 2. Connect to the multiplexed WebSocket endpoint.
-    let ws_url = daemon.mux_ws_url();
-    let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url)
+    let ws_stream = daemon
+        .connect_mux()
         .await
         .map_err(DaemonError::from_tungstenite)?;
 
     // 3. Session phase — internal anyhow, mapped to Other at boundary
-    attach_session(ws_stream, scrollback_lines, instance_id)
+    attach_session(ws_stream, scrollback_lines, instance_id, options)
         .await
         .map_err(Into::into)
 }
 
 /// Fetch scrollback_lines from the server config endpoint.
 /// Clamped to 100–100,000 to prevent degenerate allocations.
-async fn fetch_scrollback_lines(daemon: &DaemonInfo) -> usize {
+pub(super) async fn fetch_scrollback_lines(daemon: &DaemonInfo) -> usize {
     let url = format!("{}/api/admin/config", daemon.base_url());
     let result: Option<usize> = async {
-        let resp = reqwest::get(&url).await.ok()?;
+        let resp = daemon.http().get(&url).send().await.ok()?;
         let json: serde_json::Value = resp.json().await.ok()?;
         json.get("scrollback_lines")?.as_u64().map(|v| v as usize)
     }
@@ -281,30 +335,57 @@ async fn fetch_scrollback_lines(daemon: &DaemonInfo) -> usize {
         .clamp(MIN_SCROLLBACK_LINES, MAX_SCROLLBACK_LINES)
 }
 
-/// Run the attach session after WebSocket is connected.
-async fn attach_session(
-    ws_stream: tokio_tungstenite::WebSocketStream<
-        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-    >,
-    scrollback_lines: usize,
+/// One attached terminal's connection: events from the WS reader task and
+/// the queue drained by the WS writer task.
+pub(super) struct PaneLink {
+    pub(super) events: std::sync::mpsc::Receiver<AttachEvent>,
+    pub(super) writer: tokio::sync::mpsc::UnboundedSender<tungstenite::Message>,
+}
+
+/// Set up a connected socket to show `instance_id` in a `rows`×`cols`
+/// viewport: handshake, focus, register the viewport (which replays the
+/// history) and spawn the reader and writer tasks.
+pub(super) async fn open_pane(
+    mut ws_stream: WsStream,
     instance_id: &str,
-) -> Result<AttachOutcome> {
+    rows: u16,
+    cols: u16,
+    options: AttachOptions,
+) -> Result<PaneLink> {
+    // Check protocol compatibility and ask for raw output frames
+    let server = handshake(&mut ws_stream, &[Capability::BinaryFrames]).await?;
     let (mut ws_write, mut ws_read) = ws_stream.split();
 
-    // Size the PTY: terminal height minus 1 row for status bar
-    let (term_rows, term_cols) = get_terminal_size().unwrap_or((24, 80));
-    let pty_rows = term_rows.saturating_sub(1).max(1);
-
-    let mut vt_parser = vt100::Parser::new(pty_rows, term_cols, scrollback_lines);
-
     // Channels: WS reader → main loop, main loop → WS writer
     let (ws_read_tx, ws_read_rx) = std::sync::mpsc::channel::<AttachEvent>();
-    let (ws_write_tx, ws_write_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let (ws_write_tx, ws_write_rx) = tokio::sync::mpsc::unbounded_channel::<tungstenite::Message>();
+
+    // Servers that predate the handshake may still know EnableBinaryFrames;
+    // older ones ignore it and keep sending JSON Output, handled below.
+    if server.is_none() {
+        let json = serde_json::to_string(&ClientMessage::EnableBinaryFrames)?;
+        ws_write.send(tungstenite::Message::Text(json)).await?;
+    }
+
+    // Servers without screen diffs ignore this and keep sending raw output,
+    // which renders the same way.
+    if options.screen_diff {
+        let json = serde_json::to_string(&ClientMessage::SetOutputMode {
+            mode: OutputMode::ScreenDiff,
+            max_fps: options.max_fps,
+        })?;
+        ws_write.send(tungstenite::Message::Text(json)).await?;
+    }
+
+    // Only the focused instance's events are needed; skip other broadcasts
+    let json = serde_json::to_string(&ClientMessage::Subscribe { topics: vec![] })?;
+    ws_write.send(tungstenite::Message::Text(json)).await?;
 
     // Send Focus to subscribe to the instance
     let focus_msg = ClientMessage::Focus {
         instance_id: instance_id.to_string(),
         since_uuid: None,
+        output_seq: None,
     };
     let json = serde_json::to_string(&focus_msg)?;
     ws_write.send(tungstenite::Message::Text(json)).await?;
@@ -312,9 +393,9 @@ async fn attach_session(
     // Send TerminalVisible to trigger resize + OutputHistory replay
     let visible_msg = ClientMessage::TerminalVisible {
         instance_id: instance_id.to_string(),
-        rows: pty_rows,
-        cols: term_cols,
-        client_type: Some("terminal".to_string()),
+        rows,
+        cols,
+        client_type: viewport_client_type(options.spectate),
     };
     let json = serde_json::to_string(&visible_msg)?;
     ws_write.send(tungstenite::Message::Text(json)).await?;
@@ -333,7 +414,10 @@ async fn attach_session(
                             data,
                             ..
                         }) if iid == &filter_instance_id => {
-                            if read_tx.send(AttachEvent::Output(data)).is_err() {
+                            if read_tx
+                                .send(AttachEvent::Output(data.into_bytes()))
+                                .is_err()
+                            {
                                 break;
                             }
                         }
@@ -342,7 +426,27 @@ async fn attach_session(
                             instance_id: ref iid,
                             data,
                         }) if iid == &filter_instance_id => {
-                            if read_tx.send(AttachEvent::Output(data)).is_err() {
+                            if read_tx
+                                .send(AttachEvent::Output(data.into_bytes()))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        // Coalesced screen update (screen-diff output mode)
+                        Ok(ServerMessage::ScreenDiff {
+                            instance_id: ref iid,
+                            data,
+                        }) if iid == &filter_instance_id => {
+                            if read_tx
+                                .send(AttachEvent::Output(data.into_bytes()))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        Ok(ServerMessage::BinaryFramesEnabled) => {
+                            if read_tx.send(AttachEvent::BinaryFramesEnabled).is_err() {
                                 break;
                             }
                         }
@@ -365,10 +469,32 @@ async fn attach_session(
                                 break;
                             }
                         }
+                        Ok(ServerMessage::ClipboardWrite {
+                            instance_id: ref iid,
+                            selection,
+                            data,
+                        }) if iid == &filter_instance_id => {
+                            if read_tx
+                                .send(AttachEvent::Clipboard(selection, data))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
                         // Ignore everything else (InstanceList, PresenceUpdate, Chat, etc.)
                         _ => {}
                     }
                 }
+                Ok(tungstenite::Message::Binary(bytes)) => {
+                    // Output, OutputHistory and ScreenDiff frames all feed the parser
+                    if let Ok(frame) = TerminalFrame::decode(&bytes)
+                        && frame.kind != FrameKind::Input
+                        && frame.instance_id == filter_instance_id
+                        && read_tx.send(AttachEvent::Output(frame.data)).is_err()
+                    {
+                        break;
+                    }
+                }
                 Ok(tungstenite::Message::Close(_)) | Err(_) => {
                     let _ = read_tx.send(AttachEvent::Closed);
                     break;
@@ -381,17 +507,33 @@ async fn attach_session(
     // Spawn async WS writer task
     let mut ws_write_rx = ws_write_rx;
     tokio::spawn(async move {
-        while let Some(json) = ws_write_rx.recv().await {
-            if ws_write
-                .send(tungstenite::Message::Text(json))
-                .await
-                .is_err()
-            {
+        while let Some(msg) = ws_write_rx.recv().await {
+            if ws_write.send(msg).await.is_err() {
                 break;
             }
         }
     });
 
+    Ok(PaneLink {
+        events: ws_read_rx,
+        writer: ws_write_tx,
+    })
+}
+
+/// Run the attach session after WebSocket is connected.
+async fn attach_session(
+    ws_stream: WsStream,
+    scrollback_lines: usize,
+    instance_id: &str,
+    options: AttachOptions,
+) -> Result<AttachOutcome> {
+    // Size the PTY: terminal height minus 1 row for status bar
+    let (term_rows, term_cols) = get_terminal_size().unwrap_or((24, 80));
+    let pty_rows = term_rows.saturating_sub(1).max(1);
+
+    let mut vt_parser = new_parser(pty_rows, term_cols, scrollback_lines);
+    let link = open_pane(ws_stream, instance_id, pty_rows, term_cols, options).await?;
+
     // Enter the blocking ratatui render loop (with mouse capture for scroll wheel)
     let mut terminal = ratatui::init();
     let _ = ratatui::crossterm::execute!(std::io::stdout(), EnableMouseCapture);
@@ -399,10 +541,11 @@ async fn attach_session(
         run_event_loop(
             &mut terminal,
             &mut vt_parser,
-            &ws_read_rx,
-            &ws_write_tx,
+            &link.events,
+            &link.writer,
             scrollback_lines,
             instance_id,
+            options,
         )
     });
     let _ = ratatui::crossterm::execute!(std::io::stdout(), DisableMouseCapture);
@@ -416,17 +559,57 @@ async fn attach_session(
 // =============================================================================
 
 /// Mutable state for a TUI attach session.
-struct AttachState {
-    claude_state: ClaudeState,
-    scroll_offset: usize,
+pub(super) struct AttachState {
+    pub(super) claude_state: ClaudeState,
+    pub(super) scroll_offset: usize,
     badge_until: Instant,
     attach_time: Instant,
+    /// OSC 52 writes received since the last frame
+    pub(super) pending_clipboard: Vec<(String, String)>,
+    /// Send keystrokes as binary input frames (server acknowledged them)
+    binary_input: bool,
+    /// Sequence number of the next input frame
+    input_seq: u64,
+    /// Read-only session: keystrokes are dropped
+    spectator: bool,
+    pub(super) keys: KeyBindings,
+    /// Frozen screen and scrollback while copy mode is open
+    pub(super) copy: Option<CopyMode>,
+    /// Short message for the status bar, shown until the deadline
+    notice: Option<(String, Instant)>,
+}
+
+impl AttachState {
+    pub(super) fn new(spectator: bool, keys: KeyBindings) -> Self {
+        Self {
+            claude_state: ClaudeState::Initializing,
+            scroll_offset: 0,
+            badge_until: Instant::now() + Duration::from_secs(5),
+            attach_time: Instant::now(),
+            pending_clipboard: Vec::new(),
+            binary_input: false,
+            input_seq: 0,
+            spectator,
+            keys,
+            copy: None,
+            notice: None,
+        }
+    }
+
+    /// The notice to show in place of the usual status, if still current.
+    pub(super) fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, until)| Instant::now() < *until)
+            .map(|(text, _)| text.as_str())
+    }
 }
 
 /// What the input handler decided — pure classification, no side effects.
 #[derive(Debug, PartialEq)]
-enum InputAction {
+pub(super) enum InputAction {
     Detach,
+    CopyMode,
     ScrollUp(usize),
     ScrollDown(usize),
     SendBytes(Vec<u8>),
@@ -439,15 +622,19 @@ enum InputAction {
 
 /// Drain all pending WS events into the parser and state.
 /// Returns `true` if the connection closed.
-fn drain_ws(
+pub(super) fn drain_ws(
     ws_read_rx: &std::sync::mpsc::Receiver<AttachEvent>,
-    vt_parser: &mut vt100::Parser,
+    vt_parser: &mut AttachParser,
     state: &mut AttachState,
 ) -> bool {
     while let Ok(ev) = ws_read_rx.try_recv() {
         match ev {
-            AttachEvent::Output(data) => vt_parser.process(data.as_bytes()),
+            AttachEvent::Output(data) => vt_parser.process(&data),
+            AttachEvent::BinaryFramesEnabled => state.binary_input = true,
             AttachEvent::StateChange(new_state) => state.claude_state = new_state,
+            AttachEvent::Clipboard(selection, data) => {
+                state.pending_clipboard.push((selection, data));
+            }
             AttachEvent::Closed => return true,
         }
     }
@@ -456,21 +643,28 @@ fn drain_ws(
 
 /// Classify a crossterm event into an InputAction.
 /// Pure function — no state mutation, no I/O.
-fn classify_input(ev: &Event, page_size: usize) -> Option<InputAction> {
+pub(super) fn classify_input(
+    ev: &Event,
+    page_size: usize,
+    keys: &KeyBindings,
+) -> Option<InputAction> {
     match ev {
-        // Shift+scroll keys
-        Event::Key(key)
-            if key.kind == KeyEventKind::Press && key.modifiers.contains(KeyModifiers::SHIFT) =>
-        {
-            match key.code {
-                KeyCode::PageUp => Some(InputAction::ScrollUp(page_size)),
-                KeyCode::PageDown => Some(InputAction::ScrollDown(page_size)),
-                KeyCode::Up => Some(InputAction::ScrollUp(1)),
-                KeyCode::Down => Some(InputAction::ScrollDown(1)),
-                _ => {
-                    // Any other Shift+key: forward as input (apply_action snaps to bottom)
-                    key_to_bytes(key).map(InputAction::SendBytes)
-                }
+        // Bound keys; anything else is typed (apply_action snaps to bottom)
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            if keys.matches(Action::Detach, key) {
+                Some(InputAction::Detach)
+            } else if keys.matches(Action::CopyMode, key) {
+                Some(InputAction::CopyMode)
+            } else if keys.matches(Action::PageUp, key) {
+                Some(InputAction::ScrollUp(page_size))
+            } else if keys.matches(Action::PageDown, key) {
+                Some(InputAction::ScrollDown(page_size))
+            } else if keys.matches(Action::ScrollUp, key) {
+                Some(InputAction::ScrollUp(1))
+            } else if keys.matches(Action::ScrollDown, key) {
+                Some(InputAction::ScrollDown(1))
+            } else {
+                key_to_bytes(key).map(InputAction::SendBytes)
             }
         }
 
@@ -481,15 +675,6 @@ fn classify_input(ev: &Event, page_size: usize) -> Option<InputAction> {
             _ => None,
         },
 
-        // Regular key presses
-        Event::Key(key) if key.kind == KeyEventKind::Press => {
-            if is_detach_key(key) {
-                Some(InputAction::Detach)
-            } else {
-                key_to_bytes(key).map(InputAction::SendBytes)
-            }
-        }
-
         Event::Resize(cols, rows) => Some(InputAction::Resize {
             rows: *rows,
             cols: *cols,
@@ -502,9 +687,9 @@ fn classify_input(ev: &Event, page_size: usize) -> Option<InputAction> {
 /// Apply a classified action to session state. Returns `Some(outcome)` to exit the loop.
 fn apply_action(
     action: InputAction,
-    vt_parser: &mut vt100::Parser,
+    vt_parser: &mut AttachParser,
     state: &mut AttachState,
-    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<String>,
+    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<tungstenite::Message>,
     scrollback_capacity: usize,
     instance_id: &str,
 ) -> Option<AttachOutcome> {
@@ -513,77 +698,202 @@ fn apply_action(
             eprintln!("\r\n[crab: detached]");
             return Some(AttachOutcome::Detached);
         }
+        InputAction::CopyMode => enter_copy_mode(vt_parser, state),
         InputAction::ScrollUp(n) => {
             state.scroll_offset = state.scroll_offset.saturating_add(n);
         }
         InputAction::ScrollDown(n) => {
             state.scroll_offset = state.scroll_offset.saturating_sub(n);
         }
-        InputAction::SendBytes(bytes) => {
-            state.scroll_offset = 0;
-            let msg = ClientMessage::Input {
-                instance_id: instance_id.to_string(),
-                data: String::from_utf8_lossy(&bytes).to_string(),
-                task_id: None,
-            };
-            if let Ok(json) = serde_json::to_string(&msg) {
-                let _ = ws_write_tx.send(json);
-            }
-        }
+        InputAction::SendBytes(bytes) => send_input(state, ws_write_tx, instance_id, bytes),
         InputAction::Resize { rows, cols } => {
             let pty_rows = rows.saturating_sub(1).max(1);
-            // Save visible screen, recreate parser at new dims (clears
-            // scrollback — SIGWINCH redraw will rebuild it correctly).
-            let content = vt_parser.screen().contents_formatted();
-            let cursor = vt_parser.screen().cursor_position();
-            *vt_parser = vt100::Parser::new(pty_rows, cols, scrollback_capacity);
-            vt_parser.process(&content);
-            vt_parser.process(format!("\x1b[{};{}H", cursor.0 + 1, cursor.1 + 1).as_bytes());
-            // Use TerminalVisible to trigger both resize AND OutputHistory replay
-            let msg = ClientMessage::TerminalVisible {
-                instance_id: instance_id.to_string(),
-                rows: pty_rows,
+            resize_viewport(
+                vt_parser,
+                state,
+                ws_write_tx,
+                scrollback_capacity,
+                instance_id,
+                pty_rows,
                 cols,
-                client_type: Some("terminal".to_string()),
-            };
-            if let Ok(json) = serde_json::to_string(&msg) {
-                let _ = ws_write_tx.send(json);
-            }
+            );
         }
     }
     None
 }
 
+/// Freeze the screen and scrollback into copy mode, starting at the lines
+/// currently in view.
+pub(super) fn enter_copy_mode(vt_parser: &mut AttachParser, state: &mut AttachState) {
+    let (rows, _) = vt_parser.screen().size();
+    let (cursor_row, cursor_col) = vt_parser.screen().cursor_position();
+    let lines = virtual_terminal::parser_lines(vt_parser);
+    let depth = lines.len().saturating_sub(rows as usize);
+    let top = depth.saturating_sub(state.scroll_offset);
+    // Scrolled back, the terminal cursor isn't on screen; start at the bottom
+    let cursor = if state.scroll_offset == 0 {
+        (cursor_row as usize, cursor_col as usize)
+    } else {
+        (rows.saturating_sub(1) as usize, 0)
+    };
+    state.copy = Some(CopyMode::new(lines, top, rows as usize, cursor));
+}
+
+/// Route `ev` to copy mode if it is open. Returns `false` for events the
+/// caller should still handle: the detach key and resizes.
+pub(super) fn copy_mode_input(state: &mut AttachState, ev: &Event) -> bool {
+    let detach = state.keys.get(Action::Detach);
+    let Some(copy) = state.copy.as_mut() else {
+        return false;
+    };
+    let key = match ev {
+        Event::Key(key) if detach.matches(key) => return false,
+        Event::Key(key) if key.kind == KeyEventKind::Press => key,
+        Event::Resize(..) => return false,
+        _ => return true,
+    };
+    match copy.handle_key(key) {
+        CopyAction::None => {}
+        CopyAction::Exit => state.copy = None,
+        CopyAction::Copy(text) => {
+            let notice = format!("Copied {} to the clipboard", describe_lines(&text));
+            state
+                .pending_clipboard
+                .push(("c".to_string(), BASE64.encode(text)));
+            state.copy = None;
+            state.notice = Some((notice, Instant::now() + Duration::from_secs(3)));
+        }
+        CopyAction::Save(path, text) => match std::fs::write(&path, format!("{text}\n")) {
+            Ok(()) => {
+                let notice = format!("Saved {} to {}", describe_lines(&text), path.display());
+                state.copy = None;
+                state.notice = Some((notice, Instant::now() + Duration::from_secs(3)));
+            }
+            Err(e) => copy.set_message(format!("Can't write {}: {}", path.display(), e)),
+        },
+    }
+    true
+}
+
+fn describe_lines(text: &str) -> String {
+    match text.lines().count() {
+        1 => "1 line".to_string(),
+        n => format!("{n} lines"),
+    }
+}
+
+/// Type `bytes` into the instance, snapping back to the live screen.
+/// Dropped for spectators.
+pub(super) fn send_input(
+    state: &mut AttachState,
+    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<tungstenite::Message>,
+    instance_id: &str,
+    bytes: Vec<u8>,
+) {
+    if state.spectator {
+        return;
+    }
+    state.scroll_offset = 0;
+    if state.binary_input {
+        let frame = TerminalFrame {
+            kind: FrameKind::Input,
+            instance_id: instance_id.to_string(),
+            seq: state.input_seq,
+            data: bytes,
+        };
+        state.input_seq += 1;
+        let _ = ws_write_tx.send(tungstenite::Message::Binary(frame.encode()));
+    } else {
+        let msg = ClientMessage::Input {
+            instance_id: instance_id.to_string(),
+            data: String::from_utf8_lossy(&bytes).to_string(),
+            task_id: None,
+        };
+        if let Ok(json) = serde_json::to_string(&msg) {
+            let _ = ws_write_tx.send(tungstenite::Message::Text(json));
+        }
+    }
+}
+
+/// Resize the local screen to `rows`×`cols` and re-register the viewport
+/// with the daemon.
+pub(super) fn resize_viewport(
+    vt_parser: &mut AttachParser,
+    state: &AttachState,
+    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<tungstenite::Message>,
+    scrollback_capacity: usize,
+    instance_id: &str,
+    rows: u16,
+    cols: u16,
+) {
+    // Save visible screen, recreate parser at new dims (clears
+    // scrollback — SIGWINCH redraw will rebuild it correctly).
+    let content = vt_parser.screen().contents_formatted();
+    let cursor = vt_parser.screen().cursor_position();
+    let callbacks = std::mem::take(vt_parser.callbacks_mut());
+    *vt_parser = vt100::Parser::new_with_callbacks(rows, cols, scrollback_capacity, callbacks);
+    vt_parser.process(&content);
+    vt_parser.process(format!("\x1b[{};{}H", cursor.0 + 1, cursor.1 + 1).as_bytes());
+    // Use TerminalVisible to trigger both resize AND OutputHistory replay
+    let msg = ClientMessage::TerminalVisible {
+        instance_id: instance_id.to_string(),
+        rows,
+        cols,
+        client_type: viewport_client_type(state.spectator),
+    };
+    if let Ok(json) = serde_json::to_string(&msg) {
+        let _ = ws_write_tx.send(tungstenite::Message::Text(json));
+    }
+}
+
 /// Render the current frame: PTY content, status bar, optional badge.
+///
+/// ratatui cells can't carry OSC 8 (escape bytes count towards the symbol
+/// width and corrupt the diff), so hyperlinks are drawn over the frame
+/// afterwards, and pending OSC 52 writes are passed through with them.
 fn render_frame(
     terminal: &mut ratatui::DefaultTerminal,
-    vt_parser: &vt100::Parser,
-    state: &AttachState,
+    vt_parser: &AttachParser,
+    state: &mut AttachState,
 ) -> Result<()> {
     let screen = vt_parser.screen();
-    let bar_text = if state.scroll_offset > 0 {
+    let detach = state.keys.get(Action::Detach);
+    let bar_text = if let Some(copy) = &state.copy {
+        copy.status_text()
+    } else if let Some(notice) = state.notice() {
+        format!(" {} ", notice)
+    } else if state.scroll_offset > 0 {
         format!(
-            " SCROLL \u{2502} {} lines up \u{2502} Shift-PgDn or scroll to return ",
-            state.scroll_offset
+            " SCROLL \u{2502} {} lines up \u{2502} {} or scroll to return ",
+            state.scroll_offset,
+            state.keys.get(Action::PageDown)
         )
     } else {
-        status_bar_text(&state.claude_state, state.attach_time)
+        status_bar_text(&state.claude_state, state.attach_time, detach)
     };
-    let show_badge = Instant::now() < state.badge_until;
+    let copying = state.copy.is_some();
+    let show_badge = Instant::now() < state.badge_until && !copying;
     let cursor_pos = screen.cursor_position();
     let hide_cursor = screen.hide_cursor();
-    let at_bottom = state.scroll_offset == 0;
+    let at_bottom = state.scroll_offset == 0 && !copying;
+    let mut content_area = Rect::default();
 
     terminal.draw(|frame| {
         let [content, status] =
             Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        content_area = content;
 
-        frame.render_widget(PtyWidget { screen }, content);
+        if let Some(copy) = state.copy.as_mut() {
+            copy.scroll_to_cursor(content.height as usize);
+            frame.render_widget(CopyModeWidget { copy }, content);
+        } else {
+            frame.render_widget(PtyWidget { screen }, content);
+        }
         frame.render_widget(StatusBarWidget { text: &bar_text }, status);
 
         if show_badge && at_bottom {
-            const OVERLAY_TEXT: &str = "attached -- Ctrl-] to detach";
-            frame.render_widget(OverlayBadge { text: OVERLAY_TEXT }, content);
+            let text = format!("attached -- {} to detach", detach);
+            frame.render_widget(OverlayBadge { text: &text }, content);
         }
 
         if at_bottom && !hide_cursor {
@@ -591,6 +901,25 @@ fn render_frame(
             frame.set_cursor_position((content.x + col, content.y + row));
         }
     })?;
+
+    let mut extra = Vec::new();
+    let links = vt_parser.callbacks().hyperlinks();
+    // The badge is drawn over PTY cells; don't repaint links on top of it
+    let badge_visible = show_badge && at_bottom;
+    if !links.is_empty() && !badge_visible && !copying {
+        // DECSC/DECRC keep the cursor and attributes ratatui left behind
+        extra.extend_from_slice(b"\x1b7");
+        render_hyperlink_overlay(screen, links, (content_area.y, content_area.x), &mut extra);
+        extra.extend_from_slice(b"\x1b8");
+    }
+    for (selection, data) in state.pending_clipboard.drain(..) {
+        extra.extend_from_slice(format!("\x1b]52;{selection};{data}\x07").as_bytes());
+    }
+    if !extra.is_empty() {
+        let backend = terminal.backend_mut();
+        backend.write_all(&extra)?;
+        backend.flush()?;
+    }
     Ok(())
 }
 
@@ -601,18 +930,14 @@ fn render_frame(
 /// Blocking event loop: drain → clamp scroll → render → poll → classify → apply.
 fn run_event_loop(
     terminal: &mut ratatui::DefaultTerminal,
-    vt_parser: &mut vt100::Parser,
+    vt_parser: &mut AttachParser,
     ws_read_rx: &std::sync::mpsc::Receiver<AttachEvent>,
-    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<String>,
+    ws_write_tx: &tokio::sync::mpsc::UnboundedSender<tungstenite::Message>,
     scrollback_capacity: usize,
     instance_id: &str,
+    options: AttachOptions,
 ) -> Result<AttachOutcome> {
-    let mut state = AttachState {
-        claude_state: ClaudeState::Initializing,
-        scroll_offset: 0,
-        badge_until: Instant::now() + Duration::from_secs(5),
-        attach_time: Instant::now(),
-    };
+    let mut state = AttachState::new(options.spectate, options.keys);
 
     loop {
         // 1. Drain all pending WS messages
@@ -626,16 +951,19 @@ fn run_event_loop(
         state.scroll_offset = vt_parser.screen().scrollback();
 
         // 3. Render
-        render_frame(terminal, vt_parser, &state)?;
+        render_frame(terminal, vt_parser, &mut state)?;
 
         // 4. Poll, classify, apply
         if event::poll(Duration::from_millis(16))? {
             let ev = event::read()?;
+            if copy_mode_input(&mut state, &ev) {
+                continue;
+            }
             let page_size = terminal
                 .size()
                 .map_or(23, |s| s.height.saturating_sub(1).max(1) as usize);
 
-            if let Some(action) = classify_input(&ev, page_size)
+            if let Some(action) = classify_input(&ev, page_size, &state.keys)
                 && let Some(outcome) = apply_action(
                     action,
                     vt_parser,
@@ -682,7 +1010,7 @@ mod tests {
 
     #[test]
     fn pty_widget_renders_text() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         parser.process(b"Hello");
 
         let area = Rect::new(0, 0, 80, 24);
@@ -701,7 +1029,7 @@ mod tests {
 
     #[test]
     fn pty_widget_renders_colors() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         parser.process(b"\x1b[31mRed");
 
         let area = Rect::new(0, 0, 80, 24);
@@ -715,9 +1043,28 @@ mod tests {
         assert_eq!(buf[(0, 0)].fg, Color::Indexed(1));
     }
 
+    #[test]
+    fn pty_widget_renders_extended_attributes() {
+        let mut parser = new_parser(24, 80, 0);
+        parser.process(b"\x1b[2;9mA\x1b[0m\x1b[5mB\x1b[0m\x1b[4:3;58:2::255:0:0mC");
+
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buf = Buffer::empty(area);
+        PtyWidget {
+            screen: parser.screen(),
+        }
+        .render(area, &mut buf);
+
+        assert_eq!(buf[(0, 0)].modifier, Modifier::DIM | Modifier::CROSSED_OUT);
+        assert_eq!(buf[(1, 0)].modifier, Modifier::SLOW_BLINK);
+        assert_eq!(buf[(2, 0)].modifier, Modifier::UNDERLINED);
+        assert_eq!(buf[(2, 0)].underline_color, Color::Rgb(255, 0, 0));
+        assert_eq!(buf[(3, 0)].underline_color, Color::Reset);
+    }
+
     #[test]
     fn pty_widget_wide_chars() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         parser.process("漢字".as_bytes());
 
         let area = Rect::new(0, 0, 80, 24);
@@ -783,23 +1130,24 @@ mod tests {
     #[test]
     fn detach_key_matches_crossterm_representation() {
         // Crossterm reports Ctrl-] as Char('5') + CONTROL
+        let keys = KeyBindings::default();
         let crossterm_ctrl_bracket = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL);
-        assert!(is_detach_key(&crossterm_ctrl_bracket));
+        assert!(keys.matches(Action::Detach, &crossterm_ctrl_bracket));
         // Also match the "ideal" representation in case a future crossterm fixes this
         let ideal_ctrl_bracket = KeyEvent::new(KeyCode::Char(']'), KeyModifiers::CONTROL);
-        assert!(is_detach_key(&ideal_ctrl_bracket));
+        assert!(keys.matches(Action::Detach, &ideal_ctrl_bracket));
         // Regular keys should not match
         let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
-        assert!(!is_detach_key(&ctrl_c));
+        assert!(!keys.matches(Action::Detach, &ctrl_c));
         let plain_5 = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::NONE);
-        assert!(!is_detach_key(&plain_5));
+        assert!(!keys.matches(Action::Detach, &plain_5));
     }
 
     // ── Scrollback ───────────────────────────────────────────────────
 
     #[test]
     fn scrollback_preserves_history() {
-        let mut parser = vt100::Parser::new(3, 10, 100);
+        let mut parser = new_parser(3, 10, 100);
         // Write 6 lines into a 3-row terminal → 3 lines should be in scrollback
         for i in 0..6 {
             parser.process(format!("line {}\r\n", i).as_bytes());
@@ -822,7 +1170,7 @@ mod tests {
 
     #[test]
     fn scrollback_offset_clamps_to_max() {
-        let mut parser = vt100::Parser::new(3, 10, 100);
+        let mut parser = new_parser(3, 10, 100);
         // Only 2 lines of output → at most ~0-1 scrollback rows
         parser.process(b"hello\r\nworld");
         parser.screen_mut().set_scrollback(9999);
@@ -848,7 +1196,7 @@ mod tests {
         ];
         let started = Instant::now();
         for state in states {
-            let text = status_bar_text(&state, started);
+            let text = status_bar_text(&state, started, KeyBindings::default().get(Action::Detach));
             assert!(!text.is_empty(), "status bar text for {:?} is empty", state);
 
             let area = Rect::new(0, 0, 80, 1);
@@ -893,25 +1241,37 @@ mod tests {
     #[test]
     fn classify_shift_page_up_scrolls_up_by_page() {
         let ev = key_press(KeyCode::PageUp, KeyModifiers::SHIFT);
-        assert_eq!(classify_input(&ev, 24), Some(InputAction::ScrollUp(24)));
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::ScrollUp(24))
+        );
     }
 
     #[test]
     fn classify_shift_page_down_scrolls_down_by_page() {
         let ev = key_press(KeyCode::PageDown, KeyModifiers::SHIFT);
-        assert_eq!(classify_input(&ev, 24), Some(InputAction::ScrollDown(24)));
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::ScrollDown(24))
+        );
     }
 
     #[test]
     fn classify_shift_up_scrolls_up_by_one() {
         let ev = key_press(KeyCode::Up, KeyModifiers::SHIFT);
-        assert_eq!(classify_input(&ev, 24), Some(InputAction::ScrollUp(1)));
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::ScrollUp(1))
+        );
     }
 
     #[test]
     fn classify_shift_down_scrolls_down_by_one() {
         let ev = key_press(KeyCode::Down, KeyModifiers::SHIFT);
-        assert_eq!(classify_input(&ev, 24), Some(InputAction::ScrollDown(1)));
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::ScrollDown(1))
+        );
     }
 
     #[test]
@@ -919,7 +1279,7 @@ mod tests {
         // Shift+A should forward as input, not scroll
         let ev = key_press(KeyCode::Char('A'), KeyModifiers::SHIFT);
         assert_eq!(
-            classify_input(&ev, 24),
+            classify_input(&ev, 24, &KeyBindings::default()),
             Some(InputAction::SendBytes(b"A".to_vec()))
         );
     }
@@ -928,7 +1288,7 @@ mod tests {
     fn classify_mouse_scroll_up() {
         let ev = mouse_event(MouseEventKind::ScrollUp);
         assert_eq!(
-            classify_input(&ev, 24),
+            classify_input(&ev, 24, &KeyBindings::default()),
             Some(InputAction::ScrollUp(MOUSE_SCROLL_LINES))
         );
     }
@@ -937,7 +1297,7 @@ mod tests {
     fn classify_mouse_scroll_down() {
         let ev = mouse_event(MouseEventKind::ScrollDown);
         assert_eq!(
-            classify_input(&ev, 24),
+            classify_input(&ev, 24, &KeyBindings::default()),
             Some(InputAction::ScrollDown(MOUSE_SCROLL_LINES))
         );
     }
@@ -945,21 +1305,34 @@ mod tests {
     #[test]
     fn classify_mouse_move_ignored() {
         let ev = mouse_event(MouseEventKind::Moved);
-        assert_eq!(classify_input(&ev, 24), None);
+        assert_eq!(classify_input(&ev, 24, &KeyBindings::default()), None);
     }
 
     #[test]
     fn classify_detach_key() {
         // Crossterm represents Ctrl-] as Char('5') + CONTROL
         let ev = key_press(KeyCode::Char('5'), KeyModifiers::CONTROL);
-        assert_eq!(classify_input(&ev, 24), Some(InputAction::Detach));
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::Detach)
+        );
+    }
+
+    #[test]
+    fn classify_copy_mode_key() {
+        // Crossterm represents Ctrl-^ as Char('6') + CONTROL
+        let ev = key_press(KeyCode::Char('6'), KeyModifiers::CONTROL);
+        assert_eq!(
+            classify_input(&ev, 24, &KeyBindings::default()),
+            Some(InputAction::CopyMode)
+        );
     }
 
     #[test]
     fn classify_regular_char_sends_bytes() {
         let ev = key_press(KeyCode::Char('a'), KeyModifiers::NONE);
         assert_eq!(
-            classify_input(&ev, 24),
+            classify_input(&ev, 24, &KeyBindings::default()),
             Some(InputAction::SendBytes(vec![0x61]))
         );
     }
@@ -968,7 +1341,7 @@ mod tests {
     fn classify_resize_event() {
         let ev = Event::Resize(120, 40);
         assert_eq!(
-            classify_input(&ev, 24),
+            classify_input(&ev, 24, &KeyBindings::default()),
             Some(InputAction::Resize {
                 rows: 40,
                 cols: 120
@@ -983,15 +1356,21 @@ mod tests {
             KeyModifiers::NONE,
             KeyEventKind::Release,
         ));
-        assert_eq!(classify_input(&ev, 24), None);
+        assert_eq!(classify_input(&ev, 24, &KeyBindings::default()), None);
     }
 
     #[test]
     fn classify_page_size_propagates() {
         // Page size should be exactly what's passed in
         let ev = key_press(KeyCode::PageUp, KeyModifiers::SHIFT);
-        assert_eq!(classify_input(&ev, 53), Some(InputAction::ScrollUp(53)));
-        assert_eq!(classify_input(&ev, 1), Some(InputAction::ScrollUp(1)));
+        assert_eq!(
+            classify_input(&ev, 53, &KeyBindings::default()),
+            Some(InputAction::ScrollUp(53))
+        );
+        assert_eq!(
+            classify_input(&ev, 1, &KeyBindings::default()),
+            Some(InputAction::ScrollUp(1))
+        );
     }
 
     // ── apply_action ────────────────────────────────────────────────
@@ -1002,12 +1381,19 @@ mod tests {
             scroll_offset: 0,
             badge_until: Instant::now(),
             attach_time: Instant::now(),
+            pending_clipboard: Vec::new(),
+            binary_input: false,
+            input_seq: 0,
+            spectator: false,
+            keys: KeyBindings::default(),
+            copy: None,
+            notice: None,
         }
     }
 
     #[test]
     fn apply_detach_returns_detached() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
         let result = apply_action(
@@ -1023,7 +1409,7 @@ mod tests {
 
     #[test]
     fn apply_scroll_up_increases_offset() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
         apply_action(
@@ -1039,7 +1425,7 @@ mod tests {
 
     #[test]
     fn apply_scroll_down_decreases_offset() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         state.scroll_offset = 15;
         let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
@@ -1056,7 +1442,7 @@ mod tests {
 
     #[test]
     fn apply_scroll_down_saturates_at_zero() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         state.scroll_offset = 3;
         let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
@@ -1073,7 +1459,7 @@ mod tests {
 
     #[test]
     fn apply_send_bytes_snaps_to_bottom_and_sends() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         state.scroll_offset = 42;
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
@@ -1088,12 +1474,92 @@ mod tests {
         assert!(result.is_none());
         assert_eq!(state.scroll_offset, 0, "should snap to bottom on input");
         let msg = rx.try_recv().expect("should have sent a message");
-        assert!(msg.contains("hello"), "message should contain input data");
+        let text = msg.into_text().expect("JSON input before binary frames");
+        assert!(text.contains("hello"), "message should contain input data");
+    }
+
+    #[test]
+    fn apply_send_bytes_uses_frames_once_acknowledged() {
+        let (ev_tx, ev_rx) = std::sync::mpsc::channel();
+        let mut parser = new_parser(24, 80, 0);
+        let mut state = make_state();
+        ev_tx.send(AttachEvent::BinaryFramesEnabled).unwrap();
+        assert!(!drain_ws(&ev_rx, &mut parser, &mut state));
+        assert!(state.binary_input);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        for bytes in [b"\x1b[A".to_vec(), vec![0xC3, 0xA9]] {
+            apply_action(
+                InputAction::SendBytes(bytes),
+                &mut parser,
+                &mut state,
+                &tx,
+                100,
+                "test",
+            );
+        }
+
+        let decode = |msg: tungstenite::Message| match msg {
+            tungstenite::Message::Binary(bytes) => TerminalFrame::decode(&bytes).unwrap(),
+            other => panic!("expected binary frame, got {:?}", other),
+        };
+        let first = decode(rx.try_recv().unwrap());
+        assert_eq!(first.kind, FrameKind::Input);
+        assert_eq!(first.instance_id, "test");
+        assert_eq!(first.seq, 0);
+        assert_eq!(first.data, b"\x1b[A");
+        let second = decode(rx.try_recv().unwrap());
+        assert_eq!(second.seq, 1);
+        assert_eq!(second.data, [0xC3, 0xA9]);
+    }
+
+    #[test]
+    fn spectator_drops_input_and_resizes_as_spectator() {
+        let mut parser = new_parser(24, 80, 0);
+        let mut state = make_state();
+        state.spectator = true;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let result = apply_action(
+            InputAction::SendBytes(b"hello".to_vec()),
+            &mut parser,
+            &mut state,
+            &tx,
+            100,
+            "test",
+        );
+        assert!(result.is_none());
+        assert!(rx.try_recv().is_err(), "spectators send no input");
+
+        apply_action(
+            InputAction::Resize {
+                rows: 40,
+                cols: 120,
+            },
+            &mut parser,
+            &mut state,
+            &tx,
+            100,
+            "test",
+        );
+        let msg = rx.try_recv().unwrap().into_text().unwrap();
+        assert!(msg.contains(r#""client_type":"spectator""#), "{msg}");
+    }
+
+    #[test]
+    fn drain_ws_feeds_raw_output_bytes() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut parser = new_parser(24, 80, 0);
+        let mut state = make_state();
+        // A character split across two frames reassembles in the parser
+        tx.send(AttachEvent::Output(vec![0xE2, 0x94])).unwrap();
+        tx.send(AttachEvent::Output(vec![0x80])).unwrap();
+        assert!(!drain_ws(&rx, &mut parser, &mut state));
+        assert_eq!(parser.screen().cell(0, 0).unwrap().contents(), "─");
     }
 
     #[test]
     fn apply_resize_recreates_parser_and_sends_resize() {
-        let mut parser = vt100::Parser::new(24, 80, 100);
+        let mut parser = new_parser(24, 80, 100);
         parser.process(b"visible content");
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let mut state = make_state();
@@ -1117,14 +1583,18 @@ mod tests {
         let vis = virtual_terminal::read_row_text(parser.screen(), 0, cols);
         assert!(vis.contains("visible content"));
         // Resize message should have been sent
-        let msg = rx.try_recv().expect("should have sent resize message");
+        let msg = rx
+            .try_recv()
+            .expect("should have sent resize message")
+            .into_text()
+            .unwrap();
         assert!(msg.contains("\"rows\":39"));
         assert!(msg.contains("\"cols\":120"));
     }
 
     #[test]
     fn apply_resize_minimum_one_row() {
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
         let mut state = make_state();
         // rows=1 means pty_rows = max(1-1, 1) = 1
@@ -1145,9 +1615,9 @@ mod tests {
     #[test]
     fn drain_ws_processes_output() {
         let (tx, rx) = std::sync::mpsc::channel();
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
-        tx.send(AttachEvent::Output("Hello".to_string())).unwrap();
+        tx.send(AttachEvent::Output(b"Hello".to_vec())).unwrap();
         let closed = drain_ws(&rx, &mut parser, &mut state);
         assert!(!closed);
         let vis = virtual_terminal::read_row_text(parser.screen(), 0, 80);
@@ -1157,7 +1627,7 @@ mod tests {
     #[test]
     fn drain_ws_updates_claude_state() {
         let (tx, rx) = std::sync::mpsc::channel();
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         assert!(matches!(state.claude_state, ClaudeState::Idle));
         tx.send(AttachEvent::StateChange(ClaudeState::Thinking))
@@ -1167,10 +1637,32 @@ mod tests {
         assert!(matches!(state.claude_state, ClaudeState::Thinking));
     }
 
+    #[test]
+    fn drain_ws_tracks_hyperlinks_and_queues_clipboard() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut parser = new_parser(24, 80, 0);
+        let mut state = make_state();
+        tx.send(AttachEvent::Output(
+            b"\x1b]8;;https://example.com\x1b\\site\x1b]8;;\x1b\\".to_vec(),
+        ))
+        .unwrap();
+        tx.send(AttachEvent::Clipboard("c".to_string(), "aGk=".to_string()))
+            .unwrap();
+        assert!(!drain_ws(&rx, &mut parser, &mut state));
+        assert_eq!(
+            parser.callbacks().hyperlinks()[0].uri,
+            "https://example.com"
+        );
+        assert_eq!(
+            state.pending_clipboard,
+            vec![("c".to_string(), "aGk=".to_string())]
+        );
+    }
+
     #[test]
     fn drain_ws_returns_true_on_close() {
         let (tx, rx) = std::sync::mpsc::channel();
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         tx.send(AttachEvent::Closed).unwrap();
         assert!(drain_ws(&rx, &mut parser, &mut state));
@@ -1179,10 +1671,10 @@ mod tests {
     #[test]
     fn drain_ws_processes_all_pending() {
         let (tx, rx) = std::sync::mpsc::channel();
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
-        tx.send(AttachEvent::Output("A".to_string())).unwrap();
-        tx.send(AttachEvent::Output("B".to_string())).unwrap();
+        tx.send(AttachEvent::Output(b"A".to_vec())).unwrap();
+        tx.send(AttachEvent::Output(b"B".to_vec())).unwrap();
         tx.send(AttachEvent::StateChange(ClaudeState::Responding))
             .unwrap();
         let closed = drain_ws(&rx, &mut parser, &mut state);
@@ -1195,7 +1687,7 @@ mod tests {
     #[test]
     fn drain_ws_empty_channel_is_noop() {
         let (_tx, rx) = std::sync::mpsc::channel();
-        let mut parser = vt100::Parser::new(24, 80, 0);
+        let mut parser = new_parser(24, 80, 0);
         let mut state = make_state();
         let closed = drain_ws(&rx, &mut parser, &mut state);
         assert!(!closed);
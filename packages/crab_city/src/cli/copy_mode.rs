@@ -0,0 +1,514 @@
+//! Copy mode for TUI attach: a frozen plain-text view of the screen and
+//! scrollback with vi-style navigation, visual selection and search. The
+//! selection is yanked to the local clipboard via OSC 52 or saved to a file.
+
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Widget;
+use std::path::PathBuf;
+
+/// A position in the snapshot: line index and char index within it.
+type Pos = (usize, usize);
+
+/// What the attach loop should do after a copy-mode key.
+#[derive(Debug, PartialEq)]
+pub(super) enum CopyAction {
+    None,
+    Exit,
+    /// Put the text on the clipboard and leave copy mode
+    Copy(String),
+    /// Write the text to the file and leave copy mode
+    Save(PathBuf, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Selection {
+    Chars(Pos),
+    Lines(usize),
+}
+
+#[derive(Debug)]
+enum Prompt {
+    Search { forward: bool, buffer: String },
+    Save { buffer: String },
+}
+
+pub(super) struct CopyMode {
+    lines: Vec<Vec<char>>,
+    cursor: Pos,
+    /// First line shown
+    top: usize,
+    /// Rows shown at the last render, for paging
+    height: usize,
+    selection: Option<Selection>,
+    prompt: Option<Prompt>,
+    last_search: Option<(String, bool)>,
+    /// One-line feedback (no match, nothing selected)
+    message: Option<String>,
+}
+
+impl CopyMode {
+    /// Open on `lines` (scrollback then screen) with `top` as the first line
+    /// on screen and the cursor at `cursor`, relative to it.
+    pub(super) fn new(lines: Vec<String>, top: usize, height: usize, cursor: Pos) -> Self {
+        let lines: Vec<Vec<char>> = if lines.is_empty() {
+            vec![Vec::new()]
+        } else {
+            lines.into_iter().map(|l| l.chars().collect()).collect()
+        };
+        let top = top.min(lines.len() - 1);
+        let row = (top + cursor.0).min(lines.len() - 1);
+        let mut mode = Self {
+            lines,
+            cursor: (row, 0),
+            top,
+            height: height.max(1),
+            selection: None,
+            prompt: None,
+            last_search: None,
+            message: None,
+        };
+        mode.cursor.1 = cursor.1.min(mode.last_col(row));
+        mode
+    }
+
+    fn last_col(&self, row: usize) -> usize {
+        self.lines[row].len().saturating_sub(1)
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        let row = row.min(self.lines.len() - 1);
+        self.cursor = (row, col.min(self.last_col(row)));
+    }
+
+    /// Keep the cursor within `height` rows starting at `top`.
+    pub(super) fn scroll_to_cursor(&mut self, height: usize) {
+        self.height = height.max(1);
+        let row = self.cursor.0;
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + self.height {
+            self.top = row + 1 - self.height;
+        }
+    }
+
+    pub(super) fn handle_key(&mut self, key: &KeyEvent) -> CopyAction {
+        self.message = None;
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(key);
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let (row, col) = self.cursor;
+        let half = (self.height / 2).max(1);
+        match key.code {
+            KeyCode::Char('q') => return CopyAction::Exit,
+            KeyCode::Esc if self.selection.is_some() => self.selection = None,
+            KeyCode::Esc => return CopyAction::Exit,
+            KeyCode::Char('u') if ctrl => self.move_to(row.saturating_sub(half), col),
+            KeyCode::Char('d') if ctrl => self.move_to(row + half, col),
+            KeyCode::Char('b') if ctrl => self.move_to(row.saturating_sub(self.height), col),
+            KeyCode::Char('f') if ctrl => self.move_to(row + self.height, col),
+            KeyCode::PageUp => self.move_to(row.saturating_sub(self.height), col),
+            KeyCode::PageDown => self.move_to(row + self.height, col),
+            KeyCode::Char('h') | KeyCode::Left => self.move_to(row, col.saturating_sub(1)),
+            KeyCode::Char('l') | KeyCode::Right => self.move_to(row, col + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_to(row.saturating_sub(1), col),
+            KeyCode::Char('j') | KeyCode::Down => self.move_to(row + 1, col),
+            KeyCode::Char('0') | KeyCode::Home => self.move_to(row, 0),
+            KeyCode::Char('$') | KeyCode::End => self.move_to(row, usize::MAX),
+            KeyCode::Char('^') => {
+                let first = self.lines[row].iter().position(|c| !c.is_whitespace());
+                self.move_to(row, first.unwrap_or(0));
+            }
+            KeyCode::Char('g') => self.move_to(0, 0),
+            KeyCode::Char('G') => self.move_to(usize::MAX, 0),
+            KeyCode::Char('w') => self.next_word(),
+            KeyCode::Char('b') => self.prev_word(),
+            KeyCode::Char('v') => {
+                self.selection = match self.selection {
+                    Some(Selection::Chars(_)) => None,
+                    _ => Some(Selection::Chars(self.cursor)),
+                }
+            }
+            KeyCode::Char('V') => {
+                self.selection = match self.selection {
+                    Some(Selection::Lines(_)) => None,
+                    _ => Some(Selection::Lines(row)),
+                }
+            }
+            KeyCode::Char('/') | KeyCode::Char('?') => {
+                self.prompt = Some(Prompt::Search {
+                    forward: key.code == KeyCode::Char('/'),
+                    buffer: String::new(),
+                });
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                if let Some((query, forward)) = self.last_search.clone() {
+                    let forward = forward == (key.code == KeyCode::Char('n'));
+                    self.search(&query, forward);
+                }
+            }
+            KeyCode::Char('y') | KeyCode::Enter => match self.selected_text() {
+                Some(text) => return CopyAction::Copy(text),
+                None => self.message = Some("Nothing selected (v or V to select)".into()),
+            },
+            KeyCode::Char('S') => {
+                if self.selection.is_some() {
+                    self.prompt = Some(Prompt::Save {
+                        buffer: String::new(),
+                    });
+                } else {
+                    self.message = Some("Nothing selected (v or V to select)".into());
+                }
+            }
+            _ => {}
+        }
+        CopyAction::None
+    }
+
+    fn handle_prompt_key(&mut self, key: &KeyEvent) -> CopyAction {
+        let Some(prompt) = &mut self.prompt else {
+            return CopyAction::None;
+        };
+        let buffer = match prompt {
+            Prompt::Search { buffer, .. } | Prompt::Save { buffer } => buffer,
+        };
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            KeyCode::Char(c) => buffer.push(c),
+            KeyCode::Enter => match self.prompt.take() {
+                Some(Prompt::Search { forward, buffer }) if !buffer.is_empty() => {
+                    self.search(&buffer, forward);
+                    self.last_search = Some((buffer, forward));
+                }
+                Some(Prompt::Save { buffer }) if !buffer.trim().is_empty() => {
+                    if let Some(text) = self.selected_text() {
+                        return CopyAction::Save(PathBuf::from(buffer.trim()), text);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        CopyAction::None
+    }
+
+    fn next_word(&mut self) {
+        let (mut row, mut col) = self.cursor;
+        // Skip the rest of the current word, then any gap (across lines)
+        let line = &self.lines[row];
+        while col < line.len() && !line[col].is_whitespace() {
+            col += 1;
+        }
+        loop {
+            let line = &self.lines[row];
+            while col < line.len() && line[col].is_whitespace() {
+                col += 1;
+            }
+            if col < line.len() || row + 1 == self.lines.len() {
+                break;
+            }
+            row += 1;
+            col = 0;
+        }
+        self.move_to(row, col);
+    }
+
+    fn prev_word(&mut self) {
+        let (mut row, mut col) = self.cursor;
+        // Step back over the gap before the cursor, then to the word's start
+        loop {
+            let line = &self.lines[row];
+            while col > 0 && line.get(col - 1).is_some_and(|c| c.is_whitespace()) {
+                col -= 1;
+            }
+            if col > 0 || row == 0 {
+                break;
+            }
+            row -= 1;
+            col = self.lines[row].len();
+        }
+        let line = &self.lines[row];
+        while col > 0 && !line[col - 1].is_whitespace() {
+            col -= 1;
+        }
+        self.move_to(row, col);
+    }
+
+    /// Jump to the next match of `query` after (or before) the cursor,
+    /// wrapping around. Case-insensitive unless the query has capitals.
+    fn search(&mut self, query: &str, forward: bool) {
+        let fold = !query.chars().any(char::is_uppercase);
+        let needle: Vec<char> = if fold {
+            query.to_lowercase().chars().collect()
+        } else {
+            query.chars().collect()
+        };
+        let matches_at = |line: &[char], col: usize| -> bool {
+            col + needle.len() <= line.len()
+                && line[col..col + needle.len()]
+                    .iter()
+                    .zip(&needle)
+                    .all(|(a, b)| {
+                        if fold {
+                            a.to_lowercase().eq(b.to_lowercase())
+                        } else {
+                            a == b
+                        }
+                    })
+        };
+
+        let total = self.lines.len();
+        let (row, col) = self.cursor;
+        for step in 0..=total {
+            let r = if forward {
+                (row + step) % total
+            } else {
+                (row + total - step % total) % total
+            };
+            let line = &self.lines[r];
+            let found = if forward {
+                let start = if step == 0 { col + 1 } else { 0 };
+                (start..line.len()).find(|&c| matches_at(line, c))
+            } else {
+                let end = if step == 0 { col } else { line.len() };
+                (0..end).rev().find(|&c| matches_at(line, c))
+            };
+            if let Some(c) = found {
+                self.move_to(r, c);
+                return;
+            }
+        }
+        self.message = Some(format!("Not found: {}", query));
+    }
+
+    /// The selected text, lines joined by `\n`.
+    fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection_bounds()?;
+        let text = match self.selection? {
+            Selection::Lines(_) => (start.0..=end.0)
+                .map(|r| self.lines[r].iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Selection::Chars(_) => (start.0..=end.0)
+                .map(|r| {
+                    let line = &self.lines[r];
+                    let from = if r == start.0 { start.1 } else { 0 };
+                    let to = if r == end.0 { end.1 + 1 } else { line.len() };
+                    line[from.min(line.len())..to.min(line.len())]
+                        .iter()
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        Some(text)
+    }
+
+    /// Ordered (start, end) of the selection, inclusive.
+    fn selection_bounds(&self) -> Option<(Pos, Pos)> {
+        let anchor = match self.selection? {
+            Selection::Chars(pos) => pos,
+            Selection::Lines(row) => (row, 0),
+        };
+        let (start, end) = if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        };
+        Some(match self.selection? {
+            Selection::Lines(_) => ((start.0, 0), (end.0, usize::MAX)),
+            Selection::Chars(_) => (start, end),
+        })
+    }
+
+    pub(super) fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// Status bar text for the current state.
+    pub(super) fn status_text(&self) -> String {
+        match &self.prompt {
+            Some(Prompt::Search { forward, buffer }) => {
+                format!(" {}{}\u{2588}", if *forward { '/' } else { '?' }, buffer)
+            }
+            Some(Prompt::Save { buffer }) => {
+                format!(" Save selection to: {}\u{2588}", buffer)
+            }
+            None => {
+                let position = format!("line {}/{}", self.cursor.0 + 1, self.lines.len());
+                let detail = match (&self.message, self.selection) {
+                    (Some(message), _) => message.clone(),
+                    (None, Some(_)) => "y copy \u{b7} S save to file \u{b7} esc clear".into(),
+                    (None, None) => {
+                        "v/V select \u{b7} / ? search \u{b7} n/N next \u{b7} q exit".into()
+                    }
+                };
+                format!(" COPY \u{2502} {} \u{2502} {} ", position, detail)
+            }
+        }
+    }
+}
+
+/// Renders the copy-mode view: plain text with the selection and cursor
+/// highlighted.
+pub(super) struct CopyModeWidget<'a> {
+    pub(super) copy: &'a CopyMode,
+}
+
+impl Widget for CopyModeWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let copy = self.copy;
+        let bounds = copy.selection_bounds();
+        let selected = Style::default().add_modifier(Modifier::REVERSED);
+        let cursor = Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD);
+
+        for (i, line) in copy
+            .lines
+            .iter()
+            .enumerate()
+            .skip(copy.top)
+            .take(area.height as usize)
+        {
+            let y = area.y + (i - copy.top) as u16;
+            let in_selection = |col: usize| {
+                bounds.is_some_and(|(start, end)| (i, col) >= start && (i, col) <= end)
+            };
+            // An empty line or the end of one still shows the cursor
+            let mut cells: Vec<char> = line.clone();
+            if i == copy.cursor.0 && cells.len() <= copy.cursor.1 {
+                cells.push(' ');
+            }
+            let spans: Vec<Span> = cells
+                .iter()
+                .enumerate()
+                .map(|(col, c)| {
+                    let style = if (i, col) == copy.cursor {
+                        cursor
+                    } else if in_selection(col) {
+                        selected
+                    } else {
+                        Style::default()
+                    };
+                    Span::styled(c.to_string(), style)
+                })
+                .collect();
+            Line::from(spans).render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn press(copy: &mut CopyMode, keys: &str) -> CopyAction {
+        let mut last = CopyAction::None;
+        for c in keys.chars() {
+            last = copy.handle_key(&key(KeyCode::Char(c)));
+        }
+        last
+    }
+
+    fn sample() -> CopyMode {
+        let lines = [
+            "$ cargo test",
+            "running 2 tests",
+            "test ok",
+            "",
+            "done in 1.2s",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        // Three lines of scrollback, cursor on the last screen row
+        CopyMode::new(lines, 3, 2, (1, 0))
+    }
+
+    #[test]
+    fn charwise_selection_across_lines() {
+        let mut copy = sample();
+        assert_eq!(copy.cursor, (4, 0));
+        press(&mut copy, "gjw");
+        assert_eq!(copy.cursor, (1, 8));
+        press(&mut copy, "vj$");
+        assert_eq!(
+            press(&mut copy, "y"),
+            CopyAction::Copy("2 tests\ntest ok".to_string())
+        );
+    }
+
+    #[test]
+    fn linewise_selection_and_save() {
+        let mut copy = sample();
+        press(&mut copy, "gVj");
+        assert_eq!(copy.handle_key(&key(KeyCode::Char('S'))), CopyAction::None);
+        press(&mut copy, "out.txt");
+        assert_eq!(
+            copy.handle_key(&key(KeyCode::Enter)),
+            CopyAction::Save(
+                PathBuf::from("out.txt"),
+                "$ cargo test\nrunning 2 tests".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn search_wraps_and_repeats() {
+        let mut copy = sample();
+        press(&mut copy, "/TEST");
+        copy.handle_key(&key(KeyCode::Enter));
+        assert!(copy.status_text().contains("Not found"));
+
+        press(&mut copy, "/test");
+        copy.handle_key(&key(KeyCode::Enter));
+        // Smartcase: wraps from the last line to the first match
+        assert_eq!(copy.cursor, (0, 8));
+        press(&mut copy, "n");
+        assert_eq!(copy.cursor, (1, 10));
+        press(&mut copy, "N");
+        assert_eq!(copy.cursor, (0, 8));
+    }
+
+    #[test]
+    fn words_cross_blank_lines() {
+        let mut copy = sample();
+        press(&mut copy, "gjjw");
+        // "test ok" → "ok", then over the blank line to "done"
+        assert_eq!(copy.cursor, (2, 5));
+        press(&mut copy, "w");
+        assert_eq!(copy.cursor, (4, 0));
+        press(&mut copy, "b");
+        assert_eq!(copy.cursor, (2, 5));
+    }
+
+    #[test]
+    fn escape_clears_selection_before_exiting() {
+        let mut copy = sample();
+        press(&mut copy, "v");
+        assert_eq!(copy.handle_key(&key(KeyCode::Esc)), CopyAction::None);
+        assert_eq!(press(&mut copy, "y"), CopyAction::None);
+        assert_eq!(copy.handle_key(&key(KeyCode::Esc)), CopyAction::Exit);
+    }
+
+    #[test]
+    fn viewport_follows_cursor() {
+        let mut copy = sample();
+        press(&mut copy, "g");
+        copy.scroll_to_cursor(2);
+        assert_eq!(copy.top, 0);
+        press(&mut copy, "G");
+        copy.scroll_to_cursor(2);
+        assert_eq!(copy.top, 3);
+    }
+}
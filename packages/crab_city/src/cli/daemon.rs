@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
 use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 
 use crab_city::config::CrabCityConfig;
 
+use super::remote;
+
 #[derive(Debug, thiserror::Error)]
 pub enum DaemonError {
     #[error("server is unavailable")]
@@ -45,15 +48,97 @@ pub struct DaemonInfo {
     pub pid: u32,
     pub port: u16,
     pub host: String,
+    /// Set when this is a server on another machine (`--remote`) rather
+    /// than the local daemon
+    pub remote: Option<RemoteServer>,
+}
+
+/// A remote Crab City server and the token from `crab login`, if any.
+#[derive(Debug, Clone)]
+pub struct RemoteServer {
+    /// Scheme, host and port, e.g. `https://crab.example.com`
+    pub origin: String,
+    pub token: Option<String>,
 }
 
 impl DaemonInfo {
+    pub fn remote(origin: String, token: Option<String>) -> Self {
+        Self {
+            pid: 0,
+            port: 0,
+            host: String::new(),
+            remote: Some(RemoteServer { origin, token }),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.remote.is_some()
+    }
+
     pub fn base_url(&self) -> String {
-        format!("http://{}:{}", self.host, self.port)
+        match &self.remote {
+            Some(remote) => remote.origin.clone(),
+            None => format!("http://{}:{}", self.host, self.port),
+        }
     }
 
     pub fn mux_ws_url(&self) -> String {
-        format!("ws://{}:{}/api/ws", self.host, self.port)
+        match &self.remote {
+            Some(remote) => {
+                let origin = &remote.origin;
+                match origin.strip_prefix("https://") {
+                    Some(rest) => format!("wss://{}/api/ws", rest),
+                    None => format!("ws://{}/api/ws", origin.trim_start_matches("http://")),
+                }
+            }
+            None => format!("ws://{}:{}/api/ws", self.host, self.port),
+        }
+    }
+
+    /// `Authorization` header value for a remote server's token.
+    fn authorization(&self) -> Option<String> {
+        let token = self.remote.as_ref()?.token.as_ref()?;
+        Some(format!("Bearer {}", token))
+    }
+
+    fn auth_headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(value) = self.authorization()
+            && let Ok(value) = reqwest::header::HeaderValue::from_str(&value)
+        {
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        headers
+    }
+
+    /// HTTP client for this server, carrying the login token when remote.
+    pub fn http(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .default_headers(self.auth_headers())
+            .build()
+            .unwrap_or_default()
+    }
+
+    /// Blocking HTTP client for this server (see [`Self::http`]).
+    pub fn blocking_http(&self) -> reqwest::blocking::Client {
+        reqwest::blocking::Client::builder()
+            .default_headers(self.auth_headers())
+            .build()
+            .unwrap_or_default()
+    }
+
+    /// Connect to the mux WebSocket, with the login token when remote.
+    pub async fn connect_mux(&self) -> Result<super::handshake::WsStream, tungstenite::Error> {
+        let mut request = self.mux_ws_url().into_client_request()?;
+        if let Some(value) = self.authorization()
+            && let Ok(value) = value.parse()
+        {
+            request
+                .headers_mut()
+                .insert(tungstenite::http::header::AUTHORIZATION, value);
+        }
+        let (ws, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(ws)
     }
 }
 
@@ -87,6 +172,7 @@ pub fn check_daemon(config: &CrabCityConfig) -> Option<DaemonInfo> {
         pid,
         port,
         host: "127.0.0.1".to_string(),
+        remote: None,
     })
 }
 
@@ -137,6 +223,9 @@ pub fn start_daemon(config: &CrabCityConfig) -> Result<()> {
 
 /// Require a daemon to already be running. Returns an error if none is found.
 pub async fn require_running_daemon(config: &CrabCityConfig) -> Result<DaemonInfo> {
+    if let Some(url) = &config.remote {
+        return remote::connect(config, url).await;
+    }
     if let Some(info) = check_daemon(config)
         && health_check(&info).await
     {
@@ -147,6 +236,11 @@ pub async fn require_running_daemon(config: &CrabCityConfig) -> Result<DaemonInf
 
 /// Ensure a daemon is running. Start one if needed, then wait for it to be healthy.
 pub async fn ensure_daemon(config: &CrabCityConfig) -> Result<DaemonInfo> {
+    // A remote server is never started from here
+    if let Some(url) = &config.remote {
+        return remote::connect(config, url).await;
+    }
+
     // First check if already running
     if let Some(info) = check_daemon(config) {
         // Verify it's actually healthy
@@ -198,6 +292,9 @@ pub async fn ensure_daemon(config: &CrabCityConfig) -> Result<DaemonInfo> {
 /// Try to rediscover a running daemon from PID/port files on disk.
 /// Used when the current DaemonInfo is stale (e.g. server restarted on a new port).
 pub async fn rediscover_daemon(config: &CrabCityConfig) -> Option<DaemonInfo> {
+    if let Some(url) = &config.remote {
+        return remote::connect(config, url).await.ok();
+    }
     let info = check_daemon(config)?;
     if health_check(&info).await {
         Some(info)
@@ -206,7 +303,7 @@ pub async fn rediscover_daemon(config: &CrabCityConfig) -> Option<DaemonInfo> {
     }
 }
 
-/// Public health check (used by `cli::auth`).
+/// Public health check (used by `cli::auth` and `cli::remote`).
 pub async fn health_check_pub(info: &DaemonInfo) -> bool {
     health_check(info).await
 }
@@ -214,14 +311,18 @@ pub async fn health_check_pub(info: &DaemonInfo) -> bool {
 /// Health check the daemon via GET /health.
 async fn health_check(info: &DaemonInfo) -> bool {
     let url = format!("{}/health", info.base_url());
-    match reqwest::get(&url).await {
+    match info.http().get(&url).send().await {
         Ok(resp) => resp.status().is_success(),
         Err(_) => false,
     }
 }
 
-/// Send SIGTERM to the daemon process for a graceful shutdown.
+/// Send SIGTERM to the daemon process for a graceful shutdown. A remote
+/// server is left alone.
 pub fn stop_daemon(info: &DaemonInfo) {
+    if info.is_remote() {
+        return;
+    }
     #[cfg(unix)]
     {
         use nix::sys::signal::{self, Signal};
@@ -250,6 +351,7 @@ mod tests {
             db_path: data_dir.join("crabcity.db"),
             exports_dir: data_dir.join("exports"),
             logs_dir,
+            remote: None,
         };
         (config, tmp)
     }
@@ -388,6 +490,7 @@ mod tests {
             pid: 12345,
             port,
             host: host.to_string(),
+            remote: None,
         }
     }
 
@@ -409,6 +512,34 @@ mod tests {
         assert_eq!(info.mux_ws_url(), "ws://192.168.1.1:9000/api/ws");
     }
 
+    #[test]
+    fn remote_urls_use_the_origin() {
+        let info = DaemonInfo::remote("https://crab.example.com".to_string(), None);
+        assert_eq!(info.base_url(), "https://crab.example.com");
+        assert_eq!(info.mux_ws_url(), "wss://crab.example.com/api/ws");
+
+        let info = DaemonInfo::remote("http://10.0.0.5:8080".to_string(), None);
+        assert_eq!(info.mux_ws_url(), "ws://10.0.0.5:8080/api/ws");
+    }
+
+    #[test]
+    fn only_remote_tokens_are_sent() {
+        let local = make_daemon_info("127.0.0.1", 9000);
+        assert!(local.auth_headers().is_empty());
+
+        let anonymous = DaemonInfo::remote("https://crab.example.com".to_string(), None);
+        assert!(anonymous.auth_headers().is_empty());
+
+        let remote = DaemonInfo::remote(
+            "https://crab.example.com".to_string(),
+            Some("tok123".to_string()),
+        );
+        assert_eq!(
+            remote.auth_headers()[reqwest::header::AUTHORIZATION],
+            "Bearer tok123"
+        );
+    }
+
     // -- rediscover_daemon --
 
     #[tokio::test]
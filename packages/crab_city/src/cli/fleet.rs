@@ -0,0 +1,899 @@
+//! Fleet dashboard: every instance at a glance, most in need of attention
+//! first, with a live preview of the selected screen. Inbox items can be
+//! dismissed and pending tasks dispatched without the web UI.
+
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Alignment, Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite;
+
+use crab_city::inference::ClaudeState;
+use crab_city::instance_manager::ClaudeInstance;
+use crab_city::models::InboxItem;
+use crab_city::ws::{ClientMessage, ServerMessage, Topic};
+
+use super::daemon::DaemonInfo;
+use super::handshake;
+
+/// How often the selected instance's preview is refreshed.
+const PREVIEW_INTERVAL: Duration = Duration::from_secs(1);
+/// How long an action's error stays in the bottom bar.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(4);
+
+pub enum FleetResult {
+    Attach(String),
+    Back,
+}
+
+/// Updates fed to the dashboard loop by the background tasks.
+enum FleetEvent {
+    Server(Box<ServerMessage>),
+    Tasks(Vec<FleetTask>),
+    Preview {
+        instance_id: String,
+        lines: Vec<String>,
+    },
+    /// An action failed; shown in the bottom bar
+    Failed(String),
+    Disconnected,
+}
+
+/// Requests from the dashboard loop, carried out over HTTP.
+enum FleetAction {
+    Dismiss(String),
+    Dispatch { task_id: i64, instance_id: String },
+}
+
+/// The fields of a task the dashboard shows.
+#[derive(Debug, Clone, Deserialize)]
+struct FleetTask {
+    id: i64,
+    title: String,
+    status: String,
+    instance_id: Option<String>,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    sort_order: f64,
+}
+
+/// How urgently an instance wants a human, most urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Attention {
+    NeedsInput,
+    Error,
+    Finished,
+    Working,
+    Idle,
+    Stopped,
+}
+
+/// Everything the dashboard knows, built from the mux WebSocket.
+#[derive(Default)]
+struct Fleet {
+    instances: Vec<ClaudeInstance>,
+    inbox: HashMap<String, InboxItem>,
+    /// Pending tasks, in queue order
+    tasks: Vec<FleetTask>,
+    preview: Option<(String, Vec<String>)>,
+}
+
+impl Fleet {
+    fn apply(&mut self, msg: ServerMessage) {
+        match msg {
+            ServerMessage::Sequenced { message, .. } => self.apply(*message),
+            ServerMessage::InstanceList { instances } => self.instances = instances,
+            ServerMessage::InstanceCreated { instance }
+                if !self.instances.iter().any(|i| i.id == instance.id) =>
+            {
+                self.instances.push(instance);
+            }
+            ServerMessage::InstanceStopped { instance_id } => {
+                self.instances.retain(|i| i.id != instance_id);
+                self.inbox.remove(&instance_id);
+            }
+            ServerMessage::InstanceRenamed {
+                instance_id,
+                custom_name,
+            } => {
+                if let Some(inst) = self.instance_mut(&instance_id) {
+                    inst.custom_name = custom_name;
+                }
+            }
+            ServerMessage::StateChange {
+                instance_id,
+                state,
+                entered_at,
+                ..
+            } => {
+                if let Some(inst) = self.instance_mut(&instance_id) {
+                    inst.claude_state = Some(state);
+                    inst.state_entered_at = entered_at;
+                }
+            }
+            ServerMessage::InboxList { items } => {
+                self.inbox = items
+                    .into_iter()
+                    .map(|item| (item.instance_id.clone(), item))
+                    .collect();
+            }
+            ServerMessage::InboxUpdate { instance_id, item } => match item {
+                Some(item) => {
+                    self.inbox.insert(instance_id, item);
+                }
+                None => {
+                    self.inbox.remove(&instance_id);
+                }
+            },
+            ServerMessage::TaskUpdate { task } => {
+                if let Ok(task) = serde_json::from_value::<FleetTask>(task) {
+                    self.upsert_task(task);
+                }
+            }
+            ServerMessage::TaskDeleted { task_id } => self.tasks.retain(|t| t.id != task_id),
+            _ => {}
+        }
+    }
+
+    fn instance_mut(&mut self, id: &str) -> Option<&mut ClaudeInstance> {
+        self.instances.iter_mut().find(|i| i.id == id)
+    }
+
+    fn set_tasks(&mut self, tasks: Vec<FleetTask>) {
+        self.tasks.clear();
+        for task in tasks {
+            self.upsert_task(task);
+        }
+    }
+
+    /// Track a pending task; anything else leaves the list.
+    fn upsert_task(&mut self, task: FleetTask) {
+        self.tasks.retain(|t| t.id != task.id);
+        if task.status == "pending" && !task.is_deleted {
+            self.tasks.push(task);
+            self.tasks
+                .sort_by(|a, b| a.sort_order.total_cmp(&b.sort_order));
+        }
+    }
+
+    fn attention(&self, inst: &ClaudeInstance) -> Attention {
+        if !inst.running {
+            return Attention::Stopped;
+        }
+        match self.inbox.get(&inst.id).map(|i| i.event_type.as_str()) {
+            Some("needs_input") => return Attention::NeedsInput,
+            Some("error") => return Attention::Error,
+            Some(_) => return Attention::Finished,
+            None => {}
+        }
+        match &inst.claude_state {
+            Some(ClaudeState::WaitingForInput { .. }) => Attention::NeedsInput,
+            Some(state) if state.is_active() => Attention::Working,
+            _ => Attention::Idle,
+        }
+    }
+
+    /// Instances, most urgent first; within a level, waiting longest first.
+    fn rows(&self) -> Vec<&ClaudeInstance> {
+        let mut rows: Vec<&ClaudeInstance> = self.instances.iter().collect();
+        rows.sort_by_cached_key(|inst| {
+            let since = self
+                .inbox
+                .get(&inst.id)
+                .map(|i| i.created_at)
+                .or(inst.state_entered_at)
+                .unwrap_or(i64::MAX);
+            (self.attention(inst), since, display_name(inst).to_string())
+        });
+        rows
+    }
+
+    /// Pending tasks for a dispatch to `instance_id`: its own first, then
+    /// unassigned ones.
+    fn dispatchable_tasks(&self, instance_id: &str) -> Vec<&FleetTask> {
+        let mut tasks: Vec<&FleetTask> = self
+            .tasks
+            .iter()
+            .filter(|t| t.instance_id.as_deref().is_none_or(|id| id == instance_id))
+            .collect();
+        tasks.sort_by_key(|t| t.instance_id.is_none());
+        tasks
+    }
+
+    fn pending_count(&self, instance_id: &str) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| t.instance_id.as_deref() == Some(instance_id))
+            .count()
+    }
+}
+
+fn display_name(inst: &ClaudeInstance) -> &str {
+    inst.custom_name.as_deref().unwrap_or(&inst.name)
+}
+
+/// Connect to the daemon and run the dashboard until the user attaches or
+/// leaves.
+pub async fn run_live_fleet(
+    terminal: &mut DefaultTerminal,
+    daemon: &DaemonInfo,
+) -> Result<FleetResult> {
+    let mut ws = daemon
+        .connect_mux()
+        .await
+        .context("Failed to connect to the daemon")?;
+    handshake::handshake(&mut ws, &[]).await?;
+    let subscribe = ClientMessage::Subscribe {
+        topics: vec![
+            Topic::Instances { instance_id: None },
+            Topic::Inbox,
+            Topic::Tasks,
+        ],
+    };
+    ws.send(tungstenite::Message::Text(serde_json::to_string(
+        &subscribe,
+    )?))
+    .await?;
+
+    let base_url = daemon.base_url();
+    let client = daemon.http();
+    let (event_tx, event_rx) = mpsc::channel();
+    let (selected_tx, selected_rx) = watch::channel(None::<String>);
+    let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let (_, mut ws_read) = ws.split();
+    let tx = event_tx.clone();
+    let reader = tokio::spawn(async move {
+        // The instance list and inbox snapshot arrive first, then live events
+        while let Some(Ok(msg)) = ws_read.next().await {
+            let tungstenite::Message::Text(text) = msg else {
+                continue;
+            };
+            if let Ok(msg) = serde_json::from_str::<ServerMessage>(&text)
+                && tx.send(FleetEvent::Server(Box::new(msg))).is_err()
+            {
+                return;
+            }
+        }
+        let _ = tx.send(FleetEvent::Disconnected);
+    });
+    let tasks = tokio::spawn(load_tasks(
+        client.clone(),
+        base_url.clone(),
+        event_tx.clone(),
+    ));
+    let preview = tokio::spawn(poll_preview(
+        client.clone(),
+        base_url.clone(),
+        selected_rx,
+        event_tx.clone(),
+    ));
+    let actions = tokio::spawn(run_actions(client, base_url.clone(), action_rx, event_tx));
+
+    let result = tokio::task::block_in_place(|| {
+        fleet_loop(terminal, &base_url, event_rx, &selected_tx, &action_tx)
+    });
+
+    for task in [reader, tasks, preview, actions] {
+        task.abort();
+    }
+    result
+}
+
+async fn load_tasks(client: reqwest::Client, base_url: String, events: mpsc::Sender<FleetEvent>) {
+    let url = format!("{}/api/tasks?status=pending", base_url);
+    let tasks = async {
+        client
+            .get(&url)
+            .send()
+            .await?
+            .json::<Vec<FleetTask>>()
+            .await
+    };
+    match tasks.await {
+        Ok(tasks) => {
+            let _ = events.send(FleetEvent::Tasks(tasks));
+        }
+        Err(e) => {
+            let _ = events.send(FleetEvent::Failed(format!("Failed to load tasks: {}", e)));
+        }
+    }
+}
+
+/// Refresh the selected instance's screen every second, and right away
+/// when the selection changes.
+async fn poll_preview(
+    client: reqwest::Client,
+    base_url: String,
+    mut selected: watch::Receiver<Option<String>>,
+    events: mpsc::Sender<FleetEvent>,
+) {
+    loop {
+        let instance_id = selected.borrow_and_update().clone();
+        if let Some(instance_id) = instance_id {
+            let url = format!(
+                "{}/api/instances/{}/output?text=screen",
+                base_url, instance_id
+            );
+            let body = async {
+                client
+                    .get(&url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<serde_json::Value>()
+                    .await
+            };
+            if let Ok(body) = body.await {
+                let lines = body["lines"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|l| l.as_str().map(str::to_string))
+                    .collect();
+                if events
+                    .send(FleetEvent::Preview { instance_id, lines })
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+        tokio::select! {
+            changed = selected.changed() => if changed.is_err() { return },
+            _ = tokio::time::sleep(PREVIEW_INTERVAL) => {}
+        }
+    }
+}
+
+async fn run_actions(
+    client: reqwest::Client,
+    base_url: String,
+    mut actions: tokio::sync::mpsc::UnboundedReceiver<FleetAction>,
+    events: mpsc::Sender<FleetEvent>,
+) {
+    while let Some(action) = actions.recv().await {
+        let result = match action {
+            FleetAction::Dismiss(instance_id) => client
+                .post(format!("{}/api/inbox/{}/dismiss", base_url, instance_id))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map(drop)
+                .map_err(|e| format!("Failed to dismiss: {}", e)),
+            FleetAction::Dispatch {
+                task_id,
+                instance_id,
+            } => dispatch_task(&client, &base_url, task_id, &instance_id)
+                .await
+                .map_err(|e| format!("Failed to dispatch task: {}", e)),
+        };
+        if let Err(message) = result
+            && events.send(FleetEvent::Failed(message)).is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Assign the task to the instance and type it in; the daemon marks it in
+/// progress and broadcasts the update.
+async fn dispatch_task(
+    client: &reqwest::Client,
+    base_url: &str,
+    task_id: i64,
+    instance_id: &str,
+) -> Result<()> {
+    client
+        .patch(format!("{}/api/tasks/{}", base_url, task_id))
+        .json(&serde_json::json!({ "instance_id": instance_id }))
+        .send()
+        .await?
+        .error_for_status()?;
+    let resp = client
+        .post(format!("{}/api/tasks/{}/send", base_url, task_id))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        anyhow::bail!("{} {}", status, resp.text().await.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Choosing a task to dispatch to `instance_id`.
+struct TaskChooser {
+    instance_id: String,
+    state: ListState,
+}
+
+fn fleet_loop(
+    terminal: &mut DefaultTerminal,
+    base_url: &str,
+    events: mpsc::Receiver<FleetEvent>,
+    selected_tx: &watch::Sender<Option<String>>,
+    actions: &tokio::sync::mpsc::UnboundedSender<FleetAction>,
+) -> Result<FleetResult> {
+    let mut fleet = Fleet::default();
+    let mut state = ListState::default().with_selected(Some(0));
+    let mut chooser: Option<TaskChooser> = None;
+    let mut status: Option<(String, Instant)> = None;
+    let mut connected = true;
+
+    loop {
+        let selected_id = state
+            .selected()
+            .and_then(|i| fleet.rows().get(i).map(|inst| inst.id.clone()));
+        while let Ok(ev) = events.try_recv() {
+            match ev {
+                FleetEvent::Server(msg) => fleet.apply(*msg),
+                FleetEvent::Tasks(tasks) => fleet.set_tasks(tasks),
+                FleetEvent::Preview { instance_id, lines } => {
+                    fleet.preview = Some((instance_id, lines));
+                }
+                FleetEvent::Failed(message) => status = Some((message, Instant::now())),
+                FleetEvent::Disconnected => connected = false,
+            }
+        }
+
+        let rows = fleet.rows();
+        // Keep the same instance selected as rows re-sort
+        let index = selected_id
+            .and_then(|id| rows.iter().position(|inst| inst.id == id))
+            .unwrap_or_else(|| state.selected().unwrap_or(0))
+            .min(rows.len().saturating_sub(1));
+        state.select(Some(index));
+        let selected = rows.get(index).copied();
+        selected_tx.send_if_modified(|current| {
+            let id = selected.map(|inst| inst.id.clone());
+            let changed = *current != id;
+            *current = id;
+            changed
+        });
+        if let Some(c) = &chooser
+            && selected.is_none_or(|inst| inst.id != c.instance_id)
+        {
+            chooser = None;
+        }
+        if status
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() > STATUS_TIMEOUT)
+        {
+            status = None;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|inst| fleet_row(&fleet, inst, now))
+            .collect();
+        let dispatchable = chooser
+            .as_ref()
+            .map(|c| fleet.dispatchable_tasks(&c.instance_id))
+            .unwrap_or_default();
+
+        terminal.draw(|frame| {
+            let [list_area, detail_area] =
+                Layout::vertical([Constraint::Percentage(45), Constraint::Percentage(55)])
+                    .areas(frame.area());
+
+            let bottom_bar = match (&status, &chooser) {
+                (Some((message, _)), _) => Line::styled(
+                    format!(" {} ", message),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                (None, Some(_)) => Line::raw(" ↑↓ choose · enter dispatch · esc cancel "),
+                (None, None) => Line::raw(
+                    " ↑↓ navigate · enter attach · d dismiss · t dispatch task · q/esc back ",
+                ),
+            };
+            let title = if connected {
+                " crab: fleet ".to_string()
+            } else {
+                " crab: fleet (disconnected) ".to_string()
+            };
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .title(title)
+                        .title(
+                            Line::styled(
+                                format!(" {} ", base_url),
+                                Style::default().add_modifier(Modifier::DIM),
+                            )
+                            .alignment(Alignment::Right),
+                        )
+                        .title_bottom(bottom_bar)
+                        .borders(Borders::ALL)
+                        .padding(Padding::horizontal(1)),
+                )
+                .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+                .highlight_symbol("▸ ");
+            frame.render_stateful_widget(list, list_area, &mut state);
+
+            match (&mut chooser, selected) {
+                (Some(c), Some(inst)) => {
+                    let items: Vec<ListItem> = if dispatchable.is_empty() {
+                        vec![ListItem::new(Line::styled(
+                            "No pending tasks",
+                            Style::default().add_modifier(Modifier::DIM),
+                        ))]
+                    } else {
+                        dispatchable
+                            .iter()
+                            .map(|t| {
+                                let scope = if t.instance_id.is_some() {
+                                    ""
+                                } else {
+                                    " (unassigned)"
+                                };
+                                ListItem::new(Line::from(vec![
+                                    Span::raw(t.title.clone()),
+                                    Span::styled(
+                                        scope,
+                                        Style::default().add_modifier(Modifier::DIM),
+                                    ),
+                                ]))
+                            })
+                            .collect()
+                    };
+                    let list = List::new(items)
+                        .block(
+                            Block::default()
+                                .title(format!(" dispatch to {} ", display_name(inst)))
+                                .borders(Borders::ALL)
+                                .padding(Padding::horizontal(1)),
+                        )
+                        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                        .highlight_symbol("▸ ");
+                    frame.render_stateful_widget(list, detail_area, &mut c.state);
+                }
+                (_, Some(inst)) => {
+                    let lines = fleet
+                        .preview
+                        .as_ref()
+                        .filter(|(id, _)| *id == inst.id)
+                        .map(|(_, lines)| preview_tail(lines, detail_area.height.saturating_sub(2)))
+                        .unwrap_or_default();
+                    let preview =
+                        Paragraph::new(lines.into_iter().map(Line::raw).collect::<Vec<Line>>())
+                            .block(
+                                Block::default()
+                                    .title(format!(" {} ", display_name(inst)))
+                                    .title(
+                                        Line::styled(
+                                            format!(" {} ", inst.working_dir),
+                                            Style::default().add_modifier(Modifier::DIM),
+                                        )
+                                        .alignment(Alignment::Right),
+                                    )
+                                    .borders(Borders::ALL),
+                            );
+                    frame.render_widget(preview, detail_area);
+                }
+                (_, None) => {
+                    let empty = Paragraph::new("No running instances")
+                        .alignment(Alignment::Center)
+                        .block(Block::default().borders(Borders::ALL));
+                    frame.render_widget(empty, detail_area);
+                }
+            }
+        })?;
+
+        // Poll with a short timeout so we can process events between frames
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if let Some(c) = &mut chooser {
+            let total = dispatchable.len();
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => chooser = None,
+                KeyCode::Down | KeyCode::Char('j') if total > 0 => {
+                    let i = c.state.selected().unwrap_or(0);
+                    c.state.select(Some((i + 1) % total));
+                }
+                KeyCode::Up | KeyCode::Char('k') if total > 0 => {
+                    let i = c.state.selected().unwrap_or(0);
+                    c.state.select(Some(if i == 0 { total - 1 } else { i - 1 }));
+                }
+                KeyCode::Enter => {
+                    if let Some(task) = c.state.selected().and_then(|i| dispatchable.get(i)) {
+                        let _ = actions.send(FleetAction::Dispatch {
+                            task_id: task.id,
+                            instance_id: c.instance_id.clone(),
+                        });
+                    }
+                    chooser = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let total = rows.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(FleetResult::Back),
+            KeyCode::Down | KeyCode::Char('j') if total > 0 => {
+                state.select(Some((index + 1) % total));
+            }
+            KeyCode::Up | KeyCode::Char('k') if total > 0 => {
+                state.select(Some(if index == 0 { total - 1 } else { index - 1 }));
+            }
+            KeyCode::Enter => {
+                if let Some(inst) = selected {
+                    return Ok(FleetResult::Attach(inst.id.clone()));
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(inst) = selected
+                    && fleet.inbox.contains_key(&inst.id)
+                {
+                    let _ = actions.send(FleetAction::Dismiss(inst.id.clone()));
+                }
+            }
+            KeyCode::Char('t') => {
+                if let Some(inst) = selected {
+                    chooser = Some(TaskChooser {
+                        instance_id: inst.id.clone(),
+                        state: ListState::default().with_selected(Some(0)),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn fleet_row<'a>(fleet: &Fleet, inst: &ClaudeInstance, now: i64) -> ListItem<'a> {
+    let attention = fleet.attention(inst);
+    let style = match attention {
+        Attention::NeedsInput => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        Attention::Error => Style::default().fg(Color::Red),
+        Attention::Finished => Style::default().fg(Color::Yellow),
+        Attention::Working => Style::default().fg(Color::Green),
+        Attention::Idle => Style::default(),
+        Attention::Stopped => Style::default().add_modifier(Modifier::DIM),
+    };
+    let age = inst
+        .state_entered_at
+        .map(|at| format_age(now - at))
+        .unwrap_or_default();
+    let inbox = fleet
+        .inbox
+        .get(&inst.id)
+        .map(inbox_label)
+        .unwrap_or_default();
+    let tasks = match fleet.pending_count(&inst.id) {
+        0 => String::new(),
+        1 => "1 task".to_string(),
+        n => format!("{} tasks", n),
+    };
+
+    ListItem::new(Line::from(vec![
+        Span::styled(
+            format!("{:<20}", display_name(inst)),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!(" {:<24}", state_label(inst)), style),
+        Span::styled(
+            format!(" {:>4}", age),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+        Span::styled(format!("  {:<14}", inbox), style),
+        Span::raw(tasks),
+    ]))
+}
+
+fn state_label(inst: &ClaudeInstance) -> String {
+    if !inst.running {
+        return "stopped".to_string();
+    }
+    match &inst.claude_state {
+        None => "running".to_string(),
+        Some(ClaudeState::Initializing | ClaudeState::Starting) => "starting".to_string(),
+        Some(ClaudeState::Idle) => "idle".to_string(),
+        Some(ClaudeState::Thinking) => "thinking".to_string(),
+        Some(ClaudeState::Responding) => "responding".to_string(),
+        Some(ClaudeState::ToolExecuting { tool }) => format!("running {}", tool),
+        Some(ClaudeState::WaitingForInput { .. }) => "waiting for input".to_string(),
+    }
+}
+
+fn inbox_label(item: &InboxItem) -> String {
+    match item.event_type.as_str() {
+        "needs_input" => "needs input".to_string(),
+        "error" => "error".to_string(),
+        "completed_turn" if item.turn_count > 1 => format!("done ×{}", item.turn_count),
+        "completed_turn" => "done".to_string(),
+        other => other.replace('_', " "),
+    }
+}
+
+/// Compact time-in-state: `45s`, `12m`, `3h`, `2d`.
+fn format_age(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// The last `height` lines of the screen above its trailing blank rows.
+fn preview_tail(lines: &[String], height: u16) -> Vec<String> {
+    let end = lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    let start = end.saturating_sub(height as usize);
+    lines[start..end].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crab_city::instance_manager::InstanceKind;
+
+    fn instance(id: &str, state: Option<ClaudeState>, entered_at: Option<i64>) -> ClaudeInstance {
+        ClaudeInstance {
+            id: id.to_string(),
+            name: id.to_string(),
+            custom_name: None,
+            wrapper_port: 0,
+            working_dir: "/tmp".to_string(),
+            command: "claude".to_string(),
+            kind: InstanceKind::Structured {
+                provider: "claude".to_string(),
+            },
+            running: true,
+            created_at: String::new(),
+            session_id: None,
+            claude_state: state,
+            state_entered_at: entered_at,
+            title: None,
+            role: None,
+        }
+    }
+
+    fn inbox_item(instance_id: &str, event_type: &str, created_at: i64) -> InboxItem {
+        InboxItem {
+            instance_id: instance_id.to_string(),
+            event_type: event_type.to_string(),
+            turn_count: 1,
+            created_at,
+            updated_at: created_at,
+            metadata_json: None,
+        }
+    }
+
+    fn row_ids(fleet: &Fleet) -> Vec<&str> {
+        fleet.rows().iter().map(|i| i.id.as_str()).collect()
+    }
+
+    #[test]
+    fn rows_sort_by_attention_then_wait() {
+        let mut fleet = Fleet::default();
+        fleet.apply(ServerMessage::InstanceList {
+            instances: vec![
+                instance("idle", Some(ClaudeState::Idle), Some(10)),
+                instance("busy", Some(ClaudeState::Thinking), Some(20)),
+                instance("done", Some(ClaudeState::Idle), Some(30)),
+                instance(
+                    "asking",
+                    Some(ClaudeState::WaitingForInput { prompt: None }),
+                    Some(40),
+                ),
+                instance("blocked", Some(ClaudeState::Idle), Some(50)),
+            ],
+        });
+        fleet.apply(ServerMessage::InboxList {
+            items: vec![
+                inbox_item("done", "completed_turn", 100),
+                inbox_item("blocked", "needs_input", 5),
+            ],
+        });
+        assert_eq!(
+            row_ids(&fleet),
+            ["blocked", "asking", "done", "busy", "idle"]
+        );
+
+        // Dismissing and state changes re-sort live
+        fleet.apply(ServerMessage::InboxUpdate {
+            instance_id: "blocked".to_string(),
+            item: None,
+        });
+        fleet.apply(ServerMessage::StateChange {
+            instance_id: "idle".to_string(),
+            state: ClaudeState::ToolExecuting {
+                tool: "Bash".to_string(),
+            },
+            stale: false,
+            entered_at: Some(60),
+        });
+        assert_eq!(
+            row_ids(&fleet),
+            ["asking", "done", "busy", "idle", "blocked"]
+        );
+        assert_eq!(state_label(fleet.rows()[3]), "running Bash");
+
+        fleet.apply(ServerMessage::InstanceStopped {
+            instance_id: "done".to_string(),
+        });
+        assert!(!fleet.inbox.contains_key("done"));
+        assert_eq!(row_ids(&fleet), ["asking", "busy", "idle", "blocked"]);
+    }
+
+    #[test]
+    fn task_updates_track_pending_tasks() {
+        let mut fleet = Fleet::default();
+        let task = |id: i64, status: &str, instance_id: Option<&str>, sort_order: f64| {
+            serde_json::json!({
+                "id": id,
+                "uuid": "u",
+                "title": format!("task {}", id),
+                "status": status,
+                "instance_id": instance_id,
+                "sort_order": sort_order,
+                "is_deleted": false,
+                "tags": [],
+            })
+        };
+        for t in [
+            task(1, "pending", None, 3.0),
+            task(2, "pending", Some("a"), 2.0),
+            task(3, "pending", Some("b"), 1.0),
+        ] {
+            fleet.apply(ServerMessage::TaskUpdate { task: t });
+        }
+        let ids = |tasks: Vec<&FleetTask>| tasks.iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(fleet.dispatchable_tasks("a")), [2, 1]);
+        assert_eq!(fleet.pending_count("b"), 1);
+
+        // Once dispatched a task is no longer pending
+        fleet.apply(ServerMessage::TaskUpdate {
+            task: task(2, "in_progress", Some("a"), 2.0),
+        });
+        fleet.apply(ServerMessage::TaskDeleted { task_id: 3 });
+        assert_eq!(ids(fleet.dispatchable_tasks("a")), [1]);
+        assert_eq!(fleet.pending_count("b"), 0);
+    }
+
+    #[test]
+    fn labels_and_preview() {
+        assert_eq!(format_age(42), "42s");
+        assert_eq!(format_age(125), "2m");
+        assert_eq!(format_age(7300), "2h");
+        assert_eq!(format_age(-5), "0s");
+
+        let mut item = inbox_item("a", "completed_turn", 0);
+        item.turn_count = 3;
+        assert_eq!(inbox_label(&item), "done ×3");
+
+        let screen: Vec<String> = ["one", "two", "three", "", ""]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(preview_tail(&screen, 2), ["two", "three"]);
+        assert!(preview_tail(&[String::new()], 5).is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Client half of the mux protocol handshake (see `crab_city::ws::handshake`).
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite;
+
+use crab_city::ws::{
+    Capability, ClientMessage, PROTOCOL_VERSION, SOFTWARE_VERSION, ServerMessage, VersionMismatch,
+    check_server,
+};
+
+pub type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// How long to wait for the server's first message.
+const WELCOME_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the server told us in its `Welcome`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerHello {
+    pub server_version: String,
+    /// Capabilities both sides support and this client asked for
+    pub capabilities: Vec<Capability>,
+}
+
+/// Read the server's `Welcome`, check it against this build and answer with
+/// `Hello` requesting `wanted`.
+///
+/// Returns `None` for servers that predate the handshake (their first
+/// message is something else, which is discarded). A version mismatch is an
+/// error whose message tells the user what to upgrade.
+pub async fn handshake(ws: &mut WsStream, wanted: &[Capability]) -> Result<Option<ServerHello>> {
+    let first = match tokio::time::timeout(WELCOME_TIMEOUT, ws.next()).await {
+        Ok(Some(Ok(tungstenite::Message::Text(text)))) => text,
+        Ok(Some(Ok(_))) | Err(_) => return Ok(None),
+        Ok(Some(Err(e))) => return Err(e.into()),
+        Ok(None) => anyhow::bail!("server closed the connection"),
+    };
+    let Some(hello) = read_welcome(&first, wanted)? else {
+        return Ok(None);
+    };
+    let reply = ClientMessage::Hello {
+        protocol_version: PROTOCOL_VERSION,
+        client_version: SOFTWARE_VERSION.to_string(),
+        capabilities: hello.capabilities.clone(),
+    };
+    ws.send(tungstenite::Message::Text(serde_json::to_string(&reply)?))
+        .await?;
+    Ok(Some(hello))
+}
+
+/// Interpret the server's first message. `Ok(None)` if it is not a `Welcome`.
+fn read_welcome(text: &str, wanted: &[Capability]) -> Result<Option<ServerHello>, VersionMismatch> {
+    let Ok(ServerMessage::Welcome {
+        protocol_version,
+        min_protocol_version,
+        server_version,
+        capabilities,
+    }) = serde_json::from_str::<ServerMessage>(text)
+    else {
+        return Ok(None);
+    };
+    check_server(protocol_version, min_protocol_version, &server_version)?;
+    Ok(Some(ServerHello {
+        server_version,
+        capabilities: wanted
+            .iter()
+            .copied()
+            .filter(|c| capabilities.contains(c))
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn welcome(protocol: u32, min: u32, caps: &str) -> String {
+        format!(
+            r#"{{"type":"Welcome","protocol_version":{protocol},"min_protocol_version":{min},"server_version":"9.9.9","capabilities":{caps}}}"#
+        )
+    }
+
+    #[test]
+    fn welcome_negotiates_shared_capabilities() {
+        let hello = read_welcome(
+            &welcome(
+                PROTOCOL_VERSION,
+                1,
+                r#"["binary_frames","resume","holograms"]"#,
+            ),
+            &[Capability::BinaryFrames, Capability::Compression],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(hello.server_version, "9.9.9");
+        assert_eq!(hello.capabilities, [Capability::BinaryFrames]);
+    }
+
+    #[test]
+    fn newer_server_minimum_is_an_upgrade_error() {
+        let err = read_welcome(
+            &welcome(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 1, "[]"),
+            &[],
+        )
+        .unwrap_err();
+        assert!(matches!(err, VersionMismatch::ClientTooOld { .. }));
+    }
+
+    #[test]
+    fn legacy_server_has_no_welcome() {
+        let first = r#"{"type":"InstanceList","instances":[]}"#;
+        assert_eq!(read_welcome(first, &[Capability::BinaryFrames]), Ok(None));
+    }
+}
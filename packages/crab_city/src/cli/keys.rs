@@ -0,0 +1,456 @@
+//! Key bindings for the TUI client, read from the `[keys]` section of
+//! config.toml (or `CRAB_KEYS__<ACTION>` env vars), and `crab keys`.
+//!
+//! ```toml
+//! [keys]
+//! detach = "ctrl-b"
+//! copy_mode = "alt-c"
+//! picker_kill = "d"
+//! ```
+
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crab_city::config::{CrabCityConfig, FileConfig, load_config};
+
+/// Something a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Detach,
+    CopyMode,
+    NextPane,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    PickerRename,
+    PickerKill,
+    PickerFleet,
+    PickerSettings,
+    PickerKillServer,
+    PickerQuit,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Detach,
+        Action::CopyMode,
+        Action::NextPane,
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::PickerRename,
+        Action::PickerKill,
+        Action::PickerFleet,
+        Action::PickerSettings,
+        Action::PickerKillServer,
+        Action::PickerQuit,
+    ];
+
+    /// Name under `[keys]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Detach => "detach",
+            Action::CopyMode => "copy_mode",
+            Action::NextPane => "next_pane",
+            Action::ScrollUp => "scroll_up",
+            Action::ScrollDown => "scroll_down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::PickerRename => "picker_rename",
+            Action::PickerKill => "picker_kill",
+            Action::PickerFleet => "picker_fleet",
+            Action::PickerSettings => "picker_settings",
+            Action::PickerKillServer => "picker_kill_server",
+            Action::PickerQuit => "picker_quit",
+        }
+    }
+
+    fn default_key(self) -> &'static str {
+        match self {
+            Action::Detach => "ctrl-]",
+            Action::CopyMode => "ctrl-^",
+            Action::NextPane => "ctrl-\\",
+            Action::ScrollUp => "shift-up",
+            Action::ScrollDown => "shift-down",
+            Action::PageUp => "shift-pageup",
+            Action::PageDown => "shift-pagedown",
+            Action::PickerRename => "r",
+            Action::PickerKill => "x",
+            Action::PickerFleet => "f",
+            Action::PickerSettings => "s",
+            Action::PickerKillServer => "Q",
+            Action::PickerQuit => "q",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Action::Detach => "Detach from the instance",
+            Action::CopyMode => "Open copy mode",
+            Action::NextPane => "Move input to the next tiled pane",
+            Action::ScrollUp => "Scroll back one line",
+            Action::ScrollDown => "Scroll forward one line",
+            Action::PageUp => "Scroll back one page",
+            Action::PageDown => "Scroll forward one page",
+            Action::PickerRename => "Rename the selected instance",
+            Action::PickerKill => "Kill the selected instance",
+            Action::PickerFleet => "Open the fleet dashboard",
+            Action::PickerSettings => "Open settings",
+            Action::PickerKillServer => "Kill the server (asks first)",
+            Action::PickerQuit => "Quit (esc always works)",
+        }
+    }
+
+    /// Bound while attached, where every other key is typed into the
+    /// instance.
+    fn in_attach(self) -> bool {
+        !self.name().starts_with("picker_")
+    }
+}
+
+/// Keys the picker handles itself, which picker bindings can't take.
+const PICKER_RESERVED: [&str; 6] = ["up", "down", "j", "k", "enter", "esc"];
+
+/// A key with modifiers, written like `ctrl-]`, `alt-c`, `shift-pageup`,
+/// `f5` or `Q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    /// Crossterm 0.28 maps bytes 0x1C-0x1F to Ctrl+'4'-'7' (not the real
+    /// characters \, ], ^, _), so Ctrl-] arrives as `Char('5') + CONTROL`.
+    /// Both spellings are folded to the punctuation.
+    fn normalize(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(match c {
+                    '4' => '\\',
+                    '5' => ']',
+                    '6' => '^',
+                    '7' => '_',
+                    c => c.to_ascii_lowercase(),
+                })
+            }
+            code => code,
+        };
+        // Case already carries Shift for characters, and terminals differ
+        // on whether they report it as well
+        if matches!(code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        Self::normalize(key.code, key.modifiers) == *self
+    }
+
+    /// A key that would otherwise be typed as text.
+    fn is_printable(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl FromStr for KeySpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s.trim();
+        // Strip modifier prefixes; what's left is the key, so `ctrl--` is
+        // Ctrl and minus
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let modifier = [
+                ("ctrl", KeyModifiers::CONTROL),
+                ("control", KeyModifiers::CONTROL),
+                ("c", KeyModifiers::CONTROL),
+                ("alt", KeyModifiers::ALT),
+                ("meta", KeyModifiers::ALT),
+                ("m", KeyModifiers::ALT),
+                ("shift", KeyModifiers::SHIFT),
+                ("s", KeyModifiers::SHIFT),
+            ]
+            .into_iter()
+            .find(|(name, _)| {
+                lower.len() > name.len() + 1
+                    && lower.starts_with(name)
+                    && matches!(lower.as_bytes()[name.len()], b'-' | b'+')
+            });
+            match modifier {
+                Some((name, m)) => {
+                    modifiers |= m;
+                    rest = &rest[name.len() + 1..];
+                }
+                None => break,
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (None, _) => return Err(format!("no key in {:?}", s)),
+            (Some(c), None) => {
+                if modifiers.contains(KeyModifiers::SHIFT) && c.is_ascii_alphabetic() {
+                    KeyCode::Char(c.to_ascii_uppercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "insert" => KeyCode::Insert,
+                "delete" | "del" => KeyCode::Delete,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key {:?}", rest)),
+                },
+            },
+        };
+        Ok(Self::normalize(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (m, name) in [
+            (KeyModifiers::CONTROL, "Ctrl-"),
+            (KeyModifiers::ALT, "Alt-"),
+            (KeyModifiers::SHIFT, "Shift-"),
+        ] {
+            if self.modifiers.contains(m) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The key bound to every [`Action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBindings {
+    keys: [KeySpec; Action::ALL.len()],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL.map(|a| a.default_key().parse().expect("valid default key")),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> KeySpec {
+        self.keys[action as usize]
+    }
+
+    pub fn matches(&self, action: Action, key: &KeyEvent) -> bool {
+        self.get(action).matches(key)
+    }
+
+    /// Apply `[keys]` overrides to the defaults. Every problem is reported,
+    /// not just the first.
+    pub fn from_config(overrides: &BTreeMap<String, String>) -> Result<Self> {
+        let mut bindings = Self::default();
+        let mut errors = Vec::new();
+
+        for (name, value) in overrides {
+            let Some(action) = Action::ALL.iter().find(|a| a.name() == name) else {
+                errors.push(format!("unknown action {:?}", name));
+                continue;
+            };
+            match value.parse::<KeySpec>() {
+                Ok(key) if action.in_attach() && key.is_printable() => errors.push(format!(
+                    "{}: {} would be typed into the instance; add ctrl or alt",
+                    name, key
+                )),
+                Ok(key) => bindings.keys[*action as usize] = key,
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        for (i, a) in Action::ALL.iter().enumerate() {
+            let key = bindings.get(*a);
+            for b in &Action::ALL[i + 1..] {
+                if a.in_attach() == b.in_attach() && key == bindings.get(*b) {
+                    errors.push(format!(
+                        "{} and {} are both bound to {}",
+                        a.name(),
+                        b.name(),
+                        key
+                    ));
+                }
+            }
+            if !a.in_attach() {
+                let reserved = PICKER_RESERVED
+                    .iter()
+                    .map(|k| k.parse::<KeySpec>().expect("valid reserved key"));
+                if reserved.into_iter().any(|r| r == key) {
+                    errors.push(format!("{}: {} is used by the picker", a.name(), key));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(bindings)
+        } else {
+            anyhow::bail!("Invalid [keys] config:\n  {}", errors.join("\n  "))
+        }
+    }
+}
+
+fn read_overrides(config: &CrabCityConfig) -> Result<BTreeMap<String, String>> {
+    let fc: FileConfig = load_config(&config.data_dir, None)
+        .extract()
+        .context("Failed to read config")?;
+    Ok(fc.keys)
+}
+
+/// The key bindings from config, or an error naming every bad entry.
+pub fn load(config: &CrabCityConfig) -> Result<KeyBindings> {
+    KeyBindings::from_config(&read_overrides(config)?)
+}
+
+/// `crab keys`: list every action with its key.
+pub fn keys_command(config: &CrabCityConfig) -> Result<()> {
+    let overrides = read_overrides(config)?;
+    let bindings = KeyBindings::from_config(&overrides)?;
+
+    println!("{:<20} {:<16} {:<8} DESCRIPTION", "ACTION", "KEY", "SOURCE");
+    println!("{}", "-".repeat(80));
+    for action in Action::ALL {
+        let source = if overrides.contains_key(action.name()) {
+            "config"
+        } else {
+            "default"
+        };
+        println!(
+            "{:<20} {:<16} {:<8} {}",
+            action.name(),
+            bindings.get(action).to_string(),
+            source,
+            action.description()
+        );
+    }
+    println!(
+        "\nChange them under [keys] in {}",
+        config.config_toml_path().display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(s: &str) -> KeySpec {
+        s.parse().unwrap()
+    }
+
+    fn overrides(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_displays_keys() {
+        assert_eq!(spec("ctrl-]").to_string(), "Ctrl-]");
+        assert_eq!(spec("C-b").to_string(), "Ctrl-b");
+        assert_eq!(spec("Ctrl+Shift+PageUp").to_string(), "Ctrl-Shift-PgUp");
+        assert_eq!(spec("alt-c").to_string(), "Alt-c");
+        assert_eq!(spec("ctrl--").to_string(), "Ctrl--");
+        assert_eq!(spec("shift-q"), spec("Q"));
+        assert_eq!(spec("f5").to_string(), "F5");
+        assert_eq!(spec("space").to_string(), "Space");
+        assert!("ctrl-".parse::<KeySpec>().is_err());
+        assert!("hyper-x".parse::<KeySpec>().is_err());
+        assert!("f13".parse::<KeySpec>().is_err());
+    }
+
+    #[test]
+    fn matches_crossterm_control_digits() {
+        // Crossterm reports Ctrl-] as Ctrl+'5' and Ctrl-\ as Ctrl+'4'
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert!(spec("ctrl-]").matches(&ctrl('5')));
+        assert!(spec("ctrl-]").matches(&ctrl(']')));
+        assert!(spec("ctrl-\\").matches(&ctrl('4')));
+        assert!(!spec("ctrl-]").matches(&ctrl('c')));
+        assert!(!spec("]").matches(&ctrl(']')));
+        // Uppercase letters match with or without Shift reported
+        assert!(spec("Q").matches(&KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT)));
+        assert!(spec("Q").matches(&KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::NONE)));
+        assert!(!spec("q").matches(&KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn overrides_apply_over_defaults() {
+        let keys =
+            KeyBindings::from_config(&overrides(&[("detach", "ctrl-b"), ("picker_kill", "d")]))
+                .unwrap();
+        assert_eq!(keys.get(Action::Detach), spec("ctrl-b"));
+        assert_eq!(keys.get(Action::PickerKill), spec("d"));
+        assert_eq!(keys.get(Action::CopyMode), spec("ctrl-^"));
+    }
+
+    #[test]
+    fn invalid_config_reports_every_problem() {
+        let err = KeyBindings::from_config(&overrides(&[
+            ("detatch", "ctrl-b"),
+            ("copy_mode", "y"),
+            ("next_pane", "ctrl-]"),
+            ("picker_fleet", "j"),
+            ("scroll_up", "ctrl-nope"),
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("unknown action \"detatch\""), "{err}");
+        assert!(err.contains("copy_mode: y would be typed"), "{err}");
+        assert!(
+            err.contains("detach and next_pane are both bound to Ctrl-]"),
+            "{err}"
+        );
+        assert!(
+            err.contains("picker_fleet: j is used by the picker"),
+            "{err}"
+        );
+        assert!(err.contains("scroll_up: unknown key \"nope\""), "{err}");
+    }
+
+    #[test]
+    fn attach_and_picker_keys_may_share() {
+        // Bindings in different screens never see the same key press
+        assert!(KeyBindings::from_config(&overrides(&[("picker_quit", "ctrl-]")])).is_ok());
+    }
+}
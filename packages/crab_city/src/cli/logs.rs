@@ -0,0 +1,266 @@
+//! `crab logs`: print an instance's terminal as plain text, or follow its
+//! output, without attaching.
+
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use std::io::Write;
+use tokio_tungstenite::tungstenite;
+
+use crab_city::config::CrabCityConfig;
+use crab_city::ws::{ClientMessage, ServerMessage};
+
+use super::{daemon, handshake};
+
+#[derive(Debug, Clone, Copy)]
+pub struct LogsOptions {
+    /// Keep printing new output until the instance exits
+    pub follow: bool,
+    /// Include the scrollback, not just the visible screen
+    pub scrollback: bool,
+    /// Remove escape sequences from followed output
+    pub strip_ansi: bool,
+}
+
+pub async fn logs_command(
+    config: &CrabCityConfig,
+    target: &str,
+    options: LogsOptions,
+) -> Result<()> {
+    let daemon = daemon::require_running_daemon(config).await?;
+    let instance_id = super::resolve_instance(&daemon, target).await?;
+
+    if !options.follow {
+        for line in fetch_text(&daemon, &instance_id, options.scrollback).await? {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let mut ws = daemon
+        .connect_mux()
+        .await
+        .context("Failed to connect to the daemon")?;
+    handshake::handshake(&mut ws, &[]).await?;
+
+    // Spectate before focusing so this connection never sizes the PTY or
+    // holds the terminal lock
+    send_json(
+        &mut ws,
+        &ClientMessage::TerminalVisible {
+            instance_id: instance_id.clone(),
+            rows: 24,
+            cols: 80,
+            client_type: Some("spectator".to_string()),
+        },
+    )
+    .await?;
+    send_json(
+        &mut ws,
+        &ClientMessage::Focus {
+            instance_id: instance_id.clone(),
+            since_uuid: None,
+            output_seq: None,
+        },
+    )
+    .await?;
+
+    // Live output is already flowing, so anything printed between the
+    // snapshot and the first chunk may show twice but never goes missing
+    for line in fetch_text(&daemon, &instance_id, options.scrollback).await? {
+        println!("{}", line);
+    }
+
+    let mut stripper = options.strip_ansi.then(AnsiStripper::default);
+    let mut stdout = std::io::stdout();
+    while let Some(msg) = ws.next().await {
+        let tungstenite::Message::Text(text) = msg? else {
+            continue;
+        };
+        match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::Output {
+                instance_id: id,
+                data,
+                ..
+            }) if id == instance_id => {
+                let data = match stripper.as_mut() {
+                    Some(stripper) => stripper.strip(&data),
+                    None => data,
+                };
+                // A closed pipe (`| head`) ends the follow quietly
+                if stdout
+                    .write_all(data.as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Ok(ServerMessage::InstanceStopped { instance_id: id }) if id == instance_id => {
+                eprintln!("[crab: instance exited]");
+                break;
+            }
+            Ok(ServerMessage::Error {
+                instance_id: Some(id),
+                message,
+            }) if id == instance_id => anyhow::bail!(message),
+            _ => {}
+        }
+    }
+    let _ = ws.close(None).await;
+    Ok(())
+}
+
+async fn send_json(ws: &mut handshake::WsStream, msg: &ClientMessage) -> Result<()> {
+    ws.send(tungstenite::Message::Text(serde_json::to_string(msg)?))
+        .await?;
+    Ok(())
+}
+
+/// The rendered terminal text, without the blank rows below the last output.
+async fn fetch_text(
+    daemon: &daemon::DaemonInfo,
+    instance_id: &str,
+    scrollback: bool,
+) -> Result<Vec<String>> {
+    let url = format!(
+        "{}/api/instances/{}/output?text={}",
+        daemon.base_url(),
+        instance_id,
+        if scrollback { "scrollback" } else { "screen" }
+    );
+    let resp = daemon.http().get(&url).send().await?.error_for_status()?;
+    let body: serde_json::Value = resp.json().await?;
+    let mut lines: Vec<String> = body["lines"]
+        .as_array()
+        .map(|lines| {
+            lines
+                .iter()
+                .filter_map(|l| l.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    Ok(lines)
+}
+
+/// Removes escape sequences and control characters from a stream of
+/// terminal output. Sequences may be split across chunks, so the parser
+/// state carries over between calls.
+#[derive(Debug, Default)]
+struct AnsiStripper {
+    state: StripState,
+    /// A carriage return we haven't yet seen the following character of
+    pending_cr: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum StripState {
+    #[default]
+    Ground,
+    /// After ESC
+    Escape,
+    /// Inside `ESC [` ... final byte
+    Csi,
+    /// Inside an OSC/DCS/APC/PM/SOS string, ended by BEL or `ESC \`
+    String,
+    /// ESC seen inside a string
+    StringEscape,
+    /// `ESC (` and friends take one more character
+    Charset,
+}
+
+impl AnsiStripper {
+    fn strip(&mut self, input: &str) -> String {
+        let mut out = String::with_capacity(input.len());
+        for c in input.chars() {
+            self.push(c, &mut out);
+        }
+        out
+    }
+
+    fn push(&mut self, c: char, out: &mut String) {
+        match self.state {
+            StripState::Ground => self.ground(c, out),
+            StripState::Escape => self.escape(c),
+            StripState::Csi => {
+                if ('\x40'..='\x7e').contains(&c) {
+                    self.state = StripState::Ground;
+                }
+            }
+            StripState::String => match c {
+                '\x07' => self.state = StripState::Ground,
+                '\x1b' => self.state = StripState::StringEscape,
+                _ => {}
+            },
+            // ESC ends the string either way; anything but `\` starts a new sequence
+            StripState::StringEscape => {
+                if c == '\\' {
+                    self.state = StripState::Ground;
+                } else {
+                    self.escape(c);
+                }
+            }
+            StripState::Charset => self.state = StripState::Ground,
+        }
+    }
+
+    fn ground(&mut self, c: char, out: &mut String) {
+        if self.pending_cr && c != '\r' {
+            self.pending_cr = false;
+            // A bare CR redraws the line; keep each redraw on its own line
+            out.push('\n');
+            if c == '\n' {
+                return;
+            }
+        }
+        match c {
+            '\x1b' => self.state = StripState::Escape,
+            '\u{9b}' => self.state = StripState::Csi,
+            '\r' => self.pending_cr = true,
+            '\n' | '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+
+    fn escape(&mut self, c: char) {
+        self.state = match c {
+            '[' => StripState::Csi,
+            ']' | 'P' | 'X' | '^' | '_' => StripState::String,
+            '(' | ')' | '*' | '+' | '#' | '%' => StripState::Charset,
+            _ => StripState::Ground,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_colors_titles_and_charsets() {
+        let mut s = AnsiStripper::default();
+        let input = "\x1b]0;my title\x07\x1b(B\x1b[1;32mok\x1b[0m done\r\n";
+        assert_eq!(s.strip(input), "ok done\n");
+        let input = "\x1bP1$r0m\x1b\\\x1b[?25lhidden cursor\x1b=\x07\n";
+        assert_eq!(s.strip(input), "hidden cursor\n");
+    }
+
+    #[test]
+    fn sequences_split_across_chunks() {
+        let mut s = AnsiStripper::default();
+        assert_eq!(s.strip("a\x1b[3"), "a");
+        assert_eq!(s.strip("1mb\x1b]8;;http://x"), "b");
+        assert_eq!(s.strip("\x1b"), "");
+        assert_eq!(s.strip("\\c\r"), "c");
+        assert_eq!(s.strip("\nd"), "\nd");
+    }
+
+    #[test]
+    fn bare_carriage_returns_become_newlines() {
+        let mut s = AnsiStripper::default();
+        assert_eq!(s.strip("10%\r50%\r\r\n"), "10%\n50%\n");
+    }
+}
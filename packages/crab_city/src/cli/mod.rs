@@ -1,17 +1,27 @@
 pub mod attach;
 pub mod auth;
+pub mod copy_mode;
 pub mod daemon;
+pub mod fleet;
+pub mod handshake;
+pub mod keys;
+pub mod logs;
 pub mod picker;
+pub mod remote;
+pub mod search;
+pub mod send;
 pub mod settings;
+pub mod tasks;
 pub mod terminal;
+pub mod tiled;
 
 use anyhow::{Context, Result};
-use futures::StreamExt;
+use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
 use tokio_tungstenite::tungstenite;
 use tracing::{debug, error, info, warn};
 
-use attach::AttachOutcome;
+use attach::{AttachOptions, AttachOutcome};
 use crab_city::config::CrabCityConfig;
 use daemon::{DaemonError, DaemonInfo};
 use picker::{PickerEvent, PickerResult};
@@ -20,6 +30,10 @@ use picker::{PickerEvent, PickerResult};
 /// After detaching from a session, returns to the picker.
 pub async fn default_command(config: &CrabCityConfig) -> Result<()> {
     let daemon = daemon::ensure_daemon(config).await?;
+    let options = AttachOptions {
+        keys: keys::load(config)?,
+        ..Default::default()
+    };
 
     // First run: if no instances at all, create one directly
     let instances = match fetch_instances(&daemon).await {
@@ -31,11 +45,7 @@ pub async fn default_command(config: &CrabCityConfig) -> Result<()> {
         Err(e) => return Err(e.into()),
     };
     if instances.is_empty() {
-        let cwd = std::env::current_dir()
-            .context("Failed to get current directory")?
-            .to_string_lossy()
-            .to_string();
-        let instance = match create_instance(&daemon, None, Some(&cwd)).await {
+        let instance = match create_instance(&daemon, &NewInstance::here(&daemon)?).await {
             Ok(inst) => inst,
             Err(DaemonError::Unavailable) => {
                 eprintln!("[crab: server stopped]");
@@ -43,7 +53,7 @@ pub async fn default_command(config: &CrabCityConfig) -> Result<()> {
             }
             Err(e) => return Err(e.into()),
         };
-        match attach::attach(&daemon, &instance.id).await {
+        match attach::attach(&daemon, &instance.id, options).await {
             Ok(AttachOutcome::Detached) => {}
             Ok(AttachOutcome::Exited) => {
                 delete_instance(&daemon, &instance.id).await;
@@ -56,17 +66,25 @@ pub async fn default_command(config: &CrabCityConfig) -> Result<()> {
         }
     }
 
-    session_loop(config, daemon).await
+    session_loop(config, daemon, options).await
 }
 
 /// Attach to an existing instance (by name, ID, or prefix). No target: show picker.
-/// After detaching from a session, returns to the picker.
-pub async fn attach_command(config: &CrabCityConfig, target: Option<String>) -> Result<()> {
+/// Several targets: tile them side by side. After detaching from a session,
+/// returns to the picker.
+pub async fn attach_command(
+    config: &CrabCityConfig,
+    targets: Vec<String>,
+    options: AttachOptions,
+) -> Result<()> {
     let daemon = daemon::require_running_daemon(config).await?;
 
-    if let Some(t) = target {
-        let instance_id = resolve_instance(&daemon, &t).await?;
-        match attach::attach(&daemon, &instance_id).await {
+    if targets.len() > 1 {
+        return attach_tiled_command(&daemon, &targets, options).await;
+    }
+    if let Some(t) = targets.first() {
+        let instance_id = resolve_instance(&daemon, t).await?;
+        match attach::attach(&daemon, &instance_id, options).await {
             Ok(AttachOutcome::Detached) => return Ok(()),
             Ok(AttachOutcome::Exited) => {
                 delete_instance(&daemon, &instance_id).await;
@@ -83,12 +101,49 @@ pub async fn attach_command(config: &CrabCityConfig, target: Option<String>) ->
         }
     }
 
-    session_loop(config, daemon).await
+    session_loop(config, daemon, options).await
+}
+
+async fn attach_tiled_command(
+    daemon: &DaemonInfo,
+    targets: &[String],
+    options: AttachOptions,
+) -> Result<()> {
+    let instances = fetch_instances(daemon).await?;
+    let panes = targets
+        .iter()
+        .map(|t| {
+            let id = match_instance(&instances, t)?;
+            let inst = instances.iter().find(|i| i.id == id).expect("matched");
+            Ok((id, inst.display_name().to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    match tiled::attach_tiled(daemon, &panes, options).await {
+        Ok(exited) => {
+            for id in &exited {
+                delete_instance(daemon, id).await;
+            }
+            if !exited.is_empty() && should_stop_daemon(daemon).await {
+                daemon::stop_daemon(daemon);
+            }
+            Ok(())
+        }
+        Err(DaemonError::Unavailable) => {
+            eprintln!("[crab: server stopped]");
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
 }
 
 /// Picker → attach → detach → picker loop. Exits on Quit or when no instances remain.
 /// Owns the ratatui terminal so picker and settings can share it.
-async fn session_loop(config: &CrabCityConfig, daemon: DaemonInfo) -> Result<()> {
+async fn session_loop(
+    config: &CrabCityConfig,
+    daemon: DaemonInfo,
+    options: AttachOptions,
+) -> Result<()> {
     use std::io::IsTerminal;
 
     let has_tty = std::io::stdin().is_terminal();
@@ -98,7 +153,7 @@ async fn session_loop(config: &CrabCityConfig, daemon: DaemonInfo) -> Result<()>
     // the picker's terminal before attaching and re-init afterwards.
     let mut terminal = if has_tty { Some(ratatui::init()) } else { None };
 
-    let result = session_loop_inner(&mut terminal, config, daemon).await;
+    let result = session_loop_inner(&mut terminal, config, daemon, options).await;
 
     if terminal.is_some() {
         ratatui::restore();
@@ -110,6 +165,7 @@ async fn session_loop_inner(
     terminal: &mut Option<ratatui::DefaultTerminal>,
     config: &CrabCityConfig,
     mut daemon: DaemonInfo,
+    options: AttachOptions,
 ) -> Result<()> {
     /// Try to rediscover the daemon after an Unavailable error.
     /// Returns `true` if a new daemon was found and `daemon` was updated.
@@ -142,115 +198,132 @@ async fn session_loop_inner(
             Err(e) => return Err(e.into()),
         };
 
-        let (instance_id, outcome) =
-            match run_live_picker(terminal, &daemon, instances, last_attached.as_deref()).await? {
-                PickerResult::Attach(id) => {
-                    info!(instance_id = %id, "attaching to instance");
-                    // Restore terminal before attaching (attach manages its own ratatui terminal)
-                    if terminal.is_some() {
-                        ratatui::restore();
-                    }
-                    let outcome = match attach::attach(&daemon, &id).await {
-                        Ok(o) => o,
-                        Err(DaemonError::Unavailable) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            if try_rediscover(config, &mut daemon).await {
-                                continue;
-                            }
-                            eprintln!("[crab: server stopped]");
-                            return Ok(());
+        let choice = match run_live_picker(
+            terminal,
+            &daemon,
+            instances,
+            last_attached.as_deref(),
+            &options.keys,
+        )
+        .await?
+        {
+            PickerResult::Fleet => match terminal {
+                Some(term) => match fleet::run_live_fleet(term, &daemon).await? {
+                    fleet::FleetResult::Attach(id) => PickerResult::Attach(id),
+                    fleet::FleetResult::Back => continue,
+                },
+                None => continue,
+            },
+            other => other,
+        };
+        let (instance_id, outcome) = match choice {
+            PickerResult::Attach(id) => {
+                info!(instance_id = %id, "attaching to instance");
+                // Restore terminal before attaching (attach manages its own ratatui terminal)
+                if terminal.is_some() {
+                    ratatui::restore();
+                }
+                let outcome = match attach::attach(&daemon, &id, options).await {
+                    Ok(o) => o,
+                    Err(DaemonError::Unavailable) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
                         }
-                        Err(e) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            return Err(e.into());
+                        if try_rediscover(config, &mut daemon).await {
+                            continue;
                         }
-                    };
-                    // Re-init terminal for the next picker iteration
-                    if terminal.is_some() {
-                        *terminal = Some(ratatui::init());
+                        eprintln!("[crab: server stopped]");
+                        return Ok(());
                     }
-                    (id, outcome)
+                    Err(e) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
+                        }
+                        return Err(e.into());
+                    }
+                };
+                // Re-init terminal for the next picker iteration
+                if terminal.is_some() {
+                    *terminal = Some(ratatui::init());
+                }
+                (id, outcome)
+            }
+            PickerResult::NewInstance => {
+                if terminal.is_some() {
+                    ratatui::restore();
                 }
-                PickerResult::NewInstance => {
-                    if terminal.is_some() {
-                        ratatui::restore();
+                let instance = match create_instance(&daemon, &NewInstance::here(&daemon)?).await {
+                    Ok(inst) => inst,
+                    Err(DaemonError::Unavailable) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
+                        }
+                        if try_rediscover(config, &mut daemon).await {
+                            continue;
+                        }
+                        eprintln!("[crab: server stopped]");
+                        return Ok(());
                     }
-                    let cwd = std::env::current_dir()
-                        .context("Failed to get current directory")?
-                        .to_string_lossy()
-                        .to_string();
-                    let instance = match create_instance(&daemon, None, Some(&cwd)).await {
-                        Ok(inst) => inst,
-                        Err(DaemonError::Unavailable) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            if try_rediscover(config, &mut daemon).await {
-                                continue;
-                            }
-                            eprintln!("[crab: server stopped]");
-                            return Ok(());
+                    Err(e) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
                         }
-                        Err(e) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            return Err(e.into());
+                        return Err(e.into());
+                    }
+                };
+                let outcome = match attach::attach(&daemon, &instance.id, options).await {
+                    Ok(o) => o,
+                    Err(DaemonError::Unavailable) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
                         }
-                    };
-                    let outcome = match attach::attach(&daemon, &instance.id).await {
-                        Ok(o) => o,
-                        Err(DaemonError::Unavailable) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            if try_rediscover(config, &mut daemon).await {
-                                continue;
-                            }
-                            eprintln!("[crab: server stopped]");
-                            return Ok(());
+                        if try_rediscover(config, &mut daemon).await {
+                            continue;
                         }
-                        Err(e) => {
-                            if terminal.is_some() {
-                                *terminal = Some(ratatui::init());
-                            }
-                            return Err(e.into());
+                        eprintln!("[crab: server stopped]");
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        if terminal.is_some() {
+                            *terminal = Some(ratatui::init());
                         }
-                    };
-                    if terminal.is_some() {
-                        *terminal = Some(ratatui::init());
+                        return Err(e.into());
                     }
-                    (instance.id, outcome)
-                }
-                PickerResult::Rename { id, custom_name } => {
-                    info!(instance_id = %id, name = ?custom_name, "renaming instance");
-                    rename_instance(&daemon, &id, custom_name.as_deref()).await;
-                    continue;
-                }
-                PickerResult::Kill(id) => {
-                    info!(instance_id = %id, "killing instance");
-                    delete_instance(&daemon, &id).await;
-                    continue;
-                }
-                PickerResult::KillServer => {
-                    daemon::stop_daemon(&daemon);
-                    return Ok(());
+                };
+                if terminal.is_some() {
+                    *terminal = Some(ratatui::init());
                 }
-                PickerResult::Settings => {
-                    if let Some(term) = terminal {
-                        // run_settings uses reqwest::blocking which creates its own
-                        // tokio runtime. block_in_place lets it run without panicking
-                        // from inside our existing runtime.
-                        tokio::task::block_in_place(|| settings::run_settings(term, &daemon))?;
-                    }
-                    continue;
+                (instance.id, outcome)
+            }
+            PickerResult::Rename { id, custom_name } => {
+                info!(instance_id = %id, name = ?custom_name, "renaming instance");
+                rename_instance(&daemon, &id, custom_name.as_deref()).await;
+                continue;
+            }
+            PickerResult::Kill(id) => {
+                info!(instance_id = %id, "killing instance");
+                delete_instance(&daemon, &id).await;
+                continue;
+            }
+            // A remote server isn't ours to stop
+            PickerResult::KillServer if daemon.is_remote() => continue,
+            PickerResult::KillServer => {
+                daemon::stop_daemon(&daemon);
+                return Ok(());
+            }
+            PickerResult::Settings => {
+                if let Some(term) = terminal {
+                    // run_settings uses reqwest::blocking which creates its own
+                    // tokio runtime. block_in_place lets it run without panicking
+                    // from inside our existing runtime.
+                    tokio::task::block_in_place(|| settings::run_settings(term, &daemon))?;
                 }
-                PickerResult::Quit => return Ok(()),
-            };
+                continue;
+            }
+            // Resolved to an attach or a return to the picker above
+            PickerResult::Fleet => continue,
+            PickerResult::Quit => return Ok(()),
+        };
 
         match outcome {
             AttachOutcome::Detached => {
@@ -266,18 +339,67 @@ async fn session_loop_inner(
     }
 }
 
+/// Fleet dashboard → attach → detach → dashboard loop. Exits when the user
+/// leaves the dashboard.
+pub async fn fleet_command(config: &CrabCityConfig, options: AttachOptions) -> Result<()> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        anyhow::bail!("`crab fleet` needs a terminal; use `crab list` or `crab logs` instead");
+    }
+    let daemon = daemon::require_running_daemon(config).await?;
+
+    let mut terminal = ratatui::init();
+    let result = loop {
+        let id = match fleet::run_live_fleet(&mut terminal, &daemon).await {
+            Ok(fleet::FleetResult::Attach(id)) => id,
+            Ok(fleet::FleetResult::Back) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        // attach() manages its own terminal
+        ratatui::restore();
+        let outcome = attach::attach(&daemon, &id, options).await;
+        terminal = ratatui::init();
+        match outcome {
+            Ok(AttachOutcome::Detached) => {}
+            Ok(AttachOutcome::Exited) => delete_instance(&daemon, &id).await,
+            Err(DaemonError::Unavailable) => {
+                ratatui::restore();
+                eprintln!("[crab: server stopped]");
+                return Ok(());
+            }
+            Err(e) => break Err(e.into()),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
 /// Connect to the mux WebSocket for live updates and run the picker.
 async fn run_live_picker(
     terminal: &mut Option<ratatui::DefaultTerminal>,
     daemon: &DaemonInfo,
     instances: Vec<InstanceInfo>,
     selected_id: Option<&str>,
+    keys: &keys::KeyBindings,
 ) -> Result<PickerResult> {
     let (tx, rx) = std::sync::mpsc::channel();
 
-    // Best-effort WS connection for live updates; picker works fine without it
-    if let Ok((ws, _)) = tokio_tungstenite::connect_async(daemon.mux_ws_url()).await {
+    // Best-effort WS connection for live updates; picker works fine without
+    // it, but not with a server it cannot talk to.
+    if let Ok(mut ws) = daemon.connect_mux().await
+        && picker_handshake(&mut ws).await?
+    {
         debug!("picker: mux WebSocket connected");
+        // The picker only renders instance lifecycle events
+        let subscribe = crab_city::ws::ClientMessage::Subscribe {
+            topics: vec![crab_city::ws::Topic::Instances { instance_id: None }],
+        };
+        let _ = ws
+            .send(tungstenite::Message::Text(serde_json::to_string(
+                &subscribe,
+            )?))
+            .await;
         let (_, mut ws_read) = ws.split();
         tokio::spawn(async move {
             while let Some(Ok(tungstenite::Message::Text(text))) = ws_read.next().await {
@@ -304,7 +426,27 @@ async fn run_live_picker(
         });
     }
 
-    picker::run_picker(terminal, &daemon.base_url(), instances, rx, selected_id)
+    picker::run_picker(
+        terminal,
+        &daemon.base_url(),
+        instances,
+        rx,
+        selected_id,
+        keys,
+    )
+}
+
+/// Handshake for the picker's live-update socket. A version mismatch is
+/// fatal; any other failure just means no live updates (`false`).
+async fn picker_handshake(ws: &mut handshake::WsStream) -> Result<bool> {
+    match handshake::handshake(ws, &[]).await {
+        Ok(_) => Ok(true),
+        Err(e) if e.is::<crab_city::ws::VersionMismatch>() => Err(e),
+        Err(e) => {
+            debug!("picker: handshake failed: {}", e);
+            Ok(false)
+        }
+    }
 }
 
 /// Subset of server messages we care about in the CLI picker.
@@ -340,6 +482,9 @@ pub async fn kill_command(config: &CrabCityConfig, target: &str) -> Result<()> {
 
 /// Stop the daemon and all sessions.
 pub async fn kill_server_command(config: &CrabCityConfig, force: bool) -> Result<()> {
+    if config.remote.is_some() {
+        anyhow::bail!("`crab kill-server` only stops the local daemon");
+    }
     let daemon = daemon::require_running_daemon(config).await?;
 
     if !force {
@@ -367,6 +512,91 @@ pub async fn kill_server_command(config: &CrabCityConfig, force: bool) -> Result
     Ok(())
 }
 
+/// What `crab new` should create.
+pub struct NewOptions {
+    pub name: Option<String>,
+    pub dir: Option<std::path::PathBuf>,
+    pub command: Option<String>,
+    pub env: Vec<(String, String)>,
+    /// None: attach when run from a terminal
+    pub attach: Option<bool>,
+}
+
+/// Create an instance with explicit options, then attach to it or print its
+/// id and name.
+pub async fn new_command(config: &CrabCityConfig, options: NewOptions) -> Result<()> {
+    use std::io::IsTerminal;
+
+    let daemon = daemon::ensure_daemon(config).await?;
+
+    if let Some(name) = &options.name {
+        let instances = fetch_instances(&daemon).await?;
+        if instances
+            .iter()
+            .any(|i| i.name == *name || i.custom_name.as_deref() == Some(name))
+        {
+            anyhow::bail!("An instance named '{}' already exists", name);
+        }
+    }
+
+    // The daemon has its own cwd, so relative paths are resolved here. A
+    // remote server gets the path as given, or uses its default directory.
+    let dir = match options.dir {
+        Some(dir) if daemon.is_remote() => Some(dir),
+        Some(dir) => Some(
+            dir.canonicalize()
+                .with_context(|| format!("No such directory: {}", dir.display()))?,
+        ),
+        None if daemon.is_remote() => None,
+        None => Some(std::env::current_dir().context("Failed to get current directory")?),
+    };
+    let request = NewInstance {
+        name: options.name,
+        working_dir: dir.map(|d| d.to_string_lossy().to_string()),
+        command: options.command,
+        env: options.env.into_iter().collect(),
+    };
+    let attach = options
+        .attach
+        .unwrap_or_else(|| std::io::stdin().is_terminal() && std::io::stdout().is_terminal());
+    // Bad key bindings fail before anything is created
+    let attach_options = if attach {
+        AttachOptions {
+            keys: keys::load(config)?,
+            ..Default::default()
+        }
+    } else {
+        AttachOptions::default()
+    };
+
+    let instance = create_instance(&daemon, &request).await?;
+    if !attach {
+        println!("{}\t{}", instance.id, instance.name);
+        return Ok(());
+    }
+
+    match attach::attach(&daemon, &instance.id, attach_options).await {
+        Ok(AttachOutcome::Detached) => Ok(()),
+        Ok(AttachOutcome::Exited) => {
+            delete_instance(&daemon, &instance.id).await;
+            Ok(())
+        }
+        Err(DaemonError::Unavailable) => {
+            eprintln!("[crab: server stopped]");
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Parse a `KEY=VALUE` argument.
+pub fn parse_env_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
 /// List running instances.
 pub async fn list_command(config: &CrabCityConfig, json: bool) -> Result<()> {
     let daemon = daemon::ensure_daemon(config).await?;
@@ -425,16 +655,47 @@ impl InstanceInfo {
 }
 
 #[derive(Deserialize)]
-#[allow(dead_code)]
 struct CreateInstanceResponse {
     id: String,
     name: String,
 }
 
+/// Body of `POST /api/instances`. Unset fields use the daemon's defaults.
+#[derive(serde::Serialize, Default)]
+struct NewInstance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    working_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    env: std::collections::BTreeMap<String, String>,
+}
+
+impl NewInstance {
+    fn in_dir(working_dir: String) -> Self {
+        Self {
+            working_dir: Some(working_dir),
+            ..Default::default()
+        }
+    }
+
+    /// An instance in the current directory, or in the server's default
+    /// directory when the server is remote and can't see ours.
+    fn here(daemon: &DaemonInfo) -> Result<Self> {
+        if daemon.is_remote() {
+            return Ok(Self::default());
+        }
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        Ok(Self::in_dir(cwd.to_string_lossy().to_string()))
+    }
+}
+
 /// Delete a stopped instance from the daemon. Best-effort (logs errors).
 async fn delete_instance(daemon: &DaemonInfo, instance_id: &str) {
     let url = format!("{}/api/instances/{}", daemon.base_url(), instance_id);
-    if let Err(e) = reqwest::Client::new().delete(&url).send().await {
+    if let Err(e) = daemon.http().delete(&url).send().await {
         error!(instance_id, error = %e, "failed to delete instance");
     }
 }
@@ -442,7 +703,8 @@ async fn delete_instance(daemon: &DaemonInfo, instance_id: &str) {
 /// Set or clear the custom name for an instance. Best-effort (logs errors).
 async fn rename_instance(daemon: &DaemonInfo, instance_id: &str, custom_name: Option<&str>) {
     let url = format!("{}/api/instances/{}/name", daemon.base_url(), instance_id);
-    if let Err(e) = reqwest::Client::new()
+    if let Err(e) = daemon
+        .http()
         .patch(&url)
         .json(&serde_json::json!({ "custom_name": custom_name }))
         .send()
@@ -452,8 +714,12 @@ async fn rename_instance(daemon: &DaemonInfo, instance_id: &str, custom_name: Op
     }
 }
 
-/// Check if the daemon has no remaining running instances.
+/// Check if the daemon has no remaining running instances. A remote server
+/// is never stopped from here.
 async fn should_stop_daemon(daemon: &DaemonInfo) -> bool {
+    if daemon.is_remote() {
+        return false;
+    }
     match fetch_instances(daemon).await {
         Ok(instances) => instances.is_empty() || instances.iter().all(|i| !i.running),
         Err(_) => false,
@@ -462,7 +728,10 @@ async fn should_stop_daemon(daemon: &DaemonInfo) -> bool {
 
 async fn fetch_instances(daemon: &DaemonInfo) -> Result<Vec<InstanceInfo>, DaemonError> {
     let url = format!("{}/api/instances", daemon.base_url());
-    let resp = reqwest::get(&url)
+    let resp = daemon
+        .http()
+        .get(&url)
+        .send()
         .await
         .map_err(DaemonError::from_reqwest)?;
     resp.json().await.map_err(DaemonError::from_reqwest)
@@ -470,19 +739,14 @@ async fn fetch_instances(daemon: &DaemonInfo) -> Result<Vec<InstanceInfo>, Daemo
 
 async fn create_instance(
     daemon: &DaemonInfo,
-    name: Option<&str>,
-    working_dir: Option<&str>,
+    request: &NewInstance,
 ) -> Result<CreateInstanceResponse, DaemonError> {
     let url = format!("{}/api/instances", daemon.base_url());
-    let body = serde_json::json!({
-        "name": name,
-        "working_dir": working_dir,
-    });
 
-    let client = reqwest::Client::new();
-    let resp = client
+    let resp = daemon
+        .http()
         .post(&url)
-        .json(&body)
+        .json(request)
         .send()
         .await
         .map_err(DaemonError::from_reqwest)?;
@@ -624,6 +888,26 @@ mod tests {
         assert_eq!(result, "falcon");
     }
 
+    #[test]
+    fn parse_env_var_splits_on_first_equals() {
+        assert_eq!(
+            parse_env_var("RUST_LOG=crab=debug").unwrap(),
+            ("RUST_LOG".to_string(), "crab=debug".to_string())
+        );
+        assert_eq!(
+            parse_env_var("EMPTY=").unwrap(),
+            ("EMPTY".to_string(), String::new())
+        );
+        assert!(parse_env_var("NOVALUE").is_err());
+        assert!(parse_env_var("=value").is_err());
+    }
+
+    #[test]
+    fn new_instance_omits_unset_fields() {
+        let body = serde_json::to_value(NewInstance::in_dir("/tmp".into())).unwrap();
+        assert_eq!(body, serde_json::json!({ "working_dir": "/tmp" }));
+    }
+
     #[test]
     fn instance_info_serde_roundtrip() {
         let info = inst("id-1", "name-1");
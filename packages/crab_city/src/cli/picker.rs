@@ -11,6 +11,7 @@ use std::sync::mpsc;
 use std::time::Duration;
 
 use super::InstanceInfo;
+use super::keys::{Action, KeyBindings};
 
 pub enum PickerResult {
     Attach(String),
@@ -22,6 +23,7 @@ pub enum PickerResult {
     Kill(String),
     KillServer,
     Settings,
+    Fleet,
     Quit,
 }
 
@@ -51,9 +53,10 @@ pub fn run_picker(
     instances: Vec<InstanceInfo>,
     events: mpsc::Receiver<PickerEvent>,
     selected_id: Option<&str>,
+    keys: &KeyBindings,
 ) -> Result<PickerResult> {
     match terminal {
-        Some(term) => picker_loop(term, base_url, instances, events, selected_id),
+        Some(term) => picker_loop(term, base_url, instances, events, selected_id, keys),
         None => {
             // No TTY — fall back to most recent instance or new
             Ok(match instances.last() {
@@ -70,6 +73,7 @@ fn picker_loop(
     mut instances: Vec<InstanceInfo>,
     events: mpsc::Receiver<PickerEvent>,
     selected_id: Option<&str>,
+    keys: &KeyBindings,
 ) -> Result<PickerResult> {
     let initial = selected_id
         .and_then(|id| instances.iter().position(|i| i.id == id))
@@ -143,9 +147,15 @@ fn picker_loop(
             } else if renaming {
                 Line::raw(" type to rename · enter confirm · esc cancel · backspace clear name ")
             } else {
-                Line::raw(
-                    " ↑↓ navigate · enter select · r rename · x kill · s settings · Q kill server · q/esc quit ",
-                )
+                Line::raw(format!(
+                    " ↑↓ navigate · enter select · {} rename · {} kill · {} fleet · {} settings · {} kill server · {}/esc quit ",
+                    keys.get(Action::PickerRename),
+                    keys.get(Action::PickerKill),
+                    keys.get(Action::PickerFleet),
+                    keys.get(Action::PickerSettings),
+                    keys.get(Action::PickerKillServer),
+                    keys.get(Action::PickerQuit),
+                ))
             };
 
             let list = List::new(items)
@@ -247,10 +257,7 @@ fn picker_loop(
             }
 
             match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => return Ok(PickerResult::Quit),
-                KeyCode::Char('Q') => {
-                    confirming_kill_server = true;
-                }
+                KeyCode::Esc => return Ok(PickerResult::Quit),
                 KeyCode::Down | KeyCode::Char('j') => {
                     let i = state.selected().unwrap_or(0);
                     state.select(Some((i + 1) % total));
@@ -267,7 +274,11 @@ fn picker_loop(
                         Ok(PickerResult::NewInstance)
                     };
                 }
-                KeyCode::Char('r') => {
+                _ if keys.matches(Action::PickerQuit, &key) => return Ok(PickerResult::Quit),
+                _ if keys.matches(Action::PickerKillServer, &key) => {
+                    confirming_kill_server = true;
+                }
+                _ if keys.matches(Action::PickerRename, &key) => {
                     let i = state.selected().unwrap_or(0);
                     if let Some(inst) = instances.get(i) {
                         let buf = inst.display_name().to_string();
@@ -279,15 +290,18 @@ fn picker_loop(
                         });
                     }
                 }
-                KeyCode::Char('x') => {
+                _ if keys.matches(Action::PickerKill, &key) => {
                     let i = state.selected().unwrap_or(0);
                     if let Some(inst) = instances.get(i) {
                         return Ok(PickerResult::Kill(inst.id.clone()));
                     }
                 }
-                KeyCode::Char('s') => {
+                _ if keys.matches(Action::PickerSettings, &key) => {
                     return Ok(PickerResult::Settings);
                 }
+                _ if keys.matches(Action::PickerFleet, &key) => {
+                    return Ok(PickerResult::Fleet);
+                }
                 _ => {}
             }
         }
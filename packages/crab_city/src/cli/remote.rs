@@ -0,0 +1,297 @@
+//! `crab login`, `crab logout` and `--remote`: using a Crab City server on
+//! another machine over HTTPS/WSS. The loopback bypass only covers the local
+//! daemon, so remote requests carry a token saved per server by `crab login`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crab_city::config::CrabCityConfig;
+
+use super::daemon::{self, DaemonInfo};
+
+/// A saved login, keyed by server origin in `remotes.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteLogin {
+    pub username: String,
+    pub token: String,
+}
+
+type Logins = BTreeMap<String, RemoteLogin>;
+
+/// Connect to the server at `url` with the saved login for it, checking that
+/// it's reachable and that the login (if it needs one) is still valid.
+pub async fn connect(config: &CrabCityConfig, url: &str) -> Result<DaemonInfo> {
+    let origin = normalize_origin(url)?;
+    let login = load_logins(config)?.remove(&origin);
+    let has_login = login.is_some();
+    let daemon = DaemonInfo::remote(origin.clone(), login.map(|l| l.token));
+
+    if !daemon::health_check_pub(&daemon).await {
+        anyhow::bail!("Can't reach Crab City at {}", origin);
+    }
+    let me: serde_json::Value = daemon
+        .http()
+        .get(format!("{}/api/auth/me", origin))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to check the login")?;
+    let auth_enabled = me["auth_enabled"].as_bool().unwrap_or(false);
+    if auth_enabled && me["user"].is_null() {
+        if has_login {
+            anyhow::bail!(
+                "Your login for {} has expired. Run `crab login {}` again.",
+                origin,
+                origin
+            );
+        }
+        anyhow::bail!("Not logged in to {}. Run `crab login {}`.", origin, origin);
+    }
+    Ok(daemon)
+}
+
+/// Log in to a remote server and save its token.
+pub async fn login_command(
+    config: &CrabCityConfig,
+    url: &str,
+    username: Option<String>,
+) -> Result<()> {
+    let origin = normalize_origin(url)?;
+    let client = reqwest::Client::new();
+
+    let me: serde_json::Value = client
+        .get(format!("{}/api/auth/me", origin))
+        .send()
+        .await
+        .with_context(|| format!("Can't reach Crab City at {}", origin))?
+        .error_for_status()?
+        .json()
+        .await
+        .with_context(|| format!("{} doesn't look like a Crab City server", origin))?;
+    if me["auth_enabled"].as_bool() != Some(true) {
+        println!(
+            "{} doesn't require a login. Use `crab --remote {}` directly.",
+            origin, origin
+        );
+        return Ok(());
+    }
+    if me["needs_setup"].as_bool() == Some(true) {
+        anyhow::bail!(
+            "{} has no accounts yet. Register the first one in the web UI.",
+            origin
+        );
+    }
+
+    let (username, password) =
+        tokio::task::spawn_blocking(move || prompt_credentials(username)).await??;
+
+    let resp = client
+        .post(format!("{}/api/auth/login", origin))
+        .json(&serde_json::json!({ "username": username, "password": password }))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        match body["error"].as_str() {
+            Some(error) => anyhow::bail!("{}", error),
+            None => anyhow::bail!("Login failed: {}", status),
+        }
+    }
+    let token = resp
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find_map(session_cookie)
+        .context("The server didn't return a session")?;
+    let body: serde_json::Value = resp.json().await?;
+    let display_name = body["user"]["display_name"]
+        .as_str()
+        .unwrap_or(&username)
+        .to_string();
+
+    let mut logins = load_logins(config)?;
+    logins.insert(origin.clone(), RemoteLogin { username, token });
+    save_logins(config, &logins)?;
+
+    println!("Logged in to {} as {}.", origin, display_name);
+    eprintln!(
+        "Use `crab --remote {}` or set CRAB_REMOTE={} to work there.",
+        origin, origin
+    );
+    Ok(())
+}
+
+/// Forget the saved login for a server and end its session there.
+pub async fn logout_command(config: &CrabCityConfig, url: &str) -> Result<()> {
+    let origin = normalize_origin(url)?;
+    let mut logins = load_logins(config)?;
+    let Some(login) = logins.remove(&origin) else {
+        anyhow::bail!("Not logged in to {}", origin);
+    };
+    save_logins(config, &logins)?;
+
+    // Best effort: the token is forgotten here either way
+    let daemon = DaemonInfo::remote(origin.clone(), Some(login.token));
+    let _ = daemon
+        .http()
+        .post(format!("{}/api/auth/logout", origin))
+        .send()
+        .await;
+    println!("Logged out of {}.", origin);
+    Ok(())
+}
+
+fn prompt_credentials(username: Option<String>) -> Result<(String, String)> {
+    use std::io::{BufRead, Write};
+
+    let username = match username {
+        Some(u) => u,
+        None => {
+            eprint!("Username: ");
+            std::io::stderr().flush()?;
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            line.trim().to_string()
+        }
+    };
+    if username.is_empty() {
+        anyhow::bail!("No username given");
+    }
+    let password = rpassword::prompt_password("Password: ")
+        .map_err(|e| anyhow::anyhow!("Failed to read password: {}", e))?;
+    Ok((username, password))
+}
+
+/// The server part of a URL (`https://host[:port]`), which logins are saved
+/// under. A bare host means HTTPS.
+pub fn normalize_origin(url: &str) -> Result<String> {
+    let url = url.trim();
+    let with_scheme = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    };
+    let parsed =
+        reqwest::Url::parse(&with_scheme).with_context(|| format!("Not a server URL: {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        anyhow::bail!("Expected an http or https server URL, got {}", url);
+    }
+    if parsed.path() != "/" || parsed.query().is_some() {
+        anyhow::bail!(
+            "Expected the server's address without a path, like {}",
+            parsed.origin().ascii_serialization()
+        );
+    }
+    Ok(parsed.origin().ascii_serialization())
+}
+
+/// The token from a `crab_session=...` `Set-Cookie` header.
+fn session_cookie(header: &str) -> Option<String> {
+    let value = header
+        .split(';')
+        .next()?
+        .trim()
+        .strip_prefix("crab_session=")?;
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn load_logins(config: &CrabCityConfig) -> Result<Logins> {
+    let path = config.remotes_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => {
+            toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Logins::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Write the logins readable only by the owner; they're credentials.
+fn save_logins(config: &CrabCityConfig, logins: &Logins) -> Result<()> {
+    use std::io::Write;
+
+    let path = config.remotes_path();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    file.write_all(toml::to_string(logins)?.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_drop_paths_and_default_to_https() {
+        assert_eq!(
+            normalize_origin("crab.example.com").unwrap(),
+            "https://crab.example.com"
+        );
+        assert_eq!(
+            normalize_origin("https://crab.example.com/").unwrap(),
+            "https://crab.example.com"
+        );
+        assert_eq!(
+            normalize_origin("http://10.0.0.5:8080").unwrap(),
+            "http://10.0.0.5:8080"
+        );
+        // The default port is implied
+        assert_eq!(
+            normalize_origin("https://crab.example.com:443").unwrap(),
+            "https://crab.example.com"
+        );
+        assert!(normalize_origin("https://crab.example.com/spa/").is_err());
+        assert!(normalize_origin("ftp://crab.example.com").is_err());
+    }
+
+    #[test]
+    fn session_token_comes_from_the_cookie() {
+        assert_eq!(
+            session_cookie("crab_session=abc123; HttpOnly; SameSite=Lax; Path=/"),
+            Some("abc123".to_string())
+        );
+        assert_eq!(session_cookie("other=1; Path=/"), None);
+        assert_eq!(session_cookie("crab_session=; Max-Age=0"), None);
+    }
+
+    #[test]
+    fn logins_round_trip_privately() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = CrabCityConfig::new(Some(tmp.path().to_path_buf())).unwrap();
+        assert!(load_logins(&config).unwrap().is_empty());
+
+        let mut logins = Logins::new();
+        logins.insert(
+            "https://crab.example.com".to_string(),
+            RemoteLogin {
+                username: "alice".to_string(),
+                token: "tok123".to_string(),
+            },
+        );
+        save_logins(&config, &logins).unwrap();
+        assert_eq!(load_logins(&config).unwrap(), logins);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(config.remotes_path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! `crab search` and `crab show`: full-text search over stored conversations
+//! and reading one back, from the terminal.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use crab_city::config::CrabCityConfig;
+use crab_city::models::{
+    ConversationWithEntries, PaginatedResponse, SearchMatchEntry, SearchResultConversation,
+};
+
+use super::daemon;
+
+/// Message author to restrict matches to.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SearchRole {
+    User,
+    Assistant,
+}
+
+impl SearchRole {
+    fn as_str(self) -> &'static str {
+        match self {
+            SearchRole::User => "user",
+            SearchRole::Assistant => "assistant",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub role: Option<SearchRole>,
+    /// Unix time; only messages at or after it
+    pub since: Option<i64>,
+    /// Instance, project name or project directory
+    pub project: Option<String>,
+    pub limit: u32,
+    pub json: bool,
+}
+
+pub async fn search_command(
+    config: &CrabCityConfig,
+    query: &str,
+    options: SearchOptions,
+) -> Result<()> {
+    let daemon = daemon::ensure_daemon(config).await?;
+
+    let mut params = vec![
+        ("q", query.to_string()),
+        ("per_page", options.limit.clamp(1, 100).to_string()),
+    ];
+    if let Some(role) = options.role {
+        params.push(("role", role.as_str().to_string()));
+    }
+    if let Some(since) = options.since {
+        params.push(("date_from", since.to_string()));
+    }
+    if let Some(project) = &options.project {
+        params.push(("project", project_filter(project)));
+    }
+
+    let url = format!("{}/api/conversations/search", daemon.base_url());
+    let resp = daemon
+        .http()
+        .get(&url)
+        .query(&params)
+        .send()
+        .await?
+        .error_for_status()
+        .context("Search failed")?;
+
+    if options.json {
+        let body: serde_json::Value = resp.json().await?;
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+
+    let results: PaginatedResponse<SearchResultConversation> = resp.json().await?;
+    if results.items.is_empty() {
+        println!("No conversations match {:?}.", query);
+        return Ok(());
+    }
+    let color = std::io::stdout().is_terminal();
+    for convo in &results.items {
+        print_result(convo, color);
+    }
+    println!(
+        "{} of {} conversation(s). `crab show <id>` to read one.",
+        results.items.len(),
+        results.total
+    );
+    Ok(())
+}
+
+fn print_result(convo: &SearchResultConversation, color: bool) {
+    println!(
+        "{}",
+        bold(convo.title.as_deref().unwrap_or("(untitled)"), color)
+    );
+    println!(
+        "  {}  \u{b7}  {}  \u{b7}  {}  \u{b7}  {} match{}",
+        convo.id,
+        convo.instance_id,
+        format_unix(convo.updated_at),
+        convo.match_count,
+        if convo.match_count == 1 { "" } else { "es" }
+    );
+    for m in &convo.matches {
+        print_match(m, color);
+    }
+    println!();
+}
+
+fn print_match(m: &SearchMatchEntry, color: bool) {
+    println!(
+        "  {:<10} {}",
+        m.role.as_deref().unwrap_or("-"),
+        render_snippet(&m.snippet, color)
+    );
+}
+
+/// Turn a search snippet (HTML-escaped, matches in `<mark>`) into one line
+/// of terminal text, with matches in bold yellow when `color` is set.
+fn render_snippet(snippet: &str, color: bool) -> String {
+    let (start, end) = if color {
+        ("\x1b[1;33m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    let text = snippet
+        .replace("<mark>", start)
+        .replace("</mark>", end)
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Imported conversations are filed under Claude's project directory name,
+/// the absolute path with every other character turned into `-`. A path
+/// argument is converted to that form; anything else is matched as is.
+fn project_filter(arg: &str) -> String {
+    let looks_like_path = arg == "." || arg.contains('/') || arg.starts_with('~');
+    if !looks_like_path {
+        return arg.to_string();
+    }
+    let expanded = match arg.strip_prefix('~') {
+        Some(rest) => std::env::var("HOME").map_or_else(|_| arg.to_string(), |h| h + rest),
+        None => arg.to_string(),
+    };
+    let path = Path::new(&expanded)
+        .canonicalize()
+        .unwrap_or_else(|_| Path::new(&expanded).to_path_buf());
+    path.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Parse `--since`: an age (`30m`, `12h`, `7d`, `2w`), a date
+/// (`2025-01-15`, local midnight) or an RFC 3339 time.
+pub fn parse_since(arg: &str) -> Result<i64, String> {
+    since_timestamp(arg, Utc::now())
+}
+
+fn since_timestamp(arg: &str, now: DateTime<Utc>) -> Result<i64, String> {
+    let arg = arg.trim();
+    if let Some(unit) = arg.chars().last().filter(char::is_ascii_alphabetic)
+        && let Ok(n) = arg[..arg.len() - 1].parse::<i64>()
+    {
+        let secs = match unit {
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => return Err(format!("unknown unit {:?} (use m, h, d or w)", unit)),
+        };
+        return Ok(now.timestamp() - n * secs);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(arg, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("valid time");
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|t| t.timestamp())
+            .ok_or_else(|| format!("no such local time: {}", arg));
+    }
+    DateTime::parse_from_rfc3339(arg)
+        .map(|t| t.timestamp())
+        .map_err(|_| {
+            format!(
+                "expected an age like 7d, a date or an RFC 3339 time, got {:?}",
+                arg
+            )
+        })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShowOptions {
+    /// Pipe through `$PAGER` when writing to a terminal
+    pub pager: bool,
+}
+
+pub async fn show_command(
+    config: &CrabCityConfig,
+    conversation_id: &str,
+    options: ShowOptions,
+) -> Result<()> {
+    let daemon = daemon::ensure_daemon(config).await?;
+    let url = format!(
+        "{}/api/conversations/{}",
+        daemon.base_url(),
+        conversation_id
+    );
+    let resp = daemon.http().get(&url).send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        anyhow::bail!(
+            "No conversation with id '{}'. Use `crab search` to find one.",
+            conversation_id
+        );
+    }
+    let convo: ConversationWithEntries = resp.error_for_status()?.json().await?;
+
+    let terminal = std::io::stdout().is_terminal();
+    let text = format_conversation(&convo, terminal);
+    if terminal && options.pager {
+        page(&text)
+    } else {
+        print!("{}", text);
+        Ok(())
+    }
+}
+
+/// The conversation as readable text: a header, then each message under
+/// its author and time. Entries without text (tool calls) are skipped.
+fn format_conversation(convo: &ConversationWithEntries, color: bool) -> String {
+    let c = &convo.conversation;
+    let mut out = format!(
+        "{}\n{}  \u{b7}  {}  \u{b7}  started {}\n",
+        bold(c.title.as_deref().unwrap_or("(untitled)"), color),
+        c.id,
+        c.instance_id,
+        format_unix(c.created_at)
+    );
+    for entry in &convo.entries {
+        let Some(content) = entry.content.as_deref().filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+        let heading = format!(
+            "\u{2500}\u{2500} {} \u{b7} {} ",
+            entry.role.as_deref().unwrap_or(&entry.entry_type),
+            format_timestamp(&entry.timestamp)
+        );
+        let rule = "\u{2500}".repeat(60usize.saturating_sub(heading.chars().count()));
+        out.push('\n');
+        out.push_str(&bold(&format!("{}{}", heading, rule), color));
+        out.push_str("\n\n");
+        out.push_str(content.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Show `text` in `$PAGER` (default `less`), falling back to stdout when
+/// the pager can't be started.
+fn page(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    let child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&pager)
+        .env(
+            "LESS",
+            std::env::var("LESS").unwrap_or_else(|_| "FRX".into()),
+        )
+        .stdin(std::process::Stdio::piped())
+        .spawn();
+    let Ok(mut child) = child else {
+        print!("{}", text);
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; that's not an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().context("Pager failed")?;
+    Ok(())
+}
+
+fn bold(text: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[1m{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn format_unix(secs: i64) -> String {
+    Local.timestamp_opt(secs, 0).single().map_or_else(
+        || secs.to_string(),
+        |t| t.format("%Y-%m-%d %H:%M").to_string(),
+    )
+}
+
+fn format_timestamp(rfc3339: &str) -> String {
+    DateTime::parse_from_rfc3339(rfc3339).map_or_else(
+        |_| rfc3339.to_string(),
+        |t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_unescape_and_highlight() {
+        let snippet =
+            "...fix the <mark>auth</mark>\n  bug in &lt;Login&gt; &amp; &quot;signup&quot;...";
+        assert_eq!(
+            render_snippet(snippet, false),
+            "...fix the auth bug in <Login> & \"signup\"..."
+        );
+        assert_eq!(
+            render_snippet("<mark>auth</mark>", true),
+            "\x1b[1;33mauth\x1b[0m"
+        );
+        // An escaped entity is not unescaped twice
+        assert_eq!(render_snippet("&amp;lt;", false), "&lt;");
+    }
+
+    #[test]
+    fn since_accepts_ages_dates_and_times() {
+        let now = DateTime::parse_from_rfc3339("2025-06-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(since_timestamp("2h", now), Ok(now.timestamp() - 7200));
+        assert_eq!(since_timestamp("7d", now), Ok(now.timestamp() - 7 * 86_400));
+        assert_eq!(
+            since_timestamp("2025-06-01T08:00:00+02:00", now),
+            Ok(1_748_757_600)
+        );
+        let local_midnight = Local
+            .with_ymd_and_hms(2025, 6, 1, 0, 0, 0)
+            .earliest()
+            .unwrap()
+            .timestamp();
+        assert_eq!(since_timestamp("2025-06-01", now), Ok(local_midnight));
+        assert!(since_timestamp("3y", now).is_err());
+        assert!(since_timestamp("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn project_paths_use_claude_directory_names() {
+        assert_eq!(project_filter("api"), "api");
+        assert_eq!(project_filter("/home/me/src/my.app"), "-home-me-src-my-app");
+    }
+}
@@ -0,0 +1,346 @@
+//! `crab send`: type a prompt into an instance without attaching, optionally
+//! waiting for Claude to finish and printing its reply.
+
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite;
+
+use crab_city::config::CrabCityConfig;
+use crab_city::inference::ClaudeState;
+use crab_city::ws::{ClientMessage, ServerMessage, Topic, paste_enter_delay};
+
+use super::{daemon, handshake};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SendOptions {
+    /// Block until Claude has picked up the prompt and gone idle again
+    pub wait: bool,
+    /// Give up waiting after this long
+    pub timeout: Duration,
+    /// Print Claude's reply once it's done (implies `wait`)
+    pub print_reply: bool,
+}
+
+/// How often to re-read the conversation when the reply isn't written yet.
+const REPLY_RETRIES: u32 = 10;
+const REPLY_RETRY_DELAY: Duration = Duration::from_millis(300);
+
+/// How long to listen for a refusal after pressing Enter when not waiting
+/// for the reply.
+const REFUSAL_WINDOW: Duration = Duration::from_millis(500);
+
+pub async fn send_command(
+    config: &CrabCityConfig,
+    target: &str,
+    prompt: Option<String>,
+    options: SendOptions,
+) -> Result<()> {
+    let prompt = match prompt {
+        Some(p) => p,
+        None => {
+            let mut buf = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)
+                .context("Failed to read prompt from stdin")?;
+            buf
+        }
+    };
+    let prompt = prompt.trim_end();
+    if prompt.is_empty() {
+        anyhow::bail!("Nothing to send");
+    }
+
+    let daemon = daemon::require_running_daemon(config).await?;
+    let instance_id = super::resolve_instance(&daemon, target).await?;
+
+    let mut ws = daemon
+        .connect_mux()
+        .await
+        .context("Failed to connect to the daemon")?;
+    handshake::handshake(&mut ws, &[]).await?;
+
+    let wait = options.wait || options.print_reply;
+    // Subscribe before typing so the Thinking transition can't slip past
+    let topics = if wait {
+        vec![Topic::Instances {
+            instance_id: Some(instance_id.clone()),
+        }]
+    } else {
+        vec![]
+    };
+    send_json(&mut ws, &ClientMessage::Subscribe { topics }).await?;
+    // The reply must follow a user turn newer than any logged so far
+    let prompts_before = if options.print_reply {
+        user_turns(&conversation_turns(&daemon, &instance_id).await?)
+    } else {
+        0
+    };
+
+    send_json(
+        &mut ws,
+        &ClientMessage::Input {
+            instance_id: instance_id.clone(),
+            data: prompt.to_string(),
+            task_id: None,
+        },
+    )
+    .await?;
+    // Let the TUI absorb the paste; a refused paste is reported meanwhile
+    check_refused(&mut ws, &instance_id, paste_enter_delay(prompt.len())).await?;
+    send_json(
+        &mut ws,
+        &ClientMessage::Input {
+            instance_id: instance_id.clone(),
+            data: "\r".to_string(),
+            task_id: None,
+        },
+    )
+    .await?;
+
+    if !wait {
+        check_refused(&mut ws, &instance_id, REFUSAL_WINDOW).await?;
+        let _ = ws.close(None).await;
+        return Ok(());
+    }
+
+    let outcome = tokio::time::timeout(options.timeout, wait_for_reply(&mut ws, &instance_id))
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Timed out after {}s waiting for Claude to finish",
+                options.timeout.as_secs()
+            )
+        })??;
+    let _ = ws.close(None).await;
+
+    if let ClaudeState::WaitingForInput { prompt } = &outcome {
+        eprintln!(
+            "[crab: Claude is waiting for input{}]",
+            prompt
+                .as_deref()
+                .map(|p| format!(": {}", p))
+                .unwrap_or_default()
+        );
+    }
+
+    if options.print_reply {
+        match fetch_reply(&daemon, &instance_id, prompts_before).await? {
+            Some(reply) => println!("{}", reply),
+            None => eprintln!("[crab: no reply found in the conversation]"),
+        }
+    }
+    Ok(())
+}
+
+/// Listen for `within`, failing if the daemon refuses our input (a viewer's
+/// role, a spectated instance). Refusals come back as an `Error` right away.
+async fn check_refused(
+    ws: &mut handshake::WsStream,
+    instance_id: &str,
+    within: Duration,
+) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + within;
+    while let Ok(Some(msg)) = tokio::time::timeout_at(deadline, ws.next()).await {
+        let tungstenite::Message::Text(text) = msg? else {
+            continue;
+        };
+        if let Some(message) = refusal(&text, instance_id) {
+            anyhow::bail!(message);
+        }
+    }
+    Ok(())
+}
+
+/// The error message if `text` is the daemon refusing input to `instance_id`.
+fn refusal(text: &str, instance_id: &str) -> Option<String> {
+    match serde_json::from_str::<ServerMessage>(text) {
+        Ok(ServerMessage::Error {
+            instance_id: Some(id),
+            message,
+        }) if id == instance_id => Some(message),
+        _ => None,
+    }
+}
+
+async fn send_json(ws: &mut handshake::WsStream, msg: &ClientMessage) -> Result<()> {
+    ws.send(tungstenite::Message::Text(serde_json::to_string(msg)?))
+        .await?;
+    Ok(())
+}
+
+/// Read state changes until the instance has been busy and settled again.
+/// Returns the state it settled in.
+async fn wait_for_reply(ws: &mut handshake::WsStream, instance_id: &str) -> Result<ClaudeState> {
+    let mut seen_busy = false;
+    while let Some(msg) = ws.next().await {
+        let tungstenite::Message::Text(text) = msg? else {
+            continue;
+        };
+        match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::StateChange {
+                instance_id: id,
+                state,
+                ..
+            }) if id == instance_id && reply_finished(&mut seen_busy, &state) => {
+                return Ok(state);
+            }
+            Ok(ServerMessage::InstanceStopped { instance_id: id }) if id == instance_id => {
+                anyhow::bail!("Instance stopped before Claude finished");
+            }
+            Ok(ServerMessage::Error {
+                instance_id: Some(id),
+                message,
+            }) if id == instance_id => anyhow::bail!(message),
+            _ => {}
+        }
+    }
+    anyhow::bail!("Lost connection to the daemon")
+}
+
+/// Whether `state` ends the turn our prompt started. An Idle seen before
+/// Claude got busy is the prompt still being typed, not the answer.
+fn reply_finished(seen_busy: &mut bool, state: &ClaudeState) -> bool {
+    match state {
+        s if s.is_active() => {
+            *seen_busy = true;
+            false
+        }
+        ClaudeState::Idle => *seen_busy,
+        ClaudeState::WaitingForInput { .. } => true,
+        _ => false,
+    }
+}
+
+async fn conversation_turns(
+    daemon: &daemon::DaemonInfo,
+    instance_id: &str,
+) -> Result<Vec<serde_json::Value>> {
+    let url = format!(
+        "{}/api/instances/{}/conversation",
+        daemon.base_url(),
+        instance_id
+    );
+    let snapshot: serde_json::Value = daemon.http().get(&url).send().await?.json().await?;
+    Ok(snapshot["turns"].as_array().cloned().unwrap_or_default())
+}
+
+fn user_turns(turns: &[serde_json::Value]) -> usize {
+    turns.iter().filter(|t| t["role"] == "User").count()
+}
+
+/// The assistant's answer to our prompt, once it's in the log. Snapshots
+/// that don't yet have a user turn past `prompts_before` still end with the
+/// previous exchange, so they're retried.
+async fn fetch_reply(
+    daemon: &daemon::DaemonInfo,
+    instance_id: &str,
+    prompts_before: usize,
+) -> Result<Option<String>> {
+    for attempt in 0..REPLY_RETRIES {
+        if attempt > 0 {
+            tokio::time::sleep(REPLY_RETRY_DELAY).await;
+        }
+        let turns = conversation_turns(daemon, instance_id).await?;
+        if let Some(reply) = last_reply(&turns, prompts_before) {
+            return Ok(Some(reply));
+        }
+    }
+    Ok(None)
+}
+
+/// Text of the assistant turns after the last user turn, joined by blank
+/// lines. `None` until there are more than `prompts_before` user turns.
+fn last_reply(turns: &[serde_json::Value], prompts_before: usize) -> Option<String> {
+    if user_turns(turns) <= prompts_before {
+        return None;
+    }
+    let after_user = turns
+        .iter()
+        .rposition(|t| t["role"] == "User")
+        .map_or(0, |i| i + 1);
+    let parts: Vec<&str> = turns[after_user..]
+        .iter()
+        .filter(|t| t["role"] == "Assistant")
+        .filter_map(|t| t["content"].as_str())
+        .filter(|c| !c.trim().is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn idle_before_busy_is_not_the_reply() {
+        let mut seen_busy = false;
+        assert!(!reply_finished(&mut seen_busy, &ClaudeState::Idle));
+        assert!(!reply_finished(&mut seen_busy, &ClaudeState::Thinking));
+        assert!(!reply_finished(
+            &mut seen_busy,
+            &ClaudeState::ToolExecuting {
+                tool: "Bash".into()
+            }
+        ));
+        assert!(reply_finished(&mut seen_busy, &ClaudeState::Idle));
+    }
+
+    #[test]
+    fn waiting_for_input_ends_the_wait() {
+        let mut seen_busy = false;
+        assert!(reply_finished(
+            &mut seen_busy,
+            &ClaudeState::WaitingForInput { prompt: None }
+        ));
+    }
+
+    #[test]
+    fn last_reply_joins_assistant_turns_after_last_prompt() {
+        let turns = vec![
+            json!({"role": "User", "content": "first"}),
+            json!({"role": "Assistant", "content": "old answer"}),
+            json!({"role": "User", "content": "run the tests"}),
+            json!({"role": "Assistant", "content": "Running them."}),
+            json!({"role": "Assistant", "content": ""}),
+            json!({"role": "Assistant", "content": "All 12 pass."}),
+        ];
+        assert_eq!(
+            last_reply(&turns, 1).as_deref(),
+            Some("Running them.\n\nAll 12 pass.")
+        );
+        assert_eq!(last_reply(&turns[..3], 1), None);
+    }
+
+    #[test]
+    fn last_reply_waits_for_a_new_prompt() {
+        let turns = vec![
+            json!({"role": "User", "content": "first"}),
+            json!({"role": "Assistant", "content": "old answer"}),
+        ];
+        // Our prompt isn't logged yet: the old answer isn't the reply
+        assert_eq!(last_reply(&turns, 1), None);
+        assert_eq!(last_reply(&turns, 0).as_deref(), Some("old answer"));
+    }
+
+    #[test]
+    fn refusals_are_errors_for_our_instance() {
+        let error = |id: &str| {
+            serde_json::to_string(&ServerMessage::Error {
+                instance_id: Some(id.to_string()),
+                message: "Viewers can't send input".to_string(),
+            })
+            .unwrap()
+        };
+        assert_eq!(
+            refusal(&error("inst-1"), "inst-1").as_deref(),
+            Some("Viewers can't send input")
+        );
+        assert_eq!(refusal(&error("inst-2"), "inst-1"), None);
+        let stopped = serde_json::to_string(&ServerMessage::InstanceStopped {
+            instance_id: "inst-1".to_string(),
+        })
+        .unwrap();
+        assert_eq!(refusal(&stopped, "inst-1"), None);
+    }
+}
@@ -62,7 +62,7 @@ struct StatusMessage {
 }
 
 pub fn run_settings(terminal: &mut DefaultTerminal, daemon: &DaemonInfo) -> Result<()> {
-    let client = reqwest::blocking::Client::new();
+    let client = daemon.blocking_http();
     let mut config = fetch_config(&client, daemon)?;
     let mut selected = 0usize;
     let mut edit: Option<EditState> = None;
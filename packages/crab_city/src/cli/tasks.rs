@@ -0,0 +1,475 @@
+//! `crab tasks`: the task board from the terminal. Lists, files, edits,
+//! completes, tags and dispatches tasks through the daemon's REST API, so
+//! scripts can file work as easily as the web UI.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::Read;
+
+use crab_city::config::CrabCityConfig;
+use crab_city::models::{CreateTaskRequest, TaskWithTags, UpdateTaskRequest};
+
+use super::daemon::{self, DaemonInfo};
+
+/// Where a task is on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TaskStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Only tasks with this status (default: pending and in progress)
+    pub status: Option<TaskStatus>,
+    /// Include completed and cancelled tasks
+    pub all: bool,
+    /// Only tasks assigned to this instance
+    pub instance: Option<String>,
+    pub tag: Option<String>,
+    /// Only tasks whose title or body contains this text
+    pub search: Option<String>,
+    pub limit: u32,
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Body text, or `-` for stdin
+    pub body: Option<String>,
+    pub instance: Option<String>,
+    pub priority: Option<i32>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EditOptions {
+    pub title: Option<String>,
+    /// Body text, or `-` for stdin
+    pub body: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<i32>,
+    pub instance: Option<String>,
+}
+
+pub async fn list_command(config: &CrabCityConfig, options: ListOptions) -> Result<()> {
+    let daemon = daemon::ensure_daemon(config).await?;
+    let instances = instance_names(&daemon).await?;
+
+    let mut params = vec![("limit", options.limit.max(1).to_string())];
+    if let Some(status) = options.status {
+        params.push(("status", status.as_str().to_string()));
+    }
+    if let Some(target) = &options.instance {
+        params.push((
+            "instance_id",
+            super::resolve_instance(&daemon, target).await?,
+        ));
+    }
+    if let Some(tag) = &options.tag {
+        params.push(("tag", tag.clone()));
+    }
+    if let Some(search) = &options.search {
+        params.push(("search", search.clone()));
+    }
+
+    let url = format!("{}/api/tasks", daemon.base_url());
+    let resp = daemon.http().get(&url).query(&params).send().await?;
+    let mut tasks: Vec<TaskWithTags> = check(resp).await?.json().await?;
+    if options.status.is_none() && !options.all {
+        tasks.retain(|t| is_open(&t.task.status));
+    }
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&tasks)?);
+    } else if tasks.is_empty() {
+        println!("No tasks.");
+    } else {
+        println!(
+            "{:<6} {:<12} {:<4} {:<20} TITLE",
+            "ID", "STATUS", "PRI", "INSTANCE"
+        );
+        println!("{}", "-".repeat(100));
+        for t in &tasks {
+            println!("{}", format_row(t, &instances));
+        }
+        println!("\n{} task(s)", tasks.len());
+    }
+    Ok(())
+}
+
+/// File a task. A title of `-` reads the task from stdin: the first line is
+/// the title and the rest the body.
+pub async fn add_command(config: &CrabCityConfig, title: &str, options: AddOptions) -> Result<()> {
+    let (title, body) = if title == "-" {
+        if options.body.as_deref() == Some("-") {
+            anyhow::bail!("Only one of the title and --body can be read from stdin");
+        }
+        let (title, body) = split_title_body(&read_stdin()?);
+        (title, body.or(options.body))
+    } else {
+        let body = options.body.as_deref().map(text_arg).transpose()?;
+        (title.trim().to_string(), body)
+    };
+    if title.is_empty() {
+        anyhow::bail!("A task needs a title");
+    }
+
+    let daemon = daemon::ensure_daemon(config).await?;
+    let instance_id = match &options.instance {
+        Some(target) => Some(super::resolve_instance(&daemon, target).await?),
+        None => None,
+    };
+    let request = CreateTaskRequest {
+        title,
+        body: body.filter(|b| !b.is_empty()),
+        status: None,
+        priority: options.priority,
+        instance_id,
+        tags: (!options.tags.is_empty()).then_some(options.tags),
+    };
+
+    let url = format!("{}/api/tasks", daemon.base_url());
+    let resp = daemon.http().post(&url).json(&request).send().await?;
+    let task: TaskWithTags = check(resp).await?.json().await?;
+    println!("{}\t{}", task_id(&task), task.task.title);
+    Ok(())
+}
+
+pub async fn edit_command(config: &CrabCityConfig, id: i64, options: EditOptions) -> Result<()> {
+    let body = options.body.as_deref().map(text_arg).transpose()?;
+    if options.title.is_none()
+        && body.is_none()
+        && options.status.is_none()
+        && options.priority.is_none()
+        && options.instance.is_none()
+    {
+        anyhow::bail!("Nothing to change. See `crab tasks edit --help`.");
+    }
+
+    let daemon = daemon::ensure_daemon(config).await?;
+    let client = daemon.http();
+    let task = fetch_task(&client, &daemon, id).await?;
+    let instance_id = match &options.instance {
+        Some(target) => Some(super::resolve_instance(&daemon, target).await?),
+        None => None,
+    };
+    let update = UpdateTaskRequest {
+        title: options.title,
+        body,
+        status: options.status.map(|s| s.as_str().to_string()),
+        priority: options.priority,
+        instance_id,
+        ..Default::default()
+    };
+    update_task(&client, &daemon, id, &update).await?;
+    println!("Updated #{} {}", id, task.task.title);
+    Ok(())
+}
+
+pub async fn done_command(config: &CrabCityConfig, ids: &[i64]) -> Result<()> {
+    let daemon = daemon::ensure_daemon(config).await?;
+    let client = daemon.http();
+    let update = UpdateTaskRequest {
+        status: Some(TaskStatus::Completed.as_str().to_string()),
+        ..Default::default()
+    };
+    for &id in ids {
+        let task = fetch_task(&client, &daemon, id).await?;
+        update_task(&client, &daemon, id, &update).await?;
+        println!("Completed #{} {}", id, task.task.title);
+    }
+    Ok(())
+}
+
+/// Type a task into its instance, assigning it first when `instance` is
+/// given. The daemon moves pending tasks to in progress.
+pub async fn send_command(config: &CrabCityConfig, id: i64, instance: Option<&str>) -> Result<()> {
+    let daemon = daemon::require_running_daemon(config).await?;
+    let client = daemon.http();
+    let task = fetch_task(&client, &daemon, id).await?;
+
+    let instance_id = match instance {
+        Some(target) => {
+            let instance_id = super::resolve_instance(&daemon, target).await?;
+            let update = UpdateTaskRequest {
+                instance_id: Some(instance_id.clone()),
+                ..Default::default()
+            };
+            update_task(&client, &daemon, id, &update).await?;
+            instance_id
+        }
+        None => task.task.instance_id.clone().with_context(|| {
+            format!(
+                "Task #{} has no assigned instance. Pass --instance to pick one.",
+                id
+            )
+        })?,
+    };
+
+    let url = format!("{}/api/tasks/{}/send", daemon.base_url(), id);
+    let resp = client.post(&url).send().await?;
+    let sent: serde_json::Value = check(resp).await?.json().await?;
+    let instances = instance_names(&daemon).await?;
+    println!(
+        "Sent #{} to {} ({})",
+        id,
+        instance_label(&instance_id, &instances),
+        sent["status"].as_str().unwrap_or("sent")
+    );
+    Ok(())
+}
+
+/// Add tags to a task, or remove them by name with `remove`.
+pub async fn tag_command(
+    config: &CrabCityConfig,
+    id: i64,
+    tags: &[String],
+    remove: bool,
+) -> Result<()> {
+    let daemon = daemon::ensure_daemon(config).await?;
+    let client = daemon.http();
+    let task = fetch_task(&client, &daemon, id).await?;
+
+    for name in tags {
+        let resp = if remove {
+            let Some(tag) = task.tags.iter().find(|t| &t.name == name) else {
+                eprintln!("#{} isn't tagged '{}'", id, name);
+                continue;
+            };
+            let url = format!("{}/api/tasks/{}/tags/{}", daemon.base_url(), id, tag.id);
+            client.delete(&url).send().await?
+        } else {
+            let url = format!("{}/api/tasks/{}/tags", daemon.base_url(), id);
+            client
+                .post(&url)
+                .json(&serde_json::json!({ "tag": name }))
+                .send()
+                .await?
+        };
+        check(resp).await?;
+    }
+
+    let task = fetch_task(&client, &daemon, id).await?;
+    println!("#{} {}", id, format_tags(&task));
+    Ok(())
+}
+
+async fn fetch_task(
+    client: &reqwest::Client,
+    daemon: &DaemonInfo,
+    id: i64,
+) -> Result<TaskWithTags> {
+    let url = format!("{}/api/tasks/{}", daemon.base_url(), id);
+    let resp = client.get(&url).send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        anyhow::bail!("No task #{}. Use `crab tasks list` to find one.", id);
+    }
+    Ok(check(resp).await?.json().await?)
+}
+
+async fn update_task(
+    client: &reqwest::Client,
+    daemon: &DaemonInfo,
+    id: i64,
+    update: &UpdateTaskRequest,
+) -> Result<()> {
+    let url = format!("{}/api/tasks/{}", daemon.base_url(), id);
+    check(client.patch(&url).json(update).send().await?).await?;
+    Ok(())
+}
+
+/// Pass a successful response through; turn anything else into an error
+/// carrying the daemon's message.
+async fn check(resp: reqwest::Response) -> Result<reqwest::Response> {
+    if resp.status().is_success() {
+        return Ok(resp);
+    }
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    anyhow::bail!("{} {}", status, text)
+}
+
+/// Display names of the running instances, by id.
+async fn instance_names(daemon: &DaemonInfo) -> Result<HashMap<String, String>> {
+    let instances = super::fetch_instances(daemon).await?;
+    Ok(instances
+        .into_iter()
+        .map(|i| (i.id.clone(), i.display_name().to_string()))
+        .collect())
+}
+
+/// An instance's name, or its short id once it's gone.
+fn instance_label(instance_id: &str, instances: &HashMap<String, String>) -> String {
+    instances
+        .get(instance_id)
+        .cloned()
+        .unwrap_or_else(|| instance_id.chars().take(8).collect())
+}
+
+fn is_open(status: &str) -> bool {
+    status != TaskStatus::Completed.as_str() && status != TaskStatus::Cancelled.as_str()
+}
+
+fn task_id(task: &TaskWithTags) -> String {
+    task.task
+        .id
+        .map_or_else(|| "-".to_string(), |id| id.to_string())
+}
+
+fn format_tags(task: &TaskWithTags) -> String {
+    if task.tags.is_empty() {
+        return "(no tags)".to_string();
+    }
+    let names: Vec<_> = task.tags.iter().map(|t| t.name.as_str()).collect();
+    format!("[{}]", names.join(", "))
+}
+
+fn format_row(task: &TaskWithTags, instances: &HashMap<String, String>) -> String {
+    let t = &task.task;
+    let instance = t
+        .instance_id
+        .as_deref()
+        .map_or_else(|| "-".to_string(), |id| instance_label(id, instances));
+    let mut row = format!(
+        "{:<6} {:<12} {:<4} {:<20} {}",
+        task_id(task),
+        t.status,
+        t.priority,
+        instance,
+        t.title
+    );
+    if !task.tags.is_empty() {
+        row.push(' ');
+        row.push_str(&format_tags(task));
+    }
+    row
+}
+
+/// Split text from stdin into a title (its first non-blank line) and a
+/// body (everything after, if anything).
+fn split_title_body(text: &str) -> (String, Option<String>) {
+    let text = text.trim();
+    let (title, body) = text.split_once('\n').unwrap_or((text, ""));
+    let body = body.trim();
+    (
+        title.trim().to_string(),
+        (!body.is_empty()).then(|| body.to_string()),
+    )
+}
+
+/// An argument that may be `-` for stdin.
+fn text_arg(arg: &str) -> Result<String> {
+    if arg == "-" {
+        read_stdin()
+    } else {
+        Ok(arg.to_string())
+    }
+}
+
+fn read_stdin() -> Result<String> {
+    let mut buf = String::new();
+    std::io::stdin()
+        .read_to_string(&mut buf)
+        .context("Failed to read stdin")?;
+    Ok(buf.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crab_city::models::{Tag, Task};
+
+    fn task(id: i64, title: &str, status: &str, instance_id: Option<&str>) -> TaskWithTags {
+        TaskWithTags {
+            task: Task {
+                id: Some(id),
+                uuid: format!("uuid-{}", id),
+                title: title.to_string(),
+                body: None,
+                status: status.to_string(),
+                priority: 0,
+                instance_id: instance_id.map(str::to_string),
+                creator_id: None,
+                creator_name: "anonymous".to_string(),
+                sort_order: id as f64,
+                created_at: 0,
+                updated_at: 0,
+                completed_at: None,
+                is_deleted: false,
+                sent_text: None,
+                conversation_id: None,
+            },
+            tags: vec![],
+            dispatches: vec![],
+        }
+    }
+
+    #[test]
+    fn stdin_splits_into_title_and_body() {
+        assert_eq!(
+            split_title_body("\n  Fix the login redirect\n\nIt loops when\nthe cookie expires.\n"),
+            (
+                "Fix the login redirect".to_string(),
+                Some("It loops when\nthe cookie expires.".to_string())
+            )
+        );
+        assert_eq!(
+            split_title_body("TODO: drop the v1 API\n"),
+            ("TODO: drop the v1 API".to_string(), None)
+        );
+        assert_eq!(split_title_body("  \n"), (String::new(), None));
+    }
+
+    #[test]
+    fn rows_show_instance_names_and_tags() {
+        let instances = HashMap::from([("abc-123".to_string(), "api".to_string())]);
+        let mut t = task(7, "Fix login", "in_progress", Some("abc-123"));
+        t.tags = vec![
+            Tag {
+                id: 1,
+                name: "bug".to_string(),
+                color: None,
+            },
+            Tag {
+                id: 2,
+                name: "auth".to_string(),
+                color: None,
+            },
+        ];
+        assert_eq!(
+            format_row(&t, &instances),
+            format!(
+                "{:<6} {:<12} {:<4} {:<20} Fix login [bug, auth]",
+                7, "in_progress", 0, "api"
+            )
+        );
+
+        // Unassigned, and assigned to an instance that has since gone
+        let row = format_row(&task(8, "Docs", "pending", None), &instances);
+        assert!(row.contains(&format!(" {:<20} Docs", "-")));
+        let gone = task(9, "Old", "pending", Some("0123456789abcdef"));
+        assert!(format_row(&gone, &instances).contains("01234567 "));
+    }
+
+    #[test]
+    fn only_pending_and_in_progress_are_open() {
+        assert!(is_open("pending"));
+        assert!(is_open("in_progress"));
+        assert!(!is_open("completed"));
+        assert!(!is_open("cancelled"));
+    }
+}
@@ -0,0 +1,460 @@
+//! Tiled attach: two to four instances side by side. Each pane has its own
+//! connection, and so its own viewport, so the daemon sizes every PTY as if
+//! the pane were a separate client.
+
+use anyhow::Result;
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind, MouseButton, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Paragraph;
+use std::io::Write;
+use std::time::Duration;
+
+use super::attach::{
+    self, AttachOptions, AttachParser, AttachState, InputAction, PaneLink, PtyWidget,
+};
+use super::copy_mode::CopyModeWidget;
+use super::daemon::{DaemonError, DaemonInfo};
+use super::keys::{Action, KeyBindings};
+use super::terminal::get_terminal_size;
+use crab_city::inference::ClaudeState;
+use virtual_terminal::render_hyperlink_overlay;
+
+/// Most panes one tiled attach shows.
+pub const MAX_PANES: usize = 4;
+
+/// Lines scrolled per mouse wheel tick.
+const MOUSE_SCROLL_LINES: usize = 3;
+
+struct Pane {
+    instance_id: String,
+    name: String,
+    parser: AttachParser,
+    link: PaneLink,
+    state: AttachState,
+}
+
+/// Split `area` for `n` panes: two side by side, three as one left and two
+/// stacked right, four in a grid. Columns are one cell apart.
+fn pane_layout(area: Rect, n: usize) -> Vec<Rect> {
+    let halves = |area: Rect| -> [Rect; 2] {
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+            .spacing(1)
+            .areas(area)
+    };
+    let stacked = |area: Rect| -> [Rect; 2] {
+        Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(area)
+    };
+    match n {
+        0 | 1 => vec![area],
+        2 => halves(area).to_vec(),
+        3 => {
+            let [left, right] = halves(area);
+            let [top, bottom] = stacked(right);
+            vec![left, top, bottom]
+        }
+        _ => {
+            let [top, bottom] = stacked(area);
+            let [a, b] = halves(top);
+            let [c, d] = halves(bottom);
+            vec![a, b, c, d]
+        }
+    }
+}
+
+/// The terminal part of a pane: all but its bottom (status) row.
+fn pane_content(rect: Rect) -> Rect {
+    Rect {
+        height: rect.height.saturating_sub(1).max(1),
+        ..rect
+    }
+}
+
+/// The status row under a pane's content, empty if there's no room for it.
+fn pane_bar(rect: Rect, content: Rect) -> Rect {
+    Rect {
+        y: content.bottom(),
+        height: rect.height.saturating_sub(content.height),
+        ..rect
+    }
+}
+
+fn pane_state_label(state: &ClaudeState) -> String {
+    match state {
+        ClaudeState::Initializing => "INIT".to_string(),
+        ClaudeState::Starting => "STARTING".to_string(),
+        ClaudeState::Idle | ClaudeState::WaitingForInput { .. } => "READY".to_string(),
+        ClaudeState::Thinking => "Thinking...".to_string(),
+        ClaudeState::Responding => "Responding...".to_string(),
+        ClaudeState::ToolExecuting { tool } => format!("Running {}...", tool),
+    }
+}
+
+fn pane_bar_text(pane: &Pane, focused: bool) -> String {
+    if let Some(copy) = &pane.state.copy {
+        return copy.status_text();
+    }
+    let status = if let Some(notice) = pane.state.notice() {
+        notice.to_string()
+    } else if pane.state.scroll_offset > 0 {
+        format!("SCROLL {} up", pane.state.scroll_offset)
+    } else {
+        pane_state_label(&pane.state.claude_state)
+    };
+    if focused {
+        let keys = &pane.state.keys;
+        format!(
+            " {} \u{2502} {} \u{2502} {} next \u{b7} {} detach ",
+            pane.name,
+            status,
+            keys.get(Action::NextPane),
+            keys.get(Action::Detach)
+        )
+    } else {
+        format!(" {} \u{2502} {} ", pane.name, status)
+    }
+}
+
+/// Attach to `targets` (instance id, display name) in tiled panes. Returns
+/// the ids of the instances that exited while attached; the rest are still
+/// running when the user detaches.
+pub async fn attach_tiled(
+    daemon: &DaemonInfo,
+    targets: &[(String, String)],
+    options: AttachOptions,
+) -> Result<Vec<String>, DaemonError> {
+    let scrollback_lines = attach::fetch_scrollback_lines(daemon).await;
+    let (term_rows, term_cols) = get_terminal_size().unwrap_or((24, 80));
+    let rects = pane_layout(Rect::new(0, 0, term_cols, term_rows), targets.len());
+
+    let mut panes = Vec::with_capacity(targets.len());
+    for ((instance_id, name), rect) in targets.iter().zip(rects) {
+        let content = pane_content(rect);
+        let ws_stream = daemon
+            .connect_mux()
+            .await
+            .map_err(DaemonError::from_tungstenite)?;
+        let link = attach::open_pane(
+            ws_stream,
+            instance_id,
+            content.height,
+            content.width,
+            options,
+        )
+        .await?;
+        panes.push(Pane {
+            instance_id: instance_id.clone(),
+            name: name.clone(),
+            parser: attach::new_parser(content.height, content.width, scrollback_lines),
+            link,
+            state: AttachState::new(options.spectate, options.keys),
+        });
+    }
+
+    let mut terminal = ratatui::init();
+    let _ = ratatui::crossterm::execute!(std::io::stdout(), EnableMouseCapture);
+    let exited = tokio::task::block_in_place(|| {
+        run_tiled_loop(&mut terminal, &mut panes, scrollback_lines, &options.keys)
+    });
+    let _ = ratatui::crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+
+    Ok(exited?)
+}
+
+/// Give every pane its share of `area`, resizing those whose share changed.
+fn relayout(panes: &mut [Pane], area: Rect, scrollback_capacity: usize) {
+    let rects = pane_layout(area, panes.len());
+    for (pane, rect) in panes.iter_mut().zip(rects) {
+        // A tiny terminal can leave a pane no room; it keeps its last size
+        if rect.is_empty() {
+            continue;
+        }
+        let content = pane_content(rect);
+        if pane.parser.screen().size() != (content.height, content.width) {
+            attach::resize_viewport(
+                &mut pane.parser,
+                &pane.state,
+                &pane.link.writer,
+                scrollback_capacity,
+                &pane.instance_id,
+                content.height,
+                content.width,
+            );
+        }
+    }
+}
+
+/// Blocking loop: drain every pane → render → poll → route input to the
+/// focused pane. Returns the ids of the instances that exited.
+fn run_tiled_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    panes: &mut Vec<Pane>,
+    scrollback_capacity: usize,
+    keys: &KeyBindings,
+) -> Result<Vec<String>> {
+    let mut focused = 0;
+    let mut exited = Vec::new();
+
+    loop {
+        // 1. Drain every pane; a closed one is removed and the rest re-tiled
+        let mut closed = Vec::new();
+        for (i, pane) in panes.iter_mut().enumerate() {
+            if attach::drain_ws(&pane.link.events, &mut pane.parser, &mut pane.state) {
+                closed.push(i);
+            }
+        }
+        if !closed.is_empty() {
+            for i in closed.into_iter().rev() {
+                exited.push(panes.remove(i).instance_id);
+            }
+            if panes.is_empty() {
+                eprintln!("\r\n[crab: exited]");
+                return Ok(exited);
+            }
+            focused = focused.min(panes.len() - 1);
+            let size = terminal.size()?;
+            relayout(
+                panes,
+                Rect::new(0, 0, size.width, size.height),
+                scrollback_capacity,
+            );
+        }
+
+        // 2. Clamp scroll offsets to each pane's scrollback depth
+        for pane in panes.iter_mut() {
+            pane.parser
+                .screen_mut()
+                .set_scrollback(pane.state.scroll_offset);
+            pane.state.scroll_offset = pane.parser.screen().scrollback();
+        }
+
+        // 3. Render
+        render_tiled(terminal, panes, focused)?;
+
+        // 4. Poll and route input
+        if !event::poll(Duration::from_millis(16))? {
+            continue;
+        }
+        let ev = event::read()?;
+        if attach::copy_mode_input(&mut panes[focused].state, &ev) {
+            continue;
+        }
+        let size = terminal.size()?;
+        let rects = pane_layout(Rect::new(0, 0, size.width, size.height), panes.len());
+
+        match &ev {
+            Event::Key(key)
+                if key.kind == KeyEventKind::Press && keys.matches(Action::NextPane, key) =>
+            {
+                focused = (focused + 1) % panes.len();
+                continue;
+            }
+            Event::Mouse(mouse) => {
+                // Mouse events go to the pane under the pointer
+                let at = Position::new(mouse.column, mouse.row);
+                let Some(i) = rects.iter().position(|r| r.contains(at)) else {
+                    continue;
+                };
+                let pane = &mut panes[i];
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => focused = i,
+                    MouseEventKind::ScrollUp => {
+                        pane.state.scroll_offset =
+                            pane.state.scroll_offset.saturating_add(MOUSE_SCROLL_LINES);
+                    }
+                    MouseEventKind::ScrollDown => {
+                        pane.state.scroll_offset =
+                            pane.state.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let page_size = rects
+            .get(focused)
+            .map_or(1, |r| pane_content(*r).height as usize);
+        let pane = &mut panes[focused];
+        match attach::classify_input(&ev, page_size, keys) {
+            Some(InputAction::Detach) => {
+                eprintln!("\r\n[crab: detached]");
+                return Ok(exited);
+            }
+            Some(InputAction::CopyMode) => {
+                attach::enter_copy_mode(&mut pane.parser, &mut pane.state)
+            }
+            Some(InputAction::ScrollUp(n)) => {
+                pane.state.scroll_offset = pane.state.scroll_offset.saturating_add(n);
+            }
+            Some(InputAction::ScrollDown(n)) => {
+                pane.state.scroll_offset = pane.state.scroll_offset.saturating_sub(n);
+            }
+            Some(InputAction::SendBytes(bytes)) => {
+                attach::send_input(&mut pane.state, &pane.link.writer, &pane.instance_id, bytes);
+            }
+            Some(InputAction::Resize { rows, cols }) => {
+                relayout(panes, Rect::new(0, 0, cols, rows), scrollback_capacity);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Draw every pane with its status row; the cursor shows in the focused one.
+/// Hyperlinks and clipboard writes are passed through as in a single attach.
+fn render_tiled(
+    terminal: &mut ratatui::DefaultTerminal,
+    panes: &mut [Pane],
+    focused: usize,
+) -> Result<()> {
+    let mut contents = Vec::with_capacity(panes.len());
+    terminal.draw(|frame| {
+        let rects = pane_layout(frame.area(), panes.len());
+        for (i, (pane, rect)) in panes.iter_mut().zip(rects).enumerate() {
+            if rect.is_empty() {
+                contents.push(None);
+                continue;
+            }
+            let content = pane_content(rect);
+            let bar = pane_bar(rect, content);
+            contents.push(Some(content));
+
+            let screen = pane.parser.screen();
+            if let Some(copy) = pane.state.copy.as_mut() {
+                copy.scroll_to_cursor(content.height as usize);
+                frame.render_widget(CopyModeWidget { copy }, content);
+            } else {
+                frame.render_widget(PtyWidget { screen }, content);
+            }
+            let bar_style = if i == focused {
+                Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::REVERSED | Modifier::DIM)
+            };
+            frame.render_widget(
+                Paragraph::new(pane_bar_text(pane, i == focused)).style(bar_style),
+                bar,
+            );
+
+            if i == focused
+                && pane.state.scroll_offset == 0
+                && pane.state.copy.is_none()
+                && !screen.hide_cursor()
+            {
+                let (row, col) = screen.cursor_position();
+                frame.set_cursor_position((content.x + col, content.y + row));
+            }
+        }
+    })?;
+
+    let mut extra = Vec::new();
+    for (pane, content) in panes.iter_mut().zip(contents) {
+        let links = pane.parser.callbacks().hyperlinks();
+        if let Some(content) = content
+            && !links.is_empty()
+            && pane.state.copy.is_none()
+        {
+            // DECSC/DECRC keep the cursor and attributes ratatui left behind
+            extra.extend_from_slice(b"\x1b7");
+            render_hyperlink_overlay(
+                pane.parser.screen(),
+                links,
+                (content.y, content.x),
+                &mut extra,
+            );
+            extra.extend_from_slice(b"\x1b8");
+        }
+        for (selection, data) in pane.state.pending_clipboard.drain(..) {
+            extra.extend_from_slice(format!("\x1b]52;{selection};{data}\x07").as_bytes());
+        }
+    }
+    if !extra.is_empty() {
+        let backend = terminal.backend_mut();
+        backend.write_all(&extra)?;
+        backend.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covers_without_overlap(area: Rect, rects: &[Rect]) {
+        let cells: usize = rects.iter().map(|r| r.area() as usize).sum();
+        // One spacer column per side-by-side pair
+        assert!(cells <= area.area() as usize);
+        for (i, a) in rects.iter().enumerate() {
+            assert!(area.contains(a.as_position()));
+            for b in &rects[i + 1..] {
+                assert!(!a.intersects(*b), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn layouts_tile_the_screen() {
+        let area = Rect::new(0, 0, 101, 40);
+
+        let two = pane_layout(area, 2);
+        assert_eq!(two, [Rect::new(0, 0, 50, 40), Rect::new(51, 0, 50, 40)]);
+
+        let three = pane_layout(area, 3);
+        assert_eq!(three[0], Rect::new(0, 0, 50, 40));
+        assert_eq!(three[1], Rect::new(51, 0, 50, 20));
+        assert_eq!(three[2], Rect::new(51, 20, 50, 20));
+
+        let four = pane_layout(area, 4);
+        assert_eq!(four.len(), 4);
+        assert_eq!(four[3], Rect::new(51, 20, 50, 20));
+
+        for n in 1..=MAX_PANES {
+            covers_without_overlap(area, &pane_layout(area, n));
+        }
+    }
+
+    #[test]
+    fn pane_content_leaves_a_status_row() {
+        assert_eq!(
+            pane_content(Rect::new(51, 20, 50, 20)),
+            Rect::new(51, 20, 50, 19)
+        );
+        assert_eq!(pane_content(Rect::new(0, 0, 10, 1)).height, 1);
+    }
+
+    #[test]
+    fn tiny_terminals_leave_empty_panes() {
+        let rect = Rect::new(51, 20, 50, 20);
+        assert_eq!(pane_bar(rect, pane_content(rect)), Rect::new(51, 39, 50, 1));
+        // A one-row pane has no room for its status row
+        let rect = Rect::new(0, 0, 10, 1);
+        assert!(pane_bar(rect, pane_content(rect)).is_empty());
+
+        // Four panes on one row: the bottom two get nothing
+        let rects = pane_layout(Rect::new(0, 0, 20, 1), 4);
+        assert!(rects[2].is_empty() && rects[3].is_empty());
+        let empty = Rect::new(0, 1, 10, 0);
+        assert_eq!(pane_bar(empty, pane_content(empty)).height, 0);
+    }
+
+    #[test]
+    fn next_pane_key() {
+        use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let keys = KeyBindings::default();
+        let next = |key: KeyEvent| keys.matches(Action::NextPane, &key);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert!(next(ctrl('\\')));
+        assert!(next(ctrl('4')));
+        assert!(!next(ctrl(']')));
+        assert!(!next(KeyEvent::new(
+            KeyCode::Char('\\'),
+            KeyModifiers::NONE
+        )));
+    }
+}
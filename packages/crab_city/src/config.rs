@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::info;
@@ -38,6 +39,10 @@ pub struct FileConfig {
     pub auth: AuthFileConfig,
     #[serde(default)]
     pub server: ServerFileConfig,
+    /// TUI client key bindings (`[keys]`): action name → key, e.g.
+    /// `detach = "ctrl-b"`. Validated by the CLI, which owns the actions.
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
 }
 
 /// Auth-related tunables (lives under `[auth]` in config.toml).
@@ -171,6 +176,7 @@ fn profile_to_file_config(profile: Option<&Profile>) -> FileConfig {
                 host: Some("127.0.0.1".to_string()),
                 ..Default::default()
             },
+            ..Default::default()
         },
         Some(Profile::Tunnel) => FileConfig {
             profile: Some(Profile::Tunnel),
@@ -183,6 +189,7 @@ fn profile_to_file_config(profile: Option<&Profile>) -> FileConfig {
                 host: Some("127.0.0.1".to_string()),
                 ..Default::default()
             },
+            ..Default::default()
         },
         Some(Profile::Server) => FileConfig {
             profile: Some(Profile::Server),
@@ -195,6 +202,7 @@ fn profile_to_file_config(profile: Option<&Profile>) -> FileConfig {
                 host: Some("0.0.0.0".to_string()),
                 ..Default::default()
             },
+            ..Default::default()
         },
         None => FileConfig::default(),
     }
@@ -318,7 +326,8 @@ impl ServerConfig {
 }
 
 // =============================================================================
-// Directory layout config (not tunable via figment — derived from --data-dir)
+// Directory layout config (not tunable via figment — derived from --data-dir
+// and, for the CLI, --remote)
 // =============================================================================
 
 #[derive(Clone, Debug)]
@@ -328,6 +337,9 @@ pub struct CrabCityConfig {
     #[allow(dead_code)]
     pub exports_dir: PathBuf,
     pub logs_dir: PathBuf,
+    /// Server the CLI talks to instead of the local daemon (`--remote` or
+    /// `CRAB_REMOTE`)
+    pub remote: Option<String>,
 }
 
 impl CrabCityConfig {
@@ -362,6 +374,7 @@ impl CrabCityConfig {
             db_path,
             exports_dir,
             logs_dir,
+            remote: None,
         })
     }
 
@@ -414,6 +427,11 @@ impl CrabCityConfig {
     pub fn config_toml_path(&self) -> PathBuf {
         self.data_dir.join("config.toml")
     }
+
+    /// Tokens from `crab login`, one per remote server.
+    pub fn remotes_path(&self) -> PathBuf {
+        self.data_dir.join("remotes.toml")
+    }
 }
 
 #[cfg(test)]
@@ -659,4 +677,19 @@ mod tests {
         let fc: FileConfig = load_config(tmp.path(), None).extract().unwrap();
         assert_eq!(fc.server.scrollback_lines, 10_000);
     }
+
+    #[test]
+    fn test_load_config_keys_section() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.toml"),
+            "[keys]\ndetach = \"ctrl-b\"\n",
+        )
+        .unwrap();
+        let fc: FileConfig = load_config(tmp.path(), Some(&Profile::Local))
+            .extract()
+            .unwrap();
+        assert_eq!(fc.keys.get("detach").map(String::as_str), Some("ctrl-b"));
+        assert_eq!(fc.keys.len(), 1);
+    }
 }
@@ -84,7 +84,7 @@ pub struct DbStats {
 }
 
 /// Current schema version - increment when adding migrations
-const SCHEMA_VERSION: i64 = 11;
+const SCHEMA_VERSION: i64 = 13;
 
 // Run migrations manually since Bazel doesn't package the migrations directory
 pub(crate) async fn run_migrations(pool: &SqlitePool) -> Result<()> {
@@ -403,7 +403,7 @@ pub(crate) async fn run_migrations(pool: &SqlitePool) -> Result<()> {
         CREATE TABLE IF NOT EXISTS instance_permissions (
             instance_id TEXT NOT NULL,
             user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-            role TEXT NOT NULL DEFAULT 'collaborator',
+            role TEXT NOT NULL DEFAULT 'operator',
             granted_at INTEGER NOT NULL DEFAULT (unixepoch()),
             granted_by TEXT REFERENCES users(id),
             PRIMARY KEY (instance_id, user_id)
@@ -431,7 +431,7 @@ pub(crate) async fn run_migrations(pool: &SqlitePool) -> Result<()> {
             invite_token TEXT PRIMARY KEY,
             instance_id TEXT NOT NULL,
             created_by TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-            role TEXT NOT NULL DEFAULT 'collaborator',
+            role TEXT NOT NULL DEFAULT 'operator',
             max_uses INTEGER,
             use_count INTEGER NOT NULL DEFAULT 0,
             expires_at INTEGER,
@@ -712,11 +712,43 @@ pub(crate) async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     .execute(pool)
     .await?;
 
+    // v12: Instance roles — "collaborator" grants become "operator"
+    for table in ["instance_permissions", "instance_invitations"] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET role = 'operator' WHERE role = 'collaborator'"
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    // v13: Collaborative prompt queue, sent in order when an instance goes idle
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS prompt_queue (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            instance_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            display_name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_prompt_queue_instance ON prompt_queue(instance_id, position)",
+    )
+    .execute(pool)
+    .await?;
+
     // Record the schema version
     if current_version < SCHEMA_VERSION {
         sqlx::query("INSERT OR REPLACE INTO schema_version (version, description) VALUES (?, ?)")
             .bind(SCHEMA_VERSION)
-            .bind("Add instance_inbox table for fleet attention model")
+            .bind("Add prompt queue")
             .execute(pool)
             .await?;
         info!("Schema upgraded to version {}", SCHEMA_VERSION);
@@ -48,6 +48,8 @@ pub struct SearchParams {
     date_to: Option<i64>,
     /// Only return conversations containing tool use
     has_tools: Option<bool>,
+    /// Only conversations from a matching instance or project directory
+    project: Option<String>,
 }
 
 pub async fn search_conversations_handler(
@@ -62,6 +64,7 @@ pub async fn search_conversations_handler(
         date_from: params.date_from,
         date_to: params.date_to,
         has_tools: params.has_tools,
+        project: params.project,
     };
 
     match state
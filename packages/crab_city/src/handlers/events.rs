@@ -0,0 +1,296 @@
+//! Server-Sent Events stream of fleet events (`GET /api/events`).
+//!
+//! A plain-HTTP alternative to the mux WebSocket for scripts, status bars and
+//! editor plugins. Each event is named after its `ServerMessage` type and
+//! carries that message as JSON. The stream opens with an `InstanceList`
+//! snapshot, then forwards instance lifecycle, state changes, inbox and task
+//! updates.
+//!
+//! Lifecycle-backed events have an id of `<server epoch>.<seq>`; reconnecting
+//! with `Last-Event-ID` replays the ones missed while the server still has
+//! them (see `ws::replay`). `StateChange` has no id, which is why the snapshot
+//! is re-sent on every connection.
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use std::convert::Infallible;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use crate::AppState;
+use crate::auth::{AuthUser, MaybeAuthUser};
+use crate::ws::{ServerMessage, Visibility};
+
+/// Event name for messages streamed over SSE; `None` for everything else.
+fn event_name(msg: &ServerMessage) -> Option<&'static str> {
+    Some(match msg {
+        ServerMessage::InstanceList { .. } => "InstanceList",
+        ServerMessage::InstanceCreated { .. } => "InstanceCreated",
+        ServerMessage::InstanceStopped { .. } => "InstanceStopped",
+        ServerMessage::InstanceRenamed { .. } => "InstanceRenamed",
+        ServerMessage::StateChange { .. } => "StateChange",
+        ServerMessage::InboxUpdate { .. } => "InboxUpdate",
+        ServerMessage::TaskUpdate { .. } => "TaskUpdate",
+        ServerMessage::TaskDeleted { .. } => "TaskDeleted",
+        _ => return None,
+    })
+}
+
+/// Parse a `Last-Event-ID` of the form `<epoch>.<seq>`.
+fn parse_event_id(id: &str) -> Option<(&str, u64)> {
+    let (epoch, seq) = id.rsplit_once('.')?;
+    Some((epoch, seq.parse().ok()?))
+}
+
+fn to_event(msg: &ServerMessage, id: Option<String>) -> Option<Event> {
+    let name = event_name(msg)?;
+    let data = match serde_json::to_string(msg) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to serialize SSE event: {}", e);
+            return None;
+        }
+    };
+    let event = Event::default().event(name).data(data);
+    Some(match id {
+        Some(id) => event.id(id),
+        None => event,
+    })
+}
+
+/// `GET /api/events`
+pub async fn events_handler(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let (tx, rx) = mpsc::channel::<Event>(64);
+    tokio::spawn(stream_events(state, maybe_user.0, last_event_id, tx));
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (Ok(event), rx))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Feed events to one SSE client until it disconnects.
+async fn stream_events(
+    state: AppState,
+    user: Option<AuthUser>,
+    last_event_id: Option<String>,
+    tx: mpsc::Sender<Event>,
+) {
+    let gsm = state.global_state_manager.clone();
+    let epoch = gsm.epoch().to_string();
+    let restricted = user
+        .filter(|u| state.auth_config.enabled && !u.is_admin)
+        .map(|u| u.user_id);
+    let mut visibility = Visibility::new(restricted, Some(state.repository.clone()));
+
+    // Subscribe before replaying so nothing falls in between
+    let mut lifecycle_rx = gsm.subscribe_lifecycle();
+    let mut state_rx = gsm.subscribe();
+
+    let missed = last_event_id
+        .as_deref()
+        .and_then(parse_event_id)
+        .and_then(|(id_epoch, seq)| gsm.lifecycle_since(id_epoch, seq));
+    let mut replayed_to = 0;
+    if let Some(missed) = missed {
+        debug!(count = missed.len(), "SSE: replaying missed events");
+        for (seq, msg) in missed {
+            replayed_to = seq;
+            if !visibility.allows(&msg).await {
+                continue;
+            }
+            if let Some(event) = to_event(&msg, Some(format!("{epoch}.{seq}")))
+                && tx.send(event).await.is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    // Current instances and their states
+    let mut instances = Vec::new();
+    for mut inst in state.instance_manager.list().await {
+        if !visibility.can_see(&inst.id).await {
+            continue;
+        }
+        if let Some(ts) = gsm.get_state_entered_at(&inst.id).await {
+            inst.state_entered_at = Some(ts.timestamp());
+        }
+        instances.push(inst);
+    }
+    let snapshot_id = format!("{epoch}.{}", gsm.lifecycle_seq().max(replayed_to));
+    if let Some(event) = to_event(
+        &ServerMessage::InstanceList { instances },
+        Some(snapshot_id),
+    ) && tx.send(event).await.is_err()
+    {
+        return;
+    }
+
+    loop {
+        let (msg, id) = tokio::select! {
+            _ = tx.closed() => return,
+            lifecycle = lifecycle_rx.recv() => match lifecycle {
+                Ok((seq, _)) if seq <= replayed_to => continue,
+                Ok((seq, msg)) => (msg, Some(format!("{epoch}.{seq}"))),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("SSE lifecycle stream lagged by {} messages", n);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            change = state_rx.recv() => match change {
+                Ok((instance_id, claude_state, stale)) => {
+                    let entered_at = gsm
+                        .get_state_entered_at(&instance_id)
+                        .await
+                        .map(|ts| ts.timestamp());
+                    let msg = ServerMessage::StateChange {
+                        instance_id,
+                        state: claude_state,
+                        stale,
+                        entered_at,
+                    };
+                    (msg, None)
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+        };
+        if event_name(&msg).is_none() || !visibility.allows(&msg).await {
+            continue;
+        }
+        if let Some(event) = to_event(&msg, id)
+            && tx.send(event).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use futures::StreamExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    /// Read the body until `needle` shows up (or time out).
+    async fn read_until(body: Body, needle: &str) -> String {
+        let mut stream = body.into_data_stream();
+        let mut text = String::new();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !text.contains(needle) {
+                let chunk = stream.next().await.expect("stream ended").unwrap();
+                text.push_str(&String::from_utf8_lossy(&chunk));
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("timed out waiting for {needle:?}; got {text:?}"));
+        text
+    }
+
+    fn router(state: AppState) -> Router {
+        Router::new()
+            .route("/events", get(events_handler))
+            .with_state(state)
+    }
+
+    #[test]
+    fn event_ids_round_trip() {
+        assert_eq!(parse_event_id("abc.42"), Some(("abc", 42)));
+        assert_eq!(parse_event_id("abc"), None);
+        assert_eq!(parse_event_id("abc.x"), None);
+    }
+
+    #[test]
+    fn only_fleet_events_are_streamed() {
+        assert_eq!(
+            event_name(&ServerMessage::TaskDeleted { task_id: 1 }),
+            Some("TaskDeleted")
+        );
+        assert_eq!(event_name(&ServerMessage::BinaryFramesEnabled), None);
+    }
+
+    #[tokio::test]
+    async fn stream_opens_with_snapshot_then_live_events() {
+        let (state, _tmp) = crate::test_helpers::test_app_state().await;
+        let gsm = state.global_state_manager.clone();
+        let resp = router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/events")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.headers()["content-type"], "text/event-stream");
+        let mut body = resp.into_body().into_data_stream();
+        let first = body.next().await.unwrap().unwrap();
+        let first = String::from_utf8_lossy(&first).to_string();
+        assert!(first.contains("event: InstanceList"), "{first}");
+
+        gsm.broadcast_lifecycle(ServerMessage::TaskDeleted { task_id: 7 });
+        let text = read_until(Body::from_stream(body), "TaskDeleted").await;
+        assert!(text.contains(r#""task_id":7"#), "{text}");
+        assert!(text.contains(&format!("id: {}.1", gsm.epoch())), "{text}");
+    }
+
+    #[tokio::test]
+    async fn last_event_id_replays_missed_events() {
+        let (state, _tmp) = crate::test_helpers::test_app_state().await;
+        let gsm = state.global_state_manager.clone();
+        gsm.broadcast_lifecycle(ServerMessage::TaskDeleted { task_id: 1 });
+        gsm.broadcast_lifecycle(ServerMessage::TaskDeleted { task_id: 2 });
+
+        let resp = router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/events")
+                    .header("last-event-id", format!("{}.1", gsm.epoch()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let text = read_until(resp.into_body(), "InstanceList").await;
+        assert!(!text.contains(r#""task_id":1"#), "{text}");
+        assert!(text.contains(r#""task_id":2"#), "{text}");
+    }
+
+    #[tokio::test]
+    async fn non_admins_only_see_permitted_instances() {
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let user =
+            crate::test_helpers::create_test_user(&state.repository, "u-1", "bob", "Bob").await;
+        let gsm = state.global_state_manager.clone();
+        gsm.broadcast_lifecycle(ServerMessage::InstanceStopped {
+            instance_id: "hidden".into(),
+        });
+
+        let mut req = Request::builder()
+            .uri("/events")
+            .header("last-event-id", format!("{}.0", gsm.epoch()))
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut().insert(user);
+        let resp = router(state).oneshot(req).await.unwrap();
+        let text = read_until(resp.into_body(), "InstanceList").await;
+        assert!(!text.contains("hidden"), "{text}");
+    }
+}
@@ -1,7 +1,7 @@
 use crate::instance_manager::ClaudeInstance;
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
@@ -10,10 +10,12 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::AppState;
-use crate::auth::MaybeAuthUser;
+use crate::auth::{AuthUser, MaybeAuthUser};
 use crate::claude_driver::ClaudeDriver;
+use crate::models::InstanceRole;
 use crate::persistence::InstancePersistor;
 use crate::process_driver::{ProcessDriver, ShellDriver};
+use crate::virtual_terminal::SizePolicy;
 use crate::ws;
 
 #[derive(Serialize)]
@@ -23,30 +25,69 @@ pub struct CreateInstanceResponse {
     wrapper_port: u16,
 }
 
+/// The caller's role on an instance. With auth off, and for admins, everyone
+/// is an owner; otherwise it's their grant, `None` meaning no access.
+pub(crate) async fn caller_role(
+    state: &AppState,
+    user: Option<&AuthUser>,
+    instance_id: &str,
+) -> Option<InstanceRole> {
+    match user {
+        Some(user) if state.auth_config.enabled && !user.is_admin => state
+            .repository
+            .check_instance_permission(instance_id, &user.user_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|perm| perm.role),
+        _ => Some(InstanceRole::Owner),
+    }
+}
+
+/// `FORBIDDEN` unless the caller holds at least `min` on the instance.
+pub(crate) async fn require_role(
+    state: &AppState,
+    user: Option<&AuthUser>,
+    instance_id: &str,
+    min: InstanceRole,
+) -> Result<InstanceRole, StatusCode> {
+    caller_role(state, user, instance_id)
+        .await
+        .filter(|role| *role >= min)
+        .ok_or(StatusCode::FORBIDDEN)
+}
+
 pub async fn list_instances(
     State(state): State<AppState>,
     maybe_user: MaybeAuthUser,
 ) -> Json<Vec<ClaudeInstance>> {
-    let all_instances = state.instance_manager.list().await;
+    let mut all_instances = state.instance_manager.list().await;
 
     if state.auth_config.enabled
         && let MaybeAuthUser(Some(ref user)) = maybe_user
+        && !user.is_admin
     {
-        if user.is_admin {
-            return Json(all_instances);
-        }
-        let permitted = state
+        let roles: std::collections::HashMap<String, InstanceRole> = state
             .repository
-            .list_user_instance_ids(&user.user_id)
+            .list_user_instance_permissions(&user.user_id)
             .await
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .into_iter()
+            .map(|perm| (perm.instance_id, perm.role))
+            .collect();
         let filtered = all_instances
             .into_iter()
-            .filter(|i| permitted.contains(&i.id))
+            .filter_map(|mut i| {
+                i.role = Some(*roles.get(&i.id)?);
+                Some(i)
+            })
             .collect();
         return Json(filtered);
     }
 
+    for instance in &mut all_instances {
+        instance.role = Some(InstanceRole::Owner);
+    }
     Json(all_instances)
 }
 
@@ -55,6 +96,12 @@ pub struct CreateInstanceRequest {
     name: Option<String>,
     working_dir: Option<String>,
     command: Option<String>,
+    /// Extra environment variables for the process
+    #[serde(default)]
+    env: std::collections::BTreeMap<String, String>,
+    /// Instance id or session id of a previous instance whose persisted
+    /// scrollback should be restored into the new terminal.
+    restore_from: Option<String>,
 }
 
 pub async fn create_instance(
@@ -62,6 +109,17 @@ pub async fn create_instance(
     maybe_user: MaybeAuthUser,
     Json(req): Json<CreateInstanceRequest>,
 ) -> Result<Json<CreateInstanceResponse>, (StatusCode, String)> {
+    if let Some(key) = req
+        .env
+        .keys()
+        .find(|k| k.is_empty() || k.contains(['=', '\0']))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid environment variable name '{}'", key),
+        ));
+    }
+
     // Determine if the command will be Claude and create the appropriate driver.
     let command_str = req
         .command
@@ -74,6 +132,56 @@ pub async fn create_instance(
         Box::new(ShellDriver)
     };
 
+    // Restored scrollback shows what the old instance printed, and the new
+    // instance takes over its id: only its owner may bring it back. A resume
+    // by anyone else still starts, just without the scrollback.
+    let restored = match state
+        .instance_manager
+        .find_scrollback(req.restore_from.as_deref(), req.command.as_deref())
+    {
+        Some((meta, snapshot)) => {
+            let owner = require_role(
+                &state,
+                maybe_user.0.as_ref(),
+                &meta.instance_id,
+                InstanceRole::Owner,
+            )
+            .await;
+            match owner {
+                Err(status) if req.restore_from.is_some() => {
+                    return Err((
+                        status,
+                        "Only the instance's owner can restore it".to_string(),
+                    ));
+                }
+                Err(_) => None,
+                Ok(_) => {
+                    if state
+                        .instance_manager
+                        .get(&meta.instance_id)
+                        .await
+                        .is_none()
+                        && let Err(e) = state
+                            .repository
+                            .clear_instance_access(&meta.instance_id)
+                            .await
+                    {
+                        tracing::warn!(
+                            instance = %meta.instance_id,
+                            "Failed to clear old grants: {}", e
+                        );
+                        return Err((
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "Failed to clear the old instance's grants".to_string(),
+                        ));
+                    }
+                    Some((meta, snapshot))
+                }
+            }
+        }
+        None => None,
+    };
+
     let gsm = &state.global_state_manager;
     match state
         .instance_manager
@@ -81,6 +189,7 @@ pub async fn create_instance(
             req.name,
             req.working_dir,
             req.command,
+            req.env.into_iter().collect(),
             driver,
             Some(gsm.broadcast_tx().clone()),
             Some(gsm.lifecycle_tx().clone()),
@@ -89,6 +198,7 @@ pub async fn create_instance(
             gsm.pending_attributions_arc(),
             Some(state.repository.clone()),
             None,
+            restored,
         )
         .await
     {
@@ -117,7 +227,7 @@ pub async fn create_instance(
                 let perm = crate::models::InstancePermission {
                     instance_id: instance.id.clone(),
                     user_id: user.user_id.clone(),
-                    role: "owner".to_string(),
+                    role: InstanceRole::Owner,
                     granted_at: chrono::Utc::now().timestamp(),
                     granted_by: None,
                 };
@@ -163,10 +273,20 @@ pub async fn create_instance(
     }
 }
 
-pub async fn get_instance(State(state): State<AppState>, Path(id): Path<String>) -> Response {
-    match state.instance_manager.get(&id).await {
-        Some(instance) => Json(instance).into_response(),
-        None => StatusCode::NOT_FOUND.into_response(),
+pub async fn get_instance(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(mut instance) = state.instance_manager.get(&id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match caller_role(&state, maybe_user.0.as_ref(), &id).await {
+        Some(role) => {
+            instance.role = Some(role);
+            Json(instance).into_response()
+        }
+        None => StatusCode::FORBIDDEN.into_response(),
     }
 }
 
@@ -175,24 +295,20 @@ pub async fn delete_instance(
     maybe_user: MaybeAuthUser,
     Path(id): Path<String>,
 ) -> StatusCode {
-    if state.auth_config.enabled
-        && let MaybeAuthUser(Some(ref user)) = maybe_user
-        && !user.is_admin
+    if let Err(status) = require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Owner).await
     {
-        match state
-            .repository
-            .check_instance_permission(&id, &user.user_id)
-            .await
-        {
-            Ok(Some(perm)) if perm.role == "owner" => {}
-            _ => return StatusCode::FORBIDDEN,
-        }
+        return status;
     }
 
     state.instance_persistors.lock().await.remove(&id);
     state.global_state_manager.unregister_instance(&id).await;
+    if let Err(e) = state.repository.clear_prompt_queue(&id).await {
+        tracing::warn!(instance = %id, "Failed to clear prompt queue: {}", e);
+    }
 
     if state.instance_manager.stop(&id).await {
+        // Deleted for good, unlike a daemon shutdown: nothing to restore
+        state.instance_manager.discard_scrollback(&id);
         state.metrics.instance_stopped();
 
         state
@@ -212,9 +328,11 @@ pub struct SetCustomNameRequest {
 
 pub async fn set_custom_name(
     State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
     Path(id): Path<String>,
     Json(req): Json<SetCustomNameRequest>,
 ) -> Result<StatusCode, StatusCode> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Operator).await?;
     let custom_name = req.custom_name.filter(|n| !n.trim().is_empty());
 
     state
@@ -233,24 +351,78 @@ pub async fn set_custom_name(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// `PUT /api/instances/{id}/size-policy`: how the terminal size is
+/// negotiated across the instance's viewers.
+pub async fn set_size_policy(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+    Json(policy): Json<SizePolicy>,
+) -> Result<StatusCode, StatusCode> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Operator).await?;
+    if let SizePolicy::Fixed { rows, cols } = policy
+        && (rows == 0 || cols == 0)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let handle = state
+        .instance_manager
+        .get_handle(&id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    handle
+        .set_size_policy_and_resize(policy)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Which rendering of the terminal `GET /api/instances/{id}/output` returns.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputText {
+    /// Plain text of the visible screen
+    Screen,
+    /// Plain text of the scrollback followed by the visible screen
+    Scrollback,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutputQuery {
+    /// Rendered plain text instead of the raw terminal bytes
+    text: Option<OutputText>,
+}
+
 pub async fn get_instance_output(
     State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
     Path(id): Path<String>,
+    Query(query): Query<OutputQuery>,
 ) -> Response {
-    if let Some(handle) = state.instance_manager.get_handle(&id).await {
-        let max_bytes = state.server_config.websocket.max_history_replay_bytes;
-        let output = handle.get_recent_output(max_bytes, 24).await;
-        Json(serde_json::json!({ "lines": output })).into_response()
-    } else {
-        StatusCode::NOT_FOUND.into_response()
+    let Some(handle) = state.instance_manager.get_handle(&id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Err(status) =
+        require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Viewer).await
+    {
+        return status.into_response();
     }
+    let lines = match query.text {
+        Some(text) => handle.get_text(text == OutputText::Scrollback).await,
+        None => {
+            let max_bytes = state.server_config.websocket.max_history_replay_bytes;
+            handle.get_recent_output(max_bytes, 24).await
+        }
+    };
+    Json(serde_json::json!({ "lines": lines })).into_response()
 }
 
 // --- Instance invitation handlers ---
 
 #[derive(Deserialize)]
 pub struct CreateInvitationRequest {
-    role: Option<String>,
+    /// Role granted on acceptance (default: operator)
+    role: Option<InstanceRole>,
     max_uses: Option<i32>,
     expires_in_hours: Option<i64>,
 }
@@ -272,7 +444,7 @@ pub async fn create_invitation(
             .check_instance_permission(&instance_id, &user.user_id)
             .await
         {
-            Ok(Some(perm)) if perm.role == "owner" => {}
+            Ok(Some(perm)) if perm.role == InstanceRole::Owner => {}
             _ => return Err(StatusCode::FORBIDDEN),
         }
     }
@@ -282,7 +454,7 @@ pub async fn create_invitation(
         invite_token: uuid::Uuid::new_v4().to_string(),
         instance_id,
         created_by: user.user_id,
-        role: req.role.unwrap_or_else(|| "collaborator".to_string()),
+        role: req.role.unwrap_or(InstanceRole::Operator),
         max_uses: req.max_uses,
         use_count: 0,
         expires_at: req.expires_in_hours.map(|h| now + h * 3600),
@@ -320,19 +492,30 @@ pub async fn accept_invitation(
         return Err(StatusCode::GONE);
     }
 
-    let perm = crate::models::InstancePermission {
-        instance_id: invite.instance_id.clone(),
-        user_id: user.user_id,
-        role: invite.role.clone(),
-        granted_at: chrono::Utc::now().timestamp(),
-        granted_by: Some(invite.created_by.clone()),
-    };
-
-    state
+    // Never downgrade an existing grant
+    let existing = state
         .repository
-        .create_instance_permission(&perm)
+        .check_instance_permission(&invite.instance_id, &user.user_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let role = match existing {
+        Some(perm) if perm.role >= invite.role => perm.role,
+        _ => {
+            let perm = crate::models::InstancePermission {
+                instance_id: invite.instance_id.clone(),
+                user_id: user.user_id,
+                role: invite.role,
+                granted_at: chrono::Utc::now().timestamp(),
+                granted_by: Some(invite.created_by.clone()),
+            };
+            state
+                .repository
+                .create_instance_permission(&perm)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            invite.role
+        }
+    };
 
     state
         .repository
@@ -342,7 +525,7 @@ pub async fn accept_invitation(
 
     Ok(Json(serde_json::json!({
         "instance_id": invite.instance_id,
-        "role": invite.role,
+        "role": role,
     })))
 }
 
@@ -362,7 +545,7 @@ pub async fn remove_collaborator(
             .check_instance_permission(&instance_id, &user.user_id)
             .await
         {
-            Ok(Some(perm)) if perm.role == "owner" => {}
+            Ok(Some(perm)) if perm.role == InstanceRole::Owner => {}
             _ => return StatusCode::FORBIDDEN,
         }
     }
@@ -384,7 +567,7 @@ mod tests {
         Router,
         body::Body,
         http::Request,
-        routing::{delete, get, patch, post},
+        routing::{delete, get, patch, post, put},
     };
     use tower::ServiceExt;
 
@@ -395,6 +578,7 @@ mod tests {
             .route("/instances/{id}", get(get_instance))
             .route("/instances/{id}", delete(delete_instance))
             .route("/instances/{id}/name", patch(set_custom_name))
+            .route("/instances/{id}/size-policy", put(set_size_policy))
             .route("/instances/{id}/output", get(get_instance_output))
             .route("/instances/{id}/invitations", post(create_invitation))
             .route("/invitations/{token}/accept", post(accept_invitation))
@@ -423,6 +607,56 @@ mod tests {
         assert!(instances.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_create_instance_with_env() {
+        let (state, _tmp) = crate::test_helpers::test_app_state().await;
+        let app = Router::new()
+            .route("/instances", post(create_instance))
+            .with_state(state.clone());
+        let create = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/instances")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let resp = app
+            .clone()
+            .oneshot(create(
+                r#"{"command":"cat","working_dir":"/tmp","env":{"A=B":"c"}}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = app
+            .oneshot(create(
+                r#"{"command":"echo marker-$CRAB_ENV_TEST; sleep 5","working_dir":"/tmp","env":{"CRAB_ENV_TEST":"from-env"}}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap();
+
+        let handle = state.instance_manager.get_handle(id).await.unwrap();
+        let mut output = String::new();
+        for _ in 0..50 {
+            output = handle.get_recent_output(4096, 24).await.concat();
+            if output.contains("marker-from-env") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(output.contains("marker-from-env"), "output: {:?}", output);
+        state.instance_manager.stop(id).await;
+    }
+
     #[tokio::test]
     async fn test_get_instance_not_found() {
         let (app, _tmp) = test_router().await;
@@ -454,6 +688,38 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_set_size_policy_validation() {
+        let (app, _tmp) = test_router().await;
+        let put_policy = |body: &'static str| {
+            Request::builder()
+                .method("PUT")
+                .uri("/instances/nonexistent/size-policy")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let resp = app
+            .clone()
+            .oneshot(put_policy(r#"{"kind":"largest"}"#))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = app
+            .clone()
+            .oneshot(put_policy(r#"{"kind":"fixed","rows":0,"cols":80}"#))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = app
+            .oneshot(put_policy(r#"{"kind":"tallest"}"#))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[tokio::test]
     async fn test_get_instance_output_not_found() {
         let (app, _tmp) = test_router().await;
@@ -520,7 +786,7 @@ mod tests {
     async fn test_create_invitation_request_deserialization() {
         let json = r#"{"role": "collaborator", "max_uses": 5, "expires_in_hours": 24}"#;
         let req: CreateInvitationRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(req.role.as_deref(), Some("collaborator"));
+        assert_eq!(req.role, Some(InstanceRole::Operator));
         assert_eq!(req.max_uses, Some(5));
         assert_eq!(req.expires_in_hours, Some(24));
     }
@@ -693,6 +959,339 @@ mod tests {
         assert_eq!(created["name"], "my-crab");
     }
 
+    #[tokio::test]
+    async fn test_create_instance_restores_scrollback() {
+        use crate::scrollback::{ScrollbackMeta, ScrollbackStore};
+        use crate::virtual_terminal::VirtualTerminal;
+
+        let (mut state, tmp) = crate::test_helpers::test_app_state().await;
+        let scrollback_dir = tmp.path().join("scrollback");
+        state.instance_manager = Arc::new(crate::instance_manager::InstanceManager::new(
+            "echo".into(),
+            9000,
+            25 * 1024 * 1024,
+            100,
+            None,
+            Some(scrollback_dir.clone()),
+        ));
+
+        // Simulate a snapshot left behind by a previous daemon
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(b"output from before the restart\r\n");
+        let store = ScrollbackStore::new(scrollback_dir);
+        let meta = ScrollbackMeta {
+            instance_id: "prev-instance".to_string(),
+            name: "old".to_string(),
+            command: "bash".to_string(),
+            working_dir: "/tmp".to_string(),
+            session_id: Some("sess-1".to_string()),
+            saved_at_ms: 1,
+        };
+        store.save(&meta, &vt.snapshot()).unwrap();
+
+        let router = Router::new()
+            .route("/instances", post(create_instance))
+            .route("/instances/{id}", delete(delete_instance))
+            .route("/instances/{id}/output", get(get_instance_output))
+            .with_state(state);
+
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/instances")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"command":"sleep 5","working_dir":"/tmp","restore_from":"sess-1"}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        // The previous id is reused since it isn't live
+        assert_eq!(created["id"], "prev-instance");
+
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/instances/prev-instance/output")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(
+            output["lines"][0]
+                .as_str()
+                .unwrap()
+                .contains("output from before the restart")
+        );
+
+        // Rendered as text, the restored screen reads back without escapes
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/instances/prev-instance/output?text=screen")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let lines = output["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(lines[0], "output from before the restart");
+
+        // Deleting the instance discards its snapshot
+        let resp = router
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/instances/prev-instance")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(store.load("prev-instance").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_restore_requires_owner_and_drops_old_grants() {
+        use crate::auth::AuthUser;
+        use crate::models::InstancePermission;
+        use crate::scrollback::{ScrollbackMeta, ScrollbackStore};
+        use crate::virtual_terminal::VirtualTerminal;
+
+        let (mut state, tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let scrollback_dir = tmp.path().join("scrollback");
+        state.instance_manager = Arc::new(crate::instance_manager::InstanceManager::new(
+            "echo".into(),
+            9000,
+            25 * 1024 * 1024,
+            100,
+            None,
+            Some(scrollback_dir.clone()),
+        ));
+        let repo = state.repository.clone();
+        let owner = crate::test_helpers::create_test_user(&repo, "u-own", "owner", "O").await;
+        let other = crate::test_helpers::create_test_user(&repo, "u-oth", "other", "X").await;
+        crate::test_helpers::create_test_user(&repo, "u-col", "collab", "C").await;
+        for (user_id, role) in [
+            ("u-own", InstanceRole::Owner),
+            ("u-col", InstanceRole::Operator),
+        ] {
+            repo.create_instance_permission(&InstancePermission {
+                instance_id: "prev-instance".to_string(),
+                user_id: user_id.to_string(),
+                role,
+                granted_at: 0,
+                granted_by: None,
+            })
+            .await
+            .unwrap();
+        }
+
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(b"secret output\r\n");
+        let meta = ScrollbackMeta {
+            instance_id: "prev-instance".to_string(),
+            name: "old".to_string(),
+            command: "claude".to_string(),
+            working_dir: "/tmp".to_string(),
+            session_id: Some("sess-1".to_string()),
+            saved_at_ms: 1,
+        };
+        ScrollbackStore::new(scrollback_dir)
+            .save(&meta, &vt.snapshot())
+            .unwrap();
+
+        let router = Router::new()
+            .route("/instances", post(create_instance))
+            .with_state(state.clone());
+        let create = |user: &AuthUser, body: serde_json::Value| {
+            let mut req = Request::builder()
+                .method("POST")
+                .uri("/instances")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            req.extensions_mut().insert(user.clone());
+            router.clone().oneshot(req)
+        };
+        let created_id = |resp: axum::response::Response| async move {
+            let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            created["id"].as_str().unwrap().to_string()
+        };
+
+        // Someone else can't restore it explicitly...
+        let resp = create(
+            &other,
+            serde_json::json!({"command": "sleep 5", "working_dir": "/tmp", "restore_from": "sess-1"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        // ...and resuming the session starts fresh, under a new id
+        let resp = create(
+            &other,
+            serde_json::json!({"command": "sleep 5 # claude --resume sess-1", "working_dir": "/tmp"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let id = created_id(resp).await;
+        assert_ne!(id, "prev-instance");
+        state.instance_manager.stop(&id).await;
+
+        // The owner gets the old id back, without the old collaborators
+        let resp = create(
+            &owner,
+            serde_json::json!({"command": "sleep 5", "working_dir": "/tmp", "restore_from": "sess-1"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(created_id(resp).await, "prev-instance");
+        let grant = |user: &'static str| repo.check_instance_permission("prev-instance", user);
+        assert!(grant("u-col").await.unwrap().is_none());
+        assert_eq!(
+            grant("u-own").await.unwrap().unwrap().role,
+            InstanceRole::Owner
+        );
+        state.instance_manager.stop("prev-instance").await;
+    }
+
+    #[tokio::test]
+    async fn test_resumed_session_restores_scrollback_after_restart() {
+        use crate::instance_manager::InstanceManager;
+        use crate::scrollback::ScrollbackStore;
+
+        let (mut state, tmp) = crate::test_helpers::test_app_state().await;
+        let scrollback_dir = tmp.path().join("scrollback");
+        let new_manager = || {
+            Arc::new(InstanceManager::new(
+                "echo".into(),
+                9000,
+                25 * 1024 * 1024,
+                100,
+                None,
+                Some(scrollback_dir.clone()),
+            ))
+        };
+        // Stands in for Claude: prints a marker, then waits
+        let claude = tmp.path().join("claude");
+        std::fs::write(&claude, "#!/bin/sh\necho marker-before-restart\nexec cat\n").unwrap();
+        std::fs::set_permissions(&claude, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+
+        // First daemon: the instance runs, then shuts down gracefully
+        let manager = new_manager();
+        let inst = crate::test_helpers::create_test_instance(
+            &manager,
+            None,
+            Some("/tmp".into()),
+            Some(claude.display().to_string()),
+        )
+        .await
+        .unwrap();
+        let handle = manager.get_handle(&inst.id).await.unwrap();
+        handle.set_session_id("sess-e2e".to_string()).await.unwrap();
+        for _ in 0..50 {
+            if handle.get_text(false).await[0] == "marker-before-restart" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(manager.stop(&inst.id).await);
+        let store = ScrollbackStore::new(scrollback_dir.clone());
+        assert!(store.load("sess-e2e").is_some());
+
+        // Second daemon: resuming the session brings the scrollback back
+        state.instance_manager = new_manager();
+        let router = Router::new()
+            .route("/instances", post(create_instance))
+            .route("/instances/{id}", delete(delete_instance))
+            .route("/instances/{id}/output", get(get_instance_output))
+            .with_state(state);
+        let body = serde_json::json!({
+            "command": format!("{} --resume sess-e2e", claude.display()),
+            "working_dir": "/tmp",
+        });
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/instances")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["id"], inst.id.as_str());
+
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/instances/{}/output?text=screen", inst.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(output["lines"][0], "marker-before-restart");
+
+        // Deleting it is final: the snapshot goes, and a late save can't
+        // bring it back
+        let resp = router
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(format!("/instances/{}", inst.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(store.load(&inst.id).is_none());
+        assert!(store.load("sess-e2e").is_none());
+    }
+
     #[tokio::test]
     async fn test_create_instance_response_serialization() {
         let resp = CreateInstanceResponse {
@@ -719,6 +1318,8 @@ mod tests {
         let router = Router::new()
             .route("/instances", get(list_instances).post(create_instance))
             .route("/instances/{id}", get(get_instance).delete(delete_instance))
+            .route("/instances/{id}/name", patch(set_custom_name))
+            .route("/instances/{id}/output", get(get_instance_output))
             .route("/instances/{id}/invitations", post(create_invitation))
             .route("/invitations/{token}/accept", post(accept_invitation))
             .route(
@@ -833,7 +1434,7 @@ mod tests {
             invite_token: "test-invite-token".to_string(),
             instance_id: "inst-1".to_string(),
             created_by: admin_user.user_id.clone(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             max_uses: Some(5),
             use_count: 0,
             expires_at: Some(chrono::Utc::now().timestamp() + 86400),
@@ -865,7 +1466,7 @@ mod tests {
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
         assert_eq!(json["instance_id"], "inst-1");
-        assert_eq!(json["role"], "collaborator");
+        assert_eq!(json["role"], "operator");
     }
 
     #[tokio::test]
@@ -877,7 +1478,7 @@ mod tests {
             invite_token: "expired-token".to_string(),
             instance_id: "inst-1".to_string(),
             created_by: "admin-user".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             max_uses: None,
             use_count: 0,
             expires_at: Some(0), // epoch = definitely past
@@ -908,7 +1509,7 @@ mod tests {
             invite_token: "used-up-token".to_string(),
             instance_id: "inst-1".to_string(),
             created_by: "admin-user".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             max_uses: Some(1),
             use_count: 1, // already fully used
             expires_at: None,
@@ -948,7 +1549,7 @@ mod tests {
         let perm = crate::models::InstancePermission {
             instance_id: "inst-1".to_string(),
             user_id: "collab-user".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             granted_at: chrono::Utc::now().timestamp(),
             granted_by: Some("admin-user".to_string()),
         };
@@ -1058,6 +1659,158 @@ mod tests {
         let _ = app.oneshot(req).await;
     }
 
+    #[tokio::test]
+    async fn test_roles_enforced_on_rename_and_delete() {
+        let (app, _tmp, admin_user, state) = auth_test_router().await;
+
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/instances")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"command":"echo test","working_dir":"/tmp"}"#,
+            ))
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let inst_id = created["id"].as_str().unwrap().to_string();
+
+        let user = crate::test_helpers::create_test_user(
+            &state.repository,
+            "regular-5",
+            "regular5",
+            "Regular 5",
+        )
+        .await;
+        let grant = |role| crate::models::InstancePermission {
+            instance_id: inst_id.clone(),
+            user_id: "regular-5".to_string(),
+            role,
+            granted_at: 0,
+            granted_by: None,
+        };
+        let rename = |user: &crate::auth::AuthUser| {
+            let mut req = Request::builder()
+                .method("PATCH")
+                .uri(format!("/instances/{}/name", inst_id))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"custom_name":"renamed"}"#))
+                .unwrap();
+            inject_auth(&mut req, user);
+            req
+        };
+
+        // Viewer: can see the instance, can't rename it
+        state
+            .repository
+            .create_instance_permission(&grant(InstanceRole::Viewer))
+            .await
+            .unwrap();
+        let mut req = Request::builder()
+            .uri(format!("/instances/{}", inst_id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["role"], "viewer");
+        let resp = app.clone().oneshot(rename(&user)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Operator: can rename, can't delete
+        state
+            .repository
+            .create_instance_permission(&grant(InstanceRole::Operator))
+            .await
+            .unwrap();
+        let resp = app.clone().oneshot(rename(&user)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/{}", inst_id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/{}", inst_id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let _ = app.oneshot(req).await;
+    }
+
+    #[tokio::test]
+    async fn test_output_requires_viewer_role() {
+        let (app, _tmp, admin_user, state) = auth_test_router().await;
+
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/instances")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"command":"sleep 5","working_dir":"/tmp"}"#))
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let inst_id = created["id"].as_str().unwrap().to_string();
+
+        let user = crate::test_helpers::create_test_user(
+            &state.repository,
+            "regular-6",
+            "regular6",
+            "Regular 6",
+        )
+        .await;
+        let output = |user: &crate::auth::AuthUser| {
+            let mut req = Request::builder()
+                .uri(format!("/instances/{}/output?text=screen", inst_id))
+                .body(Body::empty())
+                .unwrap();
+            inject_auth(&mut req, user);
+            req
+        };
+
+        let resp = app.clone().oneshot(output(&user)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        state
+            .repository
+            .create_instance_permission(&crate::models::InstancePermission {
+                instance_id: inst_id.clone(),
+                user_id: "regular-6".to_string(),
+                role: InstanceRole::Viewer,
+                granted_at: 0,
+                granted_by: None,
+            })
+            .await
+            .unwrap();
+        let resp = app.clone().oneshot(output(&user)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/{}", inst_id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let _ = app.oneshot(req).await;
+    }
+
     #[tokio::test]
     async fn test_list_instances_auth_non_admin_filtered() {
         let (app, _tmp, admin_user, state) = auth_test_router().await;
@@ -1093,7 +1846,7 @@ mod tests {
         let perm = crate::models::InstancePermission {
             instance_id: ids[0].clone(),
             user_id: "regular-4".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             granted_at: chrono::Utc::now().timestamp(),
             granted_by: Some("admin-user".to_string()),
         };
@@ -1118,6 +1871,7 @@ mod tests {
         let instances: Vec<ClaudeInstance> = serde_json::from_slice(&body).unwrap();
         assert_eq!(instances.len(), 1);
         assert_eq!(instances[0].id, ids[0]);
+        assert_eq!(instances[0].role, Some(InstanceRole::Operator));
 
         // Admin lists all → should see both
         let mut req = Request::builder()
@@ -1133,6 +1887,11 @@ mod tests {
             .unwrap();
         let instances: Vec<ClaudeInstance> = serde_json::from_slice(&body).unwrap();
         assert!(instances.len() >= 2);
+        assert!(
+            instances
+                .iter()
+                .all(|i| i.role == Some(InstanceRole::Owner))
+        );
 
         // Clean up
         for id in &ids {
@@ -1163,7 +1922,7 @@ mod tests {
         let perm = crate::models::InstancePermission {
             instance_id: "owned-inst".to_string(),
             user_id: "owner-1".to_string(),
-            role: "owner".to_string(),
+            role: InstanceRole::Owner,
             granted_at: chrono::Utc::now().timestamp(),
             granted_by: None,
         };
@@ -1202,7 +1961,7 @@ mod tests {
         let perm = crate::models::InstancePermission {
             instance_id: "owned-inst-2".to_string(),
             user_id: "owner-2".to_string(),
-            role: "owner".to_string(),
+            role: InstanceRole::Owner,
             granted_at: chrono::Utc::now().timestamp(),
             granted_by: None,
         };
@@ -1225,7 +1984,7 @@ mod tests {
         let collab_perm = crate::models::InstancePermission {
             instance_id: "owned-inst-2".to_string(),
             user_id: "collab-to-remove".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             granted_at: chrono::Utc::now().timestamp(),
             granted_by: Some("owner-2".to_string()),
         };
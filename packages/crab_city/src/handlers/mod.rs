@@ -2,12 +2,15 @@ pub mod admin;
 pub mod browse;
 pub mod bug_report;
 pub mod conversations;
+pub mod events;
 pub mod health;
 pub mod inbox;
 pub mod instances;
 pub mod notes;
+pub mod prompts;
 pub mod settings;
 pub mod tasks;
+pub mod timeline;
 pub mod websocket;
 
 // Re-export all handlers for easy route registration
@@ -23,17 +26,22 @@ pub use conversations::{
     format_turn_with_attribution, get_comments, get_conversation, get_conversation_by_id,
     get_shared_conversation, list_conversations, poll_conversation, search_conversations_handler,
 };
+pub use events::events_handler;
 pub use health::{health_handler, health_live_handler, health_ready_handler, metrics_handler};
 pub use inbox::{dismiss_inbox_handler, list_inbox_handler};
 pub use instances::{
     accept_invitation, create_instance, create_invitation, delete_instance, get_instance,
-    get_instance_output, list_instances, remove_collaborator, set_custom_name,
+    get_instance_output, list_instances, remove_collaborator, set_custom_name, set_size_policy,
 };
 pub use notes::{create_note, delete_note, get_notes, update_note};
+pub use prompts::{
+    cancel_prompt_handler, enqueue_prompt_handler, list_prompts_handler, move_prompt_handler,
+};
 pub use settings::{get_user_settings_handler, update_user_settings_handler};
 pub use tasks::{
     add_task_tag_handler, create_dispatch_handler, create_task_handler, delete_task_handler,
     get_task_handler, list_tasks_handler, migrate_tasks_handler, remove_task_tag_handler,
     send_task_handler, update_task_handler,
 };
+pub use timeline::{get_instance_timeline, get_instance_timeline_frame};
 pub use websocket::multiplexed_websocket_handler;
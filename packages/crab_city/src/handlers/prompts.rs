@@ -0,0 +1,317 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+use crate::AppState;
+use crate::auth::MaybeAuthUser;
+use crate::models::{EnqueuePromptRequest, InstanceRole, MovePromptRequest, QueuedPrompt};
+
+use super::instances::require_role;
+
+fn internal_error(e: anyhow::Error) -> (StatusCode, String) {
+    tracing::error!("Prompt queue error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// The prompt, if it exists and is queued on `instance_id`.
+async fn prompt_on_instance(
+    state: &AppState,
+    instance_id: &str,
+    prompt_id: i64,
+) -> Result<QueuedPrompt, (StatusCode, String)> {
+    state
+        .repository
+        .get_queued_prompt(prompt_id)
+        .await
+        .map_err(internal_error)?
+        .filter(|p| p.instance_id == instance_id)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Queued prompt not found".to_string()))
+}
+
+/// GET /api/instances/{id}/prompts — the instance's queue, next to send first
+pub async fn list_prompts_handler(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<QueuedPrompt>>, (StatusCode, String)> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Viewer)
+        .await
+        .map_err(|status| (status, "No access to this instance".to_string()))?;
+    let prompts = state
+        .repository
+        .list_prompt_queue(&id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(prompts))
+}
+
+/// POST /api/instances/{id}/prompts — queue a prompt, sent as soon as
+/// Claude is idle
+pub async fn enqueue_prompt_handler(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+    Json(req): Json<EnqueuePromptRequest>,
+) -> Result<Json<QueuedPrompt>, (StatusCode, String)> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Operator)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                "Queueing prompts requires the operator role".to_string(),
+            )
+        })?;
+
+    let content = req.content.trim();
+    if content.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Prompt is empty".to_string()));
+    }
+    let instance = state
+        .instance_manager
+        .get(&id)
+        .await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Instance not found".to_string()))?;
+    // Only Claude reports when it's idle; anything else would wait forever
+    if !instance.kind.is_structured() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Prompts can only be queued on Claude instances".to_string(),
+        ));
+    }
+
+    let author = super::websocket::resolve_ws_user(maybe_user.0);
+    let prompt = state
+        .repository
+        .enqueue_prompt(&id, content, &author.user_id, &author.display_name)
+        .await
+        .map_err(internal_error)?;
+
+    let gsm = &state.global_state_manager;
+    gsm.broadcast_prompt_queue(&state.repository, &id).await;
+    gsm.dispatch_queued_prompt(&state.repository, &id).await;
+
+    Ok(Json(prompt))
+}
+
+/// DELETE /api/instances/{id}/prompts/{prompt_id} — cancel a queued prompt
+pub async fn cancel_prompt_handler(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path((id, prompt_id)): Path<(String, i64)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Operator)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                "Cancelling prompts requires the operator role".to_string(),
+            )
+        })?;
+    prompt_on_instance(&state, &id, prompt_id).await?;
+
+    state
+        .repository
+        .delete_queued_prompt(prompt_id)
+        .await
+        .map_err(internal_error)?;
+    state
+        .global_state_manager
+        .broadcast_prompt_queue(&state.repository, &id)
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /api/instances/{id}/prompts/{prompt_id}/position — reorder the queue
+pub async fn move_prompt_handler(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path((id, prompt_id)): Path<(String, i64)>,
+    Json(req): Json<MovePromptRequest>,
+) -> Result<Json<Vec<QueuedPrompt>>, (StatusCode, String)> {
+    require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Operator)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                "Reordering prompts requires the operator role".to_string(),
+            )
+        })?;
+    prompt_on_instance(&state, &id, prompt_id).await?;
+
+    state
+        .repository
+        .move_queued_prompt(prompt_id, req.position)
+        .await
+        .map_err(internal_error)?;
+    state
+        .global_state_manager
+        .broadcast_prompt_queue(&state.repository, &id)
+        .await;
+    let prompts = state
+        .repository
+        .list_prompt_queue(&id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(prompts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        http::Request,
+        routing::{delete, get, put},
+    };
+    use tower::ServiceExt;
+
+    fn inject_auth(req: &mut Request<Body>, auth_user: &crate::auth::AuthUser) {
+        req.extensions_mut().insert(auth_user.clone());
+    }
+
+    async fn json_body<T: serde::de::DeserializeOwned>(resp: axum::response::Response) -> T {
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prompt_queue_roles_and_reorder() {
+        let (state, _tmp, admin_user) = crate::test_helpers::test_app_state_with_auth().await;
+        let app = Router::new()
+            .route(
+                "/instances",
+                axum::routing::post(crate::handlers::create_instance),
+            )
+            .route(
+                "/instances/{id}/prompts",
+                get(list_prompts_handler).post(enqueue_prompt_handler),
+            )
+            .route(
+                "/instances/{id}/prompts/{prompt_id}",
+                delete(cancel_prompt_handler),
+            )
+            .route(
+                "/instances/{id}/prompts/{prompt_id}/position",
+                put(move_prompt_handler),
+            )
+            .with_state(state.clone());
+
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/instances")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"command":"echo test","working_dir":"/tmp"}"#,
+            ))
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let created: serde_json::Value = json_body(app.clone().oneshot(req).await.unwrap()).await;
+        let inst_id = created["id"].as_str().unwrap().to_string();
+
+        let enqueue = |user: &crate::auth::AuthUser| {
+            let mut req = Request::builder()
+                .method("POST")
+                .uri(format!("/instances/{}/prompts", inst_id))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"content":"run the tests"}"#))
+                .unwrap();
+            inject_auth(&mut req, user);
+            req
+        };
+
+        // Shells never go idle in Claude's sense, so nothing would be sent
+        let resp = app.clone().oneshot(enqueue(&admin_user)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let viewer =
+            crate::test_helpers::create_test_user(&state.repository, "viewer-1", "viewer1", "V")
+                .await;
+        state
+            .repository
+            .create_instance_permission(&crate::models::InstancePermission {
+                instance_id: inst_id.clone(),
+                user_id: "viewer-1".to_string(),
+                role: InstanceRole::Viewer,
+                granted_at: 0,
+                granted_by: None,
+            })
+            .await
+            .unwrap();
+        let resp = app.clone().oneshot(enqueue(&viewer)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Viewers can see the queue but not touch it
+        let a = state
+            .repository
+            .enqueue_prompt(&inst_id, "a", "u-1", "Alice")
+            .await
+            .unwrap();
+        let b = state
+            .repository
+            .enqueue_prompt(&inst_id, "b", "u-2", "Bob")
+            .await
+            .unwrap();
+        let mut req = Request::builder()
+            .uri(format!("/instances/{}/prompts", inst_id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &viewer);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let listed: Vec<QueuedPrompt> = json_body(resp).await;
+        assert_eq!(listed.len(), 2);
+
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/{}/prompts/{}", inst_id, a.id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &viewer);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Operators reorder and cancel
+        let mut req = Request::builder()
+            .method("PUT")
+            .uri(format!("/instances/{}/prompts/{}/position", inst_id, b.id))
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"position":0}"#))
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let reordered: Vec<QueuedPrompt> = json_body(resp).await;
+        assert_eq!(reordered[0].content, "b");
+
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/{}/prompts/{}", inst_id, a.id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            state.repository.list_prompt_queue(&inst_id).await.unwrap(),
+            vec![QueuedPrompt {
+                position: 0,
+                ..b.clone()
+            }]
+        );
+
+        // Prompt ids are scoped to their instance
+        let mut req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/instances/other/prompts/{}", b.id))
+            .body(Body::empty())
+            .unwrap();
+        inject_auth(&mut req, &admin_user);
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
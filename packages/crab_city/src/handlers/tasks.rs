@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::AppState;
 use crate::auth::MaybeAuthUser;
+use crate::models::InstanceRole;
 use crate::ws;
 
 /// Broadcast a full task snapshot (with tags + dispatches) to all WS clients.
@@ -50,7 +51,10 @@ pub async fn create_task_handler(
         .unwrap_or(1.0);
 
     let (creator_id, creator_name) = match &maybe_user {
-        MaybeAuthUser(Some(user)) => (Some(user.user_id.clone()), user.display_name.clone()),
+        MaybeAuthUser(Some(user)) => (
+            (!user.is_loopback()).then(|| user.user_id.clone()),
+            user.display_name.clone(),
+        ),
         _ => (None, "anonymous".to_string()),
     };
 
@@ -156,7 +160,7 @@ pub async fn delete_task_handler(
 
 pub async fn send_task_handler(
     State(state): State<AppState>,
-    _maybe_user: MaybeAuthUser,
+    maybe_user: MaybeAuthUser,
     Path(id): Path<i64>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let task = state
@@ -173,6 +177,20 @@ pub async fn send_task_handler(
         )
     })?;
 
+    super::instances::require_role(
+        &state,
+        maybe_user.0.as_ref(),
+        instance_id,
+        InstanceRole::Operator,
+    )
+    .await
+    .map_err(|status| {
+        (
+            status,
+            "Sending a task requires the operator role".to_string(),
+        )
+    })?;
+
     let handle = state
         .instance_manager
         .get_handle(instance_id)
@@ -185,14 +203,14 @@ pub async fn send_task_handler(
         })?;
 
     let text = task.body.as_deref().unwrap_or(&task.title);
-    handle.write_input(text).await.map_err(|e| {
+    handle.write_input(text.as_bytes()).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to write to PTY: {}", e),
         )
     })?;
 
-    handle.write_input("\r").await.map_err(|e| {
+    handle.write_input(b"\r").await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to send Enter: {}", e),
@@ -342,6 +360,27 @@ mod tests {
         (router, tmp)
     }
 
+    #[tokio::test]
+    async fn test_create_task_from_loopback_client() {
+        let (app, _tmp) = test_router().await;
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/tasks")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"title":"Filed from the CLI"}"#))
+            .unwrap();
+        req.extensions_mut()
+            .insert(crate::auth::synthetic_loopback_admin());
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["creator_id"], serde_json::Value::Null);
+        assert_eq!(created["creator_name"], "Local Admin");
+    }
+
     #[tokio::test]
     async fn test_list_tasks_empty() {
         let (app, _tmp) = test_router().await;
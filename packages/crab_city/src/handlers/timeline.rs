@@ -0,0 +1,484 @@
+//! Instance timeline: correlates a VT recording with conversation turns and
+//! state transitions so the UI can jump between a turn and the terminal
+//! moment it happened.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use crate::auth::MaybeAuthUser;
+use crate::inference::ClaudeState;
+use crate::models::InstanceRole;
+use crate::virtual_terminal::VtRecording;
+use crate::ws::StateTransition;
+
+use super::instances::require_role;
+
+/// Emit a keyframe once this much output has accumulated since the last one.
+const KEYFRAME_MIN_OUTPUT_BYTES: usize = 64 * 1024;
+/// Output after this much silence starts a new burst and gets a keyframe.
+const KEYFRAME_IDLE_GAP_US: u64 = 2_000_000;
+/// Max characters of turn content included in a timeline entry.
+const TURN_PREVIEW_CHARS: usize = 120;
+
+#[derive(Debug, Serialize)]
+pub struct Timeline {
+    pub instance_id: String,
+    /// Wall-clock time of recording offset 0 (RFC 3339).
+    pub recording_started_at: String,
+    pub duration_us: u64,
+    /// All entries, sorted by `at`.
+    pub entries: Vec<TimelineEntry>,
+}
+
+/// One point on the timeline. `offset_us` is relative to the recording start
+/// and is `None` for events that happened before recording began.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Turn {
+        at: DateTime<Utc>,
+        offset_us: Option<u64>,
+        uuid: Option<String>,
+        role: Option<String>,
+        preview: String,
+    },
+    State {
+        at: DateTime<Utc>,
+        offset_us: Option<u64>,
+        state: ClaudeState,
+    },
+    Keyframe {
+        at: DateTime<Utc>,
+        offset_us: u64,
+        event_index: usize,
+    },
+}
+
+impl TimelineEntry {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            TimelineEntry::Turn { at, .. }
+            | TimelineEntry::State { at, .. }
+            | TimelineEntry::Keyframe { at, .. } => *at,
+        }
+    }
+}
+
+/// Merge recording keyframes, conversation turns and state transitions into
+/// a single list ordered by wall-clock time.
+pub fn build_timeline(
+    started_at: DateTime<Utc>,
+    recording: &VtRecording,
+    turns: &[serde_json::Value],
+    states: &[StateTransition],
+) -> Vec<TimelineEntry> {
+    let offset_of = |at: DateTime<Utc>| -> Option<u64> {
+        (at - started_at)
+            .num_microseconds()
+            .and_then(|us| u64::try_from(us).ok())
+    };
+
+    let mut entries: Vec<TimelineEntry> = recording
+        .keyframes(KEYFRAME_MIN_OUTPUT_BYTES, KEYFRAME_IDLE_GAP_US)
+        .into_iter()
+        .map(|kf| TimelineEntry::Keyframe {
+            at: started_at + chrono::Duration::microseconds(kf.timestamp_us as i64),
+            offset_us: kf.timestamp_us,
+            event_index: kf.event_index,
+        })
+        .collect();
+
+    for turn in turns {
+        let Some(at) = turn
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+        else {
+            continue;
+        };
+        let str_field = |key: &str| turn.get(key).and_then(|v| v.as_str()).map(String::from);
+        let preview = str_field("content")
+            .map(|c| c.chars().take(TURN_PREVIEW_CHARS).collect())
+            .unwrap_or_default();
+        entries.push(TimelineEntry::Turn {
+            at,
+            offset_us: offset_of(at),
+            uuid: str_field("uuid"),
+            role: str_field("role"),
+            preview,
+        });
+    }
+
+    for transition in states {
+        entries.push(TimelineEntry::State {
+            at: transition.at,
+            offset_us: offset_of(transition.at),
+            state: transition.state.clone(),
+        });
+    }
+
+    // Stable sort keeps keyframes ahead of turns/states at the same instant.
+    entries.sort_by_key(TimelineEntry::at);
+    entries
+}
+
+/// Load and parse an instance's recording off the async runtime.
+async fn load_recording(state: &AppState, id: &str) -> Result<VtRecording, StatusCode> {
+    let path = state
+        .instance_manager
+        .recording_path(id)
+        .filter(|p| p.exists())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    tokio::task::spawn_blocking(move || VtRecording::from_file(&path))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|e| {
+            tracing::error!("Failed to read VT recording for {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// GET /api/instances/{id}/timeline — merged turns, states and keyframes
+pub async fn get_instance_timeline(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(handle) = state.instance_manager.get_handle(&id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Err(status) =
+        require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Viewer).await
+    {
+        return status.into_response();
+    }
+    let recording = match load_recording(&state, &id).await {
+        Ok(r) => r,
+        Err(status) => return status.into_response(),
+    };
+
+    // Recordings from before the header carried a start time fall back to
+    // the instance creation time — the recorder opens alongside the PTY.
+    let created_at = handle.get_info().await.created_at;
+    let started_at = recording
+        .header
+        .started_at_ms
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(&created_at)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        })
+        .unwrap_or_else(Utc::now);
+
+    let turns = handle.get_conversation_snapshot().await;
+    let states = state.global_state_manager.get_state_history(&id).await;
+    let entries = build_timeline(started_at, &recording, &turns, &states);
+
+    Json(Timeline {
+        instance_id: id,
+        recording_started_at: started_at.to_rfc3339(),
+        duration_us: recording.duration_us(),
+        entries,
+    })
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct FrameQuery {
+    /// Offset from the recording start, in microseconds.
+    offset_us: u64,
+    /// Receiving terminal height (sizes the scrollback flush). Defaults to 24.
+    rows: Option<u16>,
+}
+
+/// GET /api/instances/{id}/timeline/frame?offset_us=N — terminal state at an offset
+///
+/// Replays the recording up to `offset_us` and returns the same
+/// scrollback + keyframe bytes a client receives on attach.
+pub async fn get_instance_timeline_frame(
+    State(state): State<AppState>,
+    maybe_user: MaybeAuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<FrameQuery>,
+) -> Response {
+    if state.instance_manager.get_handle(&id).await.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if let Err(status) =
+        require_role(&state, maybe_user.0.as_ref(), &id, InstanceRole::Viewer).await
+    {
+        return status.into_response();
+    }
+    let recording = match load_recording(&state, &id).await {
+        Ok(r) => r,
+        Err(status) => return status.into_response(),
+    };
+
+    let max_delta = state.server_config.instance.max_buffer_bytes;
+    let rows = query.rows.unwrap_or(24);
+    let data = tokio::task::spawn_blocking(move || {
+        let mut vt = recording.replay_until(max_delta, query.offset_us);
+        vt.replay(rows)
+    })
+    .await
+    .unwrap_or_default();
+
+    Json(serde_json::json!({
+        "offset_us": query.offset_us,
+        "data": String::from_utf8_lossy(&data),
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::virtual_terminal::{VtEvent, VtRecordingHeader};
+    use serde_json::json;
+
+    fn recording(events: Vec<VtEvent>) -> VtRecording {
+        VtRecording {
+            header: VtRecordingHeader {
+                rows: 24,
+                cols: 80,
+                scrollback: 0,
+                started_at_ms: None,
+            },
+            events,
+        }
+    }
+
+    fn t0() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn merges_in_wall_clock_order() {
+        let rec = recording(vec![
+            VtEvent::Output {
+                timestamp_us: 0,
+                data: b"$ ".to_vec(),
+            },
+            VtEvent::Output {
+                timestamp_us: 5_000_000,
+                data: b"response".to_vec(),
+            },
+        ]);
+        let turns = vec![
+            json!({"uuid": "u1", "role": "User", "content": "hello", "timestamp": "2024-06-01T12:00:01Z"}),
+            json!({"uuid": "a1", "role": "Assistant", "content": "hi", "timestamp": "2024-06-01T12:00:06Z"}),
+        ];
+        let states = vec![StateTransition {
+            at: t0() + chrono::Duration::seconds(2),
+            state: ClaudeState::Thinking,
+        }];
+
+        let entries = build_timeline(t0(), &rec, &turns, &states);
+        let kinds: Vec<&str> = entries
+            .iter()
+            .map(|e| match e {
+                TimelineEntry::Turn { .. } => "turn",
+                TimelineEntry::State { .. } => "state",
+                TimelineEntry::Keyframe { .. } => "keyframe",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["keyframe", "turn", "state", "keyframe", "turn"]);
+
+        match &entries[1] {
+            TimelineEntry::Turn {
+                offset_us, uuid, ..
+            } => {
+                assert_eq!(*offset_us, Some(1_000_000));
+                assert_eq!(uuid.as_deref(), Some("u1"));
+            }
+            other => panic!("expected turn, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn events_before_recording_have_no_offset() {
+        let rec = recording(vec![]);
+        let states = vec![StateTransition {
+            at: t0() - chrono::Duration::seconds(1),
+            state: ClaudeState::Starting,
+        }];
+        let entries = build_timeline(t0(), &rec, &[], &states);
+        assert_eq!(
+            entries,
+            vec![TimelineEntry::State {
+                at: t0() - chrono::Duration::seconds(1),
+                offset_us: None,
+                state: ClaudeState::Starting,
+            }]
+        );
+    }
+
+    #[test]
+    fn turns_without_timestamp_are_skipped() {
+        let rec = recording(vec![]);
+        let turns = vec![json!({"uuid": "x", "content": "no time"})];
+        assert!(build_timeline(t0(), &rec, &turns, &[]).is_empty());
+    }
+
+    #[test]
+    fn turn_preview_is_truncated() {
+        let rec = recording(vec![]);
+        let long = "a".repeat(TURN_PREVIEW_CHARS * 2);
+        let turns = vec![json!({"content": long, "timestamp": "2024-06-01T12:00:00Z"})];
+        match &build_timeline(t0(), &rec, &turns, &[])[0] {
+            TimelineEntry::Turn { preview, .. } => {
+                assert_eq!(preview.chars().count(), TURN_PREVIEW_CHARS)
+            }
+            other => panic!("expected turn, got {other:?}"),
+        }
+    }
+
+    async fn get_json(state: AppState, uri: &str) -> (StatusCode, serde_json::Value) {
+        get_json_as(state, None, uri).await
+    }
+
+    async fn get_json_as(
+        state: AppState,
+        user: Option<&crate::auth::AuthUser>,
+        uri: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        use axum::{Router, body::Body, http::Request, routing::get};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/instances/{id}/timeline", get(get_instance_timeline))
+            .route(
+                "/instances/{id}/timeline/frame",
+                get(get_instance_timeline_frame),
+            )
+            .with_state(state);
+        let mut req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        if let Some(user) = user {
+            req.extensions_mut().insert(user.clone());
+        }
+        let resp = app.oneshot(req).await.unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn timeline_unknown_instance_is_404() {
+        let (state, _tmp) = crate::test_helpers::test_app_state().await;
+        let (status, _) = get_json(state, "/instances/nope/timeline").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn timeline_requires_viewer_role() {
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let inst = crate::test_helpers::create_test_instance(
+            &state.instance_manager,
+            None,
+            Some("/tmp".into()),
+            Some("cat".into()),
+        )
+        .await
+        .unwrap();
+        let outsider =
+            crate::test_helpers::create_test_user(&state.repository, "u-out", "outsider", "O")
+                .await;
+        let timeline = format!("/instances/{}/timeline", inst.id);
+        let frame = format!("/instances/{}/timeline/frame?offset_us=0", inst.id);
+
+        let (status, _) = get_json_as(state.clone(), Some(&outsider), &timeline).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = get_json_as(state.clone(), Some(&outsider), &frame).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        state
+            .repository
+            .create_instance_permission(&crate::models::InstancePermission {
+                instance_id: inst.id.clone(),
+                user_id: "u-out".to_string(),
+                role: InstanceRole::Viewer,
+                granted_at: 0,
+                granted_by: None,
+            })
+            .await
+            .unwrap();
+        // Past the role check; there's just no recording to read
+        let (status, _) = get_json_as(state.clone(), Some(&outsider), &timeline).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = get_json_as(state.clone(), Some(&outsider), &frame).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        state.instance_manager.stop(&inst.id).await;
+    }
+
+    #[tokio::test]
+    async fn timeline_without_recording_is_404() {
+        let (state, _tmp) = crate::test_helpers::test_app_state().await;
+        let inst = crate::test_helpers::create_test_instance(
+            &state.instance_manager,
+            None,
+            Some("/tmp".into()),
+            Some("cat".into()),
+        )
+        .await
+        .unwrap();
+
+        let (status, _) =
+            get_json(state.clone(), &format!("/instances/{}/timeline", inst.id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        state.instance_manager.stop(&inst.id).await;
+    }
+
+    #[tokio::test]
+    async fn timeline_and_frame_from_recording() {
+        let (mut state, tmp) = crate::test_helpers::test_app_state().await;
+        state.instance_manager =
+            std::sync::Arc::new(crate::instance_manager::InstanceManager::new(
+                "echo".into(),
+                9000,
+                25 * 1024 * 1024,
+                0,
+                Some(tmp.path().join("recordings")),
+                None,
+            ));
+        let inst = crate::test_helpers::create_test_instance(
+            &state.instance_manager,
+            None,
+            Some("/tmp".into()),
+            Some("echo timeline-marker".into()),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let (status, body) =
+            get_json(state.clone(), &format!("/instances/{}/timeline", inst.id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["instance_id"], inst.id);
+        let entries = body["entries"].as_array().unwrap();
+        assert!(entries.iter().any(|e| e["kind"] == "keyframe"));
+
+        let offset = body["duration_us"].as_u64().unwrap();
+        let (status, frame) = get_json(
+            state.clone(),
+            &format!("/instances/{}/timeline/frame?offset_us={offset}", inst.id),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(frame["data"].as_str().unwrap().contains("timeline-marker"));
+        state.instance_manager.stop(&inst.id).await;
+    }
+}
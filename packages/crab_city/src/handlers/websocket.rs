@@ -1,5 +1,5 @@
 use axum::{
-    extract::{State, WebSocketUpgrade},
+    extract::{Query, State, WebSocketUpgrade},
     response::Response,
 };
 
@@ -16,6 +16,7 @@ pub(crate) fn resolve_ws_user(auth_user: Option<AuthUser>) -> ws::WsUser {
         Some(u) => ws::WsUser {
             user_id: u.user_id,
             display_name: u.display_name,
+            restricted: !u.is_admin,
         },
         None => {
             let name = std::env::var("USER")
@@ -24,15 +25,20 @@ pub(crate) fn resolve_ws_user(auth_user: Option<AuthUser>) -> ws::WsUser {
             ws::WsUser {
                 user_id: name.clone(),
                 display_name: name,
+                restricted: false,
             }
         }
     }
 }
 
-/// Multiplexed WebSocket handler - single connection for all instances
+/// Multiplexed WebSocket handler - single connection for all instances.
+///
+/// Reconnecting clients pass `?resume=<token>&lifecycle_seq=<n>` to pick up
+/// where they left off (see `ws::ResumeParams`).
 pub async fn multiplexed_websocket_handler(
     State(state): State<AppState>,
     maybe_user: MaybeAuthUser,
+    Query(resume): Query<ws::ResumeParams>,
     ws: WebSocketUpgrade,
 ) -> Response {
     let instance_manager = state.instance_manager.clone();
@@ -52,6 +58,7 @@ pub async fn multiplexed_websocket_handler(
             Some(metrics),
             ws_user,
             Some(repository),
+            resume,
         )
     })
 }
@@ -72,6 +79,7 @@ mod tests {
         let ws = resolve_ws_user(Some(auth));
         assert_eq!(ws.user_id, "alice-id");
         assert_eq!(ws.display_name, "Alice");
+        assert!(ws.restricted);
     }
 
     #[test]
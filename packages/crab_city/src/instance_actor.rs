@@ -13,18 +13,63 @@ use crate::inference::ClaudeState;
 use crate::instance_manager::InstanceKind;
 use crate::process_driver::{DriverContext, DriverSignal, ProcessDriver};
 use crate::repository::ConversationRepository;
-use crate::virtual_terminal::{ClientType, VirtualTerminal, VtRecorder};
-use crate::ws::ConversationEvent;
+use crate::scrollback::{ScrollbackMeta, ScrollbackStore};
+use crate::virtual_terminal::{
+    ClientType, ScreenState, SizePolicy, TerminalEvent, VirtualTerminal, VtRecorder, VtSnapshot,
+};
+use crate::ws::{ConversationEvent, ReplayWindow, TerminalNotificationKind};
 use crate::ws::{FirstInputData, PendingAttribution, StateBroadcast};
 
+/// Bells closer together than this are reported once (shells ring on every
+/// failed tab completion).
+const BELL_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often (in 500ms ticks) a changed terminal is snapshotted to disk.
+const SCROLLBACK_SNAPSHOT_TICKS: u32 = 20;
+
+/// Output chunks kept per instance for resuming clients (see `ws::replay`).
+const OUTPUT_REPLAY_CHUNKS: usize = 4096;
+/// Byte budget of the per-instance output replay window.
+const OUTPUT_REPLAY_BYTES: usize = 256 * 1024;
+
 /// PTY output enriched with cursor position from the VirtualTerminal.
 /// Produced by the VT processing task after feeding output to the parser.
 #[derive(Debug, Clone)]
 pub struct EnrichedOutput {
+    /// Per-instance output sequence, assigned by `record_output`
+    pub seq: u64,
     pub data: Vec<u8>,
     pub cursor: (u16, u16),
     #[allow(dead_code)]
     pub timestamp: i64,
+    /// OSC 52 clipboard writes contained in this chunk.
+    pub clipboard: Vec<ClipboardWrite>,
+}
+
+/// What a reconnecting client needs to catch up on an instance's output.
+#[derive(Debug, Clone)]
+pub enum OutputResume {
+    /// Every chunk after the client's sequence, oldest first
+    Missed(Vec<EnrichedOutput>),
+    /// The window no longer reaches back that far: a full replay, current
+    /// up to output sequence `seq`
+    Replay { data: String, seq: u64 },
+}
+
+/// A screen diff for one client.
+#[derive(Debug)]
+pub struct ScreenFrame {
+    pub data: Vec<u8>,
+    pub state: ScreenState,
+    /// Output sequence of the last chunk the frame reflects
+    pub seq: u64,
+}
+
+/// An OSC 52 clipboard write, still base64-encoded as the program sent it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardWrite {
+    pub selection: String,
+    pub data: String,
 }
 
 /// Commands that can be sent to an instance actor
@@ -35,7 +80,7 @@ pub enum InstanceCommand {
         respond_to: oneshot::Sender<InstanceInfo>,
     },
     WriteInput {
-        text: String,
+        data: Vec<u8>,
         respond_to: oneshot::Sender<Result<usize>>,
     },
     Resize {
@@ -50,11 +95,30 @@ pub enum InstanceCommand {
     /// Returns chunks from the end of the buffer up to max_bytes total.
     /// `client_rows` is the receiving terminal's height — used to size the
     /// scrollback-to-keyframe flush so no lines are lost or garbled.
+    /// Responds with the replay and the output sequence it is current to.
     GetRecentOutput {
         max_bytes: usize,
         client_rows: u16,
+        respond_to: oneshot::Sender<(Vec<String>, u64)>,
+    },
+    /// Rendered plain-text lines: the visible screen, preceded by the
+    /// scrollback when `scrollback` is set.
+    GetText {
+        scrollback: bool,
         respond_to: oneshot::Sender<Vec<String>>,
     },
+    /// Catch a reconnecting client up from output sequence `seq`.
+    ResumeOutput {
+        seq: u64,
+        respond_to: oneshot::Sender<OutputResume>,
+    },
+    /// Screen diff from a client's last frame `prev` (see
+    /// `ws::output_mode`). Responds with the diff, the state to diff the
+    /// next frame against and the output sequence the diff is current to.
+    ScreenDiff {
+        prev: Option<ScreenState>,
+        respond_to: oneshot::Sender<ScreenFrame>,
+    },
     SetSessionId {
         session_id: String,
         respond_to: oneshot::Sender<()>,
@@ -97,6 +161,18 @@ pub enum InstanceCommand {
         connection_id: String,
         respond_to: oneshot::Sender<Option<(u16, u16)>>,
     },
+    /// Change how the VirtualTerminal negotiates dimensions.
+    /// Returns Some((rows, cols)) if effective dims changed.
+    SetSizePolicy {
+        policy: SizePolicy,
+        respond_to: oneshot::Sender<Option<(u16, u16)>>,
+    },
+    /// Set the connection whose viewport wins under `SizePolicy::LockHolder`.
+    /// Returns Some((rows, cols)) if effective dims changed.
+    SetSizeOwner {
+        connection_id: Option<String>,
+        respond_to: oneshot::Sender<Option<(u16, u16)>>,
+    },
     Stop {
         respond_to: oneshot::Sender<Result<()>>,
     },
@@ -118,6 +194,9 @@ pub struct InstanceInfo {
     pub session_id: Option<String>,
     /// Current Claude state (for status indicator in sidebar)
     pub claude_state: Option<ClaudeState>,
+    /// Window title set by the program via OSC 0/2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 /// Handle to communicate with an instance actor
@@ -141,11 +220,11 @@ impl InstanceHandle {
         }
     }
 
-    pub async fn write_input(&self, text: &str) -> Result<usize> {
+    pub async fn write_input(&self, data: &[u8]) -> Result<usize> {
         let (tx, rx) = oneshot::channel();
         self.sender
             .send(InstanceCommand::WriteInput {
-                text: text.to_string(),
+                data: data.to_vec(),
                 respond_to: tx,
             })
             .await
@@ -182,6 +261,18 @@ impl InstanceHandle {
     /// `client_rows` is the receiving terminal's row count — used to size
     /// the scrollback flush so the client gets the full history.
     pub async fn get_recent_output(&self, max_bytes: usize, client_rows: u16) -> Vec<String> {
+        self.get_recent_output_with_seq(max_bytes, client_rows)
+            .await
+            .0
+    }
+
+    /// Like `get_recent_output`, plus the output sequence of the last chunk
+    /// the replay includes (0 before any output).
+    pub async fn get_recent_output_with_seq(
+        &self,
+        max_bytes: usize,
+        client_rows: u16,
+    ) -> (Vec<String>, u64) {
         let (tx, rx) = oneshot::channel();
         let _ = self
             .sender
@@ -194,6 +285,47 @@ impl InstanceHandle {
         rx.await.unwrap_or_default()
     }
 
+    /// The terminal as rendered plain text (see `InstanceCommand::GetText`).
+    pub async fn get_text(&self, scrollback: bool) -> Vec<String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(InstanceCommand::GetText {
+                scrollback,
+                respond_to: tx,
+            })
+            .await;
+        rx.await.unwrap_or_default()
+    }
+
+    /// The next screen-diff frame for a client that last received `prev`
+    /// (`None` for a full repaint). `None` if the actor is gone.
+    pub async fn screen_diff(&self, prev: Option<ScreenState>) -> Option<ScreenFrame> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(InstanceCommand::ScreenDiff {
+                prev,
+                respond_to: tx,
+            })
+            .await
+            .ok()?;
+        rx.await.ok()
+    }
+
+    /// Output a client missed since output sequence `seq`, or a full replay
+    /// if it is no longer available. `None` if the actor is gone.
+    pub async fn resume_output(&self, seq: u64) -> Option<OutputResume> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(InstanceCommand::ResumeOutput {
+                seq,
+                respond_to: tx,
+            })
+            .await
+            .ok()?;
+        rx.await.ok()
+    }
+
     pub async fn stop(&self) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.sender
@@ -320,6 +452,40 @@ impl InstanceHandle {
         rx.await.ok().flatten()
     }
 
+    /// Change the dimension negotiation policy and resize the PTY if the
+    /// effective dimensions changed.
+    pub async fn set_size_policy_and_resize(&self, policy: SizePolicy) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(InstanceCommand::SetSizePolicy {
+                policy,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Instance actor is gone"))?;
+        if let Some((eff_rows, eff_cols)) = rx.await.ok().flatten() {
+            self.resize(eff_rows, eff_cols).await?;
+        }
+        Ok(())
+    }
+
+    /// Set the size owner (the terminal lock holder's connection) and
+    /// resize the PTY if the effective dimensions changed.
+    pub async fn set_size_owner_and_resize(&self, connection_id: Option<&str>) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(InstanceCommand::SetSizeOwner {
+                connection_id: connection_id.map(str::to_string),
+                respond_to: tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Instance actor is gone"))?;
+        if let Some((eff_rows, eff_cols)) = rx.await.ok().flatten() {
+            self.resize(eff_rows, eff_cols).await?;
+        }
+        Ok(())
+    }
+
     /// Update viewport and resize PTY if effective dimensions changed.
     pub async fn update_viewport_and_resize(
         &self,
@@ -364,11 +530,16 @@ impl InstanceHandle {
 
 /// Options for spawning a new instance actor
 pub struct SpawnOptions {
+    /// Reuse a previous instance id (e.g. when restoring after a restart).
+    /// None = generate a fresh one.
+    pub id: Option<String>,
     pub name: String,
     pub display_command: String,
     pub actual_command: String,
     pub args: Vec<String>,
     pub working_dir: String,
+    /// Extra environment variables for the process
+    pub env: Vec<(String, String)>,
     pub kind: InstanceKind,
     /// Maximum output ring buffer size in bytes
     pub max_buffer_bytes: usize,
@@ -376,12 +547,16 @@ pub struct SpawnOptions {
     pub scrollback_lines: usize,
     /// Directory to write VT session recordings. None = recording disabled.
     pub vt_record_dir: Option<std::path::PathBuf>,
+    /// Where to periodically snapshot the terminal. None = no persistence.
+    pub scrollback_store: Option<Arc<ScrollbackStore>>,
+    /// Snapshot to restore into the new VirtualTerminal before any output.
+    pub restore: Option<VtSnapshot>,
     /// Process-type-specific driver. Handles state detection and conversation tracking.
     pub driver: Box<dyn ProcessDriver>,
     /// Channel for broadcasting state changes to WebSocket clients.
     pub state_broadcast_tx: Option<StateBroadcast>,
     /// Channel for broadcasting lifecycle events (SessionRotated, etc.).
-    pub lifecycle_tx: Option<broadcast::Sender<crate::ws::ServerMessage>>,
+    pub lifecycle_tx: Option<crate::ws::LifecycleBroadcast>,
     /// Shared session-claiming map (for driver conversation watcher).
     pub claimed_sessions: Arc<RwLock<HashMap<String, String>>>,
     /// Shared first-input data (for session discovery).
@@ -394,7 +569,11 @@ pub struct SpawnOptions {
 
 /// Handle VT commands.
 /// Returns `None` if handled, `Some(cmd)` for PTY/metadata commands.
-fn handle_vt_command(vt: &mut VirtualTerminal, cmd: InstanceCommand) -> Option<InstanceCommand> {
+fn handle_vt_command(
+    vt: &mut VirtualTerminal,
+    output_log: &ReplayWindow<EnrichedOutput>,
+    cmd: InstanceCommand,
+) -> Option<InstanceCommand> {
     match cmd {
         InstanceCommand::UpdateViewport {
             connection_id,
@@ -424,30 +603,160 @@ fn handle_vt_command(vt: &mut VirtualTerminal, cmd: InstanceCommand) -> Option<I
             let _ = respond_to.send(result);
             None
         }
+        InstanceCommand::SetSizePolicy { policy, respond_to } => {
+            let result = vt.set_size_policy(policy);
+            let _ = respond_to.send(result);
+            None
+        }
+        InstanceCommand::SetSizeOwner {
+            connection_id,
+            respond_to,
+        } => {
+            let result = vt.set_size_owner(connection_id.as_deref());
+            let _ = respond_to.send(result);
+            None
+        }
         InstanceCommand::GetRecentOutput {
             max_bytes,
             client_rows,
             respond_to,
         } => {
-            let replay = vt.replay(client_rows);
-            let data = if replay.len() > max_bytes {
-                let mut start = replay.len() - max_bytes;
-                // Skip past UTF-8 continuation bytes (10xxxxxx) so we
-                // don't slice in the middle of a multi-byte character.
-                while start < replay.len() && (replay[start] & 0xC0) == 0x80 {
-                    start += 1;
-                }
-                replay[start..].to_vec()
+            let data = recent_output(vt, max_bytes, client_rows);
+            let _ = respond_to.send((vec![data], output_log.last_seq()));
+            None
+        }
+        InstanceCommand::GetText {
+            scrollback,
+            respond_to,
+        } => {
+            let lines = if scrollback {
+                vt.lines()
             } else {
-                replay
+                vt.screen_lines()
+            };
+            let _ = respond_to.send(lines);
+            None
+        }
+        InstanceCommand::ResumeOutput { seq, respond_to } => {
+            let resume = match output_log.since(seq) {
+                Some(missed) => {
+                    OutputResume::Missed(missed.into_iter().map(|(_, chunk)| chunk).collect())
+                }
+                None => OutputResume::Replay {
+                    data: recent_output(vt, usize::MAX, vt.effective_dims().0),
+                    seq: output_log.last_seq(),
+                },
             };
-            let _ = respond_to.send(vec![String::from_utf8_lossy(&data).to_string()]);
+            let _ = respond_to.send(resume);
+            None
+        }
+        InstanceCommand::ScreenDiff { prev, respond_to } => {
+            let (data, state) = vt.screen_diff(prev.as_ref());
+            let _ = respond_to.send(ScreenFrame {
+                data,
+                state,
+                seq: output_log.last_seq(),
+            });
             None
         }
         other => Some(other),
     }
 }
 
+/// The terminal replay, truncated to its last `max_bytes`.
+fn recent_output(vt: &mut VirtualTerminal, max_bytes: usize, client_rows: u16) -> String {
+    let replay = vt.replay(client_rows);
+    let data = if replay.len() > max_bytes {
+        let mut start = replay.len() - max_bytes;
+        // Skip past UTF-8 continuation bytes (10xxxxxx) so we
+        // don't slice in the middle of a multi-byte character.
+        while start < replay.len() && (replay[start] & 0xC0) == 0x80 {
+            start += 1;
+        }
+        &replay[start..]
+    } else {
+        &replay[..]
+    };
+    String::from_utf8_lossy(data).to_string()
+}
+
+fn new_output_log() -> ReplayWindow<EnrichedOutput> {
+    ReplayWindow::new(OUTPUT_REPLAY_CHUNKS, OUTPUT_REPLAY_BYTES)
+}
+
+/// Number an output chunk and keep it for resuming clients. Returns the
+/// chunk to broadcast.
+fn record_output(
+    log: &mut ReplayWindow<EnrichedOutput>,
+    mut chunk: EnrichedOutput,
+) -> EnrichedOutput {
+    chunk.seq = log.next_seq();
+    let weight = chunk.data.len();
+    log.push(chunk.clone(), weight);
+    chunk
+}
+
+/// Sync the OSC title into `InstanceInfo` and broadcast bells and desktop
+/// notifications the program emitted. Bells within `BELL_DEBOUNCE` of the
+/// last broadcast one are dropped.
+async fn handle_terminal_escapes(
+    vt: &mut VirtualTerminal,
+    info: &RwLock<InstanceInfo>,
+    lifecycle_tx: Option<&crate::ws::LifecycleBroadcast>,
+    last_bell: &mut Option<std::time::Instant>,
+) -> Vec<ClipboardWrite> {
+    let title = vt.title().map(str::to_string);
+    let events = vt.take_events();
+    if events.is_empty() && title == info.read().await.title {
+        return Vec::new();
+    }
+
+    let instance_id = {
+        let mut info = info.write().await;
+        if info.title != title {
+            info.title = title.clone();
+            if let Some(ltx) = lifecycle_tx {
+                let _ = ltx.send(crate::ws::ServerMessage::TerminalTitle {
+                    instance_id: info.id.clone(),
+                    title,
+                });
+            }
+        }
+        info.id.clone()
+    };
+
+    let mut clipboard = Vec::new();
+    for event in events {
+        let (kind, title, body) = match event {
+            TerminalEvent::Bell => {
+                let now = std::time::Instant::now();
+                if last_bell.is_some_and(|prev| now.duration_since(prev) < BELL_DEBOUNCE) {
+                    continue;
+                }
+                *last_bell = Some(now);
+                (TerminalNotificationKind::Bell, None, None)
+            }
+            TerminalEvent::Notification { title, body } => {
+                (TerminalNotificationKind::Notification, title, Some(body))
+            }
+            TerminalEvent::Clipboard { selection, data } => {
+                clipboard.push(ClipboardWrite { selection, data });
+                continue;
+            }
+        };
+        debug!("Instance {} terminal notification: {:?}", instance_id, kind);
+        if let Some(ltx) = lifecycle_tx {
+            let _ = ltx.send(crate::ws::ServerMessage::TerminalNotification {
+                instance_id: instance_id.clone(),
+                kind,
+                title,
+                body,
+            });
+        }
+    }
+    clipboard
+}
+
 /// The instance actor that manages a single PTY session
 struct InstanceActor {
     info: Arc<RwLock<InstanceInfo>>,
@@ -455,22 +764,40 @@ struct InstanceActor {
     receiver: mpsc::Receiver<InstanceCommand>,
     virtual_terminal: VirtualTerminal,
     enriched_tx: broadcast::Sender<EnrichedOutput>,
+    /// Recent output chunks, numbered, for resuming clients
+    output_log: ReplayWindow<EnrichedOutput>,
     recorder: Option<VtRecorder<std::fs::File>>,
     pty_output_rx: broadcast::Receiver<PtyOutput>,
     driver: Box<dyn ProcessDriver>,
     driver_rx: Option<mpsc::Receiver<DriverSignal>>,
     state_broadcast_tx: Option<StateBroadcast>,
-    lifecycle_tx: Option<broadcast::Sender<crate::ws::ServerMessage>>,
+    lifecycle_tx: Option<crate::ws::LifecycleBroadcast>,
     claimed_sessions: Arc<RwLock<HashMap<String, String>>>,
     first_input_data: Arc<RwLock<HashMap<String, FirstInputData>>>,
     pending_attributions: Arc<RwLock<HashMap<String, VecDeque<PendingAttribution>>>>,
     repository: Option<Arc<ConversationRepository>>,
+    scrollback_store: Option<Arc<ScrollbackStore>>,
+    /// `VirtualTerminal::output_generation` at the last saved snapshot
+    snapshot_generation: u64,
+    /// Session id recorded in the last saved snapshot; the snapshot is
+    /// saved again once the session is discovered, so a resume can find it
+    snapshot_session_id: Option<String>,
+    /// The snapshot write in flight, if any
+    snapshot_task: Option<tokio::task::JoinHandle<()>>,
+    /// When the last bell was broadcast (for `BELL_DEBOUNCE`)
+    last_bell: Option<std::time::Instant>,
 }
 
 impl InstanceActor {
     /// Spawn a new instance actor and return its handle
     pub async fn spawn(opts: SpawnOptions) -> Result<InstanceHandle> {
-        let id = Uuid::new_v4().to_string();
+        let id = opts.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        // A restored terminal keeps its old size so scrollback survives until
+        // a client negotiates different dimensions.
+        let (rows, cols) = opts
+            .restore
+            .as_ref()
+            .map_or((24, 80), |snap| (snap.rows, snap.cols));
 
         debug!(
             "Starting instance actor '{}' with command '{}' (actual: '{}', args: {:?}, working_dir: '{}')",
@@ -482,9 +809,9 @@ impl InstanceActor {
             command: opts.actual_command.clone(),
             args: opts.args.clone(),
             working_dir: Some(opts.working_dir.clone()),
-            env: Vec::new(),
-            rows: 24,
-            cols: 80,
+            env: opts.env.clone(),
+            rows,
+            cols,
         };
 
         // Start PTY session using pty_manager
@@ -511,11 +838,20 @@ impl InstanceActor {
             created_at: chrono::Utc::now().to_rfc3339(),
             session_id: None,
             claude_state: Some(ClaudeState::Initializing),
+            title: opts.restore.as_ref().and_then(|snap| snap.title.clone()),
         }));
 
         let (sender, receiver) = mpsc::channel(32);
-        let virtual_terminal =
-            VirtualTerminal::new(24, 80, opts.max_buffer_bytes, opts.scrollback_lines);
+        let mut virtual_terminal =
+            VirtualTerminal::new(rows, cols, opts.max_buffer_bytes, opts.scrollback_lines);
+        if let Some(ref snap) = opts.restore {
+            virtual_terminal.restore(snap);
+            info!(
+                "Restored {} bytes of scrollback into instance '{}'",
+                snap.screen.len(),
+                opts.name
+            );
+        }
         let (enriched_tx, _) = broadcast::channel::<EnrichedOutput>(64);
 
         let recorder = opts.vt_record_dir.as_ref().and_then(|dir| {
@@ -527,7 +863,21 @@ impl InstanceActor {
                 return None;
             }
             let path = dir.join(format!("{id}.vtr"));
-            match VtRecorder::open(&path, 24, 80, opts.scrollback_lines as u32) {
+            // A restored instance reuses its id: keep the earlier recording
+            if path.exists() {
+                let kept = dir.join(format!(
+                    "{id}.{}.vtr",
+                    chrono::Utc::now().timestamp_millis()
+                ));
+                if let Err(e) = std::fs::rename(&path, &kept) {
+                    tracing::warn!(
+                        "VT recording disabled — cannot move aside {}: {e}",
+                        path.display()
+                    );
+                    return None;
+                }
+            }
+            match VtRecorder::open(&path, rows, cols, opts.scrollback_lines as u32) {
                 Ok(r) => {
                     debug!("VT recording → {}", path.display());
                     Some(r)
@@ -550,6 +900,7 @@ impl InstanceActor {
             receiver,
             virtual_terminal,
             enriched_tx,
+            output_log: new_output_log(),
             recorder,
             pty_output_rx,
             driver: opts.driver,
@@ -560,6 +911,11 @@ impl InstanceActor {
             first_input_data: opts.first_input_data,
             pending_attributions: opts.pending_attributions,
             repository: opts.repository,
+            scrollback_store: opts.scrollback_store,
+            snapshot_generation: 0,
+            snapshot_session_id: None,
+            snapshot_task: None,
+            last_bell: None,
         };
 
         // Spawn the actor task
@@ -577,17 +933,85 @@ impl InstanceActor {
         }
         self.virtual_terminal.process_output(&event.data);
         let cursor = self.virtual_terminal.cursor_position();
+        let clipboard = handle_terminal_escapes(
+            &mut self.virtual_terminal,
+            &self.info,
+            self.lifecycle_tx.as_ref(),
+            &mut self.last_bell,
+        )
+        .await;
 
         // Feed driver for state detection
         if self.driver.on_output(&event.data).is_some() {
             self.broadcast_state().await;
         }
 
-        let _ = self.enriched_tx.send(EnrichedOutput {
-            data: event.data,
-            cursor,
-            timestamp: event.timestamp,
-        });
+        let chunk = record_output(
+            &mut self.output_log,
+            EnrichedOutput {
+                seq: 0,
+                data: event.data,
+                cursor,
+                timestamp: event.timestamp,
+                clipboard,
+            },
+        );
+        let _ = self.enriched_tx.send(chunk);
+    }
+
+    /// Snapshot the terminal to the scrollback store if it changed since the
+    /// last save. The file write happens off the actor task; a save is
+    /// skipped while the previous one is still writing.
+    async fn save_scrollback_snapshot(&mut self) {
+        let Some(store) = self.scrollback_store.clone() else {
+            return;
+        };
+        if self
+            .snapshot_task
+            .as_ref()
+            .is_some_and(|t| !t.is_finished())
+        {
+            return;
+        }
+        let info = self.info.read().await;
+        let generation = self.virtual_terminal.output_generation();
+        if generation == self.snapshot_generation && info.session_id == self.snapshot_session_id {
+            return;
+        }
+        self.snapshot_generation = generation;
+        self.snapshot_session_id = info.session_id.clone();
+
+        let snapshot = self.virtual_terminal.snapshot();
+        let meta = ScrollbackMeta {
+            instance_id: info.id.clone(),
+            name: info.name.clone(),
+            command: info.command.clone(),
+            working_dir: info.working_dir.clone(),
+            session_id: info.session_id.clone(),
+            saved_at_ms: snapshot.saved_at_ms,
+        };
+        drop(info);
+
+        self.snapshot_task = Some(tokio::task::spawn_blocking(move || {
+            if let Err(e) = store.save(&meta, &snapshot) {
+                warn!(
+                    "Failed to save scrollback for instance {}: {e}",
+                    meta.instance_id
+                );
+            }
+        }));
+    }
+
+    /// Save the final snapshot and wait for every write to finish, so the
+    /// files are complete (and stay gone if discarded) once the actor exits.
+    async fn flush_scrollback_snapshot(&mut self) {
+        if let Some(task) = self.snapshot_task.take() {
+            let _ = task.await;
+        }
+        self.save_scrollback_snapshot().await;
+        if let Some(task) = self.snapshot_task.take() {
+            let _ = task.await;
+        }
     }
 
     /// Broadcast a state change: update InstanceInfo and send through state_broadcast_tx.
@@ -655,11 +1079,12 @@ impl InstanceActor {
         self.driver_rx = self.driver.start(driver_ctx);
 
         let mut tick_interval = tokio::time::interval(std::time::Duration::from_millis(500));
+        let mut ticks_since_snapshot = 0u32;
 
         loop {
             tokio::select! {
                 Some(cmd) = self.receiver.recv() => {
-                    let cmd = match handle_vt_command(&mut self.virtual_terminal, cmd) {
+                    let cmd = match handle_vt_command(&mut self.virtual_terminal, &self.output_log, cmd) {
                         Some(cmd) => cmd,
                         None => continue,
                     };
@@ -668,18 +1093,22 @@ impl InstanceActor {
                             let _ = respond_to.send(self.info.read().await.clone());
                         }
 
-                        InstanceCommand::WriteInput { text, respond_to } => {
-                            debug!("Writing {} bytes to PTY", text.len());
+                        InstanceCommand::WriteInput { data, respond_to } => {
+                            debug!("Writing {} bytes to PTY", data.len());
                             if let Some(ref mut rec) = self.recorder {
-                                rec.input(text.as_bytes());
+                                rec.input(&data);
                             }
                             // Feed driver for input-based state detection
-                            if self.driver.on_input(&text).is_some() {
+                            if self
+                                .driver
+                                .on_input(&String::from_utf8_lossy(&data))
+                                .is_some()
+                            {
                                 self.broadcast_state().await;
                             }
                             let result = self
                                 .pty
-                                .write_str(&text)
+                                .write(&data)
                                 .await
                                 .map_err(|e| anyhow::anyhow!("{}", e));
                             let _ = respond_to.send(result);
@@ -753,6 +1182,7 @@ impl InstanceActor {
                         InstanceCommand::Stop { respond_to } => {
                             debug!("Stopping instance '{}'", name);
                             self.info.write().await.running = false;
+                            self.flush_scrollback_snapshot().await;
                             let result = self
                                 .pty
                                 .kill(Some("SIGTERM"))
@@ -770,7 +1200,10 @@ impl InstanceActor {
                         Err(broadcast::error::RecvError::Lagged(n)) => {
                             warn!("PTY output lagged by {n} messages");
                         }
-                        Err(broadcast::error::RecvError::Closed) => break,
+                        Err(broadcast::error::RecvError::Closed) => {
+                            self.flush_scrollback_snapshot().await;
+                            break;
+                        }
                     }
                 }
                 signal = async {
@@ -795,6 +1228,11 @@ impl InstanceActor {
                     if self.driver.tick().is_some() {
                         self.broadcast_state().await;
                     }
+                    ticks_since_snapshot += 1;
+                    if ticks_since_snapshot >= SCROLLBACK_SNAPSHOT_TICKS {
+                        ticks_since_snapshot = 0;
+                        self.save_scrollback_snapshot().await;
+                    }
                 }
             }
         }
@@ -849,6 +1287,7 @@ impl InstanceHandle {
             created_at: "2024-01-01T00:00:00Z".to_string(),
             session_id: None,
             claude_state: None,
+            title: None,
         }));
         let (sender, mut receiver) = mpsc::channel(32);
         let mut vt = VirtualTerminal::new(rows, cols, max_delta_bytes, scrollback_lines);
@@ -859,11 +1298,12 @@ impl InstanceHandle {
             Arc::new(RwLock::new(Vec::new()));
         let convo_turns_actor = conversation_turns.clone();
         let (output_tx, mut output_rx) = mpsc::channel::<Vec<u8>>(64);
+        let mut output_log = new_output_log();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     Some(cmd) = receiver.recv() => {
-                        let cmd = match handle_vt_command(&mut vt, cmd) {
+                        let cmd = match handle_vt_command(&mut vt, &output_log, cmd) {
                             Some(cmd) => cmd,
                             None => continue,
                         };
@@ -890,9 +1330,9 @@ impl InstanceHandle {
                                 let sid = info_actor.read().await.session_id.clone();
                                 let _ = respond_to.send(sid);
                             }
-                            InstanceCommand::WriteInput { text, respond_to } => {
+                            InstanceCommand::WriteInput { data, respond_to } => {
                                 // Accept writes silently (no real PTY in test)
-                                let _ = respond_to.send(Ok(text.len()));
+                                let _ = respond_to.send(Ok(data.len()));
                             }
                             InstanceCommand::GetConversationSnapshot { respond_to } => {
                                 let turns = convo_turns_actor.read().await.clone();
@@ -907,11 +1347,14 @@ impl InstanceHandle {
                     Some(data) = output_rx.recv() => {
                         vt.process_output(&data);
                         let cursor = vt.cursor_position();
-                        let _ = enriched_tx_actor.send(EnrichedOutput {
+                        let chunk = record_output(&mut output_log, EnrichedOutput {
+                            seq: 0,
                             data,
                             cursor,
                             timestamp: 0,
+                            clipboard: Vec::new(),
                         });
+                        let _ = enriched_tx_actor.send(chunk);
                     }
                     else => break,
                 }
@@ -947,7 +1390,7 @@ impl InstanceHandle {
         driver: Box<dyn ProcessDriver>,
         signal_rx: Option<mpsc::Receiver<DriverSignal>>,
         state_broadcast_tx: Option<StateBroadcast>,
-        lifecycle_tx: Option<broadcast::Sender<crate::ws::ServerMessage>>,
+        lifecycle_tx: Option<crate::ws::LifecycleBroadcast>,
     ) -> (Self, mpsc::Sender<Vec<u8>>) {
         let info = Arc::new(RwLock::new(InstanceInfo {
             id: "test-instance".to_string(),
@@ -960,6 +1403,7 @@ impl InstanceHandle {
             created_at: "2024-01-01T00:00:00Z".to_string(),
             session_id: None,
             claude_state: Some(ClaudeState::Initializing),
+            title: None,
         }));
         let (sender, mut receiver) = mpsc::channel(32);
         let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
@@ -967,6 +1411,7 @@ impl InstanceHandle {
         let enriched_tx_actor = enriched_tx.clone();
         let info_actor = info.clone();
         let (output_tx, mut output_rx) = mpsc::channel::<Vec<u8>>(64);
+        let mut output_log = new_output_log();
         let mut driver = driver;
         let mut signal_rx = signal_rx;
         let state_broadcast_tx = state_broadcast_tx;
@@ -974,17 +1419,18 @@ impl InstanceHandle {
 
         tokio::spawn(async move {
             let mut tick_interval = tokio::time::interval(std::time::Duration::from_millis(500));
+            let mut last_bell = None;
 
             loop {
                 tokio::select! {
                     Some(cmd) = receiver.recv() => {
-                        let cmd = match handle_vt_command(&mut vt, cmd) {
+                        let cmd = match handle_vt_command(&mut vt, &output_log, cmd) {
                             Some(cmd) => cmd,
                             None => continue,
                         };
                         match cmd {
-                            InstanceCommand::WriteInput { text, respond_to } => {
-                                if let Some(new_state) = driver.on_input(&text) {
+                            InstanceCommand::WriteInput { data, respond_to } => {
+                                if let Some(new_state) = driver.on_input(&String::from_utf8_lossy(&data)) {
                                     let _ = new_state; // consumed
                                     // Broadcast state
                                     let claude_state = driver.claude_state()
@@ -997,7 +1443,7 @@ impl InstanceHandle {
                                         let _ = tx.send((instance_id, claude_state, terminal_stale));
                                     }
                                 }
-                                let _ = respond_to.send(Ok(text.len()));
+                                let _ = respond_to.send(Ok(data.len()));
                             }
                             InstanceCommand::GetConversationSnapshot { respond_to } => {
                                 let snapshot = driver.conversation_snapshot().to_vec();
@@ -1035,6 +1481,13 @@ impl InstanceHandle {
                     Some(data) = output_rx.recv() => {
                         vt.process_output(&data);
                         let cursor = vt.cursor_position();
+                        let clipboard = handle_terminal_escapes(
+                            &mut vt,
+                            &info_actor,
+                            lifecycle_tx.as_ref(),
+                            &mut last_bell,
+                        )
+                        .await;
                         // Feed driver
                         if let Some(_new_state) = driver.on_output(&data) {
                             let claude_state = driver.claude_state()
@@ -1047,11 +1500,14 @@ impl InstanceHandle {
                                 let _ = tx.send((instance_id, claude_state, terminal_stale));
                             }
                         }
-                        let _ = enriched_tx_actor.send(EnrichedOutput {
+                        let chunk = record_output(&mut output_log, EnrichedOutput {
+                            seq: 0,
                             data,
                             cursor,
                             timestamp: 0,
+                            clipboard,
                         });
+                        let _ = enriched_tx_actor.send(chunk);
                     }
                     signal = async {
                         match signal_rx {
@@ -1223,6 +1679,36 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_size_policy_and_owner() {
+        let (handle, _output_tx) = InstanceHandle::spawn_test(24, 80, 4096);
+
+        handle
+            .update_viewport("conn-1", 40, 120, ClientType::Web)
+            .await;
+        handle
+            .update_viewport("conn-2", 24, 80, ClientType::Terminal)
+            .await;
+        assert!(
+            handle
+                .set_size_policy_and_resize(SizePolicy::LockHolder)
+                .await
+                .is_ok()
+        );
+        assert!(
+            handle
+                .set_size_owner_and_resize(Some("conn-1"))
+                .await
+                .is_ok()
+        );
+
+        // Owner already wins: re-registering its viewport changes nothing
+        let result = handle
+            .update_viewport("conn-1", 40, 120, ClientType::Web)
+            .await;
+        assert_eq!(result, None);
+    }
+
     #[tokio::test]
     async fn test_remove_client_and_resize_no_change() {
         let (handle, _output_tx) = InstanceHandle::spawn_test(24, 80, 4096);
@@ -1596,7 +2082,7 @@ mod tests {
         let (handle, _output_tx) =
             InstanceHandle::spawn_test_with_driver(Box::new(mock), None, None, None);
 
-        handle.write_input("world").await.unwrap();
+        handle.write_input(b"world").await.unwrap();
         tokio::task::yield_now().await;
 
         let calls = calls.lock().unwrap();
@@ -1632,6 +2118,75 @@ mod tests {
         drop(handle);
     }
 
+    #[tokio::test]
+    async fn test_terminal_title_and_notifications_broadcast() {
+        let lifecycle_tx = crate::ws::LifecycleBroadcast::new(16);
+        let mut lifecycle_rx = lifecycle_tx.subscribe();
+        let (handle, output_tx) = InstanceHandle::spawn_test_with_driver(
+            Box::new(MockDriver::new()),
+            None,
+            None,
+            Some(lifecycle_tx),
+        );
+
+        InstanceHandle::inject_output(&output_tx, b"\x1b]2;make test\x07").await;
+        // Two bells inside the debounce window are reported once
+        InstanceHandle::inject_output(&output_tx, b"\x07").await;
+        InstanceHandle::inject_output(&output_tx, b"\x07").await;
+        InstanceHandle::inject_output(&output_tx, b"\x1b]777;notify;CI;green\x07").await;
+
+        let mut received = Vec::new();
+        while let Ok(Ok((_, msg))) =
+            tokio::time::timeout(std::time::Duration::from_millis(100), lifecycle_rx.recv()).await
+        {
+            received.push(msg);
+        }
+        assert_eq!(received.len(), 3, "got: {:?}", received);
+        assert!(matches!(
+            &received[0],
+            crate::ws::ServerMessage::TerminalTitle { title: Some(t), .. } if t == "make test"
+        ));
+        assert!(matches!(
+            &received[1],
+            crate::ws::ServerMessage::TerminalNotification {
+                kind: TerminalNotificationKind::Bell,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &received[2],
+            crate::ws::ServerMessage::TerminalNotification {
+                kind: TerminalNotificationKind::Notification,
+                title: Some(t),
+                body: Some(b),
+                ..
+            } if t == "CI" && b == "green"
+        ));
+
+        assert_eq!(handle.get_info().await.title.as_deref(), Some("make test"));
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_writes_ride_along_with_output() {
+        let (handle, output_tx) =
+            InstanceHandle::spawn_test_with_driver(Box::new(MockDriver::new()), None, None, None);
+        let mut rx = handle.subscribe_output().await.unwrap();
+
+        InstanceHandle::inject_output(&output_tx, b"plain").await;
+        InstanceHandle::inject_output(&output_tx, b"\x1b]52;c;aGk=\x07").await;
+
+        let first = rx.recv().await.unwrap();
+        assert!(first.clipboard.is_empty());
+        let second = rx.recv().await.unwrap();
+        assert_eq!(
+            second.clipboard,
+            vec![ClipboardWrite {
+                selection: "c".to_string(),
+                data: "aGk=".into(),
+            }]
+        );
+    }
+
     #[tokio::test]
     async fn test_driver_signal_dispatched() {
         let mock = MockDriver::new();
@@ -1690,7 +2245,8 @@ mod tests {
             session_id: Some("sess-old".into()),
         });
         let (signal_tx, signal_rx) = mpsc::channel(16);
-        let (lifecycle_tx, mut lifecycle_rx) = broadcast::channel(16);
+        let lifecycle_tx = crate::ws::LifecycleBroadcast::new(16);
+        let mut lifecycle_rx = lifecycle_tx.subscribe();
         let on_signal_effect = Arc::clone(&mock.on_signal_effect);
         let (handle, _output_tx) = InstanceHandle::spawn_test_with_driver(
             Box::new(mock),
@@ -1721,7 +2277,7 @@ mod tests {
         let msg =
             tokio::time::timeout(std::time::Duration::from_millis(100), lifecycle_rx.recv()).await;
         assert!(msg.is_ok(), "should receive lifecycle broadcast");
-        match msg.unwrap().unwrap() {
+        match msg.unwrap().unwrap().1 {
             crate::ws::ServerMessage::SessionRotated {
                 instance_id,
                 from_session,
@@ -1745,7 +2301,8 @@ mod tests {
             session_id: Some("sess-first".into()),
         });
         let (signal_tx, signal_rx) = mpsc::channel(16);
-        let (lifecycle_tx, mut lifecycle_rx) = broadcast::channel(16);
+        let lifecycle_tx = crate::ws::LifecycleBroadcast::new(16);
+        let mut lifecycle_rx = lifecycle_tx.subscribe();
         let (handle, _output_tx) = InstanceHandle::spawn_test_with_driver(
             Box::new(mock),
             Some(signal_rx),
@@ -1826,7 +2383,7 @@ mod tests {
         let (handle, _output_tx) =
             InstanceHandle::spawn_test_with_driver(Box::new(mock), None, Some(state_tx), None);
 
-        handle.write_input("go").await.unwrap();
+        handle.write_input(b"go").await.unwrap();
         tokio::task::yield_now().await;
 
         let result =
@@ -2025,7 +2582,7 @@ mod tests {
         // Now send input through the pipeline
         let ctx = InputContext {
             instance_id: "test-instance".to_string(),
-            data: "hello\r".to_string(),
+            data: "hello\r".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -2062,7 +2619,7 @@ mod tests {
         for ch in "hello".chars() {
             let ctx = InputContext {
                 instance_id: "test-instance".to_string(),
-                data: ch.to_string(),
+                data: ch.to_string().into_bytes(),
                 connection_id: "test-conn".to_string(),
                 user: None,
                 task_id: None,
@@ -2071,7 +2628,7 @@ mod tests {
         }
         let ctx = InputContext {
             instance_id: "test-instance".to_string(),
-            data: "\r".to_string(),
+            data: "\r".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -2,14 +2,17 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
+use tokio::sync::RwLock;
 
 use tracing::{debug, info, warn};
 
 use crate::inference::ClaudeState;
 use crate::instance_actor::{InstanceHandle, SpawnOptions, create_instance};
+use crate::models::InstanceRole;
 use crate::process_driver::ProcessDriver;
 use crate::repository::ConversationRepository;
+use crate::scrollback::{ScrollbackMeta, ScrollbackStore};
+use crate::virtual_terminal::VtSnapshot;
 use crate::ws::{FirstInputData, PendingAttribution, StateBroadcast};
 
 /// Whether an instance is a structured conversation provider (e.g. Claude, Codex)
@@ -48,6 +51,27 @@ impl InstanceKind {
     }
 }
 
+/// The session a Claude command line resumes (`--resume <id>`, `-r <id>`
+/// or `--resume=<id>`), if any.
+fn resumed_session(command_line: &str) -> Option<String> {
+    if !command_line.contains("claude") {
+        return None;
+    }
+    let mut words = command_line.split_whitespace();
+    while let Some(word) = words.next() {
+        if let Some(id) = word.strip_prefix("--resume=") {
+            return Some(id.to_string());
+        }
+        if word == "--resume" || word == "-r" {
+            return words
+                .next()
+                .filter(|id| !id.starts_with('-'))
+                .map(str::to_string);
+        }
+    }
+    None
+}
+
 // This is for API compatibility - we'll remove the fake "port" concept later
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeInstance {
@@ -68,6 +92,13 @@ pub struct ClaudeInstance {
     /// Unix timestamp (seconds) when the current state was entered
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state_entered_at: Option<i64>,
+    /// Window title set by the program via OSC 0/2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The requesting user's role, on per-user responses (instance list,
+    /// `GET /api/instances/{id}`); absent in broadcasts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<InstanceRole>,
 }
 
 pub struct InstanceManager {
@@ -78,6 +109,7 @@ pub struct InstanceManager {
     max_buffer_bytes: usize,
     scrollback_lines: usize,
     vt_record_dir: Option<std::path::PathBuf>,
+    scrollback_store: Option<Arc<ScrollbackStore>>,
 }
 
 impl InstanceManager {
@@ -87,6 +119,7 @@ impl InstanceManager {
         max_buffer_bytes: usize,
         scrollback_lines: usize,
         vt_record_dir: Option<std::path::PathBuf>,
+        scrollback_dir: Option<std::path::PathBuf>,
     ) -> Self {
         let base_directory = dirs::home_dir()
             .unwrap_or_else(|| std::path::PathBuf::from("/tmp"))
@@ -101,6 +134,7 @@ impl InstanceManager {
             max_buffer_bytes,
             scrollback_lines,
             vt_record_dir,
+            scrollback_store: scrollback_dir.map(|dir| Arc::new(ScrollbackStore::open(dir))),
         }
     }
 
@@ -146,21 +180,56 @@ impl InstanceManager {
         )
     }
 
+    /// Persisted scrollback for a new instance: the snapshot `restore_from`
+    /// names (by instance id or session id), or else the one saved under the
+    /// Claude session `command` resumes. Callers check the caller may see it
+    /// before passing it to [`Self::create`].
+    pub fn find_scrollback(
+        &self,
+        restore_from: Option<&str>,
+        command: Option<&str>,
+    ) -> Option<(ScrollbackMeta, VtSnapshot)> {
+        let store = self.scrollback_store.as_ref()?;
+        match restore_from {
+            Some(key) => {
+                let found = store.load(key);
+                if found.is_none() {
+                    warn!("No scrollback snapshot found for '{}'", key);
+                }
+                found
+            }
+            None => command
+                .and_then(resumed_session)
+                .and_then(|session| store.load(&session)),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         name: Option<String>,
         working_dir: Option<String>,
         command: Option<String>,
+        env: Vec<(String, String)>,
         driver: Box<dyn ProcessDriver>,
         state_broadcast_tx: Option<StateBroadcast>,
-        lifecycle_tx: Option<broadcast::Sender<crate::ws::ServerMessage>>,
+        lifecycle_tx: Option<crate::ws::LifecycleBroadcast>,
         claimed_sessions: Arc<RwLock<HashMap<String, String>>>,
         first_input_data: Arc<RwLock<HashMap<String, FirstInputData>>>,
         pending_attributions: Arc<RwLock<HashMap<String, VecDeque<PendingAttribution>>>>,
         repository: Option<Arc<ConversationRepository>>,
         kind: Option<InstanceKind>,
+        restored: Option<(ScrollbackMeta, VtSnapshot)>,
     ) -> Result<ClaudeInstance> {
+        // The old id is reused when it isn't live, so links to it keep working
+        let (id, restore) = match restored {
+            Some((meta, snapshot)) => {
+                let id_in_use = self.instances.read().await.contains_key(&meta.instance_id);
+                ((!id_in_use).then_some(meta.instance_id), Some(snapshot))
+            }
+            None => (None, None),
+        };
+
         // Generate unique name if not provided
         let name = if let Some(provided_name) = name {
             // Add provided name to used names
@@ -219,15 +288,19 @@ impl InstanceManager {
 
         // Create the instance actor - pass both display command and actual command
         let handle = create_instance(SpawnOptions {
+            id,
             name: name.clone(),
             display_command: command_line.clone(),
             actual_command: program,
             args,
             working_dir: working_dir.clone(),
+            env,
             kind: kind.clone(),
             max_buffer_bytes: self.max_buffer_bytes,
             scrollback_lines: self.scrollback_lines,
             vt_record_dir: self.vt_record_dir.clone(),
+            scrollback_store: self.scrollback_store.clone(),
+            restore,
             driver,
             state_broadcast_tx,
             lifecycle_tx,
@@ -261,6 +334,8 @@ impl InstanceManager {
             session_id: None, // Will be detected when conversation is accessed
             claude_state: None,
             state_entered_at: None,
+            title: info.title,
+            role: None,
         })
     }
 
@@ -283,6 +358,8 @@ impl InstanceManager {
                 session_id: info.session_id,
                 claude_state: info.claude_state,
                 state_entered_at: None, // Populated by handler from GlobalStateManager
+                title: info.title,
+                role: None,
             });
         }
 
@@ -307,12 +384,22 @@ impl InstanceManager {
                 session_id: info.session_id,
                 claude_state: info.claude_state,
                 state_entered_at: None, // Populated by handler from GlobalStateManager
+                title: info.title,
+                role: None,
             })
         } else {
             None
         }
     }
 
+    /// Path of the VT recording for an instance, if recording is enabled.
+    /// The file may not exist (e.g. the recorder failed to open).
+    pub fn recording_path(&self, id: &str) -> Option<std::path::PathBuf> {
+        self.vt_record_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{id}.vtr")))
+    }
+
     pub async fn get_handle(&self, id: &str) -> Option<InstanceHandle> {
         let instances = self.instances.read().await;
         instances.get(id).cloned()
@@ -336,7 +423,8 @@ impl InstanceManager {
             // Remove name from used names
             self.used_names.write().await.remove(&info.name);
 
-            // Stop the actor
+            // Stop the actor. Its final scrollback snapshot is written by
+            // the time this returns, so the next daemon can restore it.
             if let Err(e) = handle.stop().await {
                 warn!("Error stopping instance: {}", e);
             }
@@ -347,6 +435,14 @@ impl InstanceManager {
         }
     }
 
+    /// Delete the persisted scrollback for an instance the user removed.
+    /// Call after `stop`, which waits for the actor's last snapshot write.
+    pub fn discard_scrollback(&self, id: &str) {
+        if let Some(ref store) = self.scrollback_store {
+            store.remove(id);
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn cleanup(&self) {
         debug!("Cleaning up all instances");
@@ -373,7 +469,66 @@ mod tests {
     use std::collections::HashSet;
 
     fn test_manager() -> InstanceManager {
-        InstanceManager::new("claude".to_string(), 0, 1024 * 1024, 0, None)
+        InstanceManager::new("claude".to_string(), 0, 1024 * 1024, 0, None, None)
+    }
+
+    #[test]
+    fn resumed_session_comes_from_claude_resume_flags() {
+        assert_eq!(
+            resumed_session("claude --resume abc-123").as_deref(),
+            Some("abc-123")
+        );
+        assert_eq!(
+            resumed_session("/usr/bin/claude -r abc-123 --verbose").as_deref(),
+            Some("abc-123")
+        );
+        assert_eq!(
+            resumed_session("pnpm exec claude --resume=abc-123").as_deref(),
+            Some("abc-123")
+        );
+        // `--resume` alone opens Claude's picker; there's no session yet
+        assert_eq!(resumed_session("claude --resume --verbose"), None);
+        assert_eq!(resumed_session("claude"), None);
+        assert_eq!(resumed_session("ls -r abc"), None);
+    }
+
+    #[tokio::test]
+    async fn restored_instance_reports_its_title() {
+        use crate::virtual_terminal::VirtualTerminal;
+
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(b"\x1b]0;old title\x07$ ");
+        let meta = ScrollbackMeta {
+            instance_id: "prev-instance".to_string(),
+            name: "old".to_string(),
+            command: "bash".to_string(),
+            working_dir: "/tmp".to_string(),
+            session_id: None,
+            saved_at_ms: 1,
+        };
+
+        let mgr = test_manager();
+        let instance = mgr
+            .create(
+                None,
+                Some("/tmp".to_string()),
+                Some("sleep 5".to_string()),
+                Vec::new(),
+                Box::new(crate::process_driver::ShellDriver),
+                None,
+                None,
+                Arc::new(RwLock::new(HashMap::new())),
+                Arc::new(RwLock::new(HashMap::new())),
+                Arc::new(RwLock::new(HashMap::new())),
+                None,
+                None,
+                Some((meta, vt.snapshot())),
+            )
+            .await
+            .unwrap();
+        assert_eq!(instance.id, "prev-instance");
+        assert_eq!(instance.title.as_deref(), Some("old title"));
+        mgr.stop(&instance.id).await;
     }
 
     #[test]
@@ -538,6 +693,8 @@ mod tests {
             session_id: Some("sess-abc".to_string()),
             claude_state: None,
             state_entered_at: None,
+            title: None,
+            role: None,
         };
         let json = serde_json::to_value(&inst).unwrap();
         assert_eq!(json["id"], "inst-1");
@@ -567,6 +724,8 @@ mod tests {
             session_id: None,
             claude_state: None,
             state_entered_at: None,
+            title: None,
+            role: None,
         };
         let json = serde_json::to_value(&inst).unwrap();
         assert!(json["custom_name"].is_null());
@@ -18,6 +18,7 @@ pub mod onboarding;
 pub mod persistence;
 pub mod process_driver;
 pub mod repository;
+pub mod scrollback;
 pub mod server;
 pub mod virtual_terminal;
 pub mod ws;
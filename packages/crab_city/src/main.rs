@@ -26,6 +26,11 @@ struct Cli {
     /// Custom data directory (defaults to ~/.crabcity)
     #[arg(long, global = true)]
     data_dir: Option<PathBuf>,
+
+    /// Use the Crab City server at this URL instead of the local daemon
+    /// (log in first with `crab login`)
+    #[arg(long, global = true, env = "CRAB_REMOTE", value_name = "URL")]
+    remote: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -33,12 +38,35 @@ enum Commands {
     /// Run the daemon server in the foreground
     Server(ServerArgs),
 
+    /// Create an instance with a name, directory, command or environment
+    New(NewArgs),
+
     /// Attach to an existing instance
     Attach(AttachArgs),
 
     /// List running instances
     List(ListArgs),
 
+    /// Dashboard of every instance's state, inbox and tasks
+    #[command(alias = "dashboard")]
+    Fleet,
+
+    /// Send a prompt to an instance without attaching
+    Send(SendArgs),
+
+    /// Print a session's terminal as plain text, or follow its output
+    #[command(alias = "tail")]
+    Logs(LogsArgs),
+
+    /// Full-text search over conversation history
+    Search(SearchArgs),
+
+    /// Print a conversation as formatted text
+    Show(ShowArgs),
+
+    /// List, file, edit and dispatch tasks on the task board
+    Tasks(TasksArgs),
+
     /// Kill a specific session
     Kill(KillArgs),
 
@@ -47,6 +75,15 @@ enum Commands {
 
     /// Manage authentication
     Auth(AuthArgs),
+
+    /// Log in to a remote Crab City server and save a token for it
+    Login(LoginArgs),
+
+    /// Forget the saved token for a remote server
+    Logout(LogoutArgs),
+
+    /// List the TUI key bindings and where they come from
+    Keys,
 }
 
 #[derive(Parser)]
@@ -94,8 +131,51 @@ struct ServerArgs {
 
 #[derive(Parser)]
 struct AttachArgs {
-    /// Instance name, ID, or ID prefix to attach to (default: most recent)
-    target: Option<String>,
+    /// Instance name, ID, or ID prefix to attach to (default: picker). Give
+    /// two to four to tile them side by side
+    #[arg(num_args = 0..=cli::tiled::MAX_PANES)]
+    targets: Vec<String>,
+
+    /// Receive coalesced screen updates instead of raw output (for slow or
+    /// remote links)
+    #[arg(long)]
+    screen_diff: bool,
+
+    /// Screen updates per second with --screen-diff (server default: 10)
+    #[arg(long, requires = "screen_diff")]
+    max_fps: Option<u32>,
+
+    /// Watch read-only: don't resize the terminal for others and don't
+    /// send keystrokes
+    #[arg(long)]
+    spectate: bool,
+}
+
+#[derive(Parser)]
+struct NewArgs {
+    /// Name for the instance (default: a random one)
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Working directory (default: current directory)
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+
+    /// Command to run (default: the daemon's default command)
+    #[arg(short, long)]
+    cmd: Option<String>,
+
+    /// Environment variable for the process, as KEY=VALUE (repeatable)
+    #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = cli::parse_env_var)]
+    env: Vec<(String, String)>,
+
+    /// Attach after creating (default when run from a terminal)
+    #[arg(long, conflicts_with = "detach")]
+    attach: bool,
+
+    /// Print the new instance's id and name instead of attaching
+    #[arg(long)]
+    detach: bool,
 }
 
 #[derive(Parser)]
@@ -105,6 +185,218 @@ struct ListArgs {
     json: bool,
 }
 
+#[derive(Parser)]
+struct SendArgs {
+    /// Instance name, ID, or ID prefix to send to
+    target: String,
+
+    /// Prompt to type (default: read from stdin)
+    prompt: Option<String>,
+
+    /// Wait until Claude has finished responding
+    #[arg(long)]
+    wait: bool,
+
+    /// Seconds to wait before giving up (with --wait or --print-reply)
+    #[arg(long, default_value = "600")]
+    timeout: u64,
+
+    /// Print Claude's reply when it's done (implies --wait)
+    #[arg(long)]
+    print_reply: bool,
+}
+
+#[derive(Parser)]
+struct LogsArgs {
+    /// Instance name, ID, or ID prefix to read
+    target: String,
+
+    /// Keep printing new output until the session exits
+    #[arg(short, long)]
+    follow: bool,
+
+    /// Only the visible screen
+    #[arg(long, conflicts_with = "scrollback")]
+    screen: bool,
+
+    /// The scrollback followed by the visible screen (default)
+    #[arg(long)]
+    scrollback: bool,
+
+    /// Remove escape sequences from followed output
+    #[arg(long)]
+    strip_ansi: bool,
+}
+
+#[derive(Parser)]
+struct SearchArgs {
+    /// Words to search for
+    #[arg(required = true, num_args = 1..)]
+    query: Vec<String>,
+
+    /// Only match messages from this author
+    #[arg(long, value_enum)]
+    role: Option<cli::search::SearchRole>,
+
+    /// Only match messages since an age (7d, 12h), a date or an RFC 3339 time
+    #[arg(long, value_parser = cli::search::parse_since)]
+    since: Option<i64>,
+
+    /// Only conversations from this instance, project name or directory
+    #[arg(long)]
+    project: Option<String>,
+
+    /// Most conversations to show (1–100)
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: u32,
+
+    /// Output the raw results as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct ShowArgs {
+    /// Conversation id, as printed by `crab search`
+    conversation: String,
+
+    /// Print straight to stdout instead of through $PAGER
+    #[arg(long)]
+    no_pager: bool,
+}
+
+#[derive(Parser)]
+struct TasksArgs {
+    #[command(subcommand)]
+    command: TasksCommands,
+}
+
+#[derive(Subcommand)]
+enum TasksCommands {
+    /// List tasks (default: pending and in progress)
+    List(TaskListArgs),
+    /// File a task; a title of `-` reads it from stdin (first line is the title)
+    Add(TaskAddArgs),
+    /// Change a task's title, body, status, priority or instance
+    Edit(TaskEditArgs),
+    /// Mark tasks completed
+    Done(TaskDoneArgs),
+    /// Type a task into its instance and mark it in progress
+    Send(TaskSendArgs),
+    /// Add tags to a task, or remove them
+    Tag(TaskTagArgs),
+}
+
+#[derive(Parser)]
+struct TaskListArgs {
+    /// Only tasks with this status
+    #[arg(long, value_enum, conflicts_with = "all")]
+    status: Option<cli::tasks::TaskStatus>,
+
+    /// Include completed and cancelled tasks
+    #[arg(short, long)]
+    all: bool,
+
+    /// Only tasks assigned to this instance (name, ID, or ID prefix)
+    #[arg(short, long)]
+    instance: Option<String>,
+
+    /// Only tasks with this tag
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only tasks whose title or body contains this text
+    #[arg(long)]
+    search: Option<String>,
+
+    /// Most tasks to fetch
+    #[arg(short = 'n', long, default_value_t = 100)]
+    limit: u32,
+
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct TaskAddArgs {
+    /// Task title, or `-` to read the title and body from stdin
+    title: String,
+
+    /// Task body (`-` reads it from stdin)
+    #[arg(short, long)]
+    body: Option<String>,
+
+    /// Assign to an instance (name, ID, or ID prefix)
+    #[arg(short, long)]
+    instance: Option<String>,
+
+    /// Priority (higher is more urgent)
+    #[arg(short, long)]
+    priority: Option<i32>,
+
+    /// Tag to add (repeatable)
+    #[arg(short, long = "tag")]
+    tags: Vec<String>,
+}
+
+#[derive(Parser)]
+struct TaskEditArgs {
+    /// Task ID
+    id: i64,
+
+    /// New title
+    #[arg(long)]
+    title: Option<String>,
+
+    /// New body (`-` reads it from stdin)
+    #[arg(short, long)]
+    body: Option<String>,
+
+    /// New status
+    #[arg(short, long, value_enum)]
+    status: Option<cli::tasks::TaskStatus>,
+
+    /// New priority
+    #[arg(short, long)]
+    priority: Option<i32>,
+
+    /// Assign to an instance (name, ID, or ID prefix)
+    #[arg(short, long)]
+    instance: Option<String>,
+}
+
+#[derive(Parser)]
+struct TaskDoneArgs {
+    /// Task IDs
+    #[arg(required = true, num_args = 1..)]
+    ids: Vec<i64>,
+}
+
+#[derive(Parser)]
+struct TaskSendArgs {
+    /// Task ID
+    id: i64,
+
+    /// Assign to this instance first (name, ID, or ID prefix)
+    #[arg(short, long)]
+    instance: Option<String>,
+}
+
+#[derive(Parser)]
+struct TaskTagArgs {
+    /// Task ID
+    id: i64,
+
+    /// Tags to add or remove
+    #[arg(required = true, num_args = 1..)]
+    tags: Vec<String>,
+
+    /// Remove the tags instead of adding them
+    #[arg(short, long)]
+    remove: bool,
+}
+
 #[derive(Parser)]
 struct KillArgs {
     /// Instance name, ID, or ID prefix to kill
@@ -118,6 +410,22 @@ struct KillServerArgs {
     force: bool,
 }
 
+#[derive(Parser)]
+struct LoginArgs {
+    /// Server URL, e.g. https://crab.example.com
+    url: String,
+
+    /// Username (default: prompt)
+    #[arg(short, long)]
+    username: Option<String>,
+}
+
+#[derive(Parser)]
+struct LogoutArgs {
+    /// Server URL given to `crab login`
+    url: String,
+}
+
 #[derive(Parser)]
 struct AuthArgs {
     #[command(subcommand)]
@@ -138,7 +446,8 @@ enum AuthCommands {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let config = CrabCityConfig::new(cli.data_dir.clone())?;
+    let mut config = CrabCityConfig::new(cli.data_dir.clone())?;
+    config.remote = cli.remote.clone().filter(|r| !r.trim().is_empty());
 
     // Install crash diagnostics (terminal restore + crash report file)
     install_panic_hook(config.logs_dir.clone());
@@ -153,8 +462,109 @@ async fn main() -> Result<()> {
             // Bare `crab`: create new instance in cwd and attach
             cli::default_command(&config).await
         }
-        Some(Commands::Attach(args)) => cli::attach_command(&config, args.target).await,
+        Some(Commands::New(args)) => {
+            let options = cli::NewOptions {
+                name: args.name,
+                dir: args.dir,
+                command: args.cmd,
+                env: args.env,
+                attach: match (args.attach, args.detach) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+            };
+            cli::new_command(&config, options).await
+        }
+        Some(Commands::Attach(args)) => {
+            let options = cli::attach::AttachOptions {
+                screen_diff: args.screen_diff,
+                max_fps: args.max_fps,
+                spectate: args.spectate,
+                keys: cli::keys::load(&config)?,
+            };
+            cli::attach_command(&config, args.targets, options).await
+        }
         Some(Commands::List(args)) => cli::list_command(&config, args.json).await,
+        Some(Commands::Fleet) => {
+            let options = cli::attach::AttachOptions {
+                keys: cli::keys::load(&config)?,
+                ..Default::default()
+            };
+            cli::fleet_command(&config, options).await
+        }
+        Some(Commands::Send(args)) => {
+            let options = cli::send::SendOptions {
+                wait: args.wait,
+                timeout: std::time::Duration::from_secs(args.timeout),
+                print_reply: args.print_reply,
+            };
+            cli::send::send_command(&config, &args.target, args.prompt, options).await
+        }
+        Some(Commands::Logs(args)) => {
+            let options = cli::logs::LogsOptions {
+                follow: args.follow,
+                scrollback: !args.screen,
+                strip_ansi: args.strip_ansi,
+            };
+            cli::logs::logs_command(&config, &args.target, options).await
+        }
+        Some(Commands::Search(args)) => {
+            let options = cli::search::SearchOptions {
+                role: args.role,
+                since: args.since,
+                project: args.project,
+                limit: args.limit,
+                json: args.json,
+            };
+            cli::search::search_command(&config, &args.query.join(" "), options).await
+        }
+        Some(Commands::Show(args)) => {
+            let options = cli::search::ShowOptions {
+                pager: !args.no_pager,
+            };
+            cli::search::show_command(&config, &args.conversation, options).await
+        }
+        Some(Commands::Tasks(args)) => match args.command {
+            TasksCommands::List(args) => {
+                let options = cli::tasks::ListOptions {
+                    status: args.status,
+                    all: args.all,
+                    instance: args.instance,
+                    tag: args.tag,
+                    search: args.search,
+                    limit: args.limit,
+                    json: args.json,
+                };
+                cli::tasks::list_command(&config, options).await
+            }
+            TasksCommands::Add(args) => {
+                let options = cli::tasks::AddOptions {
+                    body: args.body,
+                    instance: args.instance,
+                    priority: args.priority,
+                    tags: args.tags,
+                };
+                cli::tasks::add_command(&config, &args.title, options).await
+            }
+            TasksCommands::Edit(args) => {
+                let options = cli::tasks::EditOptions {
+                    title: args.title,
+                    body: args.body,
+                    status: args.status,
+                    priority: args.priority,
+                    instance: args.instance,
+                };
+                cli::tasks::edit_command(&config, args.id, options).await
+            }
+            TasksCommands::Done(args) => cli::tasks::done_command(&config, &args.ids).await,
+            TasksCommands::Send(args) => {
+                cli::tasks::send_command(&config, args.id, args.instance.as_deref()).await
+            }
+            TasksCommands::Tag(args) => {
+                cli::tasks::tag_command(&config, args.id, &args.tags, args.remove).await
+            }
+        },
         Some(Commands::Kill(args)) => cli::kill_command(&config, &args.target).await,
         Some(Commands::KillServer(args)) => cli::kill_server_command(&config, args.force).await,
         Some(Commands::Auth(args)) => match args.command {
@@ -162,6 +572,11 @@ async fn main() -> Result<()> {
             AuthCommands::Disable => cli::auth::disable_command(&config).await,
             AuthCommands::Status => cli::auth::status_command(&config).await,
         },
+        Some(Commands::Login(args)) => {
+            cli::remote::login_command(&config, &args.url, args.username).await
+        }
+        Some(Commands::Logout(args)) => cli::remote::logout_command(&config, &args.url).await,
+        Some(Commands::Keys) => cli::keys::keys_command(&config),
         Some(Commands::Server(args)) => run_server(args, config).await,
     }
 }
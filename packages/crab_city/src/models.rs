@@ -288,11 +288,51 @@ pub struct Session {
     pub ip_address: Option<String>,
 }
 
+/// What a user may do with an instance. Each role includes the ones before
+/// it; admins (and everyone, with auth off) act as owners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceRole {
+    /// Watch the terminal and conversation
+    Viewer,
+    /// Type, resize, hold the terminal lock, rename and send tasks
+    #[serde(alias = "collaborator")]
+    Operator,
+    /// Delete the instance and manage who has access
+    Owner,
+}
+
+impl InstanceRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Owner => "owner",
+        }
+    }
+
+    /// Parse a stored role. Grants from before roles existed say
+    /// "collaborator", which could always type.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "viewer" => Some(Self::Viewer),
+            "operator" | "collaborator" => Some(Self::Operator),
+            "owner" => Some(Self::Owner),
+            _ => None,
+        }
+    }
+
+    /// Whether the role may affect the terminal (input, resize, lock).
+    pub fn can_operate(self) -> bool {
+        self >= Self::Operator
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstancePermission {
     pub instance_id: String,
     pub user_id: String,
-    pub role: String, // "owner" or "collaborator"
+    pub role: InstanceRole,
     pub granted_at: i64,
     pub granted_by: Option<String>,
 }
@@ -302,7 +342,7 @@ pub struct InstanceInvitation {
     pub invite_token: String,
     pub instance_id: String,
     pub created_by: String,
-    pub role: String,
+    pub role: InstanceRole,
     pub max_uses: Option<i32>,
     pub use_count: i32,
     pub expires_at: Option<i64>,
@@ -462,7 +502,7 @@ pub struct Task {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InboxItem {
     pub instance_id: String,
-    /// Event type: "completed_turn", "needs_input", or "error"
+    /// Event type: "completed_turn", "needs_input", "notification", or "error"
     pub event_type: String,
     /// Number of accumulated turns (incremented for repeated completed_turn events)
     pub turn_count: i32,
@@ -529,6 +569,33 @@ pub struct MigrateTaskItem {
     pub created_at: Option<i64>,
 }
 
+// === Prompt queue models ===
+
+/// A prompt waiting its turn on an instance. Sent (and removed) when the
+/// instance goes back to Idle, attributed to whoever queued it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedPrompt {
+    pub id: i64,
+    pub instance_id: String,
+    pub content: String,
+    pub user_id: String,
+    pub display_name: String,
+    /// 0-based place in the queue
+    pub position: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnqueuePromptRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MovePromptRequest {
+    /// New 0-based place in the queue (clamped to the end)
+    pub position: i64,
+}
+
 pub fn attribution_content_matches(stored_content: &str, entry_content: &str) -> bool {
     let stored = normalize_attribution_content(stored_content);
     let entry = normalize_attribution_content(entry_content);
@@ -664,7 +731,7 @@ mod model_tests {
             invite_token: "tok".into(),
             instance_id: "inst".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: None,
             use_count: 0,
             expires_at: None,
@@ -679,7 +746,7 @@ mod model_tests {
             invite_token: "tok".into(),
             instance_id: "inst".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: None,
             use_count: 0,
             expires_at: Some(0),
@@ -694,7 +761,7 @@ mod model_tests {
             invite_token: "tok".into(),
             instance_id: "inst".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: Some(3),
             use_count: 3,
             expires_at: None,
@@ -709,7 +776,7 @@ mod model_tests {
             invite_token: "tok".into(),
             instance_id: "inst".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: Some(3),
             use_count: 1,
             expires_at: None,
@@ -724,7 +791,7 @@ mod model_tests {
             invite_token: "tok".into(),
             instance_id: "inst".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: None,
             use_count: 1000,
             expires_at: None,
@@ -1325,7 +1392,7 @@ mod serde_tests {
         let p = InstancePermission {
             instance_id: "inst-1".into(),
             user_id: "u-1".into(),
-            role: "owner".into(),
+            role: InstanceRole::Owner,
             granted_at: 500,
             granted_by: Some("admin".into()),
         };
@@ -1424,14 +1491,14 @@ mod serde_tests {
             invite_token: "tok".into(),
             instance_id: "inst-1".into(),
             created_by: "alice".into(),
-            role: "collaborator".into(),
+            role: InstanceRole::Operator,
             max_uses: Some(3),
             use_count: 1,
             expires_at: Some(99999),
             created_at: 100,
         };
         let json = serde_json::to_value(&inv).unwrap();
-        assert_eq!(json["role"], "collaborator");
+        assert_eq!(json["role"], "operator");
         assert_eq!(json["max_uses"], 3);
         let rt: InstanceInvitation = serde_json::from_value(json).unwrap();
         assert_eq!(rt.use_count, 1);
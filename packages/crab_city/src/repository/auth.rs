@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use sqlx::Row;
 
 use crate::models::{
-    InstanceInvitation, InstancePermission, InviteAcceptor, ServerInvite,
+    InstanceInvitation, InstancePermission, InstanceRole, InviteAcceptor, ServerInvite,
     ServerInviteWithAcceptors, Session, User,
 };
 
@@ -198,12 +198,13 @@ impl ConversationRepository {
         )
         .bind(&perm.instance_id)
         .bind(&perm.user_id)
-        .bind(&perm.role)
+        .bind(perm.role.as_str())
         .bind(perm.granted_at)
         .bind(&perm.granted_by)
         .execute(&self.pool)
         .await
         .context("Failed to create instance permission")?;
+        self.permissions_changed();
         Ok(())
     }
 
@@ -224,13 +225,7 @@ impl ConversationRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| InstancePermission {
-            instance_id: r.get("instance_id"),
-            user_id: r.get("user_id"),
-            role: r.get("role"),
-            granted_at: r.get("granted_at"),
-            granted_by: r.get("granted_by"),
-        }))
+        Ok(row.as_ref().map(permission_from_row))
     }
 
     pub async fn list_user_instance_ids(&self, user_id: &str) -> Result<Vec<String>> {
@@ -242,12 +237,47 @@ impl ConversationRepository {
         Ok(rows.into_iter().map(|r| r.get("instance_id")).collect())
     }
 
+    /// Every instance grant a user holds.
+    pub async fn list_user_instance_permissions(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<InstancePermission>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT instance_id, user_id, role, granted_at, granted_by
+            FROM instance_permissions
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(permission_from_row).collect())
+    }
+
     pub async fn delete_instance_permission(&self, instance_id: &str, user_id: &str) -> Result<()> {
         sqlx::query("DELETE FROM instance_permissions WHERE instance_id = ? AND user_id = ?")
             .bind(instance_id)
             .bind(user_id)
             .execute(&self.pool)
             .await?;
+        self.permissions_changed();
+        Ok(())
+    }
+
+    /// Drop every grant and invitation for an instance id, so a new
+    /// instance reusing the id starts with no collaborators.
+    pub async fn clear_instance_access(&self, instance_id: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["instance_permissions", "instance_invitations"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE instance_id = ?"))
+                .bind(instance_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        self.permissions_changed();
         Ok(())
     }
 
@@ -265,7 +295,7 @@ impl ConversationRepository {
         .bind(&invite.invite_token)
         .bind(&invite.instance_id)
         .bind(&invite.created_by)
-        .bind(&invite.role)
+        .bind(invite.role.as_str())
         .bind(invite.max_uses)
         .bind(invite.use_count)
         .bind(invite.expires_at)
@@ -291,7 +321,7 @@ impl ConversationRepository {
             invite_token: r.get("invite_token"),
             instance_id: r.get("instance_id"),
             created_by: r.get("created_by"),
-            role: r.get("role"),
+            role: stored_role(r.get("role")),
             max_uses: r.get("max_uses"),
             use_count: r.get("use_count"),
             expires_at: r.get("expires_at"),
@@ -542,9 +572,26 @@ impl ConversationRepository {
     }
 }
 
+/// Unknown stored roles get the least privilege.
+fn stored_role(role: String) -> InstanceRole {
+    InstanceRole::parse(&role).unwrap_or(InstanceRole::Viewer)
+}
+
+fn permission_from_row(r: &sqlx::sqlite::SqliteRow) -> InstancePermission {
+    InstancePermission {
+        instance_id: r.get("instance_id"),
+        user_id: r.get("user_id"),
+        role: stored_role(r.get("role")),
+        granted_at: r.get("granted_at"),
+        granted_by: r.get("granted_by"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::models::{InstanceInvitation, InstancePermission, ServerInvite, Session, User};
+    use crate::models::{
+        InstanceInvitation, InstancePermission, InstanceRole, ServerInvite, Session, User,
+    };
     use crate::repository::test_helpers;
     use chrono::Utc;
 
@@ -687,7 +734,7 @@ mod tests {
         let perm = InstancePermission {
             instance_id: "inst-1".to_string(),
             user_id: "u-1".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Viewer,
             granted_at: Utc::now().timestamp(),
             granted_by: None,
         };
@@ -698,10 +745,13 @@ mod tests {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(checked.role, "collaborator");
+        assert_eq!(checked.role, InstanceRole::Viewer);
 
         let ids = repo.list_user_instance_ids("u-1").await.unwrap();
         assert_eq!(ids, vec!["inst-1"]);
+        let perms = repo.list_user_instance_permissions("u-1").await.unwrap();
+        assert_eq!(perms.len(), 1);
+        assert_eq!(perms[0].role, InstanceRole::Viewer);
 
         repo.delete_instance_permission("inst-1", "u-1")
             .await
@@ -711,6 +761,46 @@ mod tests {
             .await
             .unwrap();
         assert!(checked.is_none());
+
+        repo.create_user(&make_user("u-2", "bob")).await.unwrap();
+        for user in ["u-1", "u-2"] {
+            repo.create_instance_permission(&InstancePermission {
+                instance_id: "inst-1".to_string(),
+                user_id: user.to_string(),
+                role: InstanceRole::Operator,
+                granted_at: 0,
+                granted_by: None,
+            })
+            .await
+            .unwrap();
+        }
+        repo.clear_instance_access("inst-1").await.unwrap();
+        for user in ["u-1", "u-2"] {
+            let checked = repo
+                .check_instance_permission("inst-1", user)
+                .await
+                .unwrap();
+            assert!(checked.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_collaborator_grants_are_operators() {
+        let repo = test_helpers::test_repository().await;
+        repo.create_user(&make_user("u-1", "alice")).await.unwrap();
+        sqlx::query(
+            "INSERT INTO instance_permissions (instance_id, user_id, role) VALUES ('inst-1', 'u-1', 'collaborator')",
+        )
+        .execute(&repo.pool)
+        .await
+        .unwrap();
+
+        let checked = repo
+            .check_instance_permission("inst-1", "u-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(checked.role, InstanceRole::Operator);
     }
 
     #[tokio::test]
@@ -722,7 +812,7 @@ mod tests {
             invite_token: "inv-tok".to_string(),
             instance_id: "inst-1".to_string(),
             created_by: "u-1".to_string(),
-            role: "collaborator".to_string(),
+            role: InstanceRole::Operator,
             max_uses: Some(3),
             use_count: 0,
             expires_at: None,
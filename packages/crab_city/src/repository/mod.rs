@@ -4,6 +4,8 @@
 // `crate::repository::SearchFilters` — no callsite changes required.
 
 use sqlx::sqlite::SqlitePool;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 mod attributions;
 mod auth;
@@ -11,6 +13,7 @@ mod chat;
 mod conversations;
 mod entries;
 mod inbox;
+mod prompts;
 mod search;
 mod settings;
 mod tasks;
@@ -29,15 +32,33 @@ pub struct SearchFilters {
     pub date_to: Option<i64>,
     /// Only return conversations containing tool use
     pub has_tools: Option<bool>,
+    /// Only conversations whose instance (or, for imports, project
+    /// directory name) contains this text
+    pub project: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct ConversationRepository {
     pub(crate) pool: SqlitePool,
+    /// Bumped whenever an instance grant changes, so caches of grants know
+    /// to re-check.
+    permissions_version: Arc<AtomicU64>,
 }
 
 impl ConversationRepository {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            permissions_version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Changes every time an instance grant is created, changed or removed.
+    pub fn permissions_version(&self) -> u64 {
+        self.permissions_version.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn permissions_changed(&self) {
+        self.permissions_version.fetch_add(1, Ordering::AcqRel);
     }
 }
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::Row;
+use sqlx::sqlite::SqliteRow;
+
+use crate::models::QueuedPrompt;
+
+use super::ConversationRepository;
+
+fn prompt_from_row(r: &SqliteRow) -> QueuedPrompt {
+    QueuedPrompt {
+        id: r.get("id"),
+        instance_id: r.get("instance_id"),
+        content: r.get("content"),
+        user_id: r.get("user_id"),
+        display_name: r.get("display_name"),
+        position: r.get("position"),
+        created_at: r.get("created_at"),
+    }
+}
+
+// Positions are kept dense (0..n) per instance, so every removal or move
+// shifts the prompts in between.
+impl ConversationRepository {
+    /// Add a prompt to the end of an instance's queue.
+    pub async fn enqueue_prompt(
+        &self,
+        instance_id: &str,
+        content: &str,
+        user_id: &str,
+        display_name: &str,
+    ) -> Result<QueuedPrompt> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO prompt_queue (instance_id, content, user_id, display_name, position, created_at)
+            VALUES (?, ?, ?, ?,
+                    (SELECT COALESCE(MAX(position) + 1, 0) FROM prompt_queue WHERE instance_id = ?),
+                    ?)
+            RETURNING id, instance_id, content, user_id, display_name, position, created_at
+            "#,
+        )
+        .bind(instance_id)
+        .bind(content)
+        .bind(user_id)
+        .bind(display_name)
+        .bind(instance_id)
+        .bind(Utc::now().timestamp())
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to enqueue prompt")?;
+        Ok(prompt_from_row(&row))
+    }
+
+    /// An instance's queued prompts, next to send first.
+    pub async fn list_prompt_queue(&self, instance_id: &str) -> Result<Vec<QueuedPrompt>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, instance_id, content, user_id, display_name, position, created_at
+            FROM prompt_queue
+            WHERE instance_id = ?
+            ORDER BY position, id
+            "#,
+        )
+        .bind(instance_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(prompt_from_row).collect())
+    }
+
+    pub async fn get_queued_prompt(&self, id: i64) -> Result<Option<QueuedPrompt>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, instance_id, content, user_id, display_name, position, created_at
+            FROM prompt_queue
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(prompt_from_row))
+    }
+
+    /// Remove a prompt from its queue. Returns the removed prompt, if any.
+    pub async fn delete_queued_prompt(&self, id: i64) -> Result<Option<QueuedPrompt>> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            DELETE FROM prompt_queue WHERE id = ?
+            RETURNING id, instance_id, content, user_id, display_name, position, created_at
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(prompt) = row.as_ref().map(prompt_from_row) else {
+            return Ok(None);
+        };
+        sqlx::query(
+            "UPDATE prompt_queue SET position = position - 1 WHERE instance_id = ? AND position > ?",
+        )
+        .bind(&prompt.instance_id)
+        .bind(prompt.position)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(prompt))
+    }
+
+    /// Take the next prompt off an instance's queue.
+    pub async fn pop_queued_prompt(&self, instance_id: &str) -> Result<Option<QueuedPrompt>> {
+        let next: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM prompt_queue WHERE instance_id = ? ORDER BY position, id LIMIT 1",
+        )
+        .bind(instance_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        match next {
+            Some(id) => self.delete_queued_prompt(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Move a prompt to `position` (clamped to the queue). Returns false if
+    /// the prompt doesn't exist.
+    pub async fn move_queued_prompt(&self, id: i64, position: i64) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let current: Option<(String, i64)> =
+            sqlx::query_as("SELECT instance_id, position FROM prompt_queue WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((instance_id, from)) = current else {
+            return Ok(false);
+        };
+        let len: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM prompt_queue WHERE instance_id = ?")
+                .bind(&instance_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        let to = position.clamp(0, len - 1);
+
+        let (shift, low, high) = if to < from {
+            (1, to, from - 1)
+        } else {
+            (-1, from + 1, to)
+        };
+        sqlx::query(
+            "UPDATE prompt_queue SET position = position + ? WHERE instance_id = ? AND position BETWEEN ? AND ?",
+        )
+        .bind(shift)
+        .bind(&instance_id)
+        .bind(low)
+        .bind(high)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE prompt_queue SET position = ? WHERE id = ?")
+            .bind(to)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Drop an instance's whole queue (when the instance goes away).
+    pub async fn clear_prompt_queue(&self, instance_id: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM prompt_queue WHERE instance_id = ?")
+            .bind(instance_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Drop every queue. Instance ids don't survive a restart, so queues
+    /// left over from a previous run can never be sent.
+    pub async fn clear_all_prompt_queues(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM prompt_queue")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::test_helpers;
+
+    async fn contents(repo: &super::ConversationRepository) -> Vec<String> {
+        repo.list_prompt_queue("inst-1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.content)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn enqueue_and_pop_in_order() {
+        let repo = test_helpers::test_repository().await;
+        let first = repo
+            .enqueue_prompt("inst-1", "first", "u-1", "Alice")
+            .await
+            .unwrap();
+        assert_eq!(first.position, 0);
+        assert_eq!(first.display_name, "Alice");
+        repo.enqueue_prompt("inst-1", "second", "u-2", "Bob")
+            .await
+            .unwrap();
+        repo.enqueue_prompt("inst-2", "elsewhere", "u-2", "Bob")
+            .await
+            .unwrap();
+
+        let next = repo.pop_queued_prompt("inst-1").await.unwrap().unwrap();
+        assert_eq!(next.content, "first");
+        let rest = repo.list_prompt_queue("inst-1").await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].position, 0);
+        assert_eq!(rest[0].user_id, "u-2");
+    }
+
+    #[tokio::test]
+    async fn move_and_cancel_keep_positions_dense() {
+        let repo = test_helpers::test_repository().await;
+        let mut ids = Vec::new();
+        for c in ["a", "b", "c", "d"] {
+            ids.push(
+                repo.enqueue_prompt("inst-1", c, "u-1", "Alice")
+                    .await
+                    .unwrap()
+                    .id,
+            );
+        }
+
+        assert!(repo.move_queued_prompt(ids[3], 0).await.unwrap());
+        assert_eq!(contents(&repo).await, ["d", "a", "b", "c"]);
+        // Past the end clamps to last
+        assert!(repo.move_queued_prompt(ids[0], 99).await.unwrap());
+        assert_eq!(contents(&repo).await, ["d", "b", "c", "a"]);
+
+        let removed = repo.delete_queued_prompt(ids[1]).await.unwrap().unwrap();
+        assert_eq!(removed.content, "b");
+        assert_eq!(contents(&repo).await, ["d", "c", "a"]);
+        let positions: Vec<i64> = repo
+            .list_prompt_queue("inst-1")
+            .await
+            .unwrap()
+            .iter()
+            .map(|p| p.position)
+            .collect();
+        assert_eq!(positions, [0, 1, 2]);
+
+        assert!(!repo.move_queued_prompt(9999, 0).await.unwrap());
+        assert!(repo.delete_queued_prompt(9999).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn clear_prompt_queue_only_touches_one_instance() {
+        let repo = test_helpers::test_repository().await;
+        repo.enqueue_prompt("inst-1", "a", "u-1", "Alice")
+            .await
+            .unwrap();
+        repo.enqueue_prompt("inst-2", "b", "u-1", "Alice")
+            .await
+            .unwrap();
+        assert_eq!(repo.clear_prompt_queue("inst-1").await.unwrap(), 1);
+        assert!(repo.list_prompt_queue("inst-1").await.unwrap().is_empty());
+        assert_eq!(repo.list_prompt_queue("inst-2").await.unwrap().len(), 1);
+
+        assert_eq!(repo.clear_all_prompt_queues().await.unwrap(), 1);
+        assert!(repo.list_prompt_queue("inst-2").await.unwrap().is_empty());
+    }
+}
@@ -39,12 +39,12 @@ impl ConversationRepository {
 
         if let Some(date_from) = filters.date_from {
             entry_conditions.push("ce.timestamp >= ?".to_string());
-            bind_values.push(date_from.to_string());
+            bind_values.push(Self::timestamp_bound(date_from));
         }
 
         if let Some(date_to) = filters.date_to {
             entry_conditions.push("ce.timestamp <= ?".to_string());
-            bind_values.push(date_to.to_string());
+            bind_values.push(Self::timestamp_bound(date_to));
         }
 
         // Build the WHERE clause suffix for entry filters
@@ -54,6 +54,20 @@ impl ConversationRepository {
             format!(" AND {}", entry_conditions.join(" AND "))
         };
 
+        // project filter: conversation-level, so the snippet query leaves it out
+        let project_pattern = filters.project.as_deref().map(|p| {
+            let escaped = p
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{}%", escaped)
+        });
+        let project_clause = if project_pattern.is_some() {
+            " AND c.instance_id LIKE ? ESCAPE '\\'"
+        } else {
+            ""
+        };
+
         // has_tools filter: require at least one entry with a tool_use mention
         let has_tools_join = if filters.has_tools == Some(true) {
             " AND EXISTS (SELECT 1 FROM conversation_entries te WHERE te.conversation_id = c.id AND (te.raw_json LIKE '%\"ToolUse\"%' OR te.entry_type LIKE '%tool%'))"
@@ -69,13 +83,13 @@ impl ConversationRepository {
             JOIN conversations c ON fts.conversation_id = c.id
             JOIN conversation_entries ce ON fts.rowid = ce.id
             WHERE conversation_entries_fts MATCH ?
-              AND c.is_deleted = 0{}{}
+              AND c.is_deleted = 0{}{}{}
             "#,
-            entry_filter_clause, has_tools_join
+            entry_filter_clause, project_clause, has_tools_join
         );
 
         let mut count_builder = sqlx::query(&count_query);
-        for val in &bind_values {
+        for val in bind_values.iter().chain(&project_pattern) {
             count_builder = count_builder.bind(val);
         }
         let count_row = count_builder.fetch_one(&self.pool).await?;
@@ -96,16 +110,16 @@ impl ConversationRepository {
             JOIN conversations c ON fts.conversation_id = c.id
             JOIN conversation_entries ce ON fts.rowid = ce.id
             WHERE conversation_entries_fts MATCH ?
-              AND c.is_deleted = 0{}{}
+              AND c.is_deleted = 0{}{}{}
             GROUP BY fts.conversation_id
             ORDER BY best_rank ASC
             LIMIT ? OFFSET ?
             "#,
-            entry_filter_clause, has_tools_join
+            entry_filter_clause, project_clause, has_tools_join
         );
 
         let mut convo_builder = sqlx::query(&convo_query);
-        for val in &bind_values {
+        for val in bind_values.iter().chain(&project_pattern) {
             convo_builder = convo_builder.bind(val);
         }
         convo_builder = convo_builder.bind(per_page).bind(offset);
@@ -192,6 +206,16 @@ impl ConversationRepository {
         })
     }
 
+    /// Entry timestamps are stored as RFC 3339 text, so a Unix time bound is
+    /// compared as text too. Without an offset or fraction it sorts before
+    /// every timestamp within the same second.
+    fn timestamp_bound(unix: i64) -> String {
+        chrono::DateTime::from_timestamp(unix, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string()
+    }
+
     /// Escape a raw user query for FTS5 by stripping all characters that are not
     /// alphanumeric or basic punctuation, then wrapping each remaining token in
     /// double quotes. This prevents both FTS5 syntax errors and any attempt to
@@ -323,6 +347,22 @@ mod tests {
         uuid: &str,
         role: &str,
         content: &str,
+    ) -> ConversationEntry {
+        make_entry_at(
+            conversation_id,
+            uuid,
+            role,
+            content,
+            &chrono::Utc::now().to_rfc3339(),
+        )
+    }
+
+    fn make_entry_at(
+        conversation_id: &str,
+        uuid: &str,
+        role: &str,
+        content: &str,
+        timestamp: &str,
     ) -> ConversationEntry {
         ConversationEntry {
             id: None,
@@ -332,7 +372,7 @@ mod tests {
             entry_type: "message".to_string(),
             role: Some(role.to_string()),
             content: Some(content.to_string()),
-            timestamp: chrono::Utc::now().to_rfc3339(),
+            timestamp: timestamp.to_string(),
             raw_json: "{}".to_string(),
             token_count: None,
             model: None,
@@ -448,6 +488,81 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn search_conversations_with_date_filter() {
+        let repo = test_helpers::test_repository().await;
+
+        for (id, timestamp) in [
+            ("conv-old", "2024-03-01T09:00:00.000Z"),
+            ("conv-new", "2025-06-01T09:00:00.000Z"),
+        ] {
+            let conv = Conversation::new(id.to_string(), "inst-1".to_string());
+            repo.create_conversation(&conv).await.unwrap();
+            let entry = make_entry_at(id, &format!("{id}-e"), "user", "deploy script", timestamp);
+            repo.add_entries_batch(&[entry]).await.unwrap();
+        }
+
+        // 2025-01-01T00:00:00Z
+        let filters = super::super::SearchFilters {
+            date_from: Some(1_735_689_600),
+            ..Default::default()
+        };
+        let result = repo
+            .search_conversations("deploy", 1, 10, 3, &filters)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.items[0].id, "conv-new");
+
+        let filters = super::super::SearchFilters {
+            date_to: Some(1_735_689_600),
+            ..Default::default()
+        };
+        let result = repo
+            .search_conversations("deploy", 1, 10, 3, &filters)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.items[0].id, "conv-old");
+    }
+
+    #[tokio::test]
+    async fn search_conversations_with_project_filter() {
+        let repo = test_helpers::test_repository().await;
+
+        for (id, project) in [
+            ("conv-api", "-home-me-src-api"),
+            ("conv-web", "-home-me-src-web"),
+        ] {
+            let conv = Conversation::new(id.to_string(), project.to_string());
+            repo.create_conversation(&conv).await.unwrap();
+            let entry = make_entry(id, &format!("{id}-e"), "user", "flaky test");
+            repo.add_entries_batch(&[entry]).await.unwrap();
+        }
+
+        let filters = super::super::SearchFilters {
+            project: Some("src-api".to_string()),
+            ..Default::default()
+        };
+        let result = repo
+            .search_conversations("flaky", 1, 10, 3, &filters)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.items[0].id, "conv-api");
+
+        // LIKE wildcards in the filter are matched literally
+        let filters = super::super::SearchFilters {
+            project: Some("src_api".to_string()),
+            ..Default::default()
+        };
+        let result = repo
+            .search_conversations("flaky", 1, 10, 3, &filters)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 0);
+    }
+
     #[tokio::test]
     async fn search_conversations_pagination() {
         let repo = test_helpers::test_repository().await;
@@ -0,0 +1,288 @@
+//! Terminal scrollback persistence.
+//!
+//! Instance actors periodically snapshot their `VirtualTerminal` into a
+//! [`ScrollbackStore`] under `state_dir()/scrollback`. Each instance gets two
+//! files: `{id}.vts` (the CBOR `VtSnapshot`) and `{id}.json` (metadata used
+//! to find a snapshot by session id). Snapshots outlive a daemon shutdown and
+//! are discarded when the instance is deleted, or pruned when the store is
+//! opened once they are older than [`MAX_AGE`] or past the newest
+//! [`MAX_SNAPSHOTS`]. When an instance is
+//! recreated with `restore_from` set, or resumes the Claude session a snapshot
+//! was saved under, the snapshot is replayed into the new terminal so
+//! attaching clients see what happened before the restart. With auth
+//! enabled only the old instance's owner gets its scrollback back, and the
+//! reused id starts without the old instance's grants.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::virtual_terminal::VtSnapshot;
+
+/// Sidecar metadata for a scrollback snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrollbackMeta {
+    pub instance_id: String,
+    pub name: String,
+    pub command: String,
+    pub working_dir: String,
+    pub session_id: Option<String>,
+    pub saved_at_ms: i64,
+}
+
+/// Snapshots of instances that exited on their own are never deleted; drop
+/// them after this long.
+pub const MAX_AGE: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Keep at most this many snapshots, newest first.
+pub const MAX_SNAPSHOTS: usize = 200;
+
+/// Directory of per-instance scrollback snapshots.
+pub struct ScrollbackStore {
+    dir: PathBuf,
+}
+
+impl ScrollbackStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Open the store, pruning stale snapshots (see [`MAX_AGE`] and
+    /// [`MAX_SNAPSHOTS`]).
+    pub fn open(dir: PathBuf) -> Self {
+        let store = Self::new(dir);
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let pruned = store.prune(now_ms - MAX_AGE.as_millis() as i64, MAX_SNAPSHOTS);
+        if pruned > 0 {
+            info!("Pruned {pruned} old scrollback snapshots");
+        }
+        store
+    }
+
+    /// Remove snapshots saved before `cutoff_ms`, all but the newest `keep`,
+    /// and snapshot files without readable metadata. Returns how many
+    /// snapshots were removed.
+    fn prune(&self, cutoff_ms: i64, keep: usize) -> usize {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return 0;
+        };
+        let mut metas = Vec::new();
+        let mut orphans = Vec::new();
+        for path in entries.flatten().map(|e| e.path()) {
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => match read_meta(&path) {
+                    Some(meta) if meta.instance_id == id => metas.push(meta),
+                    _ => orphans.push(id.to_string()),
+                },
+                Some("vts") if !self.meta_path(id).exists() => orphans.push(id.to_string()),
+                _ => {}
+            }
+        }
+        metas.sort_by_key(|m| std::cmp::Reverse(m.saved_at_ms));
+        let stale = metas
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| *i >= keep || m.saved_at_ms < cutoff_ms)
+            .map(|(_, m)| m.instance_id.clone());
+        let removed: Vec<String> = stale.chain(orphans).collect();
+        for id in &removed {
+            self.remove(id);
+        }
+        removed.len()
+    }
+
+    /// Write (or replace) the snapshot for `meta.instance_id`.
+    pub fn save(&self, meta: &ScrollbackMeta, snapshot: &VtSnapshot) -> io::Result<()> {
+        if !is_valid_key(&meta.instance_id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid instance id",
+            ));
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        snapshot.write_file(&self.snapshot_path(&meta.instance_id))?;
+
+        let meta_path = self.meta_path(&meta.instance_id);
+        let tmp = meta_path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(meta).map_err(io::Error::other)?)?;
+        std::fs::rename(&tmp, &meta_path)
+    }
+
+    /// Find a snapshot by instance id, falling back to the most recent
+    /// snapshot whose metadata carries `key` as its session id.
+    pub fn load(&self, key: &str) -> Option<(ScrollbackMeta, VtSnapshot)> {
+        if !is_valid_key(key) {
+            return None;
+        }
+        if let Some(found) = self.load_by_id(key) {
+            return Some(found);
+        }
+
+        let entries = std::fs::read_dir(&self.dir).ok()?;
+        let instance_id = entries
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| read_meta(&e.path()))
+            .filter(|m| m.session_id.as_deref() == Some(key))
+            .max_by_key(|m| m.saved_at_ms)?
+            .instance_id;
+        self.load_by_id(&instance_id)
+    }
+
+    /// Delete the snapshot for an instance (no-op if none exists).
+    pub fn remove(&self, instance_id: &str) {
+        if !is_valid_key(instance_id) {
+            return;
+        }
+        for path in [self.snapshot_path(instance_id), self.meta_path(instance_id)] {
+            if let Err(e) = std::fs::remove_file(&path)
+                && e.kind() != io::ErrorKind::NotFound
+            {
+                warn!("Failed to remove {}: {e}", path.display());
+            }
+        }
+    }
+
+    fn load_by_id(&self, instance_id: &str) -> Option<(ScrollbackMeta, VtSnapshot)> {
+        let meta = read_meta(&self.meta_path(instance_id))?;
+        match VtSnapshot::read_file(&self.snapshot_path(instance_id)) {
+            Ok(snapshot) => Some((meta, snapshot)),
+            Err(e) => {
+                warn!("Ignoring unreadable scrollback snapshot for {instance_id}: {e}");
+                None
+            }
+        }
+    }
+
+    fn snapshot_path(&self, instance_id: &str) -> PathBuf {
+        self.dir.join(format!("{instance_id}.vts"))
+    }
+
+    fn meta_path(&self, instance_id: &str) -> PathBuf {
+        self.dir.join(format!("{instance_id}.json"))
+    }
+}
+
+fn read_meta(path: &Path) -> Option<ScrollbackMeta> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Instance and session ids are UUIDs; anything that could escape the
+/// store directory is rejected.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::virtual_terminal::VirtualTerminal;
+
+    fn meta(id: &str, session: Option<&str>, saved_at_ms: i64) -> ScrollbackMeta {
+        ScrollbackMeta {
+            instance_id: id.to_string(),
+            name: "swift-amber-falcon".to_string(),
+            command: "bash".to_string(),
+            working_dir: "/tmp".to_string(),
+            session_id: session.map(str::to_string),
+            saved_at_ms,
+        }
+    }
+
+    fn snapshot(text: &str) -> VtSnapshot {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(text.as_bytes());
+        vt.snapshot()
+    }
+
+    #[test]
+    fn save_and_load_by_instance_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ScrollbackStore::new(tmp.path().join("scrollback"));
+        let snap = snapshot("hello");
+        store.save(&meta("inst-1", None, 1), &snap).unwrap();
+
+        let (m, s) = store.load("inst-1").unwrap();
+        assert_eq!(m.instance_id, "inst-1");
+        assert_eq!(s, snap);
+    }
+
+    #[test]
+    fn load_by_session_picks_most_recent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ScrollbackStore::new(tmp.path().to_path_buf());
+        store
+            .save(&meta("old", Some("sess-1"), 100), &snapshot("old"))
+            .unwrap();
+        store
+            .save(&meta("new", Some("sess-1"), 200), &snapshot("new"))
+            .unwrap();
+        store
+            .save(&meta("other", Some("sess-2"), 300), &snapshot("other"))
+            .unwrap();
+
+        let (m, _) = store.load("sess-1").unwrap();
+        assert_eq!(m.instance_id, "new");
+        assert!(store.load("sess-3").is_none());
+    }
+
+    #[test]
+    fn prune_drops_old_and_excess_snapshots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ScrollbackStore::new(tmp.path().to_path_buf());
+        for (id, saved_at_ms) in [("ancient", 10), ("old", 100), ("mid", 200), ("new", 300)] {
+            store
+                .save(&meta(id, None, saved_at_ms), &snapshot(id))
+                .unwrap();
+        }
+        std::fs::write(tmp.path().join("stray.vts"), b"x").unwrap();
+
+        // "ancient" is past the cutoff, "old" past the count, "stray" has no metadata
+        assert_eq!(store.prune(50, 2), 3);
+        assert!(store.load("ancient").is_none());
+        assert!(store.load("old").is_none());
+        assert!(store.load("mid").is_some());
+        assert!(store.load("new").is_some());
+        assert!(!tmp.path().join("stray.vts").exists());
+
+        // Opening the store prunes by age against the clock
+        ScrollbackStore::open(tmp.path().to_path_buf());
+        assert!(store.load("new").is_none());
+    }
+
+    #[test]
+    fn remove_deletes_both_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ScrollbackStore::new(tmp.path().to_path_buf());
+        store.save(&meta("gone", None, 1), &snapshot("x")).unwrap();
+        store.remove("gone");
+        assert!(store.load("gone").is_none());
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+        // Removing again is a no-op
+        store.remove("gone");
+    }
+
+    #[test]
+    fn rejects_path_traversal_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ScrollbackStore::new(tmp.path().to_path_buf());
+        assert!(store.load("../etc/passwd").is_none());
+        assert!(store.load("").is_none());
+        assert!(
+            store
+                .save(&meta("../escape", None, 1), &snapshot("x"))
+                .is_err()
+        );
+    }
+}
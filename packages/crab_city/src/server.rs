@@ -1,7 +1,7 @@
 use anyhow::{Context, Result, bail};
 use axum::{
     Router,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
 };
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -199,6 +199,7 @@ pub async fn init_server_core(
         initial_server_config.instance.max_buffer_bytes,
         initial_server_config.instance.scrollback_lines,
         initial_server_config.instance.vt_record_dir.clone(),
+        Some(config.state_dir().join("scrollback")),
     ));
 
     // Initialize notes storage
@@ -208,6 +209,12 @@ pub async fn init_server_core(
     let state_broadcast = ws::create_state_broadcast();
     let global_state_manager = Arc::new(ws::GlobalStateManager::new(state_broadcast));
     global_state_manager.start_inbox_watcher(repository.clone());
+    match repository.clear_all_prompt_queues().await {
+        Ok(0) => {}
+        Ok(n) => info!("Dropped {} queued prompts left from the previous run", n),
+        Err(e) => warn!("Failed to clear stale prompt queues: {}", e),
+    }
+    global_state_manager.start_prompt_dispatcher(repository.clone());
 
     // Initialize metrics
     let metrics = Arc::new(ServerMetrics::new());
@@ -281,11 +288,24 @@ pub fn build_router(
         .route("/api/instances/{id}", get(handlers::get_instance))
         .route("/api/instances/{id}", delete(handlers::delete_instance))
         .route("/api/instances/{id}/name", patch(handlers::set_custom_name))
+        .route(
+            "/api/instances/{id}/size-policy",
+            put(handlers::set_size_policy),
+        )
         .route("/api/ws", get(handlers::multiplexed_websocket_handler))
+        .route("/api/events", get(handlers::events_handler))
         .route(
             "/api/instances/{id}/output",
             get(handlers::get_instance_output),
         )
+        .route(
+            "/api/instances/{id}/timeline",
+            get(handlers::get_instance_timeline),
+        )
+        .route(
+            "/api/instances/{id}/timeline/frame",
+            get(handlers::get_instance_timeline_frame),
+        )
         // File routes
         .route(
             "/api/instances/{id}/files",
@@ -323,6 +343,18 @@ pub fn build_router(
             get(handlers::poll_conversation),
         )
         // Instance permission / invitation endpoints
+        .route(
+            "/api/instances/{id}/prompts",
+            get(handlers::list_prompts_handler).post(handlers::enqueue_prompt_handler),
+        )
+        .route(
+            "/api/instances/{id}/prompts/{prompt_id}",
+            delete(handlers::cancel_prompt_handler),
+        )
+        .route(
+            "/api/instances/{id}/prompts/{prompt_id}/position",
+            put(handlers::move_prompt_handler),
+        )
         .route(
             "/api/instances/{id}/invite",
             post(handlers::create_invitation),
@@ -49,6 +49,7 @@ pub async fn test_app_state() -> (AppState, tempfile::TempDir) {
             25 * 1024 * 1024,
             0,
             None,
+            None,
         )),
         conversation_watchers: Arc::new(Mutex::new(HashMap::new())),
         config: Arc::new(config),
@@ -155,6 +156,7 @@ pub async fn create_test_instance(
             name,
             working_dir,
             command,
+            Vec::new(),
             Box::new(ShellDriver),
             None,
             None,
@@ -163,6 +165,7 @@ pub async fn create_test_instance(
             Arc::new(RwLock::new(HashMap::new())),
             None,
             None,
+            None,
         )
         .await
 }
@@ -0,0 +1,204 @@
+//! Binary Terminal Frames
+//!
+//! Terminal output and input can travel as binary WebSocket messages instead
+//! of JSON `Output`/`OutputHistory`/`ScreenDiff`/`Input`, which skips lossy
+//! UTF-8 decoding and JSON escaping on every chunk. A connection opts in by requesting the
+//! `binary_frames` capability in its `Hello` (see `ws::handshake`) or, for
+//! clients that predate the handshake, by sending
+//! `ClientMessage::EnableBinaryFrames`; the server answers with
+//! `ServerMessage::BinaryFramesEnabled` and from then on sends terminal output
+//! as frames. Clients must not send binary input before that answer. All
+//! other messages stay on the JSON control channel.
+//!
+//! # Wire format
+//!
+//! ```text
+//! +------+-------+-------------+--------------+---------+
+//! | kind | n: u8 | instance id | seq: u64 BE  | payload |
+//! | 1 B  | 1 B   | n B (UTF-8) | 8 B          | rest    |
+//! +------+-------+-------------+--------------+---------+
+//! ```
+//!
+//! For output frames `seq` is the instance's output sequence (see
+//! `ws::replay`): a live chunk's own number, or for history the last chunk
+//! the replay includes, for a screen diff the last chunk it reflects. Input
+//! frames may carry any client-side counter. `payload` is the raw PTY (or
+//! keyboard) bytes, or the diff's escape sequences.
+
+/// Header bytes before the instance id: kind + id length.
+const PREFIX_LEN: usize = 2;
+/// Bytes taken by the sequence number.
+const SEQ_LEN: usize = 8;
+
+/// What a frame's payload is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FrameKind {
+    /// Live PTY output (server → client), replaces `ServerMessage::Output`
+    Output = 1,
+    /// Terminal replay (server → client), replaces `ServerMessage::OutputHistory`
+    OutputHistory = 2,
+    /// Keyboard input (client → server), replaces `ClientMessage::Input`
+    Input = 3,
+    /// Screen diff (server → client), replaces `ServerMessage::ScreenDiff`
+    ScreenDiff = 4,
+}
+
+impl TryFrom<u8> for FrameKind {
+    type Error = FrameError;
+
+    fn try_from(b: u8) -> Result<Self, FrameError> {
+        match b {
+            1 => Ok(Self::Output),
+            2 => Ok(Self::OutputHistory),
+            3 => Ok(Self::Input),
+            4 => Ok(Self::ScreenDiff),
+            other => Err(FrameError::UnknownKind(other)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FrameError {
+    #[error("frame truncated")]
+    Truncated,
+    #[error("unknown frame kind {0}")]
+    UnknownKind(u8),
+    #[error("instance id is not valid UTF-8")]
+    InvalidInstanceId,
+}
+
+/// A binary terminal frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalFrame {
+    pub kind: FrameKind,
+    pub instance_id: String,
+    pub seq: u64,
+    pub data: Vec<u8>,
+}
+
+impl TerminalFrame {
+    /// Serialize to the wire format.
+    ///
+    /// Instance ids are UUIDs; anything longer than 255 bytes is a caller bug.
+    pub fn encode(&self) -> Vec<u8> {
+        let id = self.instance_id.as_bytes();
+        debug_assert!(id.len() <= u8::MAX as usize, "instance id too long");
+        let id = &id[..id.len().min(u8::MAX as usize)];
+
+        let mut out = Vec::with_capacity(PREFIX_LEN + id.len() + SEQ_LEN + self.data.len());
+        out.push(self.kind as u8);
+        out.push(id.len() as u8);
+        out.extend_from_slice(id);
+        out.extend_from_slice(&self.seq.to_be_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    /// Parse a frame from the wire format.
+    pub fn decode(bytes: &[u8]) -> Result<Self, FrameError> {
+        let [kind, id_len, rest @ ..] = bytes else {
+            return Err(FrameError::Truncated);
+        };
+        let kind = FrameKind::try_from(*kind)?;
+        let id_len = *id_len as usize;
+        if rest.len() < id_len + SEQ_LEN {
+            return Err(FrameError::Truncated);
+        }
+        let (id, rest) = rest.split_at(id_len);
+        let (seq, data) = rest.split_at(SEQ_LEN);
+        let instance_id = std::str::from_utf8(id)
+            .map_err(|_| FrameError::InvalidInstanceId)?
+            .to_string();
+        let seq = u64::from_be_bytes(seq.try_into().expect("split_at(SEQ_LEN)"));
+        Ok(Self {
+            kind,
+            instance_id,
+            seq,
+            data: data.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: FrameKind, data: &[u8]) -> TerminalFrame {
+        TerminalFrame {
+            kind,
+            instance_id: "4f9c2a7e-8d1b-4c3e-9a6f-0b2d5e7c1a38".to_string(),
+            seq: 0x0102_0304_0506_0708,
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_kind() {
+        for kind in [
+            FrameKind::Output,
+            FrameKind::OutputHistory,
+            FrameKind::Input,
+            FrameKind::ScreenDiff,
+        ] {
+            let f = frame(kind, b"\x1b[1mhello\x1b[0m");
+            assert_eq!(TerminalFrame::decode(&f.encode()), Ok(f));
+        }
+    }
+
+    #[test]
+    fn payload_bytes_are_not_reinterpreted() {
+        // Split UTF-8, invalid bytes and NULs survive untouched
+        let data = [0xe2, 0x94, 0xff, 0x00, b'"', b'\\'];
+        let f = frame(FrameKind::Output, &data);
+        assert_eq!(TerminalFrame::decode(&f.encode()).unwrap().data, data);
+    }
+
+    #[test]
+    fn header_layout() {
+        let f = TerminalFrame {
+            kind: FrameKind::Input,
+            instance_id: "ab".to_string(),
+            seq: 7,
+            data: b"x".to_vec(),
+        };
+        assert_eq!(f.encode(), b"\x03\x02ab\0\0\0\0\0\0\0\x07x");
+    }
+
+    #[test]
+    fn empty_payload_is_valid() {
+        let f = frame(FrameKind::Output, b"");
+        assert_eq!(TerminalFrame::decode(&f.encode()), Ok(f));
+    }
+
+    #[test]
+    fn rejects_truncated_frames() {
+        let encoded = frame(FrameKind::Output, b"").encode();
+        for len in 0..encoded.len() {
+            assert_eq!(
+                TerminalFrame::decode(&encoded[..len]),
+                Err(FrameError::Truncated),
+                "prefix of {len} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_kind() {
+        let mut encoded = frame(FrameKind::Output, b"x").encode();
+        encoded[0] = 9;
+        assert_eq!(
+            TerminalFrame::decode(&encoded),
+            Err(FrameError::UnknownKind(9))
+        );
+    }
+
+    #[test]
+    fn rejects_non_utf8_instance_id() {
+        let bytes = b"\x01\x01\xff\0\0\0\0\0\0\0\0";
+        assert_eq!(
+            TerminalFrame::decode(bytes),
+            Err(FrameError::InvalidInstanceId)
+        );
+    }
+}
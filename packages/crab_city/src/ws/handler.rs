@@ -4,8 +4,10 @@
 
 use axum::extract::ws::{Message, WebSocket};
 use futures::{sink::SinkExt, stream::StreamExt};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
@@ -17,12 +19,22 @@ use crate::repository::ConversationRepository;
 use crate::virtual_terminal::ClientType;
 
 use super::focus::{handle_focus, send_conversation_since, send_output_history};
+use super::frame::{FrameKind, TerminalFrame};
+use super::handshake::{
+    Capability, MIN_CLIENT_PROTOCOL_VERSION, PROTOCOL_VERSION, SERVER_CAPABILITIES,
+    SOFTWARE_VERSION, negotiate,
+};
+use super::output_mode::{OutputMode, OutputSettings};
 use super::protocol::{BackpressureStats, ClientMessage, PresenceUser, ServerMessage, WsUser};
+use super::replay::{ResumeParams, ResumeToken};
 use super::state_manager::{
-    GlobalStateManager, InputContext, InputUser, TERMINAL_LOCK_TIMEOUT_SECS,
+    GlobalStateManager, InputContext, InputUser, LockExpiry, TERMINAL_LOCK_TIMEOUT_SECS,
 };
+use super::subscriptions::Subscriptions;
+use super::visibility::Visibility;
 
 /// Handle a multiplexed WebSocket connection
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_multiplexed_ws(
     socket: WebSocket,
     instance_manager: Arc<InstanceManager>,
@@ -31,6 +43,7 @@ pub async fn handle_multiplexed_ws(
     server_metrics: Option<Arc<ServerMetrics>>,
     ws_user: Option<WsUser>,
     repository: Option<Arc<ConversationRepository>>,
+    resume: ResumeParams,
 ) {
     info!(
         "New multiplexed WebSocket connection (user: {})",
@@ -62,17 +75,74 @@ pub async fn handle_multiplexed_ws(
     // Channel for the TerminalVisible handler to ask the focus task to re-send
     // OutputHistory with the correct client_rows (after draining stale broadcasts).
     let focus_refresh: Arc<RwLock<Option<mpsc::Sender<u16>>>> = Arc::new(RwLock::new(None));
+    // Broadcast topics this connection wants (everything until it subscribes)
+    let subscriptions: Arc<RwLock<Subscriptions>> = Arc::default();
+    // Set once the client sends EnableBinaryFrames; terminal output is then
+    // sent as binary frames instead of JSON Output/OutputHistory.
+    let binary_frames = Arc::new(AtomicBool::new(false));
+    // Raw output or screen diffs, read by the focus task (see `ws::output_mode`)
+    let output_mode = Arc::new(watch::Sender::new(OutputSettings::default()));
+    // Instances this connection watches as a read-only spectator: its
+    // viewport doesn't size the terminal and its input is refused.
+    let spectating: Arc<RwLock<HashSet<String>>> = Arc::default();
 
     // Channel for session selection (when ambiguous)
     let (session_select_tx, session_select_rx) = mpsc::channel::<String>(1);
     let session_select_rx = Arc::new(tokio::sync::Mutex::new(session_select_rx));
 
-    // Send initial instance list with states (enriched with state_entered_at)
-    let mut instances = instance_manager.list().await;
-    for inst in &mut instances {
+    // Open with the handshake so clients can check compatibility before
+    // anything else (see `ws::handshake`).
+    let _ = tx
+        .send(ServerMessage::Welcome {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_CLIENT_PROTOCOL_VERSION,
+            server_version: SOFTWARE_VERSION.to_string(),
+            capabilities: SERVER_CAPABILITIES.to_vec(),
+        })
+        .await;
+
+    // Subscribe to lifecycle broadcasts before working out what a resuming
+    // client missed, so nothing falls between the replay and live messages.
+    let mut lifecycle_rx = state_manager.subscribe_lifecycle();
+    let missed_lifecycle = state_manager.missed_lifecycle(&resume);
+    let resumed = missed_lifecycle.is_some();
+    let lifecycle_seq = match &missed_lifecycle {
+        Some(missed) => missed
+            .last()
+            .map_or(resume.lifecycle_seq.unwrap_or(0), |(seq, _)| *seq),
+        None => state_manager.lifecycle_seq(),
+    };
+    let resume_token = ResumeToken {
+        epoch: state_manager.epoch().to_string(),
+        connection_id: connection_id.clone(),
+    };
+    debug!(conn_id = %connection_id, resumed, lifecycle_seq, "Sending resume token");
+    let _ = tx
+        .send(ServerMessage::Resumable {
+            resume_token: resume_token.to_string(),
+            resumed,
+            lifecycle_seq,
+        })
+        .await;
+
+    // Send initial instance list with states (enriched with state_entered_at),
+    // limited to the instances this user may see
+    let mut visible = Visibility::new(
+        ws_user
+            .as_ref()
+            .filter(|u| u.restricted)
+            .map(|u| u.user_id.clone()),
+        repository.clone(),
+    );
+    let mut instances = Vec::new();
+    for mut inst in instance_manager.list().await {
+        if !visible.can_see(&inst.id).await {
+            continue;
+        }
         if let Some(ts) = state_manager.get_state_entered_at(&inst.id).await {
             inst.state_entered_at = Some(ts.timestamp());
         }
+        instances.push(inst);
     }
     if tx
         .send(ServerMessage::InstanceList { instances })
@@ -82,18 +152,18 @@ pub async fn handle_multiplexed_ws(
         warn!(conn_id = %connection_id, "Failed to send initial instance list - channel closed");
     }
 
-    // Send initial inbox state
+    // Send initial inbox state, limited like the instance list
     if let Some(ref repo) = repository {
         match repo.list_inbox().await {
-            Ok(items) if !items.is_empty() => {
-                if tx.send(ServerMessage::InboxList { items }).await.is_err() {
+            Ok(items) => {
+                let items = visible.visible_inbox(items).await;
+                if !items.is_empty() && tx.send(ServerMessage::InboxList { items }).await.is_err() {
                     warn!(conn_id = %connection_id, "Failed to send initial inbox list - channel closed");
                 }
             }
             Err(e) => {
                 warn!(conn_id = %connection_id, "Failed to load inbox: {}", e);
             }
-            _ => {} // Empty inbox, nothing to send
         }
     }
 
@@ -102,10 +172,24 @@ pub async fn handle_multiplexed_ws(
     let tx_state = tx.clone();
     let stats_state = stats.clone();
     let state_manager_for_broadcast = state_manager.clone();
+    let subscriptions_state = subscriptions.clone();
+    let focused_state = focused_instance.clone();
+    let mut visible_state = visible.clone();
     let state_broadcast_task = async move {
         loop {
             match state_rx.recv().await {
                 Ok((instance_id, state, stale)) => {
+                    let wanted = {
+                        let focused = focused_state.read().await;
+                        subscriptions_state
+                            .read()
+                            .await
+                            .wants_instance(&instance_id, focused.as_deref())
+                    };
+                    if !wanted || !visible_state.can_see(&instance_id).await {
+                        stats_state.record_filtered();
+                        continue;
+                    }
                     stats_state.record_state_send(1); // At least 1 receiver (us)
                     let entered_at = state_manager_for_broadcast
                         .get_state_entered_at(&instance_id)
@@ -135,13 +219,42 @@ pub async fn handle_multiplexed_ws(
         }
     };
 
-    // Subscribe to instance lifecycle broadcasts (created/stopped)
-    let mut lifecycle_rx = state_manager.subscribe_lifecycle();
+    // Forward instance lifecycle broadcasts (created/stopped, ...), after
+    // replaying whatever a resuming client missed.
     let tx_lifecycle = tx.clone();
+    let stats_lifecycle = stats.clone();
+    let subscriptions_lifecycle = subscriptions.clone();
+    let focused_lifecycle = focused_instance.clone();
+    let mut visible_lifecycle = visible;
     let lifecycle_task = async move {
+        for (seq, msg) in missed_lifecycle.into_iter().flatten() {
+            if !visible_lifecycle.allows(&msg).await {
+                continue;
+            }
+            let msg = ServerMessage::Sequenced {
+                seq,
+                message: Box::new(msg),
+            };
+            if tx_lifecycle.send(msg).await.is_err() {
+                return;
+            }
+        }
         loop {
             match lifecycle_rx.recv().await {
-                Ok(msg) => {
+                Ok((seq, _)) if resumed && seq <= lifecycle_seq => {
+                    // Already replayed above
+                }
+                Ok((_, msg))
+                    if !subscribed(&subscriptions_lifecycle, &focused_lifecycle, &msg).await
+                        || !visible_lifecycle.allows(&msg).await =>
+                {
+                    stats_lifecycle.record_filtered();
+                }
+                Ok((seq, msg)) => {
+                    let msg = ServerMessage::Sequenced {
+                        seq,
+                        message: Box::new(msg),
+                    };
                     if tx_lifecycle.send(msg).await.is_err() {
                         break;
                     }
@@ -155,14 +268,17 @@ pub async fn handle_multiplexed_ws(
     // Task to send messages to WebSocket
     let sender_task = async move {
         while let Some(msg) = rx.recv().await {
-            let json = match serde_json::to_string(&msg) {
-                Ok(j) => j,
-                Err(e) => {
-                    error!("Failed to serialize message: {}", e);
-                    continue;
-                }
+            let ws_msg = match msg {
+                ServerMessage::Frame(frame) => Message::Binary(frame.encode().into()),
+                msg => match msg.to_json() {
+                    Ok(json) => Message::Text(json.into()),
+                    Err(e) => {
+                        error!("Failed to serialize message: {}", e);
+                        continue;
+                    }
+                },
             };
-            if ws_sender.send(Message::Text(json.into())).await.is_err() {
+            if ws_sender.send(ws_msg).await.is_err() {
                 break;
             }
         }
@@ -180,6 +296,10 @@ pub async fn handle_multiplexed_ws(
     let ws_user_clone = ws_user.clone();
     let connection_id_clone = connection_id.clone();
     let repository_clone = repository.clone();
+    let binary_frames_clone = binary_frames.clone();
+    let output_mode_clone = output_mode.clone();
+    let spectating_clone = spectating.clone();
+    let subscriptions_clone = subscriptions.clone();
 
     let input_task = async move {
         while let Some(msg) = ws_receiver.next().await {
@@ -190,7 +310,23 @@ pub async fn handle_multiplexed_ws(
                             ClientMessage::Focus {
                                 instance_id,
                                 since_uuid,
+                                output_seq,
                             } => {
+                                if !can_view(
+                                    ws_user_clone.as_ref(),
+                                    repository_clone.as_ref(),
+                                    &instance_id,
+                                )
+                                .await
+                                {
+                                    let _ = tx_input
+                                        .send(ServerMessage::Error {
+                                            instance_id: Some(instance_id),
+                                            message: "No access to this instance".to_string(),
+                                        })
+                                        .await;
+                                    continue;
+                                }
                                 // Cancel previous focus tasks
                                 {
                                     let mut guard = focus_cancel_clone.write().await;
@@ -242,6 +378,23 @@ pub async fn handle_multiplexed_ws(
                                     state_mgr
                                         .reconcile_terminal_lock_with_presence(&instance_id)
                                         .await;
+                                    // Viewers and spectators don't type, so they don't keep an
+                                    // auto-granted lock
+                                    if spectating_clone.read().await.contains(&instance_id)
+                                        || !can_operate(
+                                            Some(user),
+                                            repository_clone.as_ref(),
+                                            &instance_id,
+                                        )
+                                        .await
+                                    {
+                                        state_mgr
+                                            .release_terminal_lock(
+                                                &instance_id,
+                                                &connection_id_clone,
+                                            )
+                                            .await;
+                                    }
                                     // Also reconcile previous instance (user left, sole remaining user may get auto-grant)
                                     if let Some(ref prev_id) = prev_instance {
                                         state_mgr
@@ -250,10 +403,7 @@ pub async fn handle_multiplexed_ws(
                                         broadcast_terminal_lock_update(&state_mgr, prev_id).await;
                                     }
                                     // Send current lock state to the newly focused client
-                                    let lock_msg = build_lock_update_message(
-                                        &instance_id,
-                                        state_mgr.get_terminal_lock(&instance_id).await,
-                                    );
+                                    let lock_msg = lock_update(&state_mgr, &instance_id).await;
                                     let _ = tx_input.send(lock_msg).await;
                                     // Also broadcast to all clients so everyone sees updated lock state
                                     broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
@@ -269,17 +419,29 @@ pub async fn handle_multiplexed_ws(
                                 let session_rx = session_rx_clone.clone();
                                 let (refresh_tx, refresh_rx) = mpsc::channel::<u16>(4);
                                 *focus_refresh_clone.write().await = Some(refresh_tx);
+                                let user_id = ws_user_clone.as_ref().map(|u| u.user_id.clone());
+                                let repository = repository_clone.clone();
+                                let binary_frames = binary_frames_clone.clone();
+                                let output_mode = output_mode_clone.subscribe();
+                                // Output sequences only mean something to a
+                                // client that resumed this server run.
+                                let resume_seq = if resumed { output_seq } else { None };
 
                                 tokio::spawn(async move {
                                     handle_focus(
                                         instance_id,
                                         since_uuid,
+                                        resume_seq,
                                         cancel_token,
                                         state_mgr_focus,
                                         inst_mgr_focus,
                                         tx_focus,
                                         session_rx,
                                         refresh_rx,
+                                        user_id,
+                                        repository,
+                                        binary_frames,
+                                        output_mode,
                                     )
                                     .await;
                                 });
@@ -311,8 +473,8 @@ pub async fn handle_multiplexed_ws(
                                 task_id,
                             } => {
                                 let ctx = InputContext {
-                                    instance_id: instance_id.clone(),
-                                    data,
+                                    instance_id,
+                                    data: data.into_bytes(),
                                     connection_id: connection_id_clone.clone(),
                                     user: ws_user_clone.as_ref().map(|u| InputUser {
                                         user_id: u.user_id.clone(),
@@ -320,17 +482,15 @@ pub async fn handle_multiplexed_ws(
                                     }),
                                     task_id,
                                 };
-                                if let Err(e) =
-                                    state_mgr.handle_input(ctx, repository_clone.as_ref()).await
-                                {
-                                    error!(instance = %instance_id, "Failed to write to PTY: {}", e);
-                                    let _ = tx_input
-                                        .send(ServerMessage::Error {
-                                            instance_id: Some(instance_id),
-                                            message: format!("Failed to send input: {}", e),
-                                        })
-                                        .await;
-                                }
+                                forward_input(
+                                    &state_mgr,
+                                    ctx,
+                                    &spectating_clone,
+                                    ws_user_clone.as_ref(),
+                                    repository_clone.as_ref(),
+                                    &tx_input,
+                                )
+                                .await;
                             }
                             ClientMessage::Resize {
                                 instance_id,
@@ -338,10 +498,19 @@ pub async fn handle_multiplexed_ws(
                                 cols,
                                 client_type,
                             } => {
-                                let ct = match client_type.as_deref() {
-                                    Some("terminal") => ClientType::Terminal,
-                                    _ => ClientType::Web,
-                                };
+                                let operate = can_operate(
+                                    ws_user_clone.as_ref(),
+                                    repository_clone.as_ref(),
+                                    &instance_id,
+                                )
+                                .await;
+                                let ct = client_type_for(
+                                    &spectating_clone,
+                                    &instance_id,
+                                    client_type.as_deref(),
+                                    operate,
+                                )
+                                .await;
                                 if let Some(handle) = state_mgr.get_handle(&instance_id).await
                                     && let Err(e) = handle
                                         .update_viewport_and_resize(
@@ -361,10 +530,19 @@ pub async fn handle_multiplexed_ws(
                                 cols,
                                 client_type,
                             } => {
-                                let ct = match client_type.as_deref() {
-                                    Some("terminal") => ClientType::Terminal,
-                                    _ => ClientType::Web,
-                                };
+                                let operate = can_operate(
+                                    ws_user_clone.as_ref(),
+                                    repository_clone.as_ref(),
+                                    &instance_id,
+                                )
+                                .await;
+                                let ct = client_type_for(
+                                    &spectating_clone,
+                                    &instance_id,
+                                    client_type.as_deref(),
+                                    operate,
+                                )
+                                .await;
                                 debug!(
                                     conn = %connection_id_clone,
                                     instance = %instance_id,
@@ -410,6 +588,7 @@ pub async fn handle_multiplexed_ws(
                                             &instance_id,
                                             usize::MAX,
                                             rows,
+                                            binary_frames_clone.load(Ordering::Relaxed),
                                             &tx_input,
                                         )
                                         .await;
@@ -443,26 +622,61 @@ pub async fn handle_multiplexed_ws(
                                     payload,
                                 });
                             }
-                            ClientMessage::TerminalLockRequest { instance_id } => {
-                                if let Some(ref user) = ws_user_clone {
-                                    let acquired = state_mgr
-                                        .try_acquire_terminal_lock(
+                            ClientMessage::TerminalLockRequest { instance_id, force } => {
+                                let spectator =
+                                    spectating_clone.read().await.contains(&instance_id)
+                                        || !can_operate(
+                                            ws_user_clone.as_ref(),
+                                            repository_clone.as_ref(),
                                             &instance_id,
-                                            &connection_id_clone,
-                                            user,
                                         )
                                         .await;
-                                    if acquired {
-                                        broadcast_terminal_lock_update(&state_mgr, &instance_id)
+                                if spectator {
+                                    // Spectators and viewers can't type, so they can't hold the lock
+                                    let msg = lock_update(&state_mgr, &instance_id).await;
+                                    let _ = tx_input.send(msg).await;
+                                } else if let Some(ref user) = ws_user_clone {
+                                    if force && user.restricted {
+                                        let _ = tx_input
+                                            .send(ServerMessage::Error {
+                                                instance_id: Some(instance_id.clone()),
+                                                message: "Only admins can take the terminal"
+                                                    .to_string(),
+                                            })
                                             .await;
-                                    } else {
-                                        // Send current state back so the client knows who holds it
-                                        let msg = build_lock_update_message(
+                                    } else if force {
+                                        state_mgr
+                                            .force_take_terminal_lock(
+                                                &instance_id,
+                                                &connection_id_clone,
+                                                user,
+                                            )
+                                            .await;
+                                    } else if !state_mgr
+                                        .try_acquire_terminal_lock(
                                             &instance_id,
-                                            state_mgr.get_terminal_lock(&instance_id).await,
-                                        );
-                                        let _ = tx_input.send(msg).await;
+                                            &connection_id_clone,
+                                            user,
+                                        )
+                                        .await
+                                        && state_mgr
+                                            .enqueue_terminal_lock_request(
+                                                &instance_id,
+                                                &connection_id_clone,
+                                                user,
+                                            )
+                                            .await
+                                            == Some(1)
+                                    {
+                                        // First in line: hand over if the holder goes quiet
+                                        tokio::spawn(watch_terminal_lock_expiry(
+                                            state_mgr.clone(),
+                                            instance_id.clone(),
+                                        ));
                                     }
+                                    // Everyone sees the new holder or queue; the
+                                    // holder's client prompts to grant or deny
+                                    broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
                                 }
                             }
                             ClientMessage::TerminalLockRelease { instance_id } => {
@@ -470,11 +684,50 @@ pub async fn handle_multiplexed_ws(
                                     .release_terminal_lock(&instance_id, &connection_id_clone)
                                     .await;
                                 if released {
-                                    // Reconcile: may auto-grant to remaining sole user
+                                    // Reconcile: passes to the next waiter, or may
+                                    // auto-grant to remaining sole user
                                     state_mgr
                                         .reconcile_terminal_lock_with_presence(&instance_id)
                                         .await;
                                     broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
+                                } else if state_mgr
+                                    .withdraw_terminal_lock_request(
+                                        &instance_id,
+                                        &connection_id_clone,
+                                    )
+                                    .await
+                                {
+                                    broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
+                                }
+                            }
+                            ClientMessage::TerminalLockGrant {
+                                instance_id,
+                                user_id,
+                            } => {
+                                if state_mgr
+                                    .grant_terminal_lock(
+                                        &instance_id,
+                                        &connection_id_clone,
+                                        user_id.as_deref(),
+                                    )
+                                    .await
+                                {
+                                    broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
+                                }
+                            }
+                            ClientMessage::TerminalLockDeny {
+                                instance_id,
+                                user_id,
+                            } => {
+                                if state_mgr
+                                    .deny_terminal_lock_request(
+                                        &instance_id,
+                                        &connection_id_clone,
+                                        user_id.as_deref(),
+                                    )
+                                    .await
+                                {
+                                    broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
                                 }
                             }
                             ClientMessage::ChatSend {
@@ -608,6 +861,66 @@ pub async fn handle_multiplexed_ws(
                                     });
                                 }
                             }
+                            ClientMessage::EnableBinaryFrames => {
+                                binary_frames_clone.store(true, Ordering::Relaxed);
+                                let _ = tx_input.send(ServerMessage::BinaryFramesEnabled).await;
+                            }
+                            ClientMessage::Subscribe { topics } => {
+                                let mut subs = subscriptions_clone.write().await;
+                                subs.subscribe(topics);
+                                let topics = subs.topics().unwrap_or_default();
+                                drop(subs);
+                                let _ = tx_input.send(ServerMessage::Subscribed { topics }).await;
+                            }
+                            ClientMessage::Unsubscribe { topics } => {
+                                let mut subs = subscriptions_clone.write().await;
+                                subs.unsubscribe(&topics);
+                                let topics = subs.topics().unwrap_or_default();
+                                drop(subs);
+                                let _ = tx_input.send(ServerMessage::Subscribed { topics }).await;
+                            }
+                            ClientMessage::SetOutputMode { mode, max_fps } => {
+                                let settings = OutputSettings::new(mode, max_fps);
+                                output_mode_clone.send_replace(settings);
+                                let _ = tx_input.send(output_mode_set(settings)).await;
+                            }
+                            ClientMessage::Hello {
+                                protocol_version,
+                                client_version,
+                                capabilities,
+                            } => {
+                                info!(
+                                    conn_id = %connection_id_clone,
+                                    protocol_version,
+                                    client_version = %client_version,
+                                    "Client hello"
+                                );
+                                if protocol_version < MIN_CLIENT_PROTOCOL_VERSION {
+                                    // Keep serving, but tell the user why
+                                    // things may misbehave.
+                                    let _ = tx_input
+                                        .send(ServerMessage::Error {
+                                            instance_id: None,
+                                            message: format!(
+                                                "crab {client_version} (protocol v{protocol_version}) \
+                                                 is older than this server supports \
+                                                 (v{MIN_CLIENT_PROTOCOL_VERSION}); please upgrade"
+                                            ),
+                                        })
+                                        .await;
+                                }
+                                let negotiated = negotiate(&capabilities);
+                                if negotiated.contains(&Capability::BinaryFrames) {
+                                    binary_frames_clone.store(true, Ordering::Relaxed);
+                                    let _ = tx_input.send(ServerMessage::BinaryFramesEnabled).await;
+                                }
+                                if negotiated.contains(&Capability::ScreenDiff) {
+                                    let settings =
+                                        OutputSettings::new(OutputMode::ScreenDiff, None);
+                                    output_mode_clone.send_replace(settings);
+                                    let _ = tx_input.send(output_mode_set(settings)).await;
+                                }
+                            }
                             ClientMessage::ChatTopics { scope } => {
                                 if let Some(ref repo) = repository_clone {
                                     let repo = repo.clone();
@@ -632,6 +945,40 @@ pub async fn handle_multiplexed_ws(
                         }
                     }
                 }
+                Ok(Message::Binary(bytes)) => match TerminalFrame::decode(&bytes) {
+                    Ok(TerminalFrame {
+                        kind: FrameKind::Input,
+                        instance_id,
+                        data,
+                        ..
+                    }) => {
+                        let ctx = InputContext {
+                            instance_id,
+                            data,
+                            connection_id: connection_id_clone.clone(),
+                            user: ws_user_clone.as_ref().map(|u| InputUser {
+                                user_id: u.user_id.clone(),
+                                display_name: u.display_name.clone(),
+                            }),
+                            task_id: None,
+                        };
+                        forward_input(
+                            &state_mgr,
+                            ctx,
+                            &spectating_clone,
+                            ws_user_clone.as_ref(),
+                            repository_clone.as_ref(),
+                            &tx_input,
+                        )
+                        .await;
+                    }
+                    Ok(frame) => {
+                        debug!(kind = ?frame.kind, "Ignoring non-input frame from client");
+                    }
+                    Err(e) => {
+                        warn!(conn = %connection_id_clone, "Bad binary frame: {}", e);
+                    }
+                },
                 Ok(Message::Close(_)) => {
                     debug!("Client closed connection");
                     break;
@@ -724,10 +1071,143 @@ pub async fn handle_multiplexed_ws(
     }
 }
 
+/// Map a viewport's wire `client_type` and record whether the connection is
+/// now spectating `instance_id`. Users who can't operate the instance always
+/// spectate, so their viewport never sizes the PTY.
+async fn client_type_for(
+    spectating: &RwLock<HashSet<String>>,
+    instance_id: &str,
+    client_type: Option<&str>,
+    can_operate: bool,
+) -> ClientType {
+    let ct = match client_type {
+        _ if !can_operate => ClientType::Spectator,
+        Some("terminal") => ClientType::Terminal,
+        Some("spectator") => ClientType::Spectator,
+        _ => ClientType::Web,
+    };
+    let mut spectating = spectating.write().await;
+    if ct == ClientType::Spectator {
+        spectating.insert(instance_id.to_string());
+    } else {
+        spectating.remove(instance_id);
+    }
+    ct
+}
+
+/// Whether `user` may affect `instance_id`'s terminal (input, resize, lock).
+/// Only restricted users are checked: they need at least the operator role.
+async fn can_operate(
+    user: Option<&WsUser>,
+    repository: Option<&Arc<ConversationRepository>>,
+    instance_id: &str,
+) -> bool {
+    let Some(user) = user.filter(|u| u.restricted) else {
+        return true;
+    };
+    let Some(repo) = repository else {
+        return false;
+    };
+    match repo
+        .check_instance_permission(instance_id, &user.user_id)
+        .await
+    {
+        Ok(Some(permission)) => permission.role.can_operate(),
+        Ok(None) => false,
+        Err(e) => {
+            warn!(instance = %instance_id, "Failed to check instance role: {}", e);
+            false
+        }
+    }
+}
+
+/// Whether `user` may watch `instance_id` (focus it, receive its events).
+/// Only restricted users are checked: any grant will do.
+async fn can_view(
+    user: Option<&WsUser>,
+    repository: Option<&Arc<ConversationRepository>>,
+    instance_id: &str,
+) -> bool {
+    let Some(user) = user.filter(|u| u.restricted) else {
+        return true;
+    };
+    let Some(repo) = repository else {
+        return false;
+    };
+    match repo
+        .check_instance_permission(instance_id, &user.user_id)
+        .await
+    {
+        Ok(permission) => permission.is_some(),
+        Err(e) => {
+            warn!(instance = %instance_id, "Failed to check instance role: {}", e);
+            false
+        }
+    }
+}
+
+/// Write client input to the instance's PTY, reporting failures back to the
+/// client as an `Error`. Input from a spectator of the instance, or from a
+/// user without the operator role, is refused.
+async fn forward_input(
+    state_mgr: &GlobalStateManager,
+    ctx: InputContext,
+    spectating: &RwLock<HashSet<String>>,
+    user: Option<&WsUser>,
+    repository: Option<&Arc<ConversationRepository>>,
+    tx: &mpsc::Sender<ServerMessage>,
+) {
+    let instance_id = ctx.instance_id.clone();
+    let refusal = if spectating.read().await.contains(&instance_id) {
+        Some("Spectators can't send input")
+    } else if !can_operate(user, repository, &instance_id).await {
+        Some("Viewers can't send input")
+    } else {
+        None
+    };
+    if let Some(message) = refusal {
+        let _ = tx
+            .send(ServerMessage::Error {
+                instance_id: Some(instance_id),
+                message: message.to_string(),
+            })
+            .await;
+        return;
+    }
+    if let Err(e) = state_mgr.handle_input(ctx, repository).await {
+        error!(instance = %instance_id, "Failed to write to PTY: {}", e);
+        let _ = tx
+            .send(ServerMessage::Error {
+                instance_id: Some(instance_id),
+                message: format!("Failed to send input: {}", e),
+            })
+            .await;
+    }
+}
+
+/// Whether this connection's subscriptions let a broadcast through.
+async fn subscribed(
+    subscriptions: &RwLock<Subscriptions>,
+    focused: &RwLock<Option<String>>,
+    msg: &ServerMessage,
+) -> bool {
+    let focused = focused.read().await;
+    subscriptions.read().await.wants(msg, focused.as_deref())
+}
+
+/// Acknowledge a change of output mode.
+fn output_mode_set(settings: OutputSettings) -> ServerMessage {
+    ServerMessage::OutputModeSet {
+        mode: settings.mode,
+        max_fps: settings.screen_diff().then_some(settings.max_fps),
+    }
+}
+
 /// Build a TerminalLockUpdate message from the current lock state.
 fn build_lock_update_message(
     instance_id: &str,
     lock: Option<super::state_manager::TerminalLock>,
+    queue: Vec<PresenceUser>,
 ) -> ServerMessage {
     match lock {
         Some(lock) => {
@@ -744,6 +1224,7 @@ fn build_lock_update_message(
                 }),
                 last_activity: Some(lock.last_activity.to_rfc3339()),
                 expires_in_secs: Some(expires_in),
+                queue,
             }
         }
         None => ServerMessage::TerminalLockUpdate {
@@ -751,17 +1232,55 @@ fn build_lock_update_message(
             holder: None,
             last_activity: None,
             expires_in_secs: None,
+            queue,
         },
     }
 }
 
-/// Broadcast the current terminal lock state for an instance to all connected clients.
+/// The current lock state and queue for an instance.
+async fn lock_update(state_mgr: &GlobalStateManager, instance_id: &str) -> ServerMessage {
+    build_lock_update_message(
+        instance_id,
+        state_mgr.get_terminal_lock(instance_id).await,
+        state_mgr.get_terminal_lock_queue(instance_id).await,
+    )
+}
+
+/// Broadcast the current terminal lock state for an instance to all connected
+/// clients, and make the holder the size owner for `SizePolicy::LockHolder`.
 async fn broadcast_terminal_lock_update(state_mgr: &Arc<GlobalStateManager>, instance_id: &str) {
     let lock = state_mgr.get_terminal_lock(instance_id).await;
-    let msg = build_lock_update_message(instance_id, lock);
+    if let Some(handle) = state_mgr.get_handle(instance_id).await {
+        let owner = lock.as_ref().map(|l| l.holder_connection_id.as_str());
+        if let Err(e) = handle.set_size_owner_and_resize(owner).await {
+            warn!("Failed to resize PTY for {}: {}", instance_id, e);
+        }
+    }
+    let msg = build_lock_update_message(
+        instance_id,
+        lock,
+        state_mgr.get_terminal_lock_queue(instance_id).await,
+    );
     state_mgr.broadcast_lifecycle(msg);
 }
 
+/// While anyone waits for an instance's lock, pass it on when the holder
+/// has been idle for the lock timeout instead of letting it lapse silently.
+async fn watch_terminal_lock_expiry(state_mgr: Arc<GlobalStateManager>, instance_id: String) {
+    loop {
+        match state_mgr
+            .hand_over_expired_terminal_lock(&instance_id)
+            .await
+        {
+            LockExpiry::Active(wait) => tokio::time::sleep(wait).await,
+            LockExpiry::HandedOver => {
+                broadcast_terminal_lock_update(&state_mgr, &instance_id).await;
+            }
+            LockExpiry::Idle => return,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::state_manager::TerminalLock;
@@ -770,13 +1289,14 @@ mod tests {
 
     #[test]
     fn build_lock_update_no_lock() {
-        let msg = build_lock_update_message("inst-1", None);
+        let msg = build_lock_update_message("inst-1", None, Vec::new());
         match msg {
             ServerMessage::TerminalLockUpdate {
                 instance_id,
                 holder,
                 last_activity,
                 expires_in_secs,
+                ..
             } => {
                 assert_eq!(instance_id, "inst-1");
                 assert!(holder.is_none());
@@ -796,13 +1316,14 @@ mod tests {
             last_activity: Utc::now(),
         };
 
-        let msg = build_lock_update_message("inst-1", Some(lock));
+        let msg = build_lock_update_message("inst-1", Some(lock), Vec::new());
         match msg {
             ServerMessage::TerminalLockUpdate {
                 instance_id,
                 holder,
                 last_activity,
                 expires_in_secs,
+                ..
             } => {
                 assert_eq!(instance_id, "inst-1");
                 let h = holder.unwrap();
@@ -828,7 +1349,7 @@ mod tests {
             last_activity: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
         };
 
-        let msg = build_lock_update_message("inst-1", Some(lock));
+        let msg = build_lock_update_message("inst-1", Some(lock), Vec::new());
         match msg {
             ServerMessage::TerminalLockUpdate {
                 expires_in_secs, ..
@@ -849,7 +1370,7 @@ mod tests {
             last_activity: Utc::now(),
         };
 
-        let msg = build_lock_update_message("inst-2", Some(lock));
+        let msg = build_lock_update_message("inst-2", Some(lock), Vec::new());
         match msg {
             ServerMessage::TerminalLockUpdate { last_activity, .. } => {
                 let ts = last_activity.unwrap();
@@ -859,4 +1380,133 @@ mod tests {
             _ => panic!("Expected TerminalLockUpdate"),
         }
     }
+
+    #[tokio::test]
+    async fn spectators_cannot_send_input() {
+        let spectating = RwLock::new(HashSet::new());
+        let ct = client_type_for(&spectating, "inst-1", Some("spectator"), true).await;
+        assert_eq!(ct, ClientType::Spectator);
+
+        let state_mgr = GlobalStateManager::new(crate::ws::create_state_broadcast());
+        let (tx, mut rx) = mpsc::channel(4);
+        let ctx = InputContext {
+            instance_id: "inst-1".to_string(),
+            data: "ls\r".into(),
+            connection_id: "conn-1".to_string(),
+            user: None,
+            task_id: None,
+        };
+        forward_input(&state_mgr, ctx, &spectating, None, None, &tx).await;
+        match rx.recv().await.unwrap() {
+            ServerMessage::Error { message, .. } => assert!(message.contains("Spectators")),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // Switching back to a regular viewport lifts the restriction
+        let ct = client_type_for(&spectating, "inst-1", Some("terminal"), true).await;
+        assert_eq!(ct, ClientType::Terminal);
+        assert!(spectating.read().await.is_empty());
+    }
+    #[tokio::test]
+    async fn viewers_spectate_and_cannot_send_input() {
+        use crate::models::{InstancePermission, InstanceRole};
+
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let repo = state.repository.clone();
+        crate::test_helpers::create_test_user(&repo, "u-1", "bob", "Bob").await;
+        repo.create_instance_permission(&InstancePermission {
+            instance_id: "inst-1".to_string(),
+            user_id: "u-1".to_string(),
+            role: InstanceRole::Viewer,
+            granted_at: 0,
+            granted_by: None,
+        })
+        .await
+        .unwrap();
+        let bob = WsUser {
+            user_id: "u-1".to_string(),
+            display_name: "Bob".to_string(),
+            restricted: true,
+        };
+
+        assert!(!can_operate(Some(&bob), Some(&repo), "inst-1").await);
+        // No grant at all is no better than a viewer grant
+        assert!(!can_operate(Some(&bob), Some(&repo), "inst-2").await);
+        // Unrestricted users (admins, local) are never checked
+        let admin = WsUser {
+            restricted: false,
+            ..bob.clone()
+        };
+        assert!(can_operate(Some(&admin), None, "inst-2").await);
+
+        let spectating = RwLock::new(HashSet::new());
+        let ct = client_type_for(&spectating, "inst-1", Some("terminal"), false).await;
+        assert_eq!(ct, ClientType::Spectator);
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let ctx = InputContext {
+            instance_id: "inst-1".to_string(),
+            data: "ls\r".into(),
+            connection_id: "conn-1".to_string(),
+            user: None,
+            task_id: None,
+        };
+        spectating.write().await.clear();
+        forward_input(
+            &state.global_state_manager,
+            ctx,
+            &spectating,
+            Some(&bob),
+            Some(&repo),
+            &tx,
+        )
+        .await;
+        match rx.recv().await.unwrap() {
+            ServerMessage::Error { message, .. } => assert!(message.contains("Viewers")),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // Promoting to operator restores input rights
+        repo.create_instance_permission(&InstancePermission {
+            instance_id: "inst-1".to_string(),
+            user_id: "u-1".to_string(),
+            role: InstanceRole::Operator,
+            granted_at: 0,
+            granted_by: None,
+        })
+        .await
+        .unwrap();
+        assert!(can_operate(Some(&bob), Some(&repo), "inst-1").await);
+    }
+
+    #[tokio::test]
+    async fn restricted_users_only_see_granted_instances() {
+        use crate::models::{InstancePermission, InstanceRole};
+
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let repo = state.repository.clone();
+        crate::test_helpers::create_test_user(&repo, "u-1", "bob", "Bob").await;
+        repo.create_instance_permission(&InstancePermission {
+            instance_id: "inst-1".to_string(),
+            user_id: "u-1".to_string(),
+            role: InstanceRole::Viewer,
+            granted_at: 0,
+            granted_by: None,
+        })
+        .await
+        .unwrap();
+        let bob = WsUser {
+            user_id: "u-1".to_string(),
+            display_name: "Bob".to_string(),
+            restricted: true,
+        };
+
+        assert!(can_view(Some(&bob), Some(&repo), "inst-1").await);
+        assert!(!can_view(Some(&bob), Some(&repo), "inst-2").await);
+        let admin = WsUser {
+            restricted: false,
+            ..bob.clone()
+        };
+        assert!(can_view(Some(&admin), None, "inst-2").await);
+    }
 }
@@ -0,0 +1,161 @@
+//! Protocol Handshake
+//!
+//! The server opens every mux connection with `ServerMessage::Welcome`,
+//! carrying its protocol version, the oldest client protocol it still serves,
+//! its release version and the optional features it supports. A client
+//! checks that before anything else and answers with `ClientMessage::Hello`
+//! naming its own version and the capabilities it wants switched on.
+//!
+//! Clients that predate the handshake never send `Hello`; they count as
+//! protocol 1 and keep working as long as [`MIN_CLIENT_PROTOCOL_VERSION`]
+//! is 1. Likewise a server whose first message is not `Welcome` predates the
+//! handshake and is treated as protocol 1 by clients.
+
+use serde::{Deserialize, Serialize};
+
+/// Mux protocol version spoken by this build. Bump when a change would break
+/// a peer that only knows the previous version.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest client protocol this server still accepts.
+pub const MIN_CLIENT_PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest server protocol this build's clients can talk to.
+pub const MIN_SERVER_PROTOCOL_VERSION: u32 = 1;
+
+/// Release version of this build, reported in the handshake.
+pub const SOFTWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Optional protocol features, negotiated in the handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Terminal output/input as binary frames (see `ws::frame`)
+    BinaryFrames,
+    /// permessage-deflate on the WebSocket
+    Compression,
+    /// Resume tokens and sequence numbers (see `ws::replay`)
+    Resume,
+    /// Coalesced screen-diff output (see `ws::output_mode`)
+    ScreenDiff,
+    /// A capability from a newer peer; ignored
+    #[serde(other)]
+    Unknown,
+}
+
+/// What this server offers in its `Welcome`.
+pub const SERVER_CAPABILITIES: &[Capability] = &[
+    Capability::BinaryFrames,
+    Capability::Resume,
+    Capability::ScreenDiff,
+];
+
+/// The two ends cannot talk to each other.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VersionMismatch {
+    #[error(
+        "this crab speaks protocol v{client} but the server (crab {server_version}) needs at \
+         least v{min}; upgrade crab to match the server"
+    )]
+    ClientTooOld {
+        client: u32,
+        min: u32,
+        server_version: String,
+    },
+    #[error(
+        "the server (crab {server_version}) speaks protocol v{server} but this crab needs at \
+         least v{min}; restart the daemon with this version (`crab kill-server`, then run crab \
+         again)"
+    )]
+    ServerTooOld {
+        server: u32,
+        min: u32,
+        server_version: String,
+    },
+}
+
+/// Client side: check a server's `Welcome` against this build.
+pub fn check_server(
+    protocol_version: u32,
+    min_protocol_version: u32,
+    server_version: &str,
+) -> Result<(), VersionMismatch> {
+    if PROTOCOL_VERSION < min_protocol_version {
+        return Err(VersionMismatch::ClientTooOld {
+            client: PROTOCOL_VERSION,
+            min: min_protocol_version,
+            server_version: server_version.to_string(),
+        });
+    }
+    if protocol_version < MIN_SERVER_PROTOCOL_VERSION {
+        return Err(VersionMismatch::ServerTooOld {
+            server: protocol_version,
+            min: MIN_SERVER_PROTOCOL_VERSION,
+            server_version: server_version.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Server side: the capabilities to switch on for a client's `Hello`.
+pub fn negotiate(requested: &[Capability]) -> Vec<Capability> {
+    SERVER_CAPABILITIES
+        .iter()
+        .copied()
+        .filter(|c| requested.contains(c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_serialize_snake_case() {
+        let json = serde_json::to_string(&[Capability::BinaryFrames, Capability::Resume]).unwrap();
+        assert_eq!(json, r#"["binary_frames","resume"]"#);
+    }
+
+    #[test]
+    fn unknown_capabilities_are_tolerated() {
+        let caps: Vec<Capability> = serde_json::from_str(r#"["resume","telepathy"]"#).unwrap();
+        assert_eq!(caps, [Capability::Resume, Capability::Unknown]);
+    }
+
+    #[test]
+    fn negotiate_keeps_only_supported() {
+        assert_eq!(
+            negotiate(&[
+                Capability::Compression,
+                Capability::BinaryFrames,
+                Capability::Unknown
+            ]),
+            [Capability::BinaryFrames]
+        );
+        assert!(negotiate(&[]).is_empty());
+    }
+
+    #[test]
+    fn check_server_accepts_same_version() {
+        assert_eq!(
+            check_server(PROTOCOL_VERSION, MIN_CLIENT_PROTOCOL_VERSION, "1.0.0"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_server_rejects_newer_minimum() {
+        let err = check_server(PROTOCOL_VERSION + 2, PROTOCOL_VERSION + 1, "9.0.0").unwrap_err();
+        assert!(matches!(err, VersionMismatch::ClientTooOld { .. }));
+        let msg = err.to_string();
+        assert!(msg.contains("upgrade crab"), "{msg}");
+        assert!(msg.contains("9.0.0"), "{msg}");
+    }
+
+    #[test]
+    fn check_server_rejects_ancient_server() {
+        let err = check_server(0, 0, "0.1.0").unwrap_err();
+        assert!(matches!(err, VersionMismatch::ServerTooOld { .. }));
+        assert!(err.to_string().contains("restart the daemon"));
+    }
+}
@@ -7,20 +7,35 @@
 
 mod conversation_watcher;
 mod focus;
+mod frame;
 mod handler;
+mod handshake;
 pub(crate) mod merging_watcher;
+mod output_mode;
 pub(crate) mod protocol;
+mod replay;
 mod session_discovery;
 mod state_manager;
+mod subscriptions;
+mod visibility;
 
 // Re-export the main types and functions
 pub(crate) use conversation_watcher::run_driver_conversation_watcher;
+pub use frame::{FrameError, FrameKind, TerminalFrame};
 pub use handler::handle_multiplexed_ws;
-pub use protocol::{ClientMessage, ServerMessage, WsUser};
+pub use handshake::{
+    Capability, MIN_CLIENT_PROTOCOL_VERSION, MIN_SERVER_PROTOCOL_VERSION, PROTOCOL_VERSION,
+    SERVER_CAPABILITIES, SOFTWARE_VERSION, VersionMismatch, check_server,
+};
+pub use output_mode::{DEFAULT_DIFF_FPS, MAX_DIFF_FPS, OutputMode, OutputSettings};
+pub use protocol::{ClientMessage, ServerMessage, TerminalNotificationKind, WsUser};
+pub use replay::{LifecycleBroadcast, ReplayWindow, ResumeParams, ResumeToken};
 pub use state_manager::{
     ConversationEvent, FirstInputData, GlobalStateManager, PendingAttribution, StateBroadcast,
-    create_state_broadcast,
+    StateTransition, create_state_broadcast, paste_enter_delay,
 };
+pub use subscriptions::{Subscriptions, Topic};
+pub(crate) use visibility::Visibility;
 // Re-exported for integration tests in instance_actor
 #[allow(unused_imports)]
 pub use state_manager::InputContext;
@@ -0,0 +1,93 @@
+//! Output Modes
+//!
+//! By default a connection receives every PTY chunk of its focused instance
+//! as it is produced. Clients on slow or remote links can switch to
+//! [`OutputMode::ScreenDiff`] (with `ClientMessage::SetOutputMode`, or by
+//! requesting the `screen_diff` capability in `Hello`): output is then
+//! coalesced on the server and sent as `ScreenDiff` frames at most
+//! `max_fps` times a second, each repainting only the cells that changed
+//! since the previous one (see `virtual_terminal::diff`).
+//!
+//! Frames are ordinary escape sequences, so clients render them exactly like
+//! `Output`. The focus replay (`OutputHistory`) is unaffected and still
+//! carries scrollback; later frames repaint the screen in place.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Frame rate when the client doesn't ask for one.
+pub const DEFAULT_DIFF_FPS: u32 = 10;
+/// Fastest frame rate a client may ask for.
+pub const MAX_DIFF_FPS: u32 = 30;
+
+/// How terminal output reaches a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Every PTY chunk as-is
+    #[default]
+    Raw,
+    /// Periodic screen diffs
+    ScreenDiff,
+}
+
+/// A connection's output mode and frame rate cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSettings {
+    pub mode: OutputMode,
+    /// Frames per second in `ScreenDiff` mode, within `1..=MAX_DIFF_FPS`
+    pub max_fps: u32,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self::new(OutputMode::Raw, None)
+    }
+}
+
+impl OutputSettings {
+    /// Settings for `mode`, with `max_fps` clamped to what the server allows.
+    pub fn new(mode: OutputMode, max_fps: Option<u32>) -> Self {
+        Self {
+            mode,
+            max_fps: max_fps.unwrap_or(DEFAULT_DIFF_FPS).clamp(1, MAX_DIFF_FPS),
+        }
+    }
+
+    pub fn screen_diff(&self) -> bool {
+        self.mode == OutputMode::ScreenDiff
+    }
+
+    /// Minimum time between two frames.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.max_fps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_rate_is_clamped() {
+        assert_eq!(
+            OutputSettings::new(OutputMode::ScreenDiff, None).max_fps,
+            DEFAULT_DIFF_FPS
+        );
+        assert_eq!(
+            OutputSettings::new(OutputMode::ScreenDiff, Some(0)).max_fps,
+            1
+        );
+        let fast = OutputSettings::new(OutputMode::ScreenDiff, Some(1000));
+        assert_eq!(fast.max_fps, MAX_DIFF_FPS);
+        assert_eq!(fast.frame_interval(), Duration::from_secs(1) / MAX_DIFF_FPS);
+    }
+
+    #[test]
+    fn mode_wire_format() {
+        assert_eq!(
+            serde_json::to_string(&OutputMode::ScreenDiff).unwrap(),
+            r#""screen_diff""#
+        );
+    }
+}
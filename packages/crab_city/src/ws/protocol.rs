@@ -5,6 +5,10 @@
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use super::frame::TerminalFrame;
+use super::handshake::Capability;
+use super::output_mode::OutputMode;
+use super::subscriptions::Topic;
 use crate::inference::ClaudeState;
 use crate::instance_manager::ClaudeInstance;
 #[cfg(test)]
@@ -15,6 +19,10 @@ use crate::instance_manager::InstanceKind;
 pub struct WsUser {
     pub user_id: String,
     pub display_name: String,
+    /// Non-admin with auth enabled: instance access follows their
+    /// per-instance role instead of being unrestricted.
+    #[serde(skip)]
+    pub restricted: bool,
 }
 
 /// User presence information broadcast to clients.
@@ -37,6 +45,8 @@ pub struct BackpressureStats {
     pub output_messages_lagged: AtomicU64,
     /// Total messages dropped due to lag
     pub total_lagged_count: AtomicU64,
+    /// Broadcasts not forwarded because the client did not subscribe to them
+    pub broadcasts_filtered: AtomicU64,
 }
 
 impl BackpressureStats {
@@ -57,6 +67,10 @@ impl BackpressureStats {
         self.output_messages_sent.fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn record_filtered(&self) {
+        self.broadcasts_filtered.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn record_lag(&self, lag_count: u64) {
         self.output_messages_lagged.fetch_add(1, Ordering::Relaxed);
         self.total_lagged_count
@@ -72,6 +86,7 @@ impl BackpressureStats {
             output_messages_sent: self.output_messages_sent.load(Ordering::Relaxed),
             output_messages_lagged: self.output_messages_lagged.load(Ordering::Relaxed),
             total_lagged_count: self.total_lagged_count.load(Ordering::Relaxed),
+            broadcasts_filtered: self.broadcasts_filtered.load(Ordering::Relaxed),
         }
     }
 }
@@ -84,6 +99,8 @@ pub struct BackpressureSnapshot {
     pub output_messages_sent: u64,
     pub output_messages_lagged: u64,
     pub total_lagged_count: u64,
+    #[serde(default)]
+    pub broadcasts_filtered: u64,
 }
 
 /// Messages sent FROM the client TO the server
@@ -98,6 +115,11 @@ pub enum ClientMessage {
         /// Optional: only return conversation entries after this UUID
         #[serde(default, skip_serializing_if = "Option::is_none")]
         since_uuid: Option<String>,
+        /// On a resumed connection: the last output sequence seen for this
+        /// instance. Only the missed chunks are sent if the server still has
+        /// them, otherwise a full `OutputHistory`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_seq: Option<u64>,
     },
     /// Request conversation sync without changing focus
     /// Useful for catching up when tab becomes visible
@@ -131,10 +153,27 @@ pub enum ClientMessage {
         channel: String,
         payload: serde_json::Value,
     },
-    /// Request terminal lock for an instance
-    TerminalLockRequest { instance_id: String },
-    /// Release terminal lock for an instance
+    /// Request terminal lock for an instance. If someone else holds it the
+    /// request is queued; admins may `force` to take it immediately.
+    TerminalLockRequest {
+        instance_id: String,
+        #[serde(default)]
+        force: bool,
+    },
+    /// Release terminal lock for an instance, or withdraw a queued request
     TerminalLockRelease { instance_id: String },
+    /// Holder hands the lock to a queued user (default: the oldest request)
+    TerminalLockGrant {
+        instance_id: String,
+        #[serde(default)]
+        user_id: Option<String>,
+    },
+    /// Holder turns down a queued request (default: the oldest)
+    TerminalLockDeny {
+        instance_id: String,
+        #[serde(default)]
+        user_id: Option<String>,
+    },
     /// Send a chat message
     ChatSend {
         scope: String,
@@ -171,6 +210,30 @@ pub enum ClientMessage {
     },
     /// Request list of topics for a scope
     ChatTopics { scope: String },
+    /// Switch this connection's terminal output to binary frames (see
+    /// `ws::frame`). Acknowledged with `BinaryFramesEnabled`.
+    EnableBinaryFrames,
+    /// Answer to `Welcome` (see `ws::handshake`): the client's protocol and
+    /// release version, and the capabilities it wants. Requesting
+    /// `binary_frames` here is equivalent to `EnableBinaryFrames`.
+    Hello {
+        protocol_version: u32,
+        client_version: String,
+        #[serde(default)]
+        capabilities: Vec<Capability>,
+    },
+    /// Only receive broadcasts matching these topics (plus the focused
+    /// instance's), see `ws::subscriptions`. Answered with `Subscribed`.
+    Subscribe { topics: Vec<Topic> },
+    /// Stop receiving broadcasts for these topics. Answered with `Subscribed`.
+    Unsubscribe { topics: Vec<Topic> },
+    /// Choose how terminal output is delivered (see `ws::output_mode`);
+    /// `max_fps` caps `screen_diff` frames. Answered with `OutputModeSet`.
+    SetOutputMode {
+        mode: OutputMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_fps: Option<u32>,
+    },
 }
 
 /// Messages sent FROM the server TO the client
@@ -188,6 +251,23 @@ pub enum ServerMessage {
     },
     /// Terminal history replay on focus switch
     OutputHistory { instance_id: String, data: String },
+    /// Coalesced screen update for connections in `screen_diff` output mode:
+    /// escape sequences repainting the cells that changed since the previous
+    /// frame (or the whole screen). Rendered like `Output`.
+    ScreenDiff { instance_id: String, data: String },
+    /// `Output`/`OutputHistory`/`ScreenDiff` for connections that negotiated
+    /// binary frames. Written as a binary WebSocket message, never as JSON.
+    #[serde(skip)]
+    Frame(TerminalFrame),
+    /// A lifecycle message, `Output`, `OutputHistory` or `ScreenDiff` with its
+    /// sequence number. Written as the inner message with a top-level `"seq"` field
+    /// (see `ws::replay`); lifecycle messages share one sequence, output is
+    /// numbered per instance.
+    #[serde(skip)]
+    Sequenced {
+        seq: u64,
+        message: Box<ServerMessage>,
+    },
     /// Full conversation on focus switch
     ConversationFull {
         instance_id: String,
@@ -230,6 +310,38 @@ pub enum ServerMessage {
     InstanceList { instances: Vec<ClaudeInstance> },
 
     // === Control messages ===
+    /// First message on every connection (see `ws::handshake`): the
+    /// server's protocol version, the oldest client protocol it accepts, its
+    /// release version and the capabilities it supports.
+    Welcome {
+        protocol_version: u32,
+        min_protocol_version: u32,
+        server_version: String,
+        capabilities: Vec<Capability>,
+    },
+    /// Sent right after `Welcome`: the token to reconnect with
+    /// (`/api/ws?resume=<token>&lifecycle_seq=<n>`), whether this connection
+    /// resumed the one named in the request, and the lifecycle sequence the
+    /// client is current to before any further `seq`-tagged messages. When
+    /// not resumed the client must treat its terminal and conversation state
+    /// as stale.
+    Resumable {
+        resume_token: String,
+        resumed: bool,
+        lifecycle_seq: u64,
+    },
+    /// Binary frames are on: terminal output follows as `Frame`s and the
+    /// client may send binary input frames.
+    BinaryFramesEnabled,
+    /// The connection's topics after a `Subscribe`/`Unsubscribe`
+    Subscribed { topics: Vec<Topic> },
+    /// The connection's output mode after `SetOutputMode` (or a `Hello`
+    /// requesting `screen_diff`); `max_fps` is the effective frame rate cap.
+    OutputModeSet {
+        mode: OutputMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_fps: Option<u32>,
+    },
     /// Acknowledge focus switch (sent before history replay)
     /// Includes current claude_state to prevent race conditions on focus switch
     FocusAck {
@@ -322,6 +434,9 @@ pub enum ServerMessage {
         /// Seconds until lock expires (for UI countdown)
         #[serde(skip_serializing_if = "Option::is_none")]
         expires_in_secs: Option<u64>,
+        /// Users waiting for the lock, next in line first
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        queue: Vec<PresenceUser>,
     },
 
     // === User settings ===
@@ -331,6 +446,31 @@ pub enum ServerMessage {
         settings: serde_json::Value,
     },
 
+    // === Terminal escapes ===
+    /// Window title changed via OSC 0/2 (None when cleared)
+    TerminalTitle {
+        instance_id: String,
+        title: Option<String>,
+    },
+    /// The program rang the bell or emitted an OSC 9/777 desktop notification
+    TerminalNotification {
+        instance_id: String,
+        kind: TerminalNotificationKind,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<String>,
+    },
+    /// The focused program copied to the clipboard via OSC 52. Only sent to
+    /// the focusing connection, and only if its user allows clipboard writes.
+    ClipboardWrite {
+        instance_id: String,
+        /// OSC 52 selection (`c` = clipboard, `p` = primary, ...)
+        selection: String,
+        /// Base64-encoded clipboard contents
+        data: String,
+    },
+
     // === Inbox (fleet attention model) ===
     /// Single inbox item changed for an instance (upserted or cleared)
     InboxUpdate {
@@ -344,11 +484,46 @@ pub enum ServerMessage {
         items: Vec<crate::models::InboxItem>,
     },
 
+    // === Prompt queue ===
+    /// An instance's prompt queue changed — full snapshot, next to send first
+    PromptQueueUpdate {
+        instance_id: String,
+        prompts: Vec<crate::models::QueuedPrompt>,
+    },
+
     // === Server lifecycle ===
     /// Server is shutting down — tells connected clients to show offline state immediately
     Shutdown { reason: String },
 }
 
+impl ServerMessage {
+    /// Serialize for the JSON channel. `Sequenced` is written as its inner
+    /// message with a trailing `"seq"` field.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        match self {
+            ServerMessage::Sequenced { seq, message } => {
+                let mut json = message.to_json()?;
+                // Every message is an internally tagged object, so it ends
+                // in `}` and already has a "type" field before it.
+                json.pop();
+                json.push_str(&format!(",\"seq\":{seq}}}"));
+                Ok(json)
+            }
+            msg => serde_json::to_string(msg),
+        }
+    }
+}
+
+/// Source of a `TerminalNotification`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalNotificationKind {
+    /// BEL (`^G`)
+    Bell,
+    /// OSC 9 / OSC 777 notification
+    Notification,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionCandidate {
     pub session_id: String,
@@ -370,9 +545,11 @@ mod tests {
             ClientMessage::Focus {
                 instance_id,
                 since_uuid,
+                output_seq,
             } => {
                 assert_eq!(instance_id, "abc123");
                 assert!(since_uuid.is_none());
+                assert!(output_seq.is_none());
             }
             _ => panic!("Expected Focus message"),
         }
@@ -380,16 +557,19 @@ mod tests {
 
     #[test]
     fn test_client_message_focus_with_since_uuid() {
-        let json = r#"{"type":"Focus","instance_id":"abc123","since_uuid":"uuid-42"}"#;
+        let json =
+            r#"{"type":"Focus","instance_id":"abc123","since_uuid":"uuid-42","output_seq":17}"#;
         let msg: ClientMessage = serde_json::from_str(json).unwrap();
 
         match msg {
             ClientMessage::Focus {
                 instance_id,
                 since_uuid,
+                output_seq,
             } => {
                 assert_eq!(instance_id, "abc123");
                 assert_eq!(since_uuid, Some("uuid-42".to_string()));
+                assert_eq!(output_seq, Some(17));
             }
             _ => panic!("Expected Focus message"),
         }
@@ -526,6 +706,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_server_message_terminal_notification_serialization() {
+        let msg = ServerMessage::TerminalNotification {
+            instance_id: "inst-1".to_string(),
+            kind: TerminalNotificationKind::Bell,
+            title: None,
+            body: None,
+        };
+        let json: serde_json::Value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "TerminalNotification");
+        assert_eq!(json["kind"], "bell");
+        assert!(json.get("title").is_none());
+        assert!(json.get("body").is_none());
+
+        let msg = ServerMessage::TerminalTitle {
+            instance_id: "inst-1".to_string(),
+            title: Some("vim".to_string()),
+        };
+        let json: serde_json::Value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "TerminalTitle");
+        assert_eq!(json["title"], "vim");
+    }
+
     #[test]
     fn test_server_message_state_change_serialization() {
         let msg = ServerMessage::StateChange {
@@ -646,6 +849,7 @@ mod tests {
         let original = ClientMessage::Focus {
             instance_id: "test-instance".to_string(),
             since_uuid: Some("uuid-abc".to_string()),
+            output_seq: Some(3),
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -655,9 +859,11 @@ mod tests {
             ClientMessage::Focus {
                 instance_id,
                 since_uuid,
+                output_seq,
             } => {
                 assert_eq!(instance_id, "test-instance");
                 assert_eq!(since_uuid, Some("uuid-abc".to_string()));
+                assert_eq!(output_seq, Some(3));
             }
             _ => panic!("Round-trip failed"),
         }
@@ -1141,18 +1347,177 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_binary_frames_negotiation_messages() {
+        let msg: ClientMessage = serde_json::from_str(r#"{"type":"EnableBinaryFrames"}"#).unwrap();
+        assert!(matches!(msg, ClientMessage::EnableBinaryFrames));
+
+        let json = serde_json::to_string(&ServerMessage::BinaryFramesEnabled).unwrap();
+        assert_eq!(json, r#"{"type":"BinaryFramesEnabled"}"#);
+    }
+
+    #[test]
+    fn test_handshake_messages() {
+        let json = serde_json::to_string(&ServerMessage::Welcome {
+            protocol_version: 2,
+            min_protocol_version: 1,
+            server_version: "0.45.1".to_string(),
+            capabilities: vec![Capability::BinaryFrames, Capability::Resume],
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"Welcome","protocol_version":2,"min_protocol_version":1,"server_version":"0.45.1","capabilities":["binary_frames","resume"]}"#
+        );
+
+        let msg: ClientMessage = serde_json::from_str(
+            r#"{"type":"Hello","protocol_version":3,"client_version":"1.0.0","capabilities":["binary_frames","quantum"]}"#,
+        )
+        .unwrap();
+        match msg {
+            ClientMessage::Hello {
+                protocol_version,
+                client_version,
+                capabilities,
+            } => {
+                assert_eq!(protocol_version, 3);
+                assert_eq!(client_version, "1.0.0");
+                assert_eq!(
+                    capabilities,
+                    [Capability::BinaryFrames, Capability::Unknown]
+                );
+            }
+            _ => panic!("Expected Hello"),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_messages() {
+        let msg: ClientMessage = serde_json::from_str(
+            r#"{"type":"Subscribe","topics":[{"kind":"instances"},{"kind":"chat","scope":"global"}]}"#,
+        )
+        .unwrap();
+        match msg {
+            ClientMessage::Subscribe { topics } => assert_eq!(
+                topics,
+                [
+                    Topic::Instances { instance_id: None },
+                    Topic::Chat {
+                        scope: Some("global".to_string())
+                    }
+                ]
+            ),
+            _ => panic!("Expected Subscribe"),
+        }
+
+        let json = serde_json::to_string(&ServerMessage::Subscribed {
+            topics: vec![Topic::Tasks],
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"type":"Subscribed","topics":[{"kind":"tasks"}]}"#);
+    }
+
+    #[test]
+    fn test_output_mode_roundtrip() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type":"SetOutputMode","mode":"screen_diff","max_fps":5}"#)
+                .unwrap();
+        match msg {
+            ClientMessage::SetOutputMode { mode, max_fps } => {
+                assert_eq!(mode, OutputMode::ScreenDiff);
+                assert_eq!(max_fps, Some(5));
+            }
+            _ => panic!("Expected SetOutputMode"),
+        }
+
+        let json = serde_json::to_string(&ServerMessage::OutputModeSet {
+            mode: OutputMode::Raw,
+            max_fps: None,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"type":"OutputModeSet","mode":"raw"}"#);
+    }
+
+    #[test]
+    fn test_frame_is_never_serialized_as_json() {
+        use super::super::frame::FrameKind;
+
+        let msg = ServerMessage::Frame(TerminalFrame {
+            kind: FrameKind::Output,
+            instance_id: "inst-1".to_string(),
+            seq: 0,
+            data: b"hi".to_vec(),
+        });
+        assert!(serde_json::to_string(&msg).is_err());
+        assert!(serde_json::from_str::<ServerMessage>(r#"{"type":"Frame"}"#).is_err());
+    }
+
+    #[test]
+    fn test_sequenced_adds_top_level_seq() {
+        let msg = ServerMessage::Sequenced {
+            seq: 42,
+            message: Box::new(ServerMessage::InstanceStopped {
+                instance_id: "inst-1".to_string(),
+            }),
+        };
+        let json = msg.to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"InstanceStopped","instance_id":"inst-1","seq":42}"#
+        );
+        // Clients that don't know about sequences still parse it
+        let parsed: ServerMessage = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, ServerMessage::InstanceStopped { .. }));
+        // Plain messages are unchanged
+        assert_eq!(
+            ServerMessage::BinaryFramesEnabled.to_json().unwrap(),
+            r#"{"type":"BinaryFramesEnabled"}"#
+        );
+    }
+
+    #[test]
+    fn test_resumable_serialization() {
+        let json = serde_json::to_string(&ServerMessage::Resumable {
+            resume_token: "e.c".to_string(),
+            resumed: true,
+            lifecycle_seq: 9,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"Resumable","resume_token":"e.c","resumed":true,"lifecycle_seq":9}"#
+        );
+    }
+
     #[test]
     fn test_client_message_terminal_lock_request() {
         let json = r#"{"type":"TerminalLockRequest","instance_id":"inst-1"}"#;
         let msg: ClientMessage = serde_json::from_str(json).unwrap();
         match msg {
-            ClientMessage::TerminalLockRequest { instance_id } => {
+            ClientMessage::TerminalLockRequest { instance_id, force } => {
                 assert_eq!(instance_id, "inst-1");
+                assert!(!force);
             }
             _ => panic!("Expected TerminalLockRequest"),
         }
     }
 
+    #[test]
+    fn test_client_message_terminal_lock_grant_and_deny() {
+        let json = r#"{"type":"TerminalLockGrant","instance_id":"inst-1","user_id":"u-2"}"#;
+        match serde_json::from_str::<ClientMessage>(json).unwrap() {
+            ClientMessage::TerminalLockGrant { user_id, .. } => {
+                assert_eq!(user_id.as_deref(), Some("u-2"));
+            }
+            _ => panic!("Expected TerminalLockGrant"),
+        }
+        let json = r#"{"type":"TerminalLockDeny","instance_id":"inst-1"}"#;
+        match serde_json::from_str::<ClientMessage>(json).unwrap() {
+            ClientMessage::TerminalLockDeny { user_id, .. } => assert!(user_id.is_none()),
+            _ => panic!("Expected TerminalLockDeny"),
+        }
+    }
+
     #[test]
     fn test_client_message_terminal_lock_release() {
         let json = r#"{"type":"TerminalLockRelease","instance_id":"inst-1"}"#;
@@ -1202,6 +1567,10 @@ mod tests {
             }),
             last_activity: Some("2025-01-01T00:00:00Z".to_string()),
             expires_in_secs: Some(120),
+            queue: vec![PresenceUser {
+                user_id: "u-2".to_string(),
+                display_name: "Bob".to_string(),
+            }],
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(json.contains("TerminalLockUpdate"));
@@ -1213,10 +1582,12 @@ mod tests {
             ServerMessage::TerminalLockUpdate {
                 holder,
                 expires_in_secs,
+                queue,
                 ..
             } => {
                 assert!(holder.is_some());
                 assert_eq!(expires_in_secs, Some(120));
+                assert_eq!(queue[0].display_name, "Bob");
             }
             _ => panic!("Expected TerminalLockUpdate"),
         }
@@ -1229,11 +1600,13 @@ mod tests {
             holder: None,
             last_activity: None,
             expires_in_secs: None,
+            queue: Vec::new(),
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(!json.contains("holder"));
         assert!(!json.contains("last_activity"));
         assert!(!json.contains("expires_in_secs"));
+        assert!(!json.contains("queue"));
     }
 
     #[test]
@@ -1354,6 +1727,8 @@ mod tests {
                 session_id: None,
                 claude_state: None,
                 state_entered_at: None,
+                title: None,
+                role: None,
             },
         };
         let json = serde_json::to_string(&msg).unwrap();
@@ -1432,8 +1807,10 @@ mod tests {
         let user = WsUser {
             user_id: "u-1".to_string(),
             display_name: "Alice".to_string(),
+            restricted: true,
         };
         let json = serde_json::to_value(&user).unwrap();
+        assert!(json.get("restricted").is_none());
         assert_eq!(json["user_id"], "u-1");
         assert_eq!(json["display_name"], "Alice");
         let rt: WsUser = serde_json::from_value(json).unwrap();
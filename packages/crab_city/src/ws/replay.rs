@@ -0,0 +1,316 @@
+//! Resumable Sessions
+//!
+//! Every lifecycle message and every per-instance output chunk carries a
+//! monotonically increasing sequence number. The server keeps the most recent
+//! ones in bounded [`ReplayWindow`]s, so a client that reconnects after a
+//! laptop sleep or network blip can present the [`ResumeToken`] it was given
+//! plus the last sequences it saw and receive only what it missed. When the
+//! window no longer reaches back that far the client gets the usual full
+//! replay (`OutputHistory`, `ConversationFull`) instead.
+//!
+//! Resume tokens are not credentials: they only tie sequence numbers to the
+//! server process that issued them, since sequences restart with the server.
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use super::protocol::ServerMessage;
+
+/// Bounded, sequence-numbered history of recent items.
+///
+/// Sequences start at 1, so 0 means "seen nothing yet". The window evicts
+/// from the front once it holds more than `max_items` items or their summed
+/// weight exceeds `max_weight` (the newest item is always kept).
+#[derive(Debug)]
+pub struct ReplayWindow<T> {
+    items: VecDeque<(u64, T, usize)>,
+    next_seq: u64,
+    weight: usize,
+    max_items: usize,
+    max_weight: usize,
+}
+
+impl<T: Clone> ReplayWindow<T> {
+    pub fn new(max_items: usize, max_weight: usize) -> Self {
+        Self {
+            items: VecDeque::new(),
+            next_seq: 1,
+            weight: 0,
+            max_items,
+            max_weight,
+        }
+    }
+
+    /// The sequence the next pushed item will get.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// The sequence of the newest item (0 if nothing was pushed yet).
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Append an item and return its sequence.
+    pub fn push(&mut self, item: T, weight: usize) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.items.push_back((seq, item, weight));
+        self.weight += weight;
+        while self.items.len() > 1
+            && (self.items.len() > self.max_items || self.weight > self.max_weight)
+        {
+            if let Some((_, _, w)) = self.items.pop_front() {
+                self.weight -= w;
+            }
+        }
+        seq
+    }
+
+    /// Everything after `seq`, oldest first.
+    ///
+    /// `None` when the window no longer reaches back to `seq + 1` or `seq` is
+    /// ahead of anything pushed (a sequence from another server run).
+    pub fn since(&self, seq: u64) -> Option<Vec<(u64, T)>> {
+        if seq > self.last_seq() {
+            return None;
+        }
+        let oldest = self.items.front().map_or(self.next_seq, |(s, _, _)| *s);
+        if seq + 1 < oldest {
+            return None;
+        }
+        Some(
+            self.items
+                .iter()
+                .filter(|(s, _, _)| *s > seq)
+                .map(|(s, item, _)| (*s, item.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Lifecycle messages kept for resuming clients.
+const LIFECYCLE_REPLAY_MESSAGES: usize = 1024;
+
+/// Broadcast channel for instance lifecycle events (created/stopped,
+/// presence, chat, inbox, …). Each message is numbered and kept in a replay
+/// window; subscribers receive `(seq, message)`.
+#[derive(Clone)]
+pub struct LifecycleBroadcast {
+    tx: broadcast::Sender<(u64, ServerMessage)>,
+    window: Arc<Mutex<ReplayWindow<ServerMessage>>>,
+}
+
+impl LifecycleBroadcast {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            window: Arc::new(Mutex::new(ReplayWindow::new(
+                LIFECYCLE_REPLAY_MESSAGES,
+                usize::MAX,
+            ))),
+        }
+    }
+
+    /// Number, record and broadcast a message. Returns its sequence.
+    pub fn send(&self, msg: ServerMessage) -> u64 {
+        // Hold the window lock across the send so subscribers see
+        // sequences in order.
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        let seq = window.push(msg.clone(), 1);
+        let _ = self.tx.send((seq, msg));
+        seq
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<(u64, ServerMessage)> {
+        self.tx.subscribe()
+    }
+
+    /// Sequence of the most recent message (0 if none yet).
+    pub fn last_seq(&self) -> u64 {
+        self.window
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_seq()
+    }
+
+    /// Messages broadcast after `seq`, or `None` if they are no longer all
+    /// available (see [`ReplayWindow::since`]).
+    pub fn since(&self, seq: u64) -> Option<Vec<(u64, ServerMessage)>> {
+        self.window
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .since(seq)
+    }
+}
+
+/// Opaque token handed to each connection so it can resume after a
+/// reconnect: `<server epoch>.<connection id>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeToken {
+    pub epoch: String,
+    pub connection_id: String,
+}
+
+impl ResumeToken {
+    pub fn parse(token: &str) -> Option<Self> {
+        let (epoch, connection_id) = token.split_once('.')?;
+        if epoch.is_empty() || connection_id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            epoch: epoch.to_string(),
+            connection_id: connection_id.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for ResumeToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.epoch, self.connection_id)
+    }
+}
+
+/// Query parameters of a reconnecting client:
+/// `/api/ws?resume=<token>&lifecycle_seq=<n>`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResumeParams {
+    pub resume: Option<String>,
+    pub lifecycle_seq: Option<u64>,
+}
+
+impl ResumeParams {
+    /// Lifecycle messages this client missed, if it can resume at all: the
+    /// token must come from this server run and the window must still reach
+    /// back to its sequence.
+    pub fn missed_lifecycle(
+        &self,
+        epoch: &str,
+        lifecycle: &LifecycleBroadcast,
+    ) -> Option<Vec<(u64, ServerMessage)>> {
+        let token = ResumeToken::parse(self.resume.as_deref()?)?;
+        if token.epoch != epoch {
+            return None;
+        }
+        lifecycle.since(self.lifecycle_seq.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_with(n: u64) -> ReplayWindow<u64> {
+        let mut w = ReplayWindow::new(4, usize::MAX);
+        for i in 1..=n {
+            assert_eq!(w.push(i * 10, 1), i);
+        }
+        w
+    }
+
+    #[test]
+    fn since_returns_items_after_seq() {
+        let w = window_with(3);
+        assert_eq!(w.since(0), Some(vec![(1, 10), (2, 20), (3, 30)]));
+        assert_eq!(w.since(2), Some(vec![(3, 30)]));
+        assert_eq!(w.since(3), Some(vec![]));
+    }
+
+    #[test]
+    fn since_fails_once_window_is_exceeded() {
+        let w = window_with(6); // keeps 3..=6
+        assert_eq!(w.since(1), None);
+        assert_eq!(w.since(2), Some(vec![(3, 30), (4, 40), (5, 50), (6, 60)]));
+    }
+
+    #[test]
+    fn since_rejects_sequences_from_the_future() {
+        let w = window_with(2);
+        assert_eq!(w.since(3), None);
+        assert_eq!(ReplayWindow::<u64>::new(4, 4).since(0), Some(vec![]));
+    }
+
+    #[test]
+    fn weight_bound_evicts_but_keeps_newest() {
+        let mut w = ReplayWindow::new(100, 10);
+        w.push("a", 6);
+        w.push("b", 6);
+        assert_eq!(w.since(1), Some(vec![(2, "b")]));
+        assert_eq!(w.since(0), None);
+        // An oversized item still replaces the window rather than vanishing
+        w.push("huge", 50);
+        assert_eq!(w.since(2), Some(vec![(3, "huge")]));
+        assert_eq!(w.last_seq(), 3);
+        assert_eq!(w.next_seq(), 4);
+    }
+
+    #[tokio::test]
+    async fn lifecycle_broadcast_numbers_and_records() {
+        let lifecycle = LifecycleBroadcast::new(16);
+        let mut rx = lifecycle.subscribe();
+        let first = lifecycle.send(ServerMessage::InstanceStopped {
+            instance_id: "a".into(),
+        });
+        let second = lifecycle.send(ServerMessage::InstanceStopped {
+            instance_id: "b".into(),
+        });
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(rx.recv().await.unwrap().0, 1);
+        assert_eq!(rx.recv().await.unwrap().0, 2);
+
+        let missed = lifecycle.since(1).unwrap();
+        assert_eq!(missed.len(), 1);
+        assert!(matches!(
+            &missed[0],
+            (2, ServerMessage::InstanceStopped { instance_id }) if instance_id == "b"
+        ));
+    }
+
+    #[test]
+    fn resume_params_require_matching_epoch_and_window() {
+        let lifecycle = LifecycleBroadcast::new(16);
+        lifecycle.send(ServerMessage::InstanceStopped {
+            instance_id: "a".into(),
+        });
+        let params = |resume: Option<&str>, seq| ResumeParams {
+            resume: resume.map(String::from),
+            lifecycle_seq: seq,
+        };
+        assert_eq!(
+            params(Some("e1.c1"), Some(0))
+                .missed_lifecycle("e1", &lifecycle)
+                .map(|m| m.len()),
+            Some(1)
+        );
+        assert!(
+            params(Some("e0.c1"), Some(0))
+                .missed_lifecycle("e1", &lifecycle)
+                .is_none()
+        );
+        assert!(
+            params(None, Some(0))
+                .missed_lifecycle("e1", &lifecycle)
+                .is_none()
+        );
+        assert!(
+            params(Some("e1.c1"), Some(5))
+                .missed_lifecycle("e1", &lifecycle)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn resume_token_round_trips() {
+        let token = ResumeToken {
+            epoch: "e1".into(),
+            connection_id: "c1".into(),
+        };
+        assert_eq!(token.to_string(), "e1.c1");
+        assert_eq!(ResumeToken::parse("e1.c1"), Some(token));
+        assert_eq!(ResumeToken::parse("nodot"), None);
+        assert_eq!(ResumeToken::parse(".c1"), None);
+    }
+}
@@ -13,13 +13,15 @@ use crate::instance_actor::InstanceHandle;
 use crate::models::normalize_attribution_content;
 use crate::repository::ConversationRepository;
 
-use super::protocol::{PresenceUser, ServerMessage, WsUser};
+use super::protocol::{PresenceUser, ServerMessage, TerminalNotificationKind, WsUser};
+use super::replay::{LifecycleBroadcast, ResumeParams};
 
 /// Everything a transport layer knows about an input event.
 /// Both WS handlers build this and call `handle_input()`. Nothing else.
 pub struct InputContext {
     pub instance_id: String,
-    pub data: String,
+    /// Raw bytes for the PTY; binary input frames need not be UTF-8
+    pub data: Vec<u8>,
     pub connection_id: String,
     /// Optional — TUI has no authenticated user
     pub user: Option<InputUser>,
@@ -50,6 +52,36 @@ pub struct TerminalLock {
     pub last_activity: DateTime<Utc>,
 }
 
+impl TerminalLock {
+    fn for_waiter(waiter: TerminalLockWaiter) -> Self {
+        Self {
+            holder_connection_id: waiter.connection_id,
+            holder_user_id: waiter.user_id,
+            holder_display_name: waiter.display_name,
+            last_activity: Utc::now(),
+        }
+    }
+}
+
+/// A connection waiting for an instance's terminal lock.
+#[derive(Debug, Clone)]
+pub struct TerminalLockWaiter {
+    pub connection_id: String,
+    pub user_id: String,
+    pub display_name: String,
+}
+
+/// Outcome of [`GlobalStateManager::hand_over_expired_terminal_lock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockExpiry {
+    /// The holder is still active; check again after this long
+    Active(std::time::Duration),
+    /// The lock moved to the next waiter
+    HandedOver,
+    /// Nobody is waiting (or nobody holds the lock)
+    Idle,
+}
+
 /// Data stored for the first input(s) to an instance before session discovery.
 /// Accumulates content prefixes so the discovery loop can verify that a
 /// candidate session actually contains input sent to *this* instance.
@@ -69,6 +101,34 @@ pub struct FirstInputData {
 /// Maximum number of content prefixes to store per instance.
 const FIRST_INPUT_PREFIX_CAP: usize = 20;
 
+/// Maximum number of state transitions retained per instance for the timeline.
+const STATE_HISTORY_CAP: usize = 1000;
+
+/// Connection id used when the daemon types a queued prompt on a user's behalf.
+pub const PROMPT_QUEUE_CONNECTION_ID: &str = "prompt-queue";
+
+/// How long a sent queued prompt blocks the next one while waiting for
+/// Claude to leave Idle. Covers prompts the TUI swallowed.
+const PROMPT_DISPATCH_GRACE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often the dispatcher retries held queues on Idle instances: after the
+/// grace period runs out, or once someone else's terminal lock goes away.
+const PROMPT_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long to wait between pasting a prompt and pressing Enter, so the TUI
+/// has absorbed the paste first. Same pacing as the web composer.
+pub fn paste_enter_delay(len: usize) -> std::time::Duration {
+    std::time::Duration::from_millis((50 + len as u64 / 2).min(750))
+}
+
+/// A state the instance entered, and when. Kept so the timeline API can
+/// correlate state changes with terminal recordings and conversation turns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTransition {
+    pub at: DateTime<Utc>,
+    pub state: ClaudeState,
+}
+
 /// A pending attribution: recorded when a user sends input via WebSocket,
 /// consumed when the conversation watcher sees the corresponding User entry.
 /// Content-matched rather than timestamp-correlated.
@@ -87,9 +147,6 @@ pub struct PendingAttribution {
 /// Tuple: (instance_id, state, terminal_stale)
 pub type StateBroadcast = broadcast::Sender<(String, ClaudeState, bool)>;
 
-/// Broadcast channel for instance lifecycle events (created/stopped)
-pub type LifecycleBroadcast = broadcast::Sender<ServerMessage>;
-
 /// Conversation event broadcast from server-owned watcher to consumers.
 #[derive(Debug, Clone)]
 pub enum ConversationEvent {
@@ -144,6 +201,9 @@ pub struct GlobalStateManager {
     trackers: RwLock<HashMap<String, InstanceTracker>>,
     broadcast_tx: StateBroadcast,
     lifecycle_tx: LifecycleBroadcast,
+    /// Random per-process id; resume tokens from another server run are
+    /// rejected because sequence numbers restart with the process.
+    epoch: String,
     /// Sessions that have been claimed by instances (session_id -> instance_id)
     /// Prevents multiple instances from claiming the same Claude session
     claimed_sessions: Arc<RwLock<HashMap<String, String>>>,
@@ -160,23 +220,37 @@ pub struct GlobalStateManager {
     pending_attributions: Arc<RwLock<HashMap<String, VecDeque<PendingAttribution>>>>,
     /// Terminal locks: instance_id -> lock holder info
     terminal_locks: RwLock<HashMap<String, TerminalLock>>,
+    /// Terminal lock requests: instance_id -> waiters, oldest first.
+    /// Always taken after `terminal_locks` when both are needed.
+    terminal_lock_queues: RwLock<HashMap<String, VecDeque<TerminalLockWaiter>>>,
     /// Timestamp when each instance entered its current state
     state_entered_at: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Bounded history of state transitions per instance (oldest first)
+    state_history: RwLock<HashMap<String, VecDeque<StateTransition>>>,
+    /// Instances with a queued prompt sent but not yet picked up by Claude
+    prompts_in_flight: RwLock<HashMap<String, std::time::Instant>>,
+    /// Instances whose next queued prompt is waiting on the grace period or
+    /// on another user's terminal lock; retried while they stay Idle
+    prompts_held: RwLock<HashSet<String>>,
 }
 
 impl GlobalStateManager {
     pub fn new(broadcast_tx: StateBroadcast) -> Self {
-        let (lifecycle_tx, _) = broadcast::channel(64);
         Self {
             trackers: RwLock::new(HashMap::new()),
             broadcast_tx,
-            lifecycle_tx,
+            lifecycle_tx: LifecycleBroadcast::new(64),
+            epoch: uuid::Uuid::new_v4().simple().to_string(),
             claimed_sessions: Arc::new(RwLock::new(HashMap::new())),
             first_input_at: Arc::new(RwLock::new(HashMap::new())),
             presence: RwLock::new(HashMap::new()),
             pending_attributions: Arc::new(RwLock::new(HashMap::new())),
             terminal_locks: RwLock::new(HashMap::new()),
+            terminal_lock_queues: RwLock::new(HashMap::new()),
             state_entered_at: RwLock::new(HashMap::new()),
+            state_history: RwLock::new(HashMap::new()),
+            prompts_in_flight: RwLock::new(HashMap::new()),
+            prompts_held: RwLock::new(HashSet::new()),
         }
     }
 
@@ -191,9 +265,37 @@ impl GlobalStateManager {
     }
 
     /// Spawn a background task that subscribes to state broadcasts and:
-    /// 1. Tracks `state_entered_at` timestamps (when state type changes)
+    /// 1. Tracks `state_entered_at` timestamps and state history (when state type changes)
     /// 2. Detects state transitions for inbox (completed_turn, needs_input, etc.)
     pub fn start_inbox_watcher(self: &Arc<Self>, repository: Arc<ConversationRepository>) {
+        // Terminal bells and OSC 9/777 notifications arrive as lifecycle messages
+        let mut lifecycle_rx = self.lifecycle_tx.subscribe();
+        let gsm = Arc::clone(self);
+        let repo = repository.clone();
+        tokio::spawn(async move {
+            loop {
+                match lifecycle_rx.recv().await {
+                    Ok((
+                        _,
+                        ServerMessage::TerminalNotification {
+                            instance_id,
+                            kind,
+                            title,
+                            body,
+                        },
+                    )) => {
+                        gsm.record_terminal_notification(&repo, &instance_id, kind, title, body)
+                            .await;
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("[INBOX] Lifecycle broadcast lagged by {} messages", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
         let mut state_rx = self.broadcast_tx.subscribe();
         let gsm = Arc::clone(self);
         tokio::spawn(async move {
@@ -209,7 +311,10 @@ impl GlobalStateManager {
                             .map(|p| std::mem::discriminant(p) != std::mem::discriminant(&state))
                             .unwrap_or(true);
                         if state_type_changed {
-                            gsm.set_state_entered_at(&instance_id, Utc::now()).await;
+                            let now = Utc::now();
+                            gsm.set_state_entered_at(&instance_id, now).await;
+                            gsm.record_state_transition(&instance_id, now, state.clone())
+                                .await;
                         }
 
                         // Inbox logic: detect state transitions and upsert/clear inbox items
@@ -328,6 +433,240 @@ impl GlobalStateManager {
         });
     }
 
+    /// Spawn a background task that sends the next queued prompt whenever a
+    /// Claude instance returns to Idle, and retries held queues while it stays
+    /// there.
+    pub fn start_prompt_dispatcher(self: &Arc<Self>, repository: Arc<ConversationRepository>) {
+        self.start_prompt_dispatcher_every(repository, PROMPT_RECHECK_INTERVAL);
+    }
+
+    fn start_prompt_dispatcher_every(
+        self: &Arc<Self>,
+        repository: Arc<ConversationRepository>,
+        recheck: std::time::Duration,
+    ) {
+        let mut state_rx = self.broadcast_tx.subscribe();
+        let gsm = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut prev_idle: HashMap<String, bool> = HashMap::new();
+            let mut recheck = tokio::time::interval(recheck);
+            recheck.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    msg = state_rx.recv() => match msg {
+                        Ok((instance_id, state, _stale)) => {
+                            let idle = matches!(state, ClaudeState::Idle);
+                            let was_idle = prev_idle.insert(instance_id.clone(), idle);
+                            if !idle {
+                                // Claude picked up the last prompt (or someone typed)
+                                gsm.prompts_in_flight.write().await.remove(&instance_id);
+                                gsm.prompts_held.write().await.remove(&instance_id);
+                            } else if was_idle != Some(true) {
+                                gsm.send_next_queued_prompt(&repository, &instance_id).await;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("[PROMPTS] State broadcast lagged by {} messages", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = recheck.tick() => {
+                        let held: Vec<String> =
+                            gsm.prompts_held.read().await.iter().cloned().collect();
+                        for instance_id in held {
+                            if prev_idle.get(&instance_id) == Some(&true) {
+                                gsm.send_next_queued_prompt(&repository, &instance_id).await;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Send the next queued prompt now if the instance is sitting Idle.
+    /// Called after enqueueing, since no state change will trigger it.
+    pub async fn dispatch_queued_prompt(
+        self: &Arc<Self>,
+        repository: &Arc<ConversationRepository>,
+        instance_id: &str,
+    ) -> bool {
+        let Some(handle) = self.get_handle(instance_id).await else {
+            return false;
+        };
+        if !matches!(
+            handle.get_info().await.claude_state,
+            Some(ClaudeState::Idle)
+        ) {
+            return false;
+        }
+        self.send_next_queued_prompt(repository, instance_id)
+            .await
+            .is_some()
+    }
+
+    /// Pop the head of an instance's queue and type it into the terminal,
+    /// attributed to whoever queued it. Does nothing while a previous prompt
+    /// is still in flight, or while another user holds the terminal lock;
+    /// the dispatcher retries held queues.
+    async fn send_next_queued_prompt(
+        self: &Arc<Self>,
+        repository: &Arc<ConversationRepository>,
+        instance_id: &str,
+    ) -> Option<crate::models::QueuedPrompt> {
+        {
+            let mut in_flight = self.prompts_in_flight.write().await;
+            if in_flight
+                .get(instance_id)
+                .is_some_and(|sent| sent.elapsed() < PROMPT_DISPATCH_GRACE)
+            {
+                self.prompts_held
+                    .write()
+                    .await
+                    .insert(instance_id.to_string());
+                return None;
+            }
+            // Claim the slot before popping so a concurrent caller backs off
+            in_flight.insert(instance_id.to_string(), std::time::Instant::now());
+        }
+        let next = match repository.list_prompt_queue(instance_id).await {
+            Ok(queue) => queue.into_iter().next(),
+            Err(e) => {
+                warn!(instance = %instance_id, "Failed to read prompt queue: {}", e);
+                None
+            }
+        };
+        let Some(next) = next else {
+            self.prompts_in_flight.write().await.remove(instance_id);
+            self.prompts_held.write().await.remove(instance_id);
+            return None;
+        };
+        if let Some(lock) = self.get_terminal_lock(instance_id).await
+            && lock.holder_user_id != next.user_id
+            && (Utc::now() - lock.last_activity).num_seconds() < TERMINAL_LOCK_TIMEOUT_SECS
+        {
+            debug!(
+                instance = %instance_id,
+                holder = %lock.holder_display_name,
+                "Holding queued prompt while someone else has the terminal"
+            );
+            self.prompts_in_flight.write().await.remove(instance_id);
+            self.prompts_held
+                .write()
+                .await
+                .insert(instance_id.to_string());
+            return None;
+        }
+        let prompt = match repository.delete_queued_prompt(next.id).await {
+            Ok(Some(prompt)) => prompt,
+            Ok(None) => {
+                // Removed meanwhile; the next check picks up the new head
+                self.prompts_in_flight.write().await.remove(instance_id);
+                return None;
+            }
+            Err(e) => {
+                warn!(instance = %instance_id, "Failed to pop queued prompt: {}", e);
+                self.prompts_in_flight.write().await.remove(instance_id);
+                return None;
+            }
+        };
+        info!(instance = %instance_id, prompt = prompt.id, "Sending queued prompt");
+        // Retried after the grace period in case Claude never picks it up
+        self.prompts_held
+            .write()
+            .await
+            .insert(instance_id.to_string());
+        self.broadcast_prompt_queue(repository, instance_id).await;
+
+        let gsm = Arc::clone(self);
+        let repo = Arc::clone(repository);
+        let sent = prompt.clone();
+        tokio::spawn(async move {
+            let ctx = InputContext {
+                instance_id: sent.instance_id.clone(),
+                data: sent.content.clone().into_bytes(),
+                connection_id: PROMPT_QUEUE_CONNECTION_ID.to_string(),
+                user: Some(InputUser {
+                    user_id: sent.user_id,
+                    display_name: sent.display_name,
+                }),
+                task_id: None,
+            };
+            if let Err(e) = gsm.handle_input(ctx, Some(&repo)).await {
+                warn!(instance = %sent.instance_id, "Failed to send queued prompt: {}", e);
+                return;
+            }
+            tokio::time::sleep(paste_enter_delay(sent.content.len())).await;
+            let ctx = InputContext {
+                instance_id: sent.instance_id,
+                data: "\r".into(),
+                connection_id: PROMPT_QUEUE_CONNECTION_ID.to_string(),
+                user: None,
+                task_id: None,
+            };
+            let _ = gsm.handle_input(ctx, None).await;
+        });
+        Some(prompt)
+    }
+
+    /// Broadcast an instance's current prompt queue to subscribers.
+    pub async fn broadcast_prompt_queue(
+        &self,
+        repository: &ConversationRepository,
+        instance_id: &str,
+    ) {
+        match repository.list_prompt_queue(instance_id).await {
+            Ok(prompts) => self.broadcast_lifecycle(ServerMessage::PromptQueueUpdate {
+                instance_id: instance_id.to_string(),
+                prompts,
+            }),
+            Err(e) => warn!(instance = %instance_id, "Failed to list prompt queue: {}", e),
+        }
+    }
+
+    /// Upsert a "notification" inbox item for a terminal bell or OSC
+    /// notification. Bells from Claude instances are skipped — their turn
+    /// completion is already tracked from state transitions.
+    async fn record_terminal_notification(
+        &self,
+        repository: &ConversationRepository,
+        instance_id: &str,
+        kind: TerminalNotificationKind,
+        title: Option<String>,
+        body: Option<String>,
+    ) {
+        if kind == TerminalNotificationKind::Bell {
+            let is_claude = self
+                .trackers
+                .read()
+                .await
+                .get(instance_id)
+                .is_none_or(|t| t.is_claude);
+            if is_claude {
+                return;
+            }
+        }
+
+        let metadata = serde_json::json!({
+            "kind": kind,
+            "title": title,
+            "body": body,
+        })
+        .to_string();
+        match repository
+            .upsert_inbox_item(instance_id, "notification", Some(&metadata))
+            .await
+        {
+            Ok(item) => {
+                self.broadcast_lifecycle(ServerMessage::InboxUpdate {
+                    instance_id: instance_id.to_string(),
+                    item: Some(item),
+                });
+            }
+            Err(e) => warn!("[INBOX] Failed to upsert notification: {}", e),
+        }
+    }
+
     /// Get the timestamp when an instance entered its current state.
     pub async fn get_state_entered_at(&self, instance_id: &str) -> Option<DateTime<Utc>> {
         self.state_entered_at.read().await.get(instance_id).copied()
@@ -341,6 +680,32 @@ impl GlobalStateManager {
             .insert(instance_id.to_string(), timestamp);
     }
 
+    /// Append a state transition to an instance's history, evicting the oldest
+    /// entry once `STATE_HISTORY_CAP` is reached.
+    pub async fn record_state_transition(
+        &self,
+        instance_id: &str,
+        at: DateTime<Utc>,
+        state: ClaudeState,
+    ) {
+        let mut history = self.state_history.write().await;
+        let entries = history.entry(instance_id.to_string()).or_default();
+        if entries.len() >= STATE_HISTORY_CAP {
+            entries.pop_front();
+        }
+        entries.push_back(StateTransition { at, state });
+    }
+
+    /// State transitions recorded for an instance, oldest first.
+    pub async fn get_state_history(&self, instance_id: &str) -> Vec<StateTransition> {
+        self.state_history
+            .read()
+            .await
+            .get(instance_id)
+            .map(|h| h.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Try to claim a session for an instance. Returns true if successful.
     /// Returns false if the session is already claimed by another instance.
     pub async fn try_claim_session(&self, session_id: &str, instance_id: &str) -> bool {
@@ -586,11 +951,32 @@ impl GlobalStateManager {
             }
             // Expired — fall through to acquire
             info!(
-                "[TERMINAL-LOCK] Lock expired for {} (held by {}, inactive {}s), granting to {}",
-                instance_id, existing.holder_display_name, elapsed, user.display_name
+                "[TERMINAL-LOCK] Lock expired for {} (held by {}, inactive {}s)",
+                instance_id, existing.holder_display_name, elapsed
             );
         }
 
+        // The lock is free: earlier requests go first
+        let mut queues = self.terminal_lock_queues.write().await;
+        if let Some(queue) = queues.get_mut(instance_id) {
+            if queue.front().is_some_and(|w| w.user_id != user.user_id) {
+                let next = queue.pop_front().expect("front exists");
+                info!(
+                    "[TERMINAL-LOCK] Granting lock for {} to next in queue {}",
+                    instance_id, next.display_name
+                );
+                locks.insert(instance_id.to_string(), TerminalLock::for_waiter(next));
+                if queue.is_empty() {
+                    queues.remove(instance_id);
+                }
+                return false;
+            }
+            queue.retain(|w| w.user_id != user.user_id);
+            if queue.is_empty() {
+                queues.remove(instance_id);
+            }
+        }
+
         locks.insert(
             instance_id.to_string(),
             TerminalLock {
@@ -641,9 +1027,204 @@ impl GlobalStateManager {
         self.terminal_locks.read().await.get(instance_id).cloned()
     }
 
+    /// Queue a request for the terminal lock. A user waits at most once;
+    /// asking again from another tab moves the request to that tab.
+    /// Returns the 1-based queue position if the user wasn't already waiting.
+    pub async fn enqueue_terminal_lock_request(
+        &self,
+        instance_id: &str,
+        connection_id: &str,
+        user: &WsUser,
+    ) -> Option<usize> {
+        let mut queues = self.terminal_lock_queues.write().await;
+        let queue = queues.entry(instance_id.to_string()).or_default();
+        if let Some(waiter) = queue.iter_mut().find(|w| w.user_id == user.user_id) {
+            waiter.connection_id = connection_id.to_string();
+            return None;
+        }
+        queue.push_back(TerminalLockWaiter {
+            connection_id: connection_id.to_string(),
+            user_id: user.user_id.clone(),
+            display_name: user.display_name.clone(),
+        });
+        info!(
+            "[TERMINAL-LOCK] {} requested lock for instance {} (position {})",
+            user.display_name,
+            instance_id,
+            queue.len()
+        );
+        Some(queue.len())
+    }
+
+    /// Users waiting for the terminal lock, oldest request first.
+    pub async fn get_terminal_lock_queue(&self, instance_id: &str) -> Vec<PresenceUser> {
+        self.terminal_lock_queues
+            .read()
+            .await
+            .get(instance_id)
+            .map(|queue| {
+                queue
+                    .iter()
+                    .map(|w| PresenceUser {
+                        user_id: w.user_id.clone(),
+                        display_name: w.display_name.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Remove a waiter (by user, or the oldest) from an instance's queue.
+    fn take_waiter(
+        queues: &mut HashMap<String, VecDeque<TerminalLockWaiter>>,
+        instance_id: &str,
+        user_id: Option<&str>,
+    ) -> Option<TerminalLockWaiter> {
+        let queue = queues.get_mut(instance_id)?;
+        let index = match user_id {
+            Some(user_id) => queue.iter().position(|w| w.user_id == user_id)?,
+            None => 0,
+        };
+        let waiter = queue.remove(index);
+        if queue.is_empty() {
+            queues.remove(instance_id);
+        }
+        waiter
+    }
+
+    /// The holder hands the lock to a waiter (`user_id`, or the oldest
+    /// request). Returns true if the lock changed hands.
+    pub async fn grant_terminal_lock(
+        &self,
+        instance_id: &str,
+        holder_connection_id: &str,
+        user_id: Option<&str>,
+    ) -> bool {
+        let mut locks = self.terminal_locks.write().await;
+        if locks
+            .get(instance_id)
+            .is_none_or(|l| l.holder_connection_id != holder_connection_id)
+        {
+            return false;
+        }
+        let mut queues = self.terminal_lock_queues.write().await;
+        let Some(next) = Self::take_waiter(&mut queues, instance_id, user_id) else {
+            return false;
+        };
+        info!(
+            "[TERMINAL-LOCK] Lock for {} handed to {}",
+            instance_id, next.display_name
+        );
+        locks.insert(instance_id.to_string(), TerminalLock::for_waiter(next));
+        true
+    }
+
+    /// The holder turns down a request (`user_id`, or the oldest).
+    /// Returns true if a request was removed.
+    pub async fn deny_terminal_lock_request(
+        &self,
+        instance_id: &str,
+        holder_connection_id: &str,
+        user_id: Option<&str>,
+    ) -> bool {
+        let locks = self.terminal_locks.read().await;
+        if locks
+            .get(instance_id)
+            .is_none_or(|l| l.holder_connection_id != holder_connection_id)
+        {
+            return false;
+        }
+        let mut queues = self.terminal_lock_queues.write().await;
+        match Self::take_waiter(&mut queues, instance_id, user_id) {
+            Some(denied) => {
+                info!(
+                    "[TERMINAL-LOCK] Lock request from {} for {} denied",
+                    denied.display_name, instance_id
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Withdraw a connection's pending request. Returns true if it was waiting.
+    pub async fn withdraw_terminal_lock_request(
+        &self,
+        instance_id: &str,
+        connection_id: &str,
+    ) -> bool {
+        let mut queues = self.terminal_lock_queues.write().await;
+        let Some(queue) = queues.get_mut(instance_id) else {
+            return false;
+        };
+        let before = queue.len();
+        queue.retain(|w| w.connection_id != connection_id);
+        let withdrawn = queue.len() != before;
+        if queue.is_empty() {
+            queues.remove(instance_id);
+        }
+        withdrawn
+    }
+
+    /// Take the lock regardless of the holder or the queue (admins only;
+    /// the caller checks). The requester leaves the queue if it was in it.
+    pub async fn force_take_terminal_lock(
+        &self,
+        instance_id: &str,
+        connection_id: &str,
+        user: &WsUser,
+    ) {
+        let mut locks = self.terminal_locks.write().await;
+        let mut queues = self.terminal_lock_queues.write().await;
+        Self::take_waiter(&mut queues, instance_id, Some(&user.user_id));
+        if let Some(previous) = locks.get(instance_id) {
+            info!(
+                "[TERMINAL-LOCK] {} took the lock for {} from {}",
+                user.display_name, instance_id, previous.holder_display_name
+            );
+        }
+        locks.insert(
+            instance_id.to_string(),
+            TerminalLock {
+                holder_connection_id: connection_id.to_string(),
+                holder_user_id: user.user_id.clone(),
+                holder_display_name: user.display_name.clone(),
+                last_activity: Utc::now(),
+            },
+        );
+    }
+
+    /// Pass the lock to the oldest waiter if the holder has gone quiet for
+    /// [`TERMINAL_LOCK_TIMEOUT_SECS`] (or nobody holds it).
+    pub async fn hand_over_expired_terminal_lock(&self, instance_id: &str) -> LockExpiry {
+        let mut locks = self.terminal_locks.write().await;
+        let mut queues = self.terminal_lock_queues.write().await;
+        if !queues.contains_key(instance_id) {
+            return LockExpiry::Idle;
+        }
+        if let Some(lock) = locks.get(instance_id) {
+            let elapsed = (Utc::now() - lock.last_activity).num_seconds();
+            if elapsed < TERMINAL_LOCK_TIMEOUT_SECS {
+                let remaining = (TERMINAL_LOCK_TIMEOUT_SECS - elapsed.max(0)) as u64;
+                return LockExpiry::Active(std::time::Duration::from_secs(remaining));
+            }
+        }
+        let Some(next) = Self::take_waiter(&mut queues, instance_id, None) else {
+            return LockExpiry::Idle;
+        };
+        info!(
+            "[TERMINAL-LOCK] Lock for {} expired, handed to {}",
+            instance_id, next.display_name
+        );
+        locks.insert(instance_id.to_string(), TerminalLock::for_waiter(next));
+        LockExpiry::HandedOver
+    }
+
     /// Reconcile terminal lock with presence:
-    /// - If holder disconnected, clear the lock.
-    /// - If only one user is present and no lock exists, auto-grant.
+    /// - Drop queued requests from connections that left.
+    /// - If holder disconnected, pass the lock to the next waiter or clear it.
+    /// - If nobody holds the lock, grant it to the next waiter, or
+    ///   auto-grant if only one user is present.
     ///   Returns true if lock state changed.
     pub async fn reconcile_terminal_lock_with_presence(&self, instance_id: &str) -> bool {
         let presence = self.presence.read().await;
@@ -657,16 +1238,41 @@ impl GlobalStateManager {
         drop(presence);
 
         let mut locks = self.terminal_locks.write().await;
+        let mut queues = self.terminal_lock_queues.write().await;
+
+        let mut changed = false;
+        if let Some(queue) = queues.get_mut(instance_id) {
+            let before = queue.len();
+            queue.retain(|w| connection_ids.contains(&w.connection_id));
+            changed = queue.len() != before;
+            if queue.is_empty() {
+                queues.remove(instance_id);
+            }
+        }
 
         // If holder's connection is no longer present, clear the lock
-        if let Some(lock) = locks.get(instance_id)
-            && !connection_ids.contains(&lock.holder_connection_id)
-        {
+        let holder_left = locks
+            .get(instance_id)
+            .is_some_and(|lock| !connection_ids.contains(&lock.holder_connection_id));
+        if holder_left && let Some(lock) = locks.remove(instance_id) {
             info!(
                 "[TERMINAL-LOCK] Holder {} disconnected from {}, clearing lock",
                 lock.holder_display_name, instance_id
             );
-            locks.remove(instance_id);
+        }
+
+        // Waiters go before the sole-user auto-grant
+        if !locks.contains_key(instance_id)
+            && let Some(next) = Self::take_waiter(&mut queues, instance_id, None)
+        {
+            info!(
+                "[TERMINAL-LOCK] Granting lock for {} to next in queue {}",
+                instance_id, next.display_name
+            );
+            locks.insert(instance_id.to_string(), TerminalLock::for_waiter(next));
+            return true;
+        }
+        if holder_left {
             return true;
         }
 
@@ -692,7 +1298,7 @@ impl GlobalStateManager {
             }
         }
 
-        false
+        changed
     }
 
     /// Register a new instance. The actor's ProcessDriver handles state detection
@@ -719,10 +1325,14 @@ impl GlobalStateManager {
         self.first_input_at.write().await.remove(instance_id);
         // Clean up pending attributions
         self.pending_attributions.write().await.remove(instance_id);
-        // Clean up terminal lock
+        // Clean up terminal lock and its queue
         self.terminal_locks.write().await.remove(instance_id);
-        // Clean up state_entered_at
+        self.terminal_lock_queues.write().await.remove(instance_id);
+        // Clean up state_entered_at and history
         self.state_entered_at.write().await.remove(instance_id);
+        self.state_history.write().await.remove(instance_id);
+        self.prompts_in_flight.write().await.remove(instance_id);
+        self.prompts_held.write().await.remove(instance_id);
     }
 
     /// Get a handle for an instance
@@ -783,9 +1393,13 @@ impl GlobalStateManager {
             .await
             .ok_or_else(|| "Instance handle not found".to_string())?;
 
+        // Session tracking and attribution work on text; the PTY gets the
+        // bytes as sent.
+        let text = String::from_utf8_lossy(&ctx.data);
+
         // 2. Session discovery content tracking
         if handle.get_session_id().await.is_none() {
-            self.mark_first_input(&ctx.instance_id, &ctx.data).await;
+            self.mark_first_input(&ctx.instance_id, &text).await;
         }
 
         // 3. Terminal lock keepalive
@@ -794,7 +1408,7 @@ impl GlobalStateManager {
 
         // 4–5. Attribution (only when user is authenticated)
         if let Some(ref user) = ctx.user {
-            let trimmed = ctx.data.trim();
+            let trimmed = text.trim();
             if !trimmed.is_empty() && trimmed != "\r" && trimmed != "\n" {
                 // 4. Push in-process pending attribution for real-time content matching
                 self.push_pending_attribution(
@@ -844,13 +1458,39 @@ impl GlobalStateManager {
     }
 
     /// Subscribe to instance lifecycle broadcasts (InstanceCreated/InstanceStopped)
-    pub fn subscribe_lifecycle(&self) -> broadcast::Receiver<ServerMessage> {
+    /// as `(seq, message)` pairs
+    pub fn subscribe_lifecycle(&self) -> broadcast::Receiver<(u64, ServerMessage)> {
         self.lifecycle_tx.subscribe()
     }
 
     /// Broadcast an instance lifecycle event to all connected WebSocket clients
     pub fn broadcast_lifecycle(&self, msg: ServerMessage) {
-        let _ = self.lifecycle_tx.send(msg);
+        self.lifecycle_tx.send(msg);
+    }
+
+    /// Sequence of the most recent lifecycle message.
+    pub fn lifecycle_seq(&self) -> u64 {
+        self.lifecycle_tx.last_seq()
+    }
+
+    /// Lifecycle messages a reconnecting client missed, or `None` if it
+    /// cannot resume (see [`ResumeParams::missed_lifecycle`]).
+    pub fn missed_lifecycle(&self, params: &ResumeParams) -> Option<Vec<(u64, ServerMessage)>> {
+        params.missed_lifecycle(&self.epoch, &self.lifecycle_tx)
+    }
+
+    /// Lifecycle messages after `seq` for a client that last saw this
+    /// server run as `epoch`, or `None` if it cannot resume.
+    pub fn lifecycle_since(&self, epoch: &str, seq: u64) -> Option<Vec<(u64, ServerMessage)>> {
+        if epoch != self.epoch {
+            return None;
+        }
+        self.lifecycle_tx.since(seq)
+    }
+
+    /// This server run's id, embedded in resume tokens.
+    pub fn epoch(&self) -> &str {
+        &self.epoch
     }
 
     // =========================================================================
@@ -954,6 +1594,208 @@ mod tests {
     use super::*;
     use tokio::sync::mpsc;
 
+    #[test]
+    fn paste_enter_delay_is_capped() {
+        assert_eq!(paste_enter_delay(2), std::time::Duration::from_millis(51));
+        assert_eq!(
+            paste_enter_delay(10_000),
+            std::time::Duration::from_millis(750)
+        );
+    }
+
+    /// Wait for the next `InboxUpdate` on the lifecycle channel.
+    async fn next_inbox_update(
+        rx: &mut broadcast::Receiver<(u64, ServerMessage)>,
+    ) -> Option<crate::models::InboxItem> {
+        let deadline = std::time::Duration::from_secs(2);
+        loop {
+            match tokio::time::timeout(deadline, rx.recv()).await {
+                Ok(Ok((_, ServerMessage::InboxUpdate { item, .. }))) => return item,
+                Ok(Ok(_)) => continue,
+                other => panic!("no InboxUpdate received: {:?}", other.map(|r| r.is_ok())),
+            }
+        }
+    }
+
+    async fn next_prompt_queue_update(
+        rx: &mut broadcast::Receiver<(u64, ServerMessage)>,
+    ) -> Vec<crate::models::QueuedPrompt> {
+        let deadline = std::time::Duration::from_secs(2);
+        loop {
+            match tokio::time::timeout(deadline, rx.recv()).await {
+                Ok(Ok((_, ServerMessage::PromptQueueUpdate { prompts, .. }))) => return prompts,
+                Ok(Ok(_)) => continue,
+                other => panic!(
+                    "no PromptQueueUpdate received: {:?}",
+                    other.map(|r| r.is_ok())
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prompt_dispatcher_sends_one_prompt_per_idle() {
+        let repo = Arc::new(crate::repository::test_helpers::test_repository().await);
+        let gsm = Arc::new(GlobalStateManager::new(create_state_broadcast()));
+        let (handle, _tx) = InstanceHandle::spawn_test(24, 80, 4096);
+        gsm.insert_test_tracker("claude-1", handle).await;
+        repo.enqueue_prompt("claude-1", "first task", "u-1", "Alice")
+            .await
+            .unwrap();
+        repo.enqueue_prompt("claude-1", "second task", "u-2", "Bob")
+            .await
+            .unwrap();
+        let mut rx = gsm.subscribe_lifecycle();
+        gsm.start_prompt_dispatcher(repo.clone());
+
+        let tx = gsm.broadcast_tx().clone();
+        tx.send(("claude-1".into(), ClaudeState::Idle, false))
+            .unwrap();
+        let remaining = next_prompt_queue_update(&mut rx).await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].content, "second task");
+
+        // A repeated Idle (no transition) doesn't send the next prompt
+        tx.send(("claude-1".into(), ClaudeState::Idle, false))
+            .unwrap();
+        tx.send(("claude-1".into(), ClaudeState::Thinking, false))
+            .unwrap();
+        tx.send(("claude-1".into(), ClaudeState::Idle, false))
+            .unwrap();
+        let remaining = next_prompt_queue_update(&mut rx).await;
+        assert!(remaining.is_empty());
+
+        // Both prompts were typed with their author's attribution
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let first = gsm
+            .consume_pending_attribution("claude-1", "first task")
+            .await
+            .unwrap();
+        assert_eq!(first.display_name, "Alice");
+        let second = gsm
+            .consume_pending_attribution("claude-1", "second task")
+            .await
+            .unwrap();
+        assert_eq!(second.user_id, "u-2");
+    }
+
+    #[tokio::test]
+    async fn test_prompt_dispatcher_waits_for_other_users_lock() {
+        let repo = Arc::new(crate::repository::test_helpers::test_repository().await);
+        let gsm = Arc::new(GlobalStateManager::new(create_state_broadcast()));
+        let (handle, _tx) = InstanceHandle::spawn_test(24, 80, 4096);
+        gsm.insert_test_tracker("claude-1", handle).await;
+        let bob = WsUser {
+            user_id: "u-2".to_string(),
+            display_name: "Bob".to_string(),
+            restricted: false,
+        };
+        assert!(
+            gsm.try_acquire_terminal_lock("claude-1", "conn-bob", &bob)
+                .await
+        );
+        repo.enqueue_prompt("claude-1", "queued task", "u-1", "Alice")
+            .await
+            .unwrap();
+        let mut rx = gsm.subscribe_lifecycle();
+        gsm.start_prompt_dispatcher_every(repo.clone(), std::time::Duration::from_millis(20));
+
+        gsm.broadcast_tx()
+            .send(("claude-1".into(), ClaudeState::Idle, false))
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(repo.list_prompt_queue("claude-1").await.unwrap().len(), 1);
+
+        // Once Bob lets go, the held prompt goes out without another Idle
+        assert!(gsm.release_terminal_lock("claude-1", "conn-bob").await);
+        let remaining = next_prompt_queue_update(&mut rx).await;
+        assert!(remaining.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_dispatcher_retries_after_grace() {
+        let repo = Arc::new(crate::repository::test_helpers::test_repository().await);
+        let gsm = Arc::new(GlobalStateManager::new(create_state_broadcast()));
+        let (handle, _tx) = InstanceHandle::spawn_test(24, 80, 4096);
+        gsm.insert_test_tracker("claude-1", handle).await;
+        repo.enqueue_prompt("claude-1", "swallowed", "u-1", "Alice")
+            .await
+            .unwrap();
+        repo.enqueue_prompt("claude-1", "next", "u-1", "Alice")
+            .await
+            .unwrap();
+        let mut rx = gsm.subscribe_lifecycle();
+        gsm.start_prompt_dispatcher_every(repo.clone(), std::time::Duration::from_millis(20));
+
+        gsm.broadcast_tx()
+            .send(("claude-1".into(), ClaudeState::Idle, false))
+            .unwrap();
+        assert_eq!(next_prompt_queue_update(&mut rx).await.len(), 1);
+
+        // Claude never left Idle; pretend the grace period ran out
+        gsm.prompts_in_flight.write().await.insert(
+            "claude-1".to_string(),
+            std::time::Instant::now() - PROMPT_DISPATCH_GRACE,
+        );
+        assert!(next_prompt_queue_update(&mut rx).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_terminal_bell_creates_inbox_item_for_shells() {
+        let repo = Arc::new(crate::repository::test_helpers::test_repository().await);
+        let gsm = Arc::new(GlobalStateManager::new(create_state_broadcast()));
+        let (handle, _tx) = InstanceHandle::spawn_test(24, 80, 4096);
+        gsm.register_instance("shell-1".into(), handle, "/tmp".into(), Utc::now(), false)
+            .await;
+        let mut rx = gsm.subscribe_lifecycle();
+        gsm.start_inbox_watcher(repo.clone());
+
+        gsm.broadcast_lifecycle(ServerMessage::TerminalNotification {
+            instance_id: "shell-1".into(),
+            kind: TerminalNotificationKind::Bell,
+            title: None,
+            body: None,
+        });
+
+        let item = next_inbox_update(&mut rx).await.unwrap();
+        assert_eq!(item.instance_id, "shell-1");
+        assert_eq!(item.event_type, "notification");
+        let meta: serde_json::Value =
+            serde_json::from_str(item.metadata_json.as_deref().unwrap()).unwrap();
+        assert_eq!(meta["kind"], "bell");
+        assert_eq!(repo.list_inbox().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_terminal_bell_ignored_for_claude_but_notification_kept() {
+        let repo = Arc::new(crate::repository::test_helpers::test_repository().await);
+        let gsm = Arc::new(GlobalStateManager::new(create_state_broadcast()));
+        let (handle, _tx) = InstanceHandle::spawn_test(24, 80, 4096);
+        gsm.insert_test_tracker("claude-1", handle).await;
+        let mut rx = gsm.subscribe_lifecycle();
+        gsm.start_inbox_watcher(repo.clone());
+
+        gsm.broadcast_lifecycle(ServerMessage::TerminalNotification {
+            instance_id: "claude-1".into(),
+            kind: TerminalNotificationKind::Bell,
+            title: None,
+            body: None,
+        });
+        gsm.broadcast_lifecycle(ServerMessage::TerminalNotification {
+            instance_id: "claude-1".into(),
+            kind: TerminalNotificationKind::Notification,
+            title: Some("Claude".into()),
+            body: Some("Task complete".into()),
+        });
+
+        // The first InboxUpdate is for the notification — the bell was skipped
+        let item = next_inbox_update(&mut rx).await.unwrap();
+        let meta: serde_json::Value =
+            serde_json::from_str(item.metadata_json.as_deref().unwrap()).unwrap();
+        assert_eq!(meta["kind"], "notification");
+        assert_eq!(meta["body"], "Task complete");
+    }
+
     #[tokio::test]
     async fn test_mark_first_input_returns_true_then_false() {
         let broadcast_tx = create_state_broadcast();
@@ -1225,14 +2067,14 @@ mod tests {
         // Fill the channel
         tx.send(ServerMessage::Output {
             instance_id: "inst-1".to_string(),
-            data: "1".to_string(),
+            data: "1".into(),
             cursor: None,
         })
         .await
         .unwrap();
         tx.send(ServerMessage::Output {
             instance_id: "inst-1".to_string(),
-            data: "2".to_string(),
+            data: "2".into(),
             cursor: None,
         })
         .await
@@ -1248,7 +2090,7 @@ mod tests {
         // Now we can send again
         tx.send(ServerMessage::Output {
             instance_id: "inst-1".to_string(),
-            data: "3".to_string(),
+            data: "3".into(),
             cursor: None,
         })
         .await
@@ -1489,6 +2331,7 @@ mod tests {
         WsUser {
             user_id: user_id.to_string(),
             display_name: display_name.to_string(),
+            restricted: false,
         }
     }
 
@@ -1584,6 +2427,7 @@ mod tests {
         let user = WsUser {
             user_id: "u-1".into(),
             display_name: "Alice".into(),
+            restricted: false,
         };
 
         let users = state_mgr.add_presence("inst-1", "conn-1", &user).await;
@@ -1597,6 +2441,7 @@ mod tests {
         let user = WsUser {
             user_id: "u-1".into(),
             display_name: "Alice".into(),
+            restricted: false,
         };
 
         // Same user, two connections (two tabs)
@@ -1612,10 +2457,12 @@ mod tests {
         let alice = WsUser {
             user_id: "u-1".into(),
             display_name: "Alice".into(),
+            restricted: false,
         };
         let bob = WsUser {
             user_id: "u-2".into(),
             display_name: "Bob".into(),
+            restricted: false,
         };
 
         state_mgr.add_presence("inst-1", "conn-1", &alice).await;
@@ -1634,6 +2481,7 @@ mod tests {
         let user = WsUser {
             user_id: "u-1".into(),
             display_name: "Alice".into(),
+            restricted: false,
         };
 
         state_mgr.add_presence("inst-1", "conn-1", &user).await;
@@ -1798,6 +2646,205 @@ mod tests {
         assert!(state_mgr.get_terminal_lock("inst-1").await.is_none());
     }
 
+    // =========================================================================
+    // Terminal lock queue tests
+    // =========================================================================
+
+    /// Alice holds the lock for inst-1; Bob and Carol are present too.
+    async fn lock_with_waiters() -> GlobalStateManager {
+        let state_mgr = GlobalStateManager::new(create_state_broadcast());
+        for (conn, id, name) in [
+            ("conn-1", "u-1", "Alice"),
+            ("conn-2", "u-2", "Bob"),
+            ("conn-3", "u-3", "Carol"),
+        ] {
+            state_mgr
+                .add_presence("inst-1", conn, &make_ws_user(id, name))
+                .await;
+        }
+        assert!(
+            state_mgr
+                .try_acquire_terminal_lock("inst-1", "conn-1", &make_ws_user("u-1", "Alice"))
+                .await
+        );
+        state_mgr
+    }
+
+    async fn queued_names(state_mgr: &GlobalStateManager) -> Vec<String> {
+        state_mgr
+            .get_terminal_lock_queue("inst-1")
+            .await
+            .into_iter()
+            .map(|u| u.display_name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_lock_requests_queue_in_order() {
+        let state_mgr = lock_with_waiters().await;
+        let bob = make_ws_user("u-2", "Bob");
+        let carol = make_ws_user("u-3", "Carol");
+        assert_eq!(
+            state_mgr
+                .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+                .await,
+            Some(1)
+        );
+        assert_eq!(
+            state_mgr
+                .enqueue_terminal_lock_request("inst-1", "conn-3", &carol)
+                .await,
+            Some(2)
+        );
+        // Asking again doesn't jump or duplicate
+        assert_eq!(
+            state_mgr
+                .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+                .await,
+            None
+        );
+        assert_eq!(queued_names(&state_mgr).await, ["Bob", "Carol"]);
+
+        // Releasing passes the lock to the oldest request
+        state_mgr.release_terminal_lock("inst-1", "conn-1").await;
+        assert!(
+            state_mgr
+                .reconcile_terminal_lock_with_presence("inst-1")
+                .await
+        );
+        let lock = state_mgr.get_terminal_lock("inst-1").await.unwrap();
+        assert_eq!(lock.holder_user_id, "u-2");
+        assert_eq!(queued_names(&state_mgr).await, ["Carol"]);
+    }
+
+    #[tokio::test]
+    async fn test_holder_grants_and_denies_requests() {
+        let state_mgr = lock_with_waiters().await;
+        let bob = make_ws_user("u-2", "Bob");
+        let carol = make_ws_user("u-3", "Carol");
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+            .await;
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-3", &carol)
+            .await;
+
+        // Only the holder decides
+        assert!(
+            !state_mgr
+                .grant_terminal_lock("inst-1", "conn-2", None)
+                .await
+        );
+        assert!(
+            !state_mgr
+                .deny_terminal_lock_request("inst-1", "conn-3", None)
+                .await
+        );
+
+        assert!(
+            state_mgr
+                .deny_terminal_lock_request("inst-1", "conn-1", None)
+                .await
+        );
+        assert_eq!(queued_names(&state_mgr).await, ["Carol"]);
+        assert!(
+            state_mgr
+                .grant_terminal_lock("inst-1", "conn-1", Some("u-3"))
+                .await
+        );
+        let lock = state_mgr.get_terminal_lock("inst-1").await.unwrap();
+        assert_eq!(lock.holder_connection_id, "conn-3");
+        assert!(queued_names(&state_mgr).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_and_disconnect_leave_the_queue() {
+        let state_mgr = lock_with_waiters().await;
+        let bob = make_ws_user("u-2", "Bob");
+        let carol = make_ws_user("u-3", "Carol");
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+            .await;
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-3", &carol)
+            .await;
+
+        assert!(
+            state_mgr
+                .withdraw_terminal_lock_request("inst-1", "conn-2")
+                .await
+        );
+        state_mgr
+            .remove_presence_from_instance("inst-1", "conn-3")
+            .await;
+        assert!(
+            state_mgr
+                .reconcile_terminal_lock_with_presence("inst-1")
+                .await
+        );
+        assert!(queued_names(&state_mgr).await.is_empty());
+        let lock = state_mgr.get_terminal_lock("inst-1").await.unwrap();
+        assert_eq!(lock.holder_user_id, "u-1");
+    }
+
+    #[tokio::test]
+    async fn test_force_take_skips_the_queue() {
+        let state_mgr = lock_with_waiters().await;
+        let bob = make_ws_user("u-2", "Bob");
+        let carol = make_ws_user("u-3", "Carol");
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+            .await;
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-3", &carol)
+            .await;
+
+        state_mgr
+            .force_take_terminal_lock("inst-1", "conn-3", &carol)
+            .await;
+        let lock = state_mgr.get_terminal_lock("inst-1").await.unwrap();
+        assert_eq!(lock.holder_user_id, "u-3");
+        assert_eq!(queued_names(&state_mgr).await, ["Bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_lock_goes_to_next_waiter() {
+        let state_mgr = lock_with_waiters().await;
+        let bob = make_ws_user("u-2", "Bob");
+        let carol = make_ws_user("u-3", "Carol");
+
+        // Nobody waiting: nothing to do
+        assert_eq!(
+            state_mgr.hand_over_expired_terminal_lock("inst-1").await,
+            LockExpiry::Idle
+        );
+
+        state_mgr
+            .enqueue_terminal_lock_request("inst-1", "conn-2", &bob)
+            .await;
+        assert!(matches!(
+            state_mgr.hand_over_expired_terminal_lock("inst-1").await,
+            LockExpiry::Active(_)
+        ));
+
+        // Alice goes quiet; Carol asking doesn't let her jump ahead of Bob
+        state_mgr
+            .terminal_locks
+            .write()
+            .await
+            .get_mut("inst-1")
+            .unwrap()
+            .last_activity = Utc::now() - chrono::Duration::seconds(TERMINAL_LOCK_TIMEOUT_SECS + 1);
+        assert!(
+            !state_mgr
+                .try_acquire_terminal_lock("inst-1", "conn-3", &carol)
+                .await
+        );
+        let lock = state_mgr.get_terminal_lock("inst-1").await.unwrap();
+        assert_eq!(lock.holder_user_id, "u-2");
+        assert!(queued_names(&state_mgr).await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_reconcile_empty_instance_no_change() {
         let state_mgr = GlobalStateManager::new(create_state_broadcast());
@@ -1821,7 +2868,8 @@ mod tests {
             instance_id: "inst-1".to_string(),
         });
 
-        let msg = rx.recv().await.unwrap();
+        let (seq, msg) = rx.recv().await.unwrap();
+        assert_eq!(seq, 1);
         match msg {
             ServerMessage::InstanceStopped { instance_id } => {
                 assert_eq!(instance_id, "inst-1");
@@ -1904,7 +2952,7 @@ mod tests {
         for ch in chars.chars() {
             let ctx = InputContext {
                 instance_id: instance_id.to_string(),
-                data: ch.to_string(),
+                data: ch.to_string().into_bytes(),
                 connection_id: "test-conn".to_string(),
                 user: None,
                 task_id: None,
@@ -1913,7 +2961,7 @@ mod tests {
         }
         let ctx = InputContext {
             instance_id: instance_id.to_string(),
-            data: "\r".to_string(),
+            data: "\r".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -1932,7 +2980,7 @@ mod tests {
     ) {
         let ctx = InputContext {
             instance_id: instance_id.to_string(),
-            data: text.to_string(),
+            data: text.into(),
             connection_id: "test-conn".to_string(),
             user,
             task_id,
@@ -1940,7 +2988,7 @@ mod tests {
         let _ = state_mgr.handle_input(ctx, None).await;
         let ctx = InputContext {
             instance_id: instance_id.to_string(),
-            data: "\r".to_string(),
+            data: "\r".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -2095,7 +3143,7 @@ mod tests {
         // handle_input uses connection_id "test-conn" which matches the lock holder
         let ctx = InputContext {
             instance_id: "inst-1".to_string(),
-            data: "x".to_string(),
+            data: "x".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -2320,7 +3368,7 @@ mod tests {
         for ch in ['h', 'i'] {
             let ctx = InputContext {
                 instance_id: "test-instance".to_string(),
-                data: ch.to_string(),
+                data: ch.to_string().into_bytes(),
                 connection_id: "test-conn".to_string(),
                 user: None,
                 task_id: None,
@@ -2329,7 +3377,7 @@ mod tests {
         }
         let ctx = InputContext {
             instance_id: "test-instance".to_string(),
-            data: "\r".to_string(),
+            data: "\r".into(),
             connection_id: "test-conn".to_string(),
             user: None,
             task_id: None,
@@ -2380,7 +3428,7 @@ mod tests {
         for ch in ['a', 'b', 'c'] {
             let ctx = InputContext {
                 instance_id: "test-instance".to_string(),
-                data: ch.to_string(),
+                data: ch.to_string().into_bytes(),
                 connection_id: "test-conn".to_string(),
                 user: None,
                 task_id: None,
@@ -2421,11 +3469,15 @@ mod tests {
         state_mgr
             .try_acquire_terminal_lock("inst-1", "conn-1", &alice)
             .await;
+        state_mgr
+            .record_state_transition("inst-1", Utc::now(), ClaudeState::Thinking)
+            .await;
 
         // Unregister should clean up everything
         state_mgr.unregister_instance("inst-1").await;
 
         assert!(state_mgr.get_first_input_at("inst-1").await.is_none());
+        assert!(state_mgr.get_state_history("inst-1").await.is_empty());
         assert!(state_mgr.get_terminal_lock("inst-1").await.is_none());
         // Session should be reclaimable
         assert!(state_mgr.try_claim_session("sess-1", "inst-2").await);
@@ -2437,4 +3489,76 @@ mod tests {
                 .is_none()
         );
     }
+
+    #[tokio::test]
+    async fn test_state_history_is_bounded() {
+        let state_mgr = GlobalStateManager::new(create_state_broadcast());
+        for i in 0..STATE_HISTORY_CAP + 5 {
+            let state = if i % 2 == 0 {
+                ClaudeState::Thinking
+            } else {
+                ClaudeState::Idle
+            };
+            state_mgr
+                .record_state_transition("inst-1", Utc::now(), state)
+                .await;
+        }
+
+        let history = state_mgr.get_state_history("inst-1").await;
+        assert_eq!(history.len(), STATE_HISTORY_CAP);
+        // Oldest five were evicted: first retained entry is index 5 (odd → Idle)
+        assert_eq!(history[0].state, ClaudeState::Idle);
+    }
+
+    #[tokio::test]
+    async fn handle_input_writes_non_utf8_bytes_unchanged() {
+        let manager = crate::instance_manager::InstanceManager::new(
+            "sh".into(),
+            9000,
+            25 * 1024 * 1024,
+            0,
+            None,
+            None,
+        );
+        let inst = crate::test_helpers::create_test_instance(
+            &manager,
+            None,
+            Some("/tmp".into()),
+            Some("head -c 3 | od -An -tx1".into()),
+        )
+        .await
+        .unwrap();
+        let handle = manager.get_handle(&inst.id).await.unwrap();
+        let state_mgr = GlobalStateManager::new(create_state_broadcast());
+        state_mgr
+            .register_instance(
+                inst.id.clone(),
+                handle.clone(),
+                "/tmp".into(),
+                Utc::now(),
+                false,
+            )
+            .await;
+
+        let ctx = InputContext {
+            instance_id: inst.id.clone(),
+            data: vec![0xff, 0xfe, b'\n'],
+            connection_id: "conn-1".to_string(),
+            user: None,
+            task_id: None,
+        };
+        state_mgr.handle_input(ctx, None).await.unwrap();
+
+        let mut dump = String::new();
+        for _ in 0..50 {
+            dump = handle.get_text(false).await.join("\n");
+            if dump.contains("ff fe 0a") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        // A lossy decode would have sent U+FFFD (ef bf bd) instead
+        assert!(dump.contains("ff fe 0a"), "od output: {dump}");
+        manager.stop(&inst.id).await;
+    }
 }
@@ -0,0 +1,280 @@
+//! Selective Subscriptions
+//!
+//! By default a connection receives every broadcast on the server: state
+//! changes, presence, tasks, chat, inbox and lobby traffic for all instances.
+//! Lightweight clients (the TUI picker, a status-bar widget) send
+//! `ClientMessage::Subscribe` to switch to filtered mode, after which only
+//! broadcasts matching one of their [`Topic`]s are forwarded. Events for the
+//! connection's focused instance and replies to its own requests always get
+//! through.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::protocol::ServerMessage;
+
+/// A class of broadcasts a connection can subscribe to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Topic {
+    /// Lifecycle, state, presence, terminal lock, prompt queue, title and
+    /// notification events, for every instance or only `instance_id`
+    Instances {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        instance_id: Option<String>,
+    },
+    /// Task updates and deletions
+    Tasks,
+    /// Chat messages, in every scope or only `scope`
+    Chat {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<String>,
+    },
+    /// Inbox updates
+    Inbox,
+    /// Lobby relay traffic
+    Lobby,
+}
+
+impl Topic {
+    /// Every topic, unfiltered: what a connection receives until it subscribes.
+    pub fn all() -> Vec<Topic> {
+        vec![
+            Topic::Instances { instance_id: None },
+            Topic::Tasks,
+            Topic::Chat { scope: None },
+            Topic::Inbox,
+            Topic::Lobby,
+        ]
+    }
+}
+
+/// Which topic a broadcast belongs to.
+enum Category<'a> {
+    Instance(&'a str),
+    Tasks,
+    Chat(&'a str),
+    Inbox,
+    Lobby,
+}
+
+fn categorize(msg: &ServerMessage) -> Option<Category<'_>> {
+    use ServerMessage as M;
+    Some(match msg {
+        M::Sequenced { message, .. } => return categorize(message),
+        M::StateChange { instance_id, .. }
+        | M::InstanceStopped { instance_id }
+        | M::InstanceRenamed { instance_id, .. }
+        | M::PresenceUpdate { instance_id, .. }
+        | M::SessionRotated { instance_id, .. }
+        | M::TerminalLockUpdate { instance_id, .. }
+        | M::TerminalTitle { instance_id, .. }
+        | M::TerminalNotification { instance_id, .. }
+        | M::PromptQueueUpdate { instance_id, .. } => Category::Instance(instance_id),
+        M::InstanceCreated { instance } => Category::Instance(&instance.id),
+        M::TaskUpdate { .. } | M::TaskDeleted { .. } => Category::Tasks,
+        M::ChatMessage { scope, .. } => Category::Chat(scope),
+        M::InboxUpdate { .. } | M::InboxList { .. } => Category::Inbox,
+        M::LobbyBroadcast { .. } => Category::Lobby,
+        _ => return None,
+    })
+}
+
+/// The instance a broadcast is about, if any. Inbox updates belong to
+/// [`Topic::Inbox`] but are still about one instance.
+pub fn broadcast_instance(msg: &ServerMessage) -> Option<&str> {
+    match msg {
+        ServerMessage::Sequenced { message, .. } => broadcast_instance(message),
+        ServerMessage::InboxUpdate { instance_id, .. } => Some(instance_id),
+        _ => match categorize(msg) {
+            Some(Category::Instance(id)) => Some(id),
+            _ => None,
+        },
+    }
+}
+
+/// A connection's subscriptions. `None` until the client first subscribes,
+/// meaning everything.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    topics: Option<HashSet<Topic>>,
+}
+
+impl Subscriptions {
+    /// Add topics, switching to filtered mode if not already in it.
+    pub fn subscribe(&mut self, topics: Vec<Topic>) {
+        self.topics.get_or_insert_default().extend(topics);
+    }
+
+    /// Remove topics. Unsubscribing while unfiltered starts from
+    /// [`Topic::all`]. Only exact topics are removed: dropping
+    /// `Instances { instance_id: Some(..) }` does not carve one instance out
+    /// of `Instances { instance_id: None }`.
+    pub fn unsubscribe(&mut self, topics: &[Topic]) {
+        let current = self
+            .topics
+            .get_or_insert_with(|| Topic::all().into_iter().collect());
+        for topic in topics {
+            current.remove(topic);
+        }
+    }
+
+    /// The active topics, sorted for stable output (`None` = everything).
+    pub fn topics(&self) -> Option<Vec<Topic>> {
+        self.topics.as_ref().map(|set| {
+            let mut topics: Vec<Topic> = set.iter().cloned().collect();
+            topics.sort_by_key(|t| format!("{t:?}"));
+            topics
+        })
+    }
+
+    /// Whether to forward events about `instance_id`.
+    pub fn wants_instance(&self, instance_id: &str, focused: Option<&str>) -> bool {
+        let Some(topics) = &self.topics else {
+            return true;
+        };
+        focused == Some(instance_id)
+            || topics.contains(&Topic::Instances { instance_id: None })
+            || topics.contains(&Topic::Instances {
+                instance_id: Some(instance_id.to_string()),
+            })
+    }
+
+    /// Whether to forward a broadcast. `focused` is the connection's focused
+    /// instance, whose events are always wanted.
+    pub fn wants(&self, msg: &ServerMessage, focused: Option<&str>) -> bool {
+        let Some(topics) = &self.topics else {
+            return true;
+        };
+        match categorize(msg) {
+            None => true,
+            Some(Category::Instance(id)) => self.wants_instance(id, focused),
+            Some(Category::Tasks) => topics.contains(&Topic::Tasks),
+            Some(Category::Chat(scope)) => {
+                topics.contains(&Topic::Chat { scope: None })
+                    || topics.contains(&Topic::Chat {
+                        scope: Some(scope.to_string()),
+                    })
+            }
+            Some(Category::Inbox) => topics.contains(&Topic::Inbox),
+            Some(Category::Lobby) => topics.contains(&Topic::Lobby),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stopped(id: &str) -> ServerMessage {
+        ServerMessage::InstanceStopped {
+            instance_id: id.to_string(),
+        }
+    }
+
+    fn task_deleted() -> ServerMessage {
+        ServerMessage::TaskDeleted { task_id: 1 }
+    }
+
+    #[test]
+    fn unfiltered_until_first_subscribe() {
+        let subs = Subscriptions::default();
+        assert!(subs.wants(&stopped("a"), None));
+        assert!(subs.wants(&task_deleted(), None));
+        assert_eq!(subs.topics(), None);
+    }
+
+    #[test]
+    fn subscribe_filters_to_topics() {
+        let mut subs = Subscriptions::default();
+        subs.subscribe(vec![Topic::Instances {
+            instance_id: Some("a".into()),
+        }]);
+        assert!(subs.wants(&stopped("a"), None));
+        assert!(!subs.wants(&stopped("b"), None));
+        assert!(!subs.wants(&task_deleted(), None));
+        // Uncategorized messages (errors, replies) always pass
+        assert!(subs.wants(&ServerMessage::BinaryFramesEnabled, None));
+    }
+
+    #[test]
+    fn focused_instance_is_implicitly_subscribed() {
+        let mut subs = Subscriptions::default();
+        subs.subscribe(vec![]);
+        assert!(subs.wants(&stopped("b"), Some("b")));
+        assert!(!subs.wants(&stopped("c"), Some("b")));
+    }
+
+    #[test]
+    fn sequenced_messages_are_categorized_by_content() {
+        let mut subs = Subscriptions::default();
+        subs.subscribe(vec![Topic::Tasks]);
+        let seq = |message| ServerMessage::Sequenced {
+            seq: 1,
+            message: Box::new(message),
+        };
+        assert!(subs.wants(&seq(task_deleted()), None));
+        assert!(!subs.wants(&seq(stopped("a")), None));
+    }
+
+    #[test]
+    fn chat_scope_filter() {
+        let mut subs = Subscriptions::default();
+        subs.subscribe(vec![Topic::Chat {
+            scope: Some("global".into()),
+        }]);
+        let chat = |scope: &str| ServerMessage::ChatMessage {
+            id: 1,
+            uuid: "u".into(),
+            scope: scope.into(),
+            user_id: "x".into(),
+            display_name: "X".into(),
+            content: "hi".into(),
+            created_at: 0,
+            forwarded_from: None,
+            topic: None,
+        };
+        assert!(subs.wants(&chat("global"), None));
+        assert!(!subs.wants(&chat("inst-1"), None));
+    }
+
+    #[test]
+    fn unsubscribe_from_everything() {
+        let mut subs = Subscriptions::default();
+        subs.unsubscribe(&[Topic::Lobby, Topic::Chat { scope: None }]);
+        assert!(subs.wants(&stopped("a"), None));
+        assert!(subs.wants(&task_deleted(), None));
+        assert!(!subs.wants(
+            &ServerMessage::LobbyBroadcast {
+                sender_id: "s".into(),
+                channel: "c".into(),
+                payload: serde_json::Value::Null,
+            },
+            None
+        ));
+        assert_eq!(subs.topics().map(|t| t.len()), Some(3));
+    }
+
+    #[test]
+    fn topic_wire_format() {
+        let json = serde_json::to_string(&[
+            Topic::Instances { instance_id: None },
+            Topic::Chat {
+                scope: Some("global".into()),
+            },
+        ])
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"[{"kind":"instances"},{"kind":"chat","scope":"global"}]"#
+        );
+        let parsed: Topic =
+            serde_json::from_str(r#"{"kind":"instances","instance_id":"a"}"#).unwrap();
+        assert_eq!(
+            parsed,
+            Topic::Instances {
+                instance_id: Some("a".into())
+            }
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-connection instance visibility.
+//!
+//! With auth enabled, non-admin users only see the instances they hold a
+//! grant on: listings, inbox items and instance broadcasts for anything else
+//! are dropped. The mux WebSocket and the SSE stream both filter through
+//! [`Visibility`].
+//!
+//! Grants are looked up on first sight and cached per connection. The cache
+//! is dropped whenever the repository's permission version moves (a grant
+//! was made or revoked), and an instance's entry is forgotten once it stops,
+//! since a restored instance may reuse its id with different grants.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::models::InboxItem;
+use crate::repository::ConversationRepository;
+
+use super::protocol::ServerMessage;
+use super::subscriptions::broadcast_instance;
+
+/// Which instances a connection may see — see the module docs.
+#[derive(Clone)]
+pub(crate) struct Visibility {
+    /// The restricted user being filtered for; `None` sees everything.
+    user_id: Option<String>,
+    repository: Option<Arc<ConversationRepository>>,
+    permitted: HashSet<String>,
+    /// Repository permission version `permitted` was filled at.
+    permissions_version: u64,
+}
+
+impl Visibility {
+    /// `restricted_user` is the user id to filter for, or `None` for
+    /// connections that see everything (auth off, admins).
+    pub(crate) fn new(
+        restricted_user: Option<String>,
+        repository: Option<Arc<ConversationRepository>>,
+    ) -> Self {
+        Self {
+            user_id: restricted_user,
+            repository,
+            permitted: HashSet::new(),
+            permissions_version: 0,
+        }
+    }
+
+    pub(crate) async fn can_see(&mut self, instance_id: &str) -> bool {
+        let Some(user_id) = &self.user_id else {
+            return true;
+        };
+        let Some(repo) = &self.repository else {
+            return false;
+        };
+        let version = repo.permissions_version();
+        if version != self.permissions_version {
+            self.permitted.clear();
+            self.permissions_version = version;
+        }
+        if self.permitted.contains(instance_id) {
+            return true;
+        }
+        match repo.check_instance_permission(instance_id, user_id).await {
+            Ok(Some(_)) => {
+                self.permitted.insert(instance_id.to_string());
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                warn!(instance = %instance_id, "Failed to check instance permission: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Whether a broadcast may be forwarded. Broadcasts not about an
+    /// instance always are.
+    pub(crate) async fn allows(&mut self, msg: &ServerMessage) -> bool {
+        let Some(instance_id) = broadcast_instance(msg) else {
+            return true;
+        };
+        let allowed = self.can_see(instance_id).await;
+        if let ServerMessage::InstanceStopped { instance_id } = msg {
+            self.permitted.remove(instance_id);
+        }
+        allowed
+    }
+
+    /// The inbox items for instances this connection may see.
+    pub(crate) async fn visible_inbox(&mut self, items: Vec<InboxItem>) -> Vec<InboxItem> {
+        let mut visible = Vec::with_capacity(items.len());
+        for item in items {
+            if self.can_see(&item.instance_id).await {
+                visible.push(item);
+            }
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InstancePermission, InstanceRole};
+
+    fn grant(instance_id: &str) -> InstancePermission {
+        InstancePermission {
+            instance_id: instance_id.to_string(),
+            user_id: "u-1".to_string(),
+            role: InstanceRole::Viewer,
+            granted_at: 0,
+            granted_by: None,
+        }
+    }
+
+    fn stopped(instance_id: &str) -> ServerMessage {
+        ServerMessage::InstanceStopped {
+            instance_id: instance_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn restricted_users_only_see_granted_instances() {
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let repo = state.repository.clone();
+        crate::test_helpers::create_test_user(&repo, "u-1", "bob", "Bob").await;
+        repo.create_instance_permission(&grant("inst-1"))
+            .await
+            .unwrap();
+
+        let mut visible = Visibility::new(Some("u-1".to_string()), Some(repo.clone()));
+        assert!(visible.allows(&stopped("inst-1")).await);
+        assert!(!visible.allows(&stopped("inst-2")).await);
+        assert!(
+            !visible
+                .allows(&ServerMessage::PromptQueueUpdate {
+                    instance_id: "inst-2".to_string(),
+                    prompts: Vec::new(),
+                })
+                .await
+        );
+        // Inbox updates and the initial inbox are scoped to the instance too
+        let inbox_update = |id: &str| ServerMessage::InboxUpdate {
+            instance_id: id.to_string(),
+            item: None,
+        };
+        assert!(visible.allows(&inbox_update("inst-1")).await);
+        assert!(!visible.allows(&inbox_update("inst-2")).await);
+        repo.upsert_inbox_item("inst-1", "needs_input", None)
+            .await
+            .unwrap();
+        repo.upsert_inbox_item("inst-2", "needs_input", None)
+            .await
+            .unwrap();
+        let inbox = visible
+            .visible_inbox(repo.list_inbox().await.unwrap())
+            .await;
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].instance_id, "inst-1");
+
+        // Broadcasts not about an instance pass through
+        assert!(
+            visible
+                .allows(&ServerMessage::TaskDeleted { task_id: 1 })
+                .await
+        );
+
+        // A grant made after the connection opened is picked up
+        repo.create_instance_permission(&grant("inst-2"))
+            .await
+            .unwrap();
+        assert!(visible.allows(&stopped("inst-2")).await);
+
+        // Unrestricted connections see everything
+        let mut everyone = Visibility::new(None, None);
+        assert!(everyone.can_see("inst-3").await);
+    }
+
+    #[tokio::test]
+    async fn revoked_and_stopped_instances_are_rechecked() {
+        let (state, _tmp, _admin) = crate::test_helpers::test_app_state_with_auth().await;
+        let repo = state.repository.clone();
+        crate::test_helpers::create_test_user(&repo, "u-1", "bob", "Bob").await;
+        repo.create_instance_permission(&grant("inst-1"))
+            .await
+            .unwrap();
+        repo.create_instance_permission(&grant("inst-2"))
+            .await
+            .unwrap();
+
+        let mut visible = Visibility::new(Some("u-1".to_string()), Some(repo.clone()));
+        assert!(visible.can_see("inst-1").await);
+        assert!(visible.can_see("inst-2").await);
+
+        // Revoking a grant takes effect on the next check
+        repo.delete_instance_permission("inst-1", "u-1")
+            .await
+            .unwrap();
+        assert!(!visible.can_see("inst-1").await);
+
+        // Once an instance stops its cached grant is forgotten, so a reused
+        // id is looked up afresh. The stop itself is still delivered.
+        assert!(visible.allows(&stopped("inst-2")).await);
+        assert!(!visible.permitted.contains("inst-2"));
+    }
+}
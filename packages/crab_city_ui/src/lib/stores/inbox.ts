@@ -22,7 +22,7 @@ import { api } from '$lib/utils/api';
 
 export interface InboxItem {
   instance_id: string;
-  event_type: 'completed_turn' | 'needs_input' | 'error';
+  event_type: 'completed_turn' | 'needs_input' | 'notification' | 'error';
   turn_count: number;
   created_at: number;
   updated_at: number;
@@ -41,12 +41,13 @@ export const inboxItems = writable<Map<string, InboxItem>>(new Map());
 /** Total count of inbox items */
 export const inboxCount = derived(inboxItems, ($items) => $items.size);
 
-/** Sorted inbox items: needs_input first, then error, then completed_turn; oldest first within tier */
+/** Sorted inbox items: needs_input first, then error, then completed_turn/notification; oldest first within tier */
 export const inboxSorted = derived(inboxItems, ($items) => {
   const priorityOrder: Record<string, number> = {
     needs_input: 0,
     error: 1,
-    completed_turn: 2
+    completed_turn: 2,
+    notification: 2
   };
 
   return Array.from($items.values()).sort((a, b) => {
@@ -73,7 +74,11 @@ export function handleInboxUpdate(instanceId: string, item: InboxItem | null): v
   });
 
   // Browser notification for actionable inbox events
-  if (item?.event_type === 'needs_input' || item?.event_type === 'completed_turn') {
+  if (
+    item?.event_type === 'needs_input' ||
+    item?.event_type === 'completed_turn' ||
+    item?.event_type === 'notification'
+  ) {
     const focusedId = get(currentInstanceId);
     const isHidden = typeof document !== 'undefined' && document.visibilityState === 'hidden';
     // Notify if the instance isn't focused, or the tab is hidden (user in another app)
@@ -121,13 +126,24 @@ export async function dismissInboxItem(instanceId: string): Promise<boolean> {
 // Pure Utilities
 // =============================================================================
 
+/** Title/body of a "notification" inbox item (terminal bell or OSC 9/777) */
+export function parseTerminalNotification(item: InboxItem): { title?: string; body?: string } {
+  if (!item.metadata_json) return {};
+  try {
+    const meta = JSON.parse(item.metadata_json);
+    return { title: meta?.title ?? undefined, body: meta?.body ?? undefined };
+  } catch {
+    return {};
+  }
+}
+
 /** Compute attention level from instance state + inbox item */
 export function getAttentionLevel(instance: Instance, inboxItem?: InboxItem): AttentionLevel {
   if (inboxItem) {
     if (inboxItem.event_type === 'needs_input' || inboxItem.event_type === 'error') {
       return 'critical';
     }
-    if (inboxItem.event_type === 'completed_turn') {
+    if (inboxItem.event_type === 'completed_turn' || inboxItem.event_type === 'notification') {
       return 'warning';
     }
   }
@@ -205,6 +221,10 @@ function notifyInboxEvent(instanceId: string, item: InboxItem): void {
         /* ignore parse errors */
       }
     }
+  } else if (item.event_type === 'notification') {
+    const meta = parseTerminalNotification(item);
+    title = meta.title ? `${displayName}: ${meta.title}` : displayName;
+    body = meta.body ?? 'Rang the bell';
   } else {
     // completed_turn
     title = `${displayName} finished`;
@@ -0,0 +1,115 @@
+/**
+ * Prompt Queue Store
+ *
+ * Server-side, per-instance queue of prompts that the daemon types into
+ * Claude one at a time, each time it returns to Idle. Any operator can add,
+ * reorder or cancel entries; every change is broadcast as a full snapshot
+ * (PromptQueueUpdate), so the server stays the single source of truth.
+ */
+
+import { writable, derived } from 'svelte/store';
+import { currentInstanceId } from './instances';
+import { addToast } from './toasts';
+import { api } from '$lib/utils/api';
+
+// =============================================================================
+// Types (match Rust wire format — snake_case field names)
+// =============================================================================
+
+export interface QueuedPrompt {
+  id: number;
+  instance_id: string;
+  content: string;
+  user_id: string;
+  display_name: string;
+  position: number;
+  created_at: number;
+}
+
+// =============================================================================
+// Stores
+// =============================================================================
+
+/** Queued prompts per instance, next to send first */
+export const promptQueues = writable<Map<string, QueuedPrompt[]>>(new Map());
+
+/** Queue for the focused instance */
+export const currentInstancePrompts = derived([promptQueues, currentInstanceId], ([$queues, $id]) =>
+  $id ? ($queues.get($id) ?? []) : []
+);
+
+// =============================================================================
+// WebSocket Handlers (called from ws-handlers.ts)
+// =============================================================================
+
+/** Handle a PromptQueueUpdate message — replace the instance's queue */
+export function handlePromptQueueUpdate(instanceId: string, prompts: QueuedPrompt[]): void {
+  promptQueues.update((map) => {
+    if (prompts.length > 0) {
+      map.set(instanceId, prompts);
+    } else {
+      map.delete(instanceId);
+    }
+    return new Map(map);
+  });
+}
+
+// =============================================================================
+// API Actions
+// =============================================================================
+
+function queueUrl(instanceId: string): string {
+  return `/api/instances/${encodeURIComponent(instanceId)}/prompts`;
+}
+
+/** Fetch an instance's queue (on focus; later changes arrive via WS) */
+export async function loadPromptQueue(instanceId: string): Promise<void> {
+  try {
+    const response = await api(queueUrl(instanceId));
+    if (!response.ok) return;
+    handlePromptQueueUpdate(instanceId, await response.json());
+  } catch (error) {
+    console.error('[PromptQueue] Failed to load:', error);
+  }
+}
+
+/** Queue a prompt to be sent the next time Claude is idle */
+export async function enqueuePrompt(instanceId: string, content: string): Promise<boolean> {
+  try {
+    const response = await api(queueUrl(instanceId), {
+      method: 'POST',
+      body: JSON.stringify({ content })
+    });
+    if (!response.ok) {
+      addToast(await response.text(), 'error');
+      return false;
+    }
+    return true;
+  } catch (error) {
+    console.error('[PromptQueue] Failed to enqueue:', error);
+    return false;
+  }
+}
+
+/** Cancel a queued prompt */
+export async function cancelPrompt(instanceId: string, promptId: number): Promise<void> {
+  try {
+    const response = await api(`${queueUrl(instanceId)}/${promptId}`, { method: 'DELETE' });
+    if (!response.ok) addToast(await response.text(), 'error');
+  } catch (error) {
+    console.error('[PromptQueue] Failed to cancel:', error);
+  }
+}
+
+/** Move a queued prompt to a new 0-based position */
+export async function movePrompt(instanceId: string, promptId: number, position: number): Promise<void> {
+  try {
+    const response = await api(`${queueUrl(instanceId)}/${promptId}/position`, {
+      method: 'PUT',
+      body: JSON.stringify({ position })
+    });
+    if (!response.ok) addToast(await response.text(), 'error');
+  } catch (error) {
+    console.error('[PromptQueue] Failed to move:', error);
+  }
+}
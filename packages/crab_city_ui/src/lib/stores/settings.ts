@@ -25,6 +25,11 @@ export interface UserSettings {
   terminalFontSize: number;
   terminalFontFamily: string;
   showNotifications: boolean;
+  allowClipboardWrite: boolean;
+  /** Ask for coalesced screen updates instead of raw output (slow links) */
+  screenDiffOutput: boolean;
+  /** Watch terminals read-only: never resize them for others, send no input */
+  spectateTerminals: boolean;
   drawerWidth: number;
 }
 
@@ -36,11 +41,14 @@ export const DEFAULT_SETTINGS: UserSettings = {
   terminalFontSize: 14,
   terminalFontFamily: "'JetBrains Mono', 'SF Mono', Monaco, 'Cascadia Code', monospace",
   showNotifications: true,
+  allowClipboardWrite: false,
+  screenDiffOutput: false,
+  spectateTerminals: false,
   drawerWidth: 400
 };
 
 // Keys that are UI-only and should NOT sync to the server
-const LOCAL_ONLY_KEYS: ReadonlySet<string> = new Set(['drawerWidth']);
+const LOCAL_ONLY_KEYS: ReadonlySet<string> = new Set(['drawerWidth', 'screenDiffOutput', 'spectateTerminals']);
 
 const STORAGE_KEY = 'crab_city_settings';
 
@@ -13,8 +13,9 @@ import { currentInstanceId } from './instances';
 // =============================================================================
 
 interface TerminalBuffer {
-  /** Pending output chunks to be written to terminal */
-  chunks: string[];
+  /** Pending output chunks to be written to terminal (raw bytes when the
+   *  connection uses binary frames) */
+  chunks: (string | Uint8Array)[];
   /** Whether terminal should be cleared before next write */
   shouldClear: boolean;
   /** When true, Output messages are silently dropped while waiting
@@ -50,7 +51,7 @@ const MAX_BUFFER_SIZE = 10000;
  * Write output to a specific instance's terminal buffer.
  * Called by WebSocket handler when receiving terminal output.
  */
-export function writeTerminalOutput(instanceId: string, data: string): void {
+export function writeTerminalOutput(instanceId: string, data: string | Uint8Array): void {
   terminalBuffers.update((buffers) => {
     let buffer = buffers.get(instanceId);
     if (!buffer) {
@@ -87,7 +88,7 @@ export function writeTerminalOutput(instanceId: string, data: string): void {
  * Write history output (on focus switch) - clears terminal first.
  * Clears the awaitingReplay flag so the subscription consumes the replay.
  */
-export function writeTerminalHistory(instanceId: string, data: string): void {
+export function writeTerminalHistory(instanceId: string, data: string | Uint8Array): void {
   terminalBuffers.update((buffers) => {
     buffers.set(instanceId, {
       chunks: [data],
@@ -3,6 +3,7 @@
  *
  * Tracks which user holds the terminal lock for each instance.
  * Lock only matters when 2+ users are present — solo users always have implicit control.
+ * Requests for a held lock queue on the server; the holder grants or denies them.
  * Server is the source of truth; this store just mirrors TerminalLockUpdate messages.
  */
 
@@ -19,6 +20,8 @@ export interface TerminalLockState {
   holder: PresenceUser | null;
   lastActivity: string | null;
   expiresInSecs: number | null;
+  /** Users waiting for the lock, next in line first */
+  queue: PresenceUser[];
 }
 
 // =============================================================================
@@ -47,6 +50,18 @@ export const isLockedByOther = derived([currentTerminalLock, currentUser], ([$lo
   return $lock.holder.user_id !== $user.id;
 });
 
+/** My 1-based place in the focused instance's lock queue (0 = not waiting) */
+export const myLockQueuePosition = derived([currentTerminalLock, currentUser], ([$lock, $user]) => {
+  if (!$lock || !$user) return 0;
+  return $lock.queue.findIndex((u) => u.user_id === $user.id) + 1;
+});
+
+/** The oldest request for a lock I hold — what the holder is prompted about */
+export const pendingLockRequest = derived([currentTerminalLock, iHoldLock], ([$lock, $iHold]) => {
+  if (!$iHold || !$lock) return null;
+  return $lock.queue[0] ?? null;
+});
+
 // =============================================================================
 // Actions
 // =============================================================================
@@ -56,13 +71,14 @@ export function handleTerminalLockUpdate(
   instanceId: string,
   holder: PresenceUser | null,
   lastActivity: string | null,
-  expiresInSecs: number | null
+  expiresInSecs: number | null,
+  queue: PresenceUser[] = []
 ): void {
   instanceTerminalLock.update((map) => {
-    if (!holder) {
+    if (!holder && queue.length === 0) {
       map.delete(instanceId);
     } else {
-      map.set(instanceId, { holder, lastActivity, expiresInSecs });
+      map.set(instanceId, { holder, lastActivity, expiresInSecs, queue });
     }
     return new Map(map);
   });
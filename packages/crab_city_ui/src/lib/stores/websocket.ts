@@ -11,13 +11,15 @@
 
 import { get, writable, derived } from 'svelte/store';
 import type { WsMessage, PresenceUser } from '$lib/types';
-import { currentInstanceId, addPendingInput, flushPendingInput, getLastConversationUuid } from './instances';
+import { currentInstance, currentInstanceId, addPendingInput, flushPendingInput, getLastConversationUuid } from './instances';
 import { recordWebSocketMessage, recordWebSocketReconnect } from './metrics';
 import { setLoadingHistory } from './chat';
 import { createMessageHandler, type MuxClientMessage, type MuxServerMessage } from './ws-handlers';
-import { fetchServerSettings } from './settings';
+import { fetchServerSettings, userSettings } from './settings';
 import { fetchServerConfig } from './server-config';
 import { checkAuth } from './auth';
+import { FrameKind, decodeTerminalFrame, encodeTerminalFrame } from '$lib/utils/terminal-frame';
+import { applyResumable, createResumeState, recordSeq, resumeUrl } from '$lib/utils/ws-resume';
 
 // =============================================================================
 // Connection State (formerly connection.ts)
@@ -91,6 +93,47 @@ let lastMessageTime: number = Date.now();
 let visibilityHandler: (() => void) | null = null;
 let heartbeatInterval: ReturnType<typeof setInterval> | null = null;
 let conversationSyncTimeout: ReturnType<typeof setTimeout> | null = null;
+/** Server acknowledged binary frames on this socket: send input as frames */
+let binaryInput = false;
+let inputSeq = 0n;
+const inputEncoder = new TextEncoder();
+/** Resume token and last sequences seen, presented on reconnect */
+const resume = createResumeState();
+/** The next Focus is the first on a reconnect: ask for missed output only */
+let resumingFocus = false;
+
+/** Mux protocol version this client speaks (see `ws/handshake.rs`) */
+const PROTOCOL_VERSION = 2;
+
+// Switch the live connection's output mode when the setting changes; new
+// connections ask for it in Hello.
+let screenDiffOutput = get(userSettings).screenDiffOutput;
+userSettings.subscribe((s) => {
+  if (s.screenDiffOutput === screenDiffOutput) return;
+  screenDiffOutput = s.screenDiffOutput;
+  if (socket?.readyState === WebSocket.OPEN) {
+    socket.send(
+      JSON.stringify({
+        type: 'SetOutputMode',
+        mode: screenDiffOutput ? 'screen_diff' : 'raw'
+      } as MuxClientMessage)
+    );
+  }
+});
+
+// Spectators' viewports don't take part in size negotiation and the server
+// refuses their input. Re-register the visible viewport when this changes.
+let spectating = get(userSettings).spectateTerminals;
+/** The terminal viewport last reported as visible */
+let visibleViewport: { instanceId: string; rows: number; cols: number } | null = null;
+userSettings.subscribe((s) => {
+  if (s.spectateTerminals === spectating) return;
+  spectating = s.spectateTerminals;
+  if (visibleViewport) {
+    const { instanceId, rows, cols } = visibleViewport;
+    sendResize(rows, cols, instanceId);
+  }
+});
 
 const STALE_THRESHOLD_MS = 60_000;
 const HEARTBEAT_INTERVAL_MS = 30_000;
@@ -129,6 +172,31 @@ const handleMultiplexedMessage = createMessageHandler({
     if (selectedId) {
       setServerGone(selectedId, reason);
     }
+  },
+  enableBinaryInput: () => {
+    binaryInput = true;
+  },
+  onWelcome: (protocolVersion, minProtocolVersion, serverVersion, capabilities) => {
+    console.log(`[WebSocket] Server crab ${serverVersion}, protocol v${protocolVersion}`);
+    if (minProtocolVersion > PROTOCOL_VERSION) {
+      console.warn('[WebSocket] This page is older than the server supports — reload to upgrade');
+    }
+    // Terminal output as raw binary frames (or screen diffs on slow links),
+    // when offered
+    const wanted = ['binary_frames', 'resume'];
+    if (screenDiffOutput) wanted.push('screen_diff');
+    socket?.send(
+      JSON.stringify({
+        type: 'Hello',
+        protocol_version: PROTOCOL_VERSION,
+        client_version: 'web',
+        capabilities: capabilities.filter((c) => wanted.includes(c))
+      } as MuxClientMessage)
+    );
+  },
+  onResumable: (token, resumed, lifecycleSeq) => {
+    applyResumable(resume, token, resumed, lifecycleSeq);
+    if (resumed) console.log('[WebSocket] Session resumed');
   }
 });
 
@@ -222,36 +290,52 @@ export function sendMessage(message: string, taskId?: number): void {
  */
 export function sendRaw(data: string, taskId?: number): void {
   const instanceId = get(currentInstanceId);
-  if (!instanceId) return;
+  // The server refuses viewers' input anyway; don't queue it
+  if (!instanceId || spectating || get(currentInstance)?.role === 'viewer') return;
 
   if (socket?.readyState === WebSocket.OPEN) {
-    const msg: MuxClientMessage = { type: 'Input', instance_id: instanceId, data };
-    if (taskId != null) msg.task_id = taskId;
-    socket.send(JSON.stringify(msg));
+    sendInputTo(socket, instanceId, data, taskId);
     return;
   }
 
   addPendingInput(instanceId, data);
 }
 
+function clientType(): MuxClientMessage['client_type'] {
+  return spectating ? 'spectator' : 'web';
+}
+
 /** Send terminal resize notification. Uses explicit instanceId to avoid races during instance switching. */
 export function sendResize(rows: number, cols: number, explicitInstanceId?: string): void {
   const instanceId = explicitInstanceId ?? get(currentInstanceId);
   if (socket?.readyState !== WebSocket.OPEN || !instanceId) return;
-  socket.send(JSON.stringify({ type: 'Resize', instance_id: instanceId, rows, cols } as MuxClientMessage));
+  visibleViewport = { instanceId, rows, cols };
+  socket.send(
+    JSON.stringify({ type: 'Resize', instance_id: instanceId, rows, cols, client_type: clientType() } as MuxClientMessage)
+  );
 }
 
 /** Notify server that terminal panel is visible (include in dimension negotiation). */
 export function sendTerminalVisible(rows: number, cols: number, explicitInstanceId?: string): void {
   const instanceId = explicitInstanceId ?? get(currentInstanceId);
   if (socket?.readyState !== WebSocket.OPEN || !instanceId) return;
-  socket.send(JSON.stringify({ type: 'TerminalVisible', instance_id: instanceId, rows, cols } as MuxClientMessage));
+  visibleViewport = { instanceId, rows, cols };
+  socket.send(
+    JSON.stringify({
+      type: 'TerminalVisible',
+      instance_id: instanceId,
+      rows,
+      cols,
+      client_type: clientType()
+    } as MuxClientMessage)
+  );
 }
 
 /** Notify server that terminal panel is hidden (exclude from dimension negotiation). */
 export function sendTerminalHidden(explicitInstanceId?: string): void {
   const instanceId = explicitInstanceId ?? get(currentInstanceId);
   if (socket?.readyState !== WebSocket.OPEN || !instanceId) return;
+  if (visibleViewport?.instanceId === instanceId) visibleViewport = null;
   socket.send(JSON.stringify({ type: 'TerminalHidden', instance_id: instanceId } as MuxClientMessage));
 }
 
@@ -278,14 +362,31 @@ export function offLobbyMessage(channel: string): void {
   lobbyHandlers.delete(channel);
 }
 
-/** Request terminal lock for the current instance. */
-export function requestTerminalLock(): void {
+/**
+ * Request terminal lock for the current instance. Queues behind the holder if
+ * it's taken; `force` (admins only) takes it immediately.
+ */
+export function requestTerminalLock(force = false): void {
+  const instanceId = get(currentInstanceId);
+  if (!instanceId || socket?.readyState !== WebSocket.OPEN) return;
+  socket.send(JSON.stringify({ type: 'TerminalLockRequest', instance_id: instanceId, force } as MuxClientMessage));
+}
+
+/** Hand the lock I hold to a waiting user (default: next in line). */
+export function grantTerminalLock(userId?: string): void {
   const instanceId = get(currentInstanceId);
   if (!instanceId || socket?.readyState !== WebSocket.OPEN) return;
-  socket.send(JSON.stringify({ type: 'TerminalLockRequest', instance_id: instanceId } as MuxClientMessage));
+  socket.send(JSON.stringify({ type: 'TerminalLockGrant', instance_id: instanceId, user_id: userId } as MuxClientMessage));
 }
 
-/** Release terminal lock for the current instance. */
+/** Turn down a request for the lock I hold (default: next in line). */
+export function denyTerminalLockRequest(userId?: string): void {
+  const instanceId = get(currentInstanceId);
+  if (!instanceId || socket?.readyState !== WebSocket.OPEN) return;
+  socket.send(JSON.stringify({ type: 'TerminalLockDeny', instance_id: instanceId, user_id: userId } as MuxClientMessage));
+}
+
+/** Release terminal lock for the current instance, or withdraw my queued request. */
 export function releaseTerminalLock(): void {
   const instanceId = get(currentInstanceId);
   if (!instanceId || socket?.readyState !== WebSocket.OPEN) return;
@@ -324,11 +425,11 @@ export function requestChatTopics(scope: string): void {
 /** Send composed text to a specific instance's PTY. */
 export function sendToInstance(instanceId: string, content: string): void {
   if (socket?.readyState !== WebSocket.OPEN) return;
-  socket.send(JSON.stringify({ type: 'Input', instance_id: instanceId, data: content } as MuxClientMessage));
+  sendInputTo(socket, instanceId, content);
   const delay = Math.min(750, 50 + content.length * 0.5);
   setTimeout(() => {
     if (socket?.readyState === WebSocket.OPEN) {
-      socket.send(JSON.stringify({ type: 'Input', instance_id: instanceId, data: '\r' } as MuxClientMessage));
+      sendInputTo(socket, instanceId, '\r');
     }
   }, delay);
 }
@@ -380,16 +481,19 @@ function connectMultiplexed(onConnected?: () => void): void {
   }
 
   const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-  const wsUrl = `${protocol}//${window.location.host}/api/ws`;
+  const wsUrl = resumeUrl(`${protocol}//${window.location.host}/api/ws`, resume);
 
   console.log('[WebSocket] Connecting to multiplexed endpoint:', wsUrl);
 
   socket = new WebSocket(wsUrl);
+  socket.binaryType = 'arraybuffer';
+  binaryInput = false;
 
   socket.onopen = () => {
     console.log('[WebSocket] Connected');
     reconnectAttempt = 0;
     shutdownReason.set(null);
+    resumingFocus = resume.token !== null;
 
     const pendingFocus = get(currentInstanceId);
     if (pendingFocus) {
@@ -413,14 +517,36 @@ function connectMultiplexed(onConnected?: () => void): void {
     recordWebSocketMessage();
 
     const t0 = performance.now();
-    let msg: MuxServerMessage;
-    try {
-      msg = JSON.parse(event.data);
-    } catch {
-      console.warn('[WebSocket] Received non-JSON message:', event.data.slice(0, 100));
-      return;
+    let msg: MuxServerMessage & { seq?: number };
+    if (event.data instanceof ArrayBuffer) {
+      const frame = decodeTerminalFrame(event.data);
+      if (!frame || frame.kind === FrameKind.Input) {
+        console.warn('[WebSocket] Received malformed binary frame');
+        return;
+      }
+      msg = {
+        type:
+          frame.kind === FrameKind.OutputHistory
+            ? 'OutputHistory'
+            : frame.kind === FrameKind.ScreenDiff
+              ? 'ScreenDiff'
+              : 'Output',
+        instance_id: frame.instanceId,
+        data: frame.data,
+        seq: Number(frame.seq)
+      };
+    } else {
+      try {
+        msg = JSON.parse(event.data);
+      } catch {
+        console.warn('[WebSocket] Received non-JSON message:', event.data.slice(0, 100));
+        return;
+      }
     }
     const parseMs = performance.now() - t0;
+    if (typeof msg.seq === 'number') {
+      recordSeq(resume, msg.type, msg.seq, 'instance_id' in msg ? msg.instance_id : undefined);
+    }
 
     try {
       const t1 = performance.now();
@@ -428,9 +554,10 @@ function connectMultiplexed(onConnected?: () => void): void {
       const handleMs = performance.now() - t1;
       const totalMs = parseMs + handleMs;
       if (totalMs > 50) {
+        const size = event.data instanceof ArrayBuffer ? event.data.byteLength : event.data.length;
         console.warn(
           `[WebSocket] Slow message: ${msg.type} parse=${parseMs.toFixed(1)}ms handle=${handleMs.toFixed(1)}ms total=${totalMs.toFixed(1)}ms` +
-            (event.data.length > 1024 ? ` size=${(event.data.length / 1024).toFixed(0)}KB` : '')
+            (size > 1024 ? ` size=${(size / 1024).toFixed(0)}KB` : '')
         );
       }
     } catch (e) {
@@ -561,10 +688,36 @@ function sendFocus(instanceId: string): void {
   console.log('[WebSocket] Focusing on instance:', instanceId);
   currentFocusedId = instanceId;
 
-  socket.send(JSON.stringify({ type: 'Focus', instance_id: instanceId } as MuxClientMessage));
+  const focus: MuxClientMessage = { type: 'Focus', instance_id: instanceId };
+  // Right after a reconnect, ask for just the output and conversation turns
+  // we missed. The server ignores these unless the session was resumed.
+  const outputSeq = resumingFocus ? resume.outputSeqs.get(instanceId) : undefined;
+  resumingFocus = false;
+  if (outputSeq !== undefined) {
+    focus.output_seq = outputSeq;
+    focus.since_uuid = getLastConversationUuid(instanceId) ?? undefined;
+  }
+  socket.send(JSON.stringify(focus));
 
   const pending = flushPendingInput(instanceId);
   for (const data of pending) {
-    socket.send(JSON.stringify({ type: 'Input', instance_id: instanceId, data } as MuxClientMessage));
+    sendInputTo(socket, instanceId, data);
+  }
+}
+
+/**
+ * Send terminal input as a binary frame once the server has acknowledged
+ * them, else as JSON. Input tied to a task always goes as JSON, since frames
+ * carry no task id.
+ */
+function sendInputTo(ws: WebSocket, instanceId: string, data: string, taskId?: number): void {
+  if (binaryInput && taskId == null) {
+    ws.send(
+      encodeTerminalFrame({ kind: FrameKind.Input, instanceId, seq: inputSeq++, data: inputEncoder.encode(data) })
+    );
+    return;
   }
+  const msg: MuxClientMessage = { type: 'Input', instance_id: instanceId, data };
+  if (taskId != null) msg.task_id = taskId;
+  ws.send(JSON.stringify(msg));
 }
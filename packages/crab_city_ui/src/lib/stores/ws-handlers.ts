@@ -21,8 +21,9 @@ import {
   type ChatTopicSummary
 } from './chat';
 import { handleTaskUpdate, handleTaskDeleted } from './tasks';
-import { handleUserSettingsUpdate } from './settings';
+import { handleUserSettingsUpdate, userSettings } from './settings';
 import { handleInboxUpdate, handleInboxList, type InboxItem } from './inbox';
+import { handlePromptQueueUpdate, type QueuedPrompt } from './promptQueue';
 
 // =============================================================================
 // Multiplexed Message Types
@@ -38,17 +39,25 @@ export interface MuxClientMessage {
     | 'Lobby'
     | 'TerminalLockRequest'
     | 'TerminalLockRelease'
+    | 'TerminalLockGrant'
+    | 'TerminalLockDeny'
     | 'ChatSend'
     | 'ChatHistory'
     | 'ChatForward'
     | 'ChatTopics'
     | 'TerminalVisible'
-    | 'TerminalHidden';
+    | 'TerminalHidden'
+    | 'EnableBinaryFrames'
+    | 'Hello'
+    | 'SetOutputMode';
   instance_id?: string;
   since_uuid?: string;
+  output_seq?: number;
   data?: string;
   rows?: number;
   cols?: number;
+  /** Viewport kind for Resize/TerminalVisible; spectators don't size the PTY */
+  client_type?: 'web' | 'terminal' | 'spectator';
   session_id?: string;
   channel?: string;
   payload?: unknown;
@@ -61,8 +70,18 @@ export interface MuxClientMessage {
   message_id?: number;
   target_scope?: string;
   topic?: string | null;
+  protocol_version?: number;
+  client_version?: string;
+  capabilities?: string[];
+  mode?: OutputMode;
+  max_fps?: number;
+  force?: boolean;
+  user_id?: string;
 }
 
+/** How the server delivers terminal output (see `ws/output_mode.rs`). */
+export type OutputMode = 'raw' | 'screen_diff';
+
 interface SessionCandidate {
   session_id: string;
   started_at?: string;
@@ -71,8 +90,21 @@ interface SessionCandidate {
 }
 
 export type MuxServerMessage =
-  | { type: 'Output'; instance_id: string; data: string }
-  | { type: 'OutputHistory'; instance_id: string; data: string }
+  // `data` is a Uint8Array when it arrived as a binary frame
+  | { type: 'Output'; instance_id: string; data: string | Uint8Array }
+  | { type: 'OutputHistory'; instance_id: string; data: string | Uint8Array }
+  // Coalesced screen update in `screen_diff` output mode; rendered like Output
+  | { type: 'ScreenDiff'; instance_id: string; data: string | Uint8Array }
+  | { type: 'BinaryFramesEnabled' }
+  | { type: 'OutputModeSet'; mode: OutputMode; max_fps?: number }
+  | {
+      type: 'Welcome';
+      protocol_version: number;
+      min_protocol_version: number;
+      server_version: string;
+      capabilities: string[];
+    }
+  | { type: 'Resumable'; resume_token: string; resumed: boolean; lifecycle_seq: number }
   | { type: 'ConversationFull'; instance_id: string; turns: unknown[] }
   | { type: 'ConversationUpdate'; instance_id: string; turns: unknown[] }
   | { type: 'SessionAmbiguous'; instance_id: string; candidates: SessionCandidate[] }
@@ -92,6 +124,7 @@ export type MuxServerMessage =
       holder?: PresenceUser | null;
       last_activity?: string | null;
       expires_in_secs?: number | null;
+      queue?: PresenceUser[];
     }
   | {
       type: 'ChatMessage';
@@ -110,8 +143,18 @@ export type MuxServerMessage =
   | { type: 'TaskUpdate'; task: Task }
   | { type: 'TaskDeleted'; task_id: number }
   | { type: 'UserSettingsUpdate'; user_id: string; settings: Record<string, string> }
+  | { type: 'TerminalTitle'; instance_id: string; title: string | null }
+  | {
+      type: 'TerminalNotification';
+      instance_id: string;
+      kind: 'bell' | 'notification';
+      title?: string | null;
+      body?: string | null;
+    }
+  | { type: 'ClipboardWrite'; instance_id: string; selection: string; data: string }
   | { type: 'InboxUpdate'; instance_id: string; item: InboxItem | null }
   | { type: 'InboxList'; items: InboxItem[] }
+  | { type: 'PromptQueueUpdate'; instance_id: string; prompts: QueuedPrompt[] }
   | { type: 'Shutdown'; reason: string };
 
 // =============================================================================
@@ -135,6 +178,12 @@ export interface HandlerContext {
   setError: (instanceId: string, error?: string) => void;
   /** Server shutdown notification */
   setServerGone: (reason?: string) => void;
+  /** Server acknowledged binary frames — input may be sent as frames */
+  enableBinaryInput: () => void;
+  /** Server handshake: answer with Hello */
+  onWelcome: (protocolVersion: number, minProtocolVersion: number, serverVersion: string, capabilities: string[]) => void;
+  /** Resume token (and whether this connection resumed the previous one) */
+  onResumable: (token: string, resumed: boolean, lifecycleSeq: number) => void;
 }
 
 // =============================================================================
@@ -209,6 +258,22 @@ export function createMessageHandler(ctx: HandlerContext): (msg: MuxServerMessag
         });
         break;
 
+      case 'TerminalTitle':
+        instances.update((map) => {
+          const instance = map.get(msg.instance_id);
+          if (instance) {
+            map.set(msg.instance_id, { ...instance, title: msg.title });
+          }
+          return new Map(map);
+        });
+        break;
+
+      case 'ClipboardWrite':
+        if (msg.instance_id === ctx.getFocusedId()) {
+          writeClipboard(msg.data);
+        }
+        break;
+
       case 'FocusAck':
         console.log('[WebSocket] Focus acknowledged:', msg.instance_id);
         ctx.setConnected(msg.instance_id);
@@ -226,14 +291,35 @@ export function createMessageHandler(ctx: HandlerContext): (msg: MuxServerMessag
         }
         break;
 
+      case 'BinaryFramesEnabled':
+        ctx.enableBinaryInput();
+        break;
+
+      case 'OutputModeSet':
+        console.log(
+          '[WebSocket] Output mode:',
+          msg.mode,
+          msg.max_fps !== undefined ? `(${msg.max_fps} fps)` : ''
+        );
+        break;
+
+      case 'Welcome':
+        ctx.onWelcome(msg.protocol_version, msg.min_protocol_version, msg.server_version, msg.capabilities);
+        break;
+
+      case 'Resumable':
+        ctx.onResumable(msg.resume_token, msg.resumed, msg.lifecycle_seq);
+        break;
+
       case 'OutputHistory': {
         if (!validateInstanceId(msg.instance_id, 'OutputHistory')) break;
         writeTerminalHistory(msg.instance_id, msg.data);
         break;
       }
 
-      case 'Output': {
-        if (!validateInstanceId(msg.instance_id, 'Output')) break;
+      case 'Output':
+      case 'ScreenDiff': {
+        if (!validateInstanceId(msg.instance_id, msg.type)) break;
         writeTerminalOutput(msg.instance_id, msg.data);
         if (msg.instance_id === ctx.getFocusedId()) {
           trackOutput(msg.data.length);
@@ -296,7 +382,8 @@ export function createMessageHandler(ctx: HandlerContext): (msg: MuxServerMessag
           msg.instance_id,
           msg.holder ?? null,
           msg.last_activity ?? null,
-          msg.expires_in_secs ?? null
+          msg.expires_in_secs ?? null,
+          msg.queue ?? []
         );
         break;
 
@@ -342,6 +429,10 @@ export function createMessageHandler(ctx: HandlerContext): (msg: MuxServerMessag
         handleInboxList(msg.items);
         break;
 
+      case 'PromptQueueUpdate':
+        handlePromptQueueUpdate(msg.instance_id, msg.prompts);
+        break;
+
       case 'Shutdown':
         console.warn('[WebSocket] Server shutting down:', msg.reason);
         ctx.setServerGone(msg.reason);
@@ -362,6 +453,19 @@ function clearConversationSyncTimeout(ctx: HandlerContext): void {
   }
 }
 
+/** Copy OSC 52 data (base64 UTF-8) to the system clipboard, if the user opted in. */
+function writeClipboard(data: string): void {
+  if (!get(userSettings).allowClipboardWrite || !navigator.clipboard) return;
+  try {
+    const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+    navigator.clipboard.writeText(new TextDecoder().decode(bytes)).catch((e) => {
+      console.warn('[WebSocket] Clipboard write rejected:', e);
+    });
+  } catch {
+    console.warn('[WebSocket] Ignoring malformed ClipboardWrite payload');
+  }
+}
+
 function updateInstancesWithStates(serverInstances: Instance[]): void {
   instances.update((map) => {
     const newMap = new Map<string, Instance>();
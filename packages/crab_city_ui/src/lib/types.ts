@@ -14,8 +14,13 @@ export interface Instance {
   claude_state?: ClaudeState;
   claude_state_stale?: boolean; // True if terminal output is stale
   state_entered_at?: number; // Unix timestamp when current state started
+  title?: string | null; // Window title set by the program via OSC 0/2
+  role?: InstanceRole; // Caller's role on this instance (REST responses only)
 }
 
+/** What a user may do with an instance; each role includes the ones before it. */
+export type InstanceRole = 'viewer' | 'operator' | 'owner';
+
 export interface CreateInstanceRequest {
   name?: string;
   command?: string;
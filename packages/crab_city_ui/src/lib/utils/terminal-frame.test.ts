@@ -0,0 +1,51 @@
+import { FrameKind, decodeTerminalFrame, encodeTerminalFrame } from './terminal-frame.js';
+
+function toArrayBuffer(bytes: Uint8Array): ArrayBuffer {
+  return bytes.buffer.slice(bytes.byteOffset, bytes.byteOffset + bytes.byteLength) as ArrayBuffer;
+}
+
+describe('terminal frames', () => {
+  it('matches the server header layout', () => {
+    const encoded = encodeTerminalFrame({
+      kind: FrameKind.Input,
+      instanceId: 'ab',
+      seq: 7n,
+      data: new Uint8Array([0x78])
+    });
+    expect(Array.from(encoded)).toEqual([3, 2, 0x61, 0x62, 0, 0, 0, 0, 0, 0, 0, 7, 0x78]);
+  });
+
+  it('round-trips raw payload bytes', () => {
+    const data = new Uint8Array([0xe2, 0x94, 0xff, 0x00]);
+    const frame = { kind: FrameKind.Output, instanceId: 'inst-1', seq: 42n, data };
+    const decoded = decodeTerminalFrame(toArrayBuffer(encodeTerminalFrame(frame)));
+    expect(decoded?.kind).toBe(FrameKind.Output);
+    expect(decoded?.instanceId).toBe('inst-1');
+    expect(decoded?.seq).toBe(42n);
+    expect(Array.from(decoded?.data ?? [])).toEqual(Array.from(data));
+  });
+
+  it('decodes screen diff frames', () => {
+    const data = new Uint8Array([0x1b, 0x5b, 0x48]);
+    const frame = { kind: FrameKind.ScreenDiff, instanceId: 'inst-1', seq: 3n, data };
+    const decoded = decodeTerminalFrame(toArrayBuffer(encodeTerminalFrame(frame)));
+    expect(decoded?.kind).toBe(FrameKind.ScreenDiff);
+    expect(Array.from(decoded?.data ?? [])).toEqual(Array.from(data));
+  });
+
+  it('rejects truncated frames', () => {
+    const encoded = encodeTerminalFrame({
+      kind: FrameKind.Output,
+      instanceId: 'inst-1',
+      seq: 0n,
+      data: new Uint8Array()
+    });
+    for (let len = 0; len < encoded.length; len++) {
+      expect(decodeTerminalFrame(toArrayBuffer(encoded.subarray(0, len)))).toBeNull();
+    }
+  });
+
+  it('rejects unknown kinds', () => {
+    expect(decodeTerminalFrame(toArrayBuffer(new Uint8Array([9, 0, 0, 0, 0, 0, 0, 0, 0, 0])))).toBeNull();
+  });
+});
@@ -0,0 +1,67 @@
+/**
+ * Binary terminal frames — mirror of `ws/frame.rs`.
+ *
+ * Layout: kind (u8) | instance id length n (u8) | instance id (n bytes UTF-8)
+ * | seq (u64 big-endian) | payload (raw bytes).
+ */
+
+export const FrameKind = {
+  Output: 1,
+  OutputHistory: 2,
+  Input: 3,
+  ScreenDiff: 4
+} as const;
+
+export type FrameKind = (typeof FrameKind)[keyof typeof FrameKind];
+
+export interface TerminalFrame {
+  kind: FrameKind;
+  instanceId: string;
+  seq: bigint;
+  data: Uint8Array;
+}
+
+const SEQ_LEN = 8;
+const encoder = new TextEncoder();
+const decoder = new TextDecoder('utf-8', { fatal: true });
+
+export function encodeTerminalFrame(frame: TerminalFrame): Uint8Array {
+  const id = encoder.encode(frame.instanceId);
+  const out = new Uint8Array(2 + id.length + SEQ_LEN + frame.data.length);
+  out[0] = frame.kind;
+  out[1] = id.length;
+  out.set(id, 2);
+  new DataView(out.buffer).setBigUint64(2 + id.length, frame.seq);
+  out.set(frame.data, 2 + id.length + SEQ_LEN);
+  return out;
+}
+
+/** Parse a frame; returns null for anything malformed. */
+export function decodeTerminalFrame(buf: ArrayBuffer): TerminalFrame | null {
+  const bytes = new Uint8Array(buf);
+  if (bytes.length < 2) return null;
+  const kind = bytes[0];
+  if (
+    kind !== FrameKind.Output &&
+    kind !== FrameKind.OutputHistory &&
+    kind !== FrameKind.Input &&
+    kind !== FrameKind.ScreenDiff
+  ) {
+    return null;
+  }
+  const idLen = bytes[1] ?? 0;
+  const seqAt = 2 + idLen;
+  if (bytes.length < seqAt + SEQ_LEN) return null;
+  let instanceId: string;
+  try {
+    instanceId = decoder.decode(bytes.subarray(2, seqAt));
+  } catch {
+    return null;
+  }
+  return {
+    kind,
+    instanceId,
+    seq: new DataView(buf).getBigUint64(seqAt),
+    data: bytes.subarray(seqAt + SEQ_LEN)
+  };
+}
@@ -0,0 +1,34 @@
+import { applyResumable, createResumeState, recordSeq, resumeUrl } from './ws-resume.js';
+
+describe('ws resume state', () => {
+  it('adds resume parameters only once a token is known', () => {
+    const state = createResumeState();
+    expect(resumeUrl('ws://host/api/ws', state)).toBe('ws://host/api/ws');
+    applyResumable(state, 'e1.c1', false, 4);
+    expect(resumeUrl('ws://host/api/ws', state)).toBe('ws://host/api/ws?resume=e1.c1&lifecycle_seq=4');
+  });
+
+  it('tracks output per instance and lifecycle globally', () => {
+    const state = createResumeState();
+    recordSeq(state, 'Output', 7, 'a');
+    recordSeq(state, 'OutputHistory', 3, 'b');
+    recordSeq(state, 'InstanceCreated', 12);
+    recordSeq(state, 'PresenceUpdate', 9);
+    expect(state.outputSeqs.get('a')).toBe(7);
+    expect(state.outputSeqs.get('b')).toBe(3);
+    expect(state.lifecycleSeq).toBe(12);
+  });
+
+  it('forgets output sequences when the session was not resumed', () => {
+    const state = createResumeState();
+    recordSeq(state, 'Output', 7, 'a');
+    recordSeq(state, 'InstanceCreated', 12);
+    applyResumable(state, 'e1.c1', true, 10);
+    expect(state.outputSeqs.get('a')).toBe(7);
+    expect(state.lifecycleSeq).toBe(12);
+
+    applyResumable(state, 'e2.c2', false, 2);
+    expect(state.outputSeqs.size).toBe(0);
+    expect(state.lifecycleSeq).toBe(2);
+  });
+});
@@ -0,0 +1,51 @@
+/**
+ * Resumable WebSocket sessions — client half of `ws/replay.rs`.
+ *
+ * The server numbers lifecycle messages with one sequence and each
+ * instance's terminal output with its own. We remember the last of each we
+ * saw plus the connection's resume token, and present them on reconnect so
+ * the server can send only what we missed.
+ */
+
+export interface ResumeState {
+  /** Token from the last `Resumable`, null before the first connection */
+  token: string | null;
+  /** Last lifecycle sequence seen */
+  lifecycleSeq: number;
+  /** Last output sequence seen, per instance */
+  outputSeqs: Map<string, number>;
+}
+
+export function createResumeState(): ResumeState {
+  return { token: null, lifecycleSeq: 0, outputSeqs: new Map() };
+}
+
+/** The multiplexed endpoint, with resume parameters once we hold a token. */
+export function resumeUrl(base: string, state: ResumeState): string {
+  if (!state.token) return base;
+  const params = new URLSearchParams({ resume: state.token, lifecycle_seq: String(state.lifecycleSeq) });
+  return `${base}?${params}`;
+}
+
+/**
+ * Apply the server's `Resumable` greeting. Output sequences from a
+ * connection that could not be resumed are meaningless, so they are dropped.
+ */
+export function applyResumable(state: ResumeState, token: string, resumed: boolean, lifecycleSeq: number): void {
+  state.token = token;
+  if (!resumed) {
+    state.outputSeqs.clear();
+    state.lifecycleSeq = lifecycleSeq;
+  } else {
+    state.lifecycleSeq = Math.max(state.lifecycleSeq, lifecycleSeq);
+  }
+}
+
+/** Record the `seq` of a received message (terminal output is numbered per instance). */
+export function recordSeq(state: ResumeState, type: string, seq: number, instanceId?: string): void {
+  if (type === 'Output' || type === 'OutputHistory' || type === 'ScreenDiff') {
+    if (instanceId) state.outputSeqs.set(instanceId, seq);
+  } else if (seq > state.lifecycleSeq) {
+    state.lifecycleSeq = seq;
+  }
+}
@@ -11,7 +11,7 @@ fn main() {
     let mut input_count = 0u32;
     let mut resize_count = 0u32;
     let mut output_bytes = 0usize;
-    let mut last_ts = 0u32;
+    let mut last_ts = 0u64;
 
     for event in &recording.events {
         match event {
@@ -0,0 +1,394 @@
+//! Out-of-band escape sequences: window titles, bells, notifications,
+//! clipboard writes and hyperlinks.
+//!
+//! vt100 reports these through its `Callbacks` trait rather than the screen.
+//! [`VtCallbacks`] records the current title and queues [`TerminalEvent`]s
+//! until the owner drains them with `VirtualTerminal::take_events`.
+//!
+//! vt100 cells have no hyperlink attribute, so OSC 8 links are remembered as
+//! [`Hyperlink`] anchors (start column + link text) captured when the link is
+//! closed. Renderers re-apply a link wherever a row still shows that text at
+//! that column — see `row_hyperlinks`.
+
+/// Upper bound on undrained events — a program spamming BEL/OSC 9 while
+/// nobody drains must not grow memory without limit.
+const MAX_PENDING_EVENTS: usize = 64;
+
+/// Most recent hyperlink anchors kept for rendering.
+const MAX_HYPERLINKS: usize = 256;
+
+/// OSC 52 payloads (base64) larger than this are dropped.
+const MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
+
+/// An attention-worthy event emitted by the program in the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalEvent {
+    /// BEL (`^G`). Consecutive bells between drains are collapsed.
+    Bell,
+    /// Desktop notification: OSC 9 (`\e]9;body\a`) or OSC 777
+    /// (`\e]777;notify;title;body\a`).
+    Notification { title: Option<String>, body: String },
+    /// Clipboard write via OSC 52 (`\e]52;c;<base64>\a`). `data` is still
+    /// base64-encoded; `selection` is the raw selector (`c`, `p`, `s`, ...).
+    Clipboard { selection: String, data: String },
+}
+
+/// An OSC 8 hyperlink, anchored by the text it covered on one row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    pub uri: String,
+    /// Column of the first linked cell.
+    pub col: u16,
+    /// Cell contents from `col` up to the end of the link on that row.
+    pub text: String,
+}
+
+/// A link that has been opened but not yet closed.
+#[derive(Debug)]
+struct OpenLink {
+    uri: String,
+    row: u16,
+    col: u16,
+}
+
+/// vt100 callback sink used by `VirtualTerminal`.
+#[derive(Debug, Default)]
+pub struct VtCallbacks {
+    title: Option<String>,
+    events: Vec<TerminalEvent>,
+    hyperlinks: Vec<Hyperlink>,
+    open_link: Option<OpenLink>,
+}
+
+impl VtCallbacks {
+    /// Current window title (OSC 0/2), if the program set a non-empty one.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub(crate) fn set_title(&mut self, title: Option<String>) {
+        self.title = title.filter(|t| !t.is_empty());
+    }
+
+    /// Drain queued events, oldest first.
+    pub fn take_events(&mut self) -> Vec<TerminalEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Known hyperlink anchors, oldest first.
+    pub fn hyperlinks(&self) -> &[Hyperlink] {
+        &self.hyperlinks
+    }
+
+    fn push(&mut self, event: TerminalEvent) {
+        if event == TerminalEvent::Bell && self.events.last() == Some(&TerminalEvent::Bell) {
+            return;
+        }
+        // Only the latest undrained write to a selection matters
+        if let TerminalEvent::Clipboard { selection, .. } = &event {
+            self.events.retain(
+                |e| !matches!(e, TerminalEvent::Clipboard { selection: s, .. } if s == selection),
+            );
+        }
+        if self.events.len() < MAX_PENDING_EVENTS {
+            self.events.push(event);
+        }
+    }
+
+    fn open_link(&mut self, screen: &vt100::Screen, uri: String) {
+        self.close_link(screen);
+        let (row, col) = screen.cursor_position();
+        self.open_link = Some(OpenLink { uri, row, col });
+    }
+
+    /// Record an anchor for every row the open link covered. Rows are taken
+    /// at their current position, so a link that scrolled while open yields
+    /// anchors that simply never match.
+    fn close_link(&mut self, screen: &vt100::Screen) {
+        let Some(link) = self.open_link.take() else {
+            return;
+        };
+        let (rows, cols) = screen.size();
+        let (end_row, end_col) = screen.cursor_position();
+        for row in link.row..=end_row.min(rows.saturating_sub(1)) {
+            let start = if row == link.row { link.col } else { 0 };
+            let end = if row == end_row { end_col } else { cols };
+            let text = crate::read_cells_text(screen, row, start..end);
+            if text.trim().is_empty() {
+                continue;
+            }
+            self.add_hyperlink(Hyperlink {
+                uri: link.uri.clone(),
+                col: start,
+                text,
+            });
+        }
+    }
+
+    fn add_hyperlink(&mut self, link: Hyperlink) {
+        self.hyperlinks.retain(|l| *l != link);
+        if self.hyperlinks.len() >= MAX_HYPERLINKS {
+            self.hyperlinks.remove(0);
+        }
+        self.hyperlinks.push(link);
+    }
+}
+
+impl vt100::Callbacks for VtCallbacks {
+    fn audible_bell(&mut self, _: &mut vt100::Screen) {
+        self.push(TerminalEvent::Bell);
+    }
+
+    fn set_window_title(&mut self, _: &mut vt100::Screen, title: &[u8]) {
+        self.set_title(Some(String::from_utf8_lossy(title).into_owned()));
+    }
+
+    fn copy_to_clipboard(&mut self, _: &mut vt100::Screen, ty: &[u8], data: &[u8]) {
+        if data.len() > MAX_CLIPBOARD_BYTES {
+            return;
+        }
+        // vt100 has already checked both are ASCII (selector chars / base64)
+        self.push(TerminalEvent::Clipboard {
+            selection: String::from_utf8_lossy(ty).into_owned(),
+            data: String::from_utf8_lossy(data).into_owned(),
+        });
+    }
+
+    fn unhandled_osc(&mut self, screen: &mut vt100::Screen, params: &[&[u8]]) {
+        match params {
+            // OSC 8 ; params ; URI — an empty URI closes the current link
+            [b"8", _, uri @ ..] => {
+                let uri = join_params(uri);
+                if uri.is_empty() {
+                    self.close_link(screen);
+                } else {
+                    self.open_link(screen, uri);
+                }
+            }
+            // vt100 splits on ';' and only handles the two-param form, so a
+            // title containing ';' lands here.
+            [b"0" | b"2", rest @ ..] if !rest.is_empty() => {
+                self.set_title(Some(join_params(rest)));
+            }
+            // ConEmu reuses OSC 9 for numbered subcommands (progress etc.)
+            [b"9", sub, _, ..] if !sub.is_empty() && sub.iter().all(u8::is_ascii_digit) => {}
+            [b"9", rest @ ..] if !rest.is_empty() => {
+                self.push(TerminalEvent::Notification {
+                    title: None,
+                    body: join_params(rest),
+                });
+            }
+            [b"777", b"notify", title, body @ ..] => {
+                let title = String::from_utf8_lossy(title).into_owned();
+                self.push(TerminalEvent::Notification {
+                    title: (!title.is_empty()).then_some(title),
+                    body: join_params(body),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn join_params(params: &[&[u8]]) -> String {
+    String::from_utf8_lossy(&params.join(&b';')).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VirtualTerminal;
+
+    fn vt() -> VirtualTerminal {
+        VirtualTerminal::new(24, 80, 4096, 0)
+    }
+
+    #[test]
+    fn osc_title_is_tracked() {
+        let mut vt = vt();
+        assert_eq!(vt.title(), None);
+        vt.process_output(b"\x1b]0;vim main.rs\x07");
+        assert_eq!(vt.title(), Some("vim main.rs"));
+        vt.process_output(b"\x1b]2;a;b\x1b\\");
+        assert_eq!(vt.title(), Some("a;b"));
+        vt.process_output(b"\x1b]2;\x07");
+        assert_eq!(vt.title(), None);
+    }
+
+    #[test]
+    fn title_survives_resize() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]2;build\x07");
+        vt.resize(40, 120);
+        assert_eq!(vt.title(), Some("build"));
+    }
+
+    #[test]
+    fn bells_are_collapsed_until_drained() {
+        let mut vt = vt();
+        vt.process_output(b"done\x07\x07\x07");
+        assert_eq!(vt.take_events(), vec![TerminalEvent::Bell]);
+        assert!(vt.take_events().is_empty());
+        assert!(vt.screen().contents().contains("done"));
+    }
+
+    #[test]
+    fn osc9_and_osc777_notifications() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]9;Build finished\x07");
+        vt.process_output(b"\x1b]777;notify;Tests;42 passed; 0 failed\x07");
+        assert_eq!(
+            vt.take_events(),
+            vec![
+                TerminalEvent::Notification {
+                    title: None,
+                    body: "Build finished".to_string(),
+                },
+                TerminalEvent::Notification {
+                    title: Some("Tests".to_string()),
+                    body: "42 passed; 0 failed".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn conemu_osc9_subcommands_are_ignored() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]9;4;1;50\x07");
+        assert!(vt.take_events().is_empty());
+    }
+
+    #[test]
+    fn pending_events_are_bounded() {
+        let mut vt = vt();
+        for i in 0..(MAX_PENDING_EVENTS + 10) {
+            vt.process_output(format!("\x1b]9;n{i}\x07").as_bytes());
+        }
+        assert_eq!(vt.take_events().len(), MAX_PENDING_EVENTS);
+    }
+
+    #[test]
+    fn restore_keeps_title_but_not_events() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]2;htop\x07");
+        let snap = vt.snapshot();
+
+        let mut restored = VirtualTerminal::new(24, 80, 4096, 0);
+        restored.restore(&snap);
+        assert_eq!(restored.title(), Some("htop"));
+        assert!(restored.take_events().is_empty());
+    }
+
+    #[test]
+    fn osc52_clipboard_writes_keep_latest_per_selection() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]52;c;Zmlyc3Q=\x07");
+        vt.process_output(b"\x1b]52;p;cHJpbWFyeQ==\x07");
+        vt.process_output(b"\x1b]52;c;c2Vjb25k\x1b\\");
+        // Paste queries and invalid payloads are not writes
+        vt.process_output(b"\x1b]52;c;?\x07\x1b]52;c;not base64!\x07");
+        assert_eq!(
+            vt.take_events(),
+            vec![
+                TerminalEvent::Clipboard {
+                    selection: "p".to_string(),
+                    data: "cHJpbWFyeQ==".to_string(),
+                },
+                TerminalEvent::Clipboard {
+                    selection: "c".to_string(),
+                    data: "c2Vjb25k".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn osc8_links_are_anchored_to_their_text() {
+        let mut vt = vt();
+        vt.process_output(b"see \x1b]8;id=1;https://example.com/a;b\x1b\\docs\x1b]8;;\x1b\\ now");
+        assert_eq!(
+            vt.hyperlinks(),
+            &[Hyperlink {
+                uri: "https://example.com/a;b".to_string(),
+                col: 4,
+                text: "docs".to_string(),
+            }]
+        );
+        assert_eq!(vt.screen().contents(), "see docs now");
+    }
+
+    #[test]
+    fn osc8_link_wrapping_rows_gets_an_anchor_per_row() {
+        let mut vt = VirtualTerminal::new(4, 10, 4096, 0);
+        vt.process_output(b"abcdef\x1b]8;;https://x.dev\x07ghijklm\x1b]8;;\x07");
+        let anchors: Vec<_> = vt
+            .hyperlinks()
+            .iter()
+            .map(|l| (l.col, l.text.as_str()))
+            .collect();
+        assert_eq!(anchors, vec![(6, "ghij"), (0, "klm")]);
+    }
+
+    #[test]
+    fn hyperlinks_are_bounded_and_deduplicated() {
+        let mut vt = vt();
+        for i in 0..(MAX_HYPERLINKS + 10) {
+            vt.process_output(format!("\x1b]8;;https://x.dev/{i}\x07l\x1b]8;;\x07\r").as_bytes());
+        }
+        vt.process_output(b"\x1b]8;;https://x.dev/0\x07l\x1b]8;;\x07\r");
+        vt.process_output(b"\x1b]8;;https://x.dev/0\x07l\x1b]8;;\x07\r");
+        assert_eq!(vt.hyperlinks().len(), MAX_HYPERLINKS);
+        assert_eq!(vt.hyperlinks().last().unwrap().uri, "https://x.dev/0");
+        assert_eq!(
+            vt.hyperlinks()
+                .iter()
+                .filter(|l| l.uri == "https://x.dev/0")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn replay_preserves_hyperlinks_on_screen_and_in_scrollback() {
+        let mut vt = VirtualTerminal::new(3, 20, 4096, 100);
+        vt.process_output(b"\x1b]8;;https://a.dev\x07first\x1b]8;;\x07 line\r\n");
+        vt.process_output(b"x\r\ny\r\n");
+        vt.process_output(b"go \x1b[1m\x1b]8;;https://b.dev\x07here\x1b]8;;\x07\x1b[0m!");
+        let replay = vt.replay(3);
+        let text = String::from_utf8_lossy(&replay);
+        assert!(text.contains("\x1b]8;;https://a.dev\x1b\\first\x1b]8;;\x1b\\"));
+        assert!(text.contains("\x1b]8;;https://b.dev\x1b\\\x1b[1mhere\x1b]8;;\x1b\\"));
+
+        // A client parsing the replay learns the same links
+        let mut client = VirtualTerminal::new(3, 20, 4096, 100);
+        client.process_output(&replay);
+        assert_eq!(client.screen().contents(), vt.screen().contents());
+        let mut uris: Vec<_> = client.hyperlinks().iter().map(|l| l.uri.as_str()).collect();
+        uris.sort();
+        assert_eq!(uris, vec!["https://a.dev", "https://b.dev"]);
+        assert!(client.screen().cell(2, 3).unwrap().bold());
+        assert!(!client.screen().cell(2, 7).unwrap().bold());
+        assert_eq!(client.cursor_position(), vt.cursor_position());
+    }
+
+    #[test]
+    fn overwritten_link_text_is_no_longer_linked() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b]8;;https://a.dev\x07link\x1b]8;;\x07");
+        vt.process_output(b"\x1b[2J\x1b[Hplain text");
+        let replay = String::from_utf8_lossy(&vt.replay(24)).into_owned();
+        assert!(!replay.contains("\x1b]8;"));
+    }
+
+    #[test]
+    fn keyframe_without_links_is_unchanged() {
+        let mut vt = vt();
+        vt.process_output(b"\x1b[31mred\x1b[0m");
+        let screen = vt.screen();
+        let mut expected = b"\x1b[H\x1b[2J\x1b[0m".to_vec();
+        expected.extend_from_slice(&screen.contents_formatted());
+        expected.extend_from_slice(b"\x1b[1;4H");
+        assert_eq!(vt.replay(24), expected);
+    }
+}
@@ -0,0 +1,132 @@
+//! Screen diffs for clients that can't keep up with raw PTY output.
+//!
+//! Instead of every byte the program wrote (spinner redraws included), a
+//! client gets periodic frames: escape sequences that turn the screen it
+//! last saw into the current one, touching only the cells that changed.
+//! The caller keeps the [`ScreenState`] returned with each frame and passes
+//! it back for the next one.
+//!
+//! Frames repaint the visible screen in place, so lines that scroll off the
+//! top between two frames never reach the client's scrollback. OSC 8
+//! hyperlinks are not carried either.
+
+use crate::VirtualTerminal;
+
+/// The visible screen as a client last received it (no scrollback).
+#[derive(Clone)]
+pub struct ScreenState {
+    screen: vt100::Screen,
+}
+
+impl ScreenState {
+    /// Copy the visible screen and input modes of `screen`.
+    fn capture(screen: &vt100::Screen) -> Self {
+        let (rows, cols) = screen.size();
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(&screen.state_formatted());
+        Self {
+            screen: parser.screen().clone(),
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.screen.size()
+    }
+}
+
+impl std::fmt::Debug for ScreenState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScreenState")
+            .field("size", &self.size())
+            .finish_non_exhaustive()
+    }
+}
+
+impl VirtualTerminal {
+    /// Bytes that bring a client showing `prev` up to the current visible
+    /// screen, and the state to diff the next frame against.
+    ///
+    /// Without `prev`, or when the dimensions changed since, the frame is a
+    /// full repaint. An empty frame means nothing changed.
+    pub fn screen_diff(&self, prev: Option<&ScreenState>) -> (Vec<u8>, ScreenState) {
+        let screen = self.screen();
+        let data = match prev {
+            Some(prev) if prev.size() == screen.size() => screen.state_diff(&prev.screen),
+            _ => screen.state_formatted(),
+        };
+        (data, ScreenState::capture(screen))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply frames to a fresh client-side terminal, like a remote viewer.
+    fn client_view(rows: u16, cols: u16, frames: &[&[u8]]) -> vt100::Parser {
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        for frame in frames {
+            parser.process(frame);
+        }
+        parser
+    }
+
+    #[test]
+    fn first_frame_is_a_full_repaint() {
+        let mut vt = VirtualTerminal::new(5, 20, 4096, 100);
+        vt.process_output(b"\x1b[1;31mred\x1b[0m plain\r\nsecond");
+        let (frame, state) = vt.screen_diff(None);
+        assert_eq!(state.size(), (5, 20));
+
+        let client = client_view(5, 20, &[&frame]);
+        assert_eq!(client.screen().contents(), vt.screen().contents());
+        assert_eq!(client.screen().cursor_position(), vt.cursor_position());
+        assert!(client.screen().cell(0, 0).unwrap().bold());
+    }
+
+    #[test]
+    fn later_frames_only_carry_changes() {
+        let mut vt = VirtualTerminal::new(10, 40, 4096, 100);
+        for i in 0..9 {
+            vt.process_output(format!("row {i} with some text\r\n").as_bytes());
+        }
+        let (full, state) = vt.screen_diff(None);
+
+        // A spinner redrawing one cell many times
+        for c in "|/-\\|/-\\".chars() {
+            vt.process_output(format!("\r{c}").as_bytes());
+        }
+        let (diff, state) = vt.screen_diff(Some(&state));
+        assert!(diff.len() < 32, "{diff:?}");
+
+        let client = client_view(10, 40, &[&full, &diff]);
+        assert_eq!(client.screen().contents(), vt.screen().contents());
+        assert_eq!(client.screen().cursor_position(), vt.cursor_position());
+
+        let (nothing, _) = vt.screen_diff(Some(&state));
+        assert!(nothing.is_empty(), "{nothing:?}");
+    }
+
+    #[test]
+    fn resize_forces_a_full_repaint() {
+        let mut vt = VirtualTerminal::new(5, 20, 4096, 100);
+        vt.process_output(b"hello");
+        let (_, state) = vt.screen_diff(None);
+
+        vt.update_viewport("c", 6, 30, crate::ClientType::Terminal);
+        let (frame, state) = vt.screen_diff(Some(&state));
+        assert_eq!(state.size(), (6, 30));
+        let client = client_view(6, 30, &[b"stale junk", &frame]);
+        assert_eq!(client.screen().contents(), "hello");
+    }
+
+    #[test]
+    fn input_modes_follow_the_screen() {
+        let mut vt = VirtualTerminal::new(5, 20, 4096, 100);
+        let (full, state) = vt.screen_diff(None);
+        vt.process_output(b"\x1b[?2004h");
+        let (diff, _) = vt.screen_diff(Some(&state));
+        let client = client_view(5, 20, &[&full, &diff]);
+        assert!(client.screen().bracketed_paste());
+    }
+}
@@ -4,10 +4,21 @@
 //! generates keyframe snapshots, stores deltas (raw PTY output since last
 //! keyframe), and negotiates dimensions across multiple clients.
 
+pub mod callbacks;
+pub use callbacks::{Hyperlink, TerminalEvent, VtCallbacks};
+pub mod diff;
+pub use diff::ScreenState;
 pub mod recorder;
-pub use recorder::{VtEvent, VtRecorder, VtRecording, VtRecordingHeader};
+pub use recorder::{VtEvent, VtKeyframe, VtRecorder, VtRecording, VtRecordingHeader};
+pub mod snapshot;
+pub use snapshot::VtSnapshot;
 
 use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bytes of raw PTY output retained for [`VtSnapshot::raw_tail`].
+pub const RAW_TAIL_BYTES: usize = 64 * 1024;
 
 /// Attributes of a single visible cell, extracted from vt100.
 ///
@@ -23,9 +34,14 @@ pub struct CellInfo<'a> {
     pub fg: vt100::Color,
     pub bg: vt100::Color,
     pub bold: bool,
+    pub dim: bool,
     pub italic: bool,
-    pub underline: bool,
+    pub underline: vt100::UnderlineStyle,
+    /// Underline colour (`Default` = same as the foreground).
+    pub underline_color: vt100::Color,
     pub inverse: bool,
+    pub strikethrough: bool,
+    pub blink: bool,
 }
 
 /// Iterate visible cells in a row, skipping wide-continuation cells.
@@ -47,9 +63,13 @@ pub fn walk_row(screen: &vt100::Screen, row: u16, cols: u16) -> impl Iterator<It
             fg: cell.fgcolor(),
             bg: cell.bgcolor(),
             bold: cell.bold(),
+            dim: cell.dim(),
             italic: cell.italic(),
-            underline: cell.underline(),
+            underline: cell.underline_style(),
+            underline_color: cell.underline_color(),
             inverse: cell.inverse(),
+            strikethrough: cell.strikethrough(),
+            blink: cell.blink(),
         })
     })
 }
@@ -59,6 +79,24 @@ pub fn walk_row(screen: &vt100::Screen, row: u16, cols: u16) -> impl Iterator<It
 pub enum ClientType {
     Web,
     Terminal,
+    /// Read-only viewer: never takes part in dimension negotiation
+    Spectator,
+}
+
+/// How the effective dimensions are picked from the negotiating viewports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SizePolicy {
+    /// min(rows) x min(cols), so everyone sees the whole screen
+    #[default]
+    Smallest,
+    /// max(rows) x max(cols); smaller clients see a clipped screen
+    Largest,
+    /// The size owner's viewport (see [`VirtualTerminal::set_size_owner`]),
+    /// falling back to `Smallest` while it has none
+    LockHolder,
+    /// A fixed size, whatever the clients
+    Fixed { rows: u16, cols: u16 },
 }
 
 /// Per-client viewport tracking.
@@ -75,14 +113,20 @@ pub struct ClientViewport {
 /// keyframe + delta replay for efficient client attach.
 pub struct VirtualTerminal {
     /// VT100 terminal emulator — processes PTY output, maintains screen state
-    parser: vt100::Parser,
+    parser: vt100::Parser<VtCallbacks>,
 
     /// Per-client viewport tracking
     client_viewports: HashMap<String, ClientViewport>,
 
-    /// Current effective dimensions (min of all active viewports)
+    /// Current effective dimensions (negotiated per `size_policy`)
     effective_dims: (u16, u16),
 
+    /// How `effective_dims` is negotiated
+    size_policy: SizePolicy,
+
+    /// Connection whose viewport wins under `SizePolicy::LockHolder`
+    size_owner: Option<String>,
+
     /// Last keyframe: screen state rendered as ANSI escape sequences
     keyframe: Option<Vec<u8>>,
 
@@ -94,6 +138,12 @@ pub struct VirtualTerminal {
 
     /// Scrollback capacity (lines) — stored for parser recreation on resize
     scrollback_capacity: usize,
+
+    /// Most recent raw PTY output (at most `2 * RAW_TAIL_BYTES` between trims)
+    raw_tail: Vec<u8>,
+
+    /// Bumped on every `process_output` — lets callers skip unchanged snapshots
+    output_generation: u64,
 }
 
 /// Diagnostic snapshot of VT state for debugging.
@@ -113,13 +163,22 @@ impl VirtualTerminal {
     /// Create a new virtual terminal with the given initial dimensions.
     pub fn new(rows: u16, cols: u16, max_delta_bytes: usize, scrollback_lines: usize) -> Self {
         Self {
-            parser: vt100::Parser::new(rows, cols, scrollback_lines),
+            parser: vt100::Parser::new_with_callbacks(
+                rows,
+                cols,
+                scrollback_lines,
+                VtCallbacks::default(),
+            ),
             client_viewports: HashMap::new(),
             effective_dims: (rows, cols),
+            size_policy: SizePolicy::default(),
+            size_owner: None,
             keyframe: None,
             deltas: Vec::new(),
             max_delta_bytes,
             scrollback_capacity: scrollback_lines,
+            raw_tail: Vec::new(),
+            output_generation: 0,
         }
     }
 
@@ -138,6 +197,13 @@ impl VirtualTerminal {
     pub fn process_output(&mut self, data: &[u8]) {
         self.parser.process(data);
         self.deltas.extend_from_slice(data);
+        self.output_generation += 1;
+
+        self.raw_tail.extend_from_slice(data);
+        if self.raw_tail.len() > 2 * RAW_TAIL_BYTES {
+            let excess = self.raw_tail.len() - RAW_TAIL_BYTES;
+            self.raw_tail.drain(..excess);
+        }
 
         // Auto-compact when deltas get large
         if self.deltas.len() > self.max_delta_bytes {
@@ -148,14 +214,12 @@ impl VirtualTerminal {
     /// Generate a keyframe: snapshot the screen as ANSI escape sequences.
     /// Clears the delta buffer.
     pub fn compact(&mut self) {
-        let screen = self.parser.screen();
         let mut kf = Vec::new();
-        // Reset terminal state, move home, clear screen, reset attributes
-        kf.extend_from_slice(b"\x1b[H\x1b[2J\x1b[0m");
-        kf.extend_from_slice(&screen.contents_formatted());
-        // Restore cursor position
-        let (row, col) = screen.cursor_position();
-        kf.extend_from_slice(format!("\x1b[{};{}H", row + 1, col + 1).as_bytes());
+        render_visible_screen(
+            self.parser.screen(),
+            self.parser.callbacks().hyperlinks(),
+            &mut kf,
+        );
         self.keyframe = Some(kf);
         self.deltas.clear();
     }
@@ -184,7 +248,11 @@ impl VirtualTerminal {
             append_scrollback_flush(&mut result, scrollback_lines, client_rows);
         }
 
-        render_visible_screen(self.parser.screen(), &mut result);
+        render_visible_screen(
+            self.parser.screen(),
+            self.parser.callbacks().hyperlinks(),
+            &mut result,
+        );
 
         tracing::debug!(
             client_rows,
@@ -201,6 +269,68 @@ impl VirtualTerminal {
         result
     }
 
+    /// Window title last set by the program via OSC 0/2.
+    pub fn title(&self) -> Option<&str> {
+        self.parser.callbacks().title()
+    }
+
+    /// OSC 8 hyperlink anchors, for renderers that draw the screen themselves.
+    pub fn hyperlinks(&self) -> &[Hyperlink] {
+        self.parser.callbacks().hyperlinks()
+    }
+
+    /// Drain bells, notifications and clipboard writes emitted since the last call.
+    pub fn take_events(&mut self) -> Vec<TerminalEvent> {
+        self.parser.callbacks_mut().take_events()
+    }
+
+    /// Number of `process_output` calls so far. Unchanged generation means
+    /// unchanged screen, so a previous snapshot is still current.
+    pub fn output_generation(&self) -> u64 {
+        self.output_generation
+    }
+
+    /// Capture scrollback, visible screen and the raw output tail.
+    pub fn snapshot(&mut self) -> VtSnapshot {
+        let (rows, cols) = self.parser.screen().size();
+        let screen = self.replay(rows);
+        let start = self.raw_tail.len().saturating_sub(RAW_TAIL_BYTES);
+        VtSnapshot {
+            rows,
+            cols,
+            scrollback: self.scrollback_capacity as u32,
+            screen,
+            raw_tail: self.raw_tail[start..].to_vec(),
+            title: self.title().map(str::to_string),
+            saved_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as i64),
+        }
+    }
+
+    /// Replace the terminal contents with a snapshot.
+    ///
+    /// The parser is recreated at the snapshot's dimensions (which become
+    /// the effective dims) so scrollback survives until a client negotiates
+    /// a different size. Client viewports are left untouched.
+    pub fn restore(&mut self, snapshot: &VtSnapshot) {
+        let mut callbacks = std::mem::take(self.parser.callbacks_mut());
+        callbacks.set_title(snapshot.title.clone());
+        self.parser = vt100::Parser::new_with_callbacks(
+            snapshot.rows,
+            snapshot.cols,
+            self.scrollback_capacity,
+            callbacks,
+        );
+        self.parser.process(&snapshot.screen);
+        // Bells/notifications in the replayed history were already delivered
+        self.parser.callbacks_mut().take_events();
+        self.effective_dims = (snapshot.rows, snapshot.cols);
+        self.keyframe = None;
+        self.deltas.clear();
+        self.raw_tail = snapshot.raw_tail.clone();
+    }
+
     /// Update a client's viewport. Returns new effective dims if changed.
     pub fn update_viewport(
         &mut self,
@@ -235,6 +365,25 @@ impl VirtualTerminal {
         self.recalculate_effective_dims()
     }
 
+    /// Change the negotiation policy. Returns new effective dims if changed.
+    pub fn set_size_policy(&mut self, policy: SizePolicy) -> Option<(u16, u16)> {
+        self.size_policy = policy;
+        self.recalculate_effective_dims()
+    }
+
+    /// Current negotiation policy.
+    pub fn size_policy(&self) -> SizePolicy {
+        self.size_policy
+    }
+
+    /// Set the connection that wins under [`SizePolicy::LockHolder`]
+    /// (typically the terminal lock holder). Returns new effective dims if
+    /// changed.
+    pub fn set_size_owner(&mut self, connection_id: Option<&str>) -> Option<(u16, u16)> {
+        self.size_owner = connection_id.map(str::to_string);
+        self.recalculate_effective_dims()
+    }
+
     /// Apply a resize to the vt100 parser (called when effective dims change).
     /// Resize the terminal: saves visible screen, creates a fresh parser at the
     /// new dimensions (clearing scrollback), and restores the visible content.
@@ -243,7 +392,9 @@ impl VirtualTerminal {
     pub fn resize(&mut self, rows: u16, cols: u16) {
         let content = self.parser.screen().contents_formatted();
         let cursor = self.parser.screen().cursor_position();
-        self.parser = vt100::Parser::new(rows, cols, self.scrollback_capacity);
+        let callbacks = std::mem::take(self.parser.callbacks_mut());
+        self.parser =
+            vt100::Parser::new_with_callbacks(rows, cols, self.scrollback_capacity, callbacks);
         self.parser.process(&content);
         self.parser
             .process(format!("\x1b[{};{}H", cursor.0 + 1, cursor.1 + 1).as_bytes());
@@ -263,23 +414,15 @@ impl VirtualTerminal {
     /// then visible screen rows. Reads cells directly from the vt100 parser
     /// — same data the TUI renders.
     pub fn lines(&mut self) -> Vec<String> {
-        let (_, cols) = self.parser.screen().size();
-
-        // Scrollback (oldest first)
-        let depth = scrollback_depth(&mut self.parser);
-        let mut out = Vec::with_capacity(depth);
-        for offset in (1..=depth).rev() {
-            self.parser.screen_mut().set_scrollback(offset);
-            out.push(read_row_text(self.parser.screen(), 0, cols));
-        }
-        self.parser.screen_mut().set_scrollback(0);
+        parser_lines(&mut self.parser)
+    }
 
-        // Visible screen
+    /// The visible screen rows as plain text.
+    pub fn screen_lines(&self) -> Vec<String> {
         let (rows, cols) = self.parser.screen().size();
-        for r in 0..rows {
-            out.push(read_row_text(self.parser.screen(), r, cols));
-        }
-        out
+        (0..rows)
+            .map(|r| read_row_text(self.parser.screen(), r, cols))
+            .collect()
     }
 
     /// Diagnostic dump of VT state for debugging replay/corruption issues.
@@ -314,7 +457,11 @@ impl VirtualTerminal {
     /// Recalculate effective dims from active viewports.
     /// Returns `Some((rows, cols))` if dims changed, `None` otherwise.
     fn recalculate_effective_dims(&mut self) -> Option<(u16, u16)> {
-        let new_dims = calculate_effective_dims(&self.client_viewports);
+        let new_dims = calculate_effective_dims(
+            &self.client_viewports,
+            self.size_policy,
+            self.size_owner.as_deref(),
+        );
         if let Some((rows, cols)) = new_dims
             && (rows, cols) != self.effective_dims
         {
@@ -324,7 +471,7 @@ impl VirtualTerminal {
             self.deltas.clear();
             return Some((rows, cols));
         }
-        // If no active viewports, keep current dims (don't shrink to nothing)
+        // If no viewport negotiates, keep current dims (don't shrink to nothing)
         None
     }
 }
@@ -341,13 +488,30 @@ impl VirtualTerminal {
 ///
 /// Temporarily sets scrollback to `usize::MAX` (vt100 clamps to actual depth),
 /// reads the clamped value, and restores scrollback to 0.
-fn scrollback_depth(parser: &mut vt100::Parser) -> usize {
+fn scrollback_depth<CB: vt100::Callbacks>(parser: &mut vt100::Parser<CB>) -> usize {
     parser.screen_mut().set_scrollback(usize::MAX);
     let depth = parser.screen().scrollback();
     parser.screen_mut().set_scrollback(0);
     depth
 }
 
+/// All of a parser's content as plain-text lines: scrollback (oldest
+/// first) then the visible rows. Leaves the scrollback offset at 0.
+pub fn parser_lines<CB: vt100::Callbacks>(parser: &mut vt100::Parser<CB>) -> Vec<String> {
+    let (rows, cols) = parser.screen().size();
+
+    let depth = scrollback_depth(parser);
+    let mut out = Vec::with_capacity(depth + rows as usize);
+    for offset in (1..=depth).rev() {
+        parser.screen_mut().set_scrollback(offset);
+        out.push(read_row_text(parser.screen(), 0, cols));
+    }
+    parser.screen_mut().set_scrollback(0);
+
+    out.extend((0..rows).map(|r| read_row_text(parser.screen(), r, cols)));
+    out
+}
+
 /// Read a single row as plain text, trimming trailing whitespace.
 ///
 /// Shared by `VirtualTerminal::lines()`, `debug_state()`, and test helpers.
@@ -358,6 +522,102 @@ pub fn read_row_text(screen: &vt100::Screen, row: u16, cols: u16) -> String {
     s.trim_end().to_string()
 }
 
+/// Concatenated contents of the cells in `cols` (untrimmed, `" "` for blanks).
+pub(crate) fn read_cells_text(screen: &vt100::Screen, row: u16, cols: Range<u16>) -> String {
+    walk_row(screen, row, cols.end)
+        .skip_while(|cell| cell.col < cols.start)
+        .map(|cell| cell.contents)
+        .collect()
+}
+
+/// A hyperlink applied to columns `start..end` of a rendered row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperlinkSpan<'a> {
+    pub start: u16,
+    pub end: u16,
+    pub uri: &'a str,
+}
+
+/// Hyperlinks whose anchor text is shown on `row`, sorted by column and
+/// non-overlapping (newer anchors win).
+pub fn row_hyperlinks<'a>(
+    screen: &vt100::Screen,
+    row: u16,
+    cols: u16,
+    links: &'a [Hyperlink],
+) -> Vec<HyperlinkSpan<'a>> {
+    let mut spans: Vec<HyperlinkSpan<'a>> = Vec::new();
+    for link in links.iter().rev() {
+        let Some(end) = match_anchor(screen, row, cols, link) else {
+            continue;
+        };
+        if spans.iter().any(|s| link.col < s.end && s.start < end) {
+            continue;
+        }
+        spans.push(HyperlinkSpan {
+            start: link.col,
+            end,
+            uri: &link.uri,
+        });
+    }
+    spans.sort_by_key(|s| s.start);
+    spans
+}
+
+/// End column if the cells starting at `link.col` spell out `link.text`.
+fn match_anchor(screen: &vt100::Screen, row: u16, cols: u16, link: &Hyperlink) -> Option<u16> {
+    let mut rest = link.text.as_str();
+    let mut end = link.col;
+    for cell in walk_row(screen, row, cols).skip_while(|cell| cell.col < link.col) {
+        if rest.is_empty() {
+            break;
+        }
+        if end != cell.col {
+            return None;
+        }
+        rest = rest.strip_prefix(cell.contents)?;
+        let wide = screen.cell(row, cell.col).is_some_and(|c| c.is_wide());
+        end = cell.col + if wide { 2 } else { 1 };
+    }
+    (rest.is_empty() && end > link.col).then_some(end)
+}
+
+/// Re-draw linked cells of the visible screen wrapped in OSC 8, each span
+/// positioned with CUP relative to `origin` (0-indexed row, col).
+///
+/// Leaves the cursor and drawing attributes wherever the last span ended —
+/// callers restore them.
+pub fn render_hyperlink_overlay(
+    screen: &vt100::Screen,
+    links: &[Hyperlink],
+    origin: (u16, u16),
+    out: &mut Vec<u8>,
+) {
+    if links.is_empty() {
+        return;
+    }
+    let (rows, cols) = screen.size();
+    for row in 0..rows {
+        for span in row_hyperlinks(screen, row, cols, links) {
+            out.extend_from_slice(
+                format!(
+                    "\x1b[{};{}H\x1b[0m",
+                    origin.0 + row + 1,
+                    origin.1 + span.start + 1
+                )
+                .as_bytes(),
+            );
+            emit_osc8_open(out, span.uri);
+            let mut sgr = SgrState::default();
+            for cell in walk_row(screen, row, span.end).skip_while(|c| c.col < span.start) {
+                sgr.apply(&cell, out);
+                out.extend_from_slice(cell.contents.as_bytes());
+            }
+            emit_osc8_close(out);
+        }
+    }
+}
+
 // =============================================================================
 // Functional Shell — pure(ish) rendering functions
 //
@@ -376,7 +636,7 @@ pub fn read_row_text(screen: &vt100::Screen, row: u16, cols: u16) -> String {
 /// Temporarily shifts the parser's scrollback viewport (restored to 0 on return).
 /// Render scrollback content as plain rows (no CUP). Returns the number of
 /// scrollback lines emitted (0 means no scrollback).
-fn render_scrollback(parser: &mut vt100::Parser, out: &mut Vec<u8>) -> usize {
+fn render_scrollback(parser: &mut vt100::Parser<VtCallbacks>, out: &mut Vec<u8>) -> usize {
     let total_scrollback = scrollback_depth(parser);
 
     if total_scrollback == 0 {
@@ -385,6 +645,8 @@ fn render_scrollback(parser: &mut vt100::Parser, out: &mut Vec<u8>) -> usize {
 
     let (rows, cols) = parser.screen().size();
     let page_size = rows as usize;
+    // Cloned: the loop below needs the parser mutably to page through scrollback
+    let links = parser.callbacks().hyperlinks().to_vec();
 
     let mut remaining = total_scrollback;
     while remaining > 0 {
@@ -392,7 +654,7 @@ fn render_scrollback(parser: &mut vt100::Parser, out: &mut Vec<u8>) -> usize {
         let page_rows = remaining.min(page_size);
 
         for i in 0..page_rows {
-            format_row_no_cup(parser.screen(), i as u16, cols, out);
+            format_row_no_cup(parser.screen(), i as u16, cols, &links, out);
             out.extend_from_slice(b"\x1b[0m\r\n");
         }
 
@@ -425,9 +687,16 @@ fn append_scrollback_flush(out: &mut Vec<u8>, scrollback_lines: usize, client_ro
 }
 
 /// Render the visible screen as a keyframe: clear + contents + cursor restore.
-fn render_visible_screen(screen: &vt100::Screen, out: &mut Vec<u8>) {
+/// Hyperlinks are drawn over the formatted contents, after which the active
+/// drawing attributes are restored.
+fn render_visible_screen(screen: &vt100::Screen, links: &[Hyperlink], out: &mut Vec<u8>) {
     out.extend_from_slice(b"\x1b[H\x1b[2J\x1b[0m");
     out.extend_from_slice(&screen.contents_formatted());
+    let overlay_start = out.len();
+    render_hyperlink_overlay(screen, links, (0, 0), out);
+    if out.len() > overlay_start {
+        out.extend_from_slice(&screen.attributes_formatted());
+    }
     let (row, col) = screen.cursor_position();
     out.extend_from_slice(format!("\x1b[{};{}H", row + 1, col + 1).as_bytes());
 }
@@ -435,79 +704,143 @@ fn render_visible_screen(screen: &vt100::Screen, out: &mut Vec<u8>) {
 /// Emit a single row as SGR-styled text — no CUP or cursor movement.
 /// Iterates cells left-to-right, emitting SGR diffs for attribute changes
 /// and cell contents (space for empty/default cells). Wide-continuation
-/// cells are skipped.
-fn format_row_no_cup(screen: &vt100::Screen, row: u16, cols: u16, out: &mut Vec<u8>) {
-    let mut cur_fg = vt100::Color::Default;
-    let mut cur_bg = vt100::Color::Default;
-    let mut cur_bold = false;
-    let mut cur_italic = false;
-    let mut cur_underline = false;
-    let mut cur_inverse = false;
+/// cells are skipped. Linked cells are wrapped in OSC 8.
+fn format_row_no_cup(
+    screen: &vt100::Screen,
+    row: u16,
+    cols: u16,
+    links: &[Hyperlink],
+    out: &mut Vec<u8>,
+) {
+    let mut sgr = SgrState::default();
+    let spans = row_hyperlinks(screen, row, cols, links);
+    let mut spans = spans.iter().peekable();
+    let mut link_end = None;
 
     for cell in walk_row(screen, row, cols) {
-        let attrs_differ = cell.fg != cur_fg
-            || cell.bg != cur_bg
-            || cell.bold != cur_bold
-            || cell.italic != cur_italic
-            || cell.underline != cur_underline
-            || cell.inverse != cur_inverse;
-
-        if attrs_differ {
-            // Check if any attribute was *removed* — needs a full reset
-            let needs_reset = (cur_bold && !cell.bold)
-                || (cur_italic && !cell.italic)
-                || (cur_underline && !cell.underline)
-                || (cur_inverse && !cell.inverse)
-                || (cur_fg != vt100::Color::Default && cell.fg == vt100::Color::Default)
-                || (cur_bg != vt100::Color::Default && cell.bg == vt100::Color::Default);
-
-            if needs_reset {
-                out.extend_from_slice(b"\x1b[0m");
-                if cell.bold {
-                    out.extend_from_slice(b"\x1b[1m");
-                }
-                if cell.italic {
-                    out.extend_from_slice(b"\x1b[3m");
-                }
-                if cell.underline {
-                    out.extend_from_slice(b"\x1b[4m");
-                }
-                if cell.inverse {
-                    out.extend_from_slice(b"\x1b[7m");
-                }
-                emit_sgr_fg(out, cell.fg);
-                emit_sgr_bg(out, cell.bg);
-            } else {
-                if !cur_bold && cell.bold {
-                    out.extend_from_slice(b"\x1b[1m");
-                }
-                if !cur_italic && cell.italic {
-                    out.extend_from_slice(b"\x1b[3m");
-                }
-                if !cur_underline && cell.underline {
-                    out.extend_from_slice(b"\x1b[4m");
-                }
-                if !cur_inverse && cell.inverse {
-                    out.extend_from_slice(b"\x1b[7m");
-                }
-                if cur_fg != cell.fg {
-                    emit_sgr_fg(out, cell.fg);
-                }
-                if cur_bg != cell.bg {
-                    emit_sgr_bg(out, cell.bg);
-                }
-            }
-
-            cur_fg = cell.fg;
-            cur_bg = cell.bg;
-            cur_bold = cell.bold;
-            cur_italic = cell.italic;
-            cur_underline = cell.underline;
-            cur_inverse = cell.inverse;
+        if link_end.is_some_and(|end| cell.col >= end) {
+            emit_osc8_close(out);
+            link_end = None;
+        }
+        if link_end.is_none()
+            && let Some(span) = spans.next_if(|s| s.start == cell.col)
+        {
+            emit_osc8_open(out, span.uri);
+            link_end = Some(span.end);
         }
 
+        sgr.apply(&cell, out);
         out.extend_from_slice(cell.contents.as_bytes());
     }
+    if link_end.is_some() {
+        emit_osc8_close(out);
+    }
+}
+
+/// Drawing attributes already emitted, so each cell only emits the SGR diff.
+/// Starts from the reset (default) state.
+#[derive(Default, PartialEq)]
+struct SgrState {
+    fg: vt100::Color,
+    bg: vt100::Color,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: vt100::UnderlineStyle,
+    underline_color: vt100::Color,
+    inverse: bool,
+    strikethrough: bool,
+    blink: bool,
+}
+
+impl SgrState {
+    fn of(cell: &CellInfo<'_>) -> Self {
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            bold: cell.bold,
+            dim: cell.dim,
+            italic: cell.italic,
+            underline: cell.underline,
+            underline_color: cell.underline_color,
+            inverse: cell.inverse,
+            strikethrough: cell.strikethrough,
+            blink: cell.blink,
+        }
+    }
+
+    /// Whether moving from `self` to `next` turns any attribute *off*,
+    /// which needs a full reset.
+    fn removes_any(&self, next: &Self) -> bool {
+        use vt100::{Color, UnderlineStyle};
+        (self.bold && !next.bold)
+            || (self.dim && !next.dim)
+            || (self.italic && !next.italic)
+            || (self.underline != UnderlineStyle::None && next.underline == UnderlineStyle::None)
+            || (self.underline_color != Color::Default && next.underline_color == Color::Default)
+            || (self.inverse && !next.inverse)
+            || (self.strikethrough && !next.strikethrough)
+            || (self.blink && !next.blink)
+            || (self.fg != Color::Default && next.fg == Color::Default)
+            || (self.bg != Color::Default && next.bg == Color::Default)
+    }
+
+    fn apply(&mut self, cell: &CellInfo<'_>, out: &mut Vec<u8>) {
+        let next = Self::of(cell);
+        if next == *self {
+            return;
+        }
+
+        let prev = if self.removes_any(&next) {
+            out.extend_from_slice(b"\x1b[0m");
+            Self::default()
+        } else {
+            std::mem::take(self)
+        };
+
+        if next.bold && !prev.bold {
+            out.extend_from_slice(b"\x1b[1m");
+        }
+        if next.dim && !prev.dim {
+            out.extend_from_slice(b"\x1b[2m");
+        }
+        if next.italic && !prev.italic {
+            out.extend_from_slice(b"\x1b[3m");
+        }
+        if next.underline != prev.underline {
+            emit_sgr_underline(out, next.underline);
+        }
+        if next.underline_color != prev.underline_color {
+            emit_sgr_underline_color(out, next.underline_color);
+        }
+        if next.inverse && !prev.inverse {
+            out.extend_from_slice(b"\x1b[7m");
+        }
+        if next.strikethrough && !prev.strikethrough {
+            out.extend_from_slice(b"\x1b[9m");
+        }
+        if next.blink && !prev.blink {
+            out.extend_from_slice(b"\x1b[5m");
+        }
+        if next.fg != prev.fg {
+            emit_sgr_fg(out, next.fg);
+        }
+        if next.bg != prev.bg {
+            emit_sgr_bg(out, next.bg);
+        }
+
+        *self = next;
+    }
+}
+
+fn emit_osc8_open(out: &mut Vec<u8>, uri: &str) {
+    out.extend_from_slice(b"\x1b]8;;");
+    out.extend_from_slice(uri.as_bytes());
+    out.extend_from_slice(b"\x1b\\");
+}
+
+fn emit_osc8_close(out: &mut Vec<u8>) {
+    out.extend_from_slice(b"\x1b]8;;\x1b\\");
 }
 
 fn emit_sgr_fg(out: &mut Vec<u8>, color: vt100::Color) {
@@ -522,6 +855,31 @@ fn emit_sgr_fg(out: &mut Vec<u8>, color: vt100::Color) {
     }
 }
 
+/// Styled underlines use the colon form, which terminals without support
+/// parse as a plain underline.
+fn emit_sgr_underline(out: &mut Vec<u8>, style: vt100::UnderlineStyle) {
+    out.extend_from_slice(match style {
+        vt100::UnderlineStyle::None => b"\x1b[24m",
+        vt100::UnderlineStyle::Single => b"\x1b[4m",
+        vt100::UnderlineStyle::Double => b"\x1b[4:2m",
+        vt100::UnderlineStyle::Curly => b"\x1b[4:3m",
+        vt100::UnderlineStyle::Dotted => b"\x1b[4:4m",
+        vt100::UnderlineStyle::Dashed => b"\x1b[4:5m",
+    });
+}
+
+fn emit_sgr_underline_color(out: &mut Vec<u8>, color: vt100::Color) {
+    match color {
+        vt100::Color::Default => out.extend_from_slice(b"\x1b[59m"),
+        vt100::Color::Idx(n) => {
+            out.extend_from_slice(format!("\x1b[58:5:{}m", n).as_bytes());
+        }
+        vt100::Color::Rgb(r, g, b) => {
+            out.extend_from_slice(format!("\x1b[58:2::{}:{}:{}m", r, g, b).as_bytes());
+        }
+    }
+}
+
 fn emit_sgr_bg(out: &mut Vec<u8>, color: vt100::Color) {
     match color {
         vt100::Color::Default => out.extend_from_slice(b"\x1b[49m"),
@@ -534,15 +892,34 @@ fn emit_sgr_bg(out: &mut Vec<u8>, color: vt100::Color) {
     }
 }
 
-/// Calculate effective dimensions from active viewports.
-/// Returns min(rows) x min(cols) across all active clients.
-fn calculate_effective_dims(viewports: &HashMap<String, ClientViewport>) -> Option<(u16, u16)> {
-    let active: Vec<_> = viewports.values().filter(|v| v.is_active).collect();
-    if active.is_empty() {
-        return None;
+/// Calculate effective dimensions from active, non-spectator viewports
+/// according to `policy`. `None` if no viewport takes part.
+fn calculate_effective_dims(
+    viewports: &HashMap<String, ClientViewport>,
+    policy: SizePolicy,
+    owner: Option<&str>,
+) -> Option<(u16, u16)> {
+    let negotiating = |v: &&ClientViewport| v.is_active && v.client_type != ClientType::Spectator;
+    let active: Vec<_> = viewports.values().filter(negotiating).collect();
+    match policy {
+        SizePolicy::Fixed { rows, cols } => return Some((rows, cols)),
+        SizePolicy::LockHolder => {
+            if let Some(v) = owner
+                .and_then(|id| viewports.get(id))
+                .filter(|v| negotiating(v))
+            {
+                return Some((v.rows, v.cols));
+            }
+        }
+        SizePolicy::Largest => {
+            let rows = active.iter().map(|v| v.rows).max()?;
+            let cols = active.iter().map(|v| v.cols).max()?;
+            return Some((rows, cols));
+        }
+        SizePolicy::Smallest => {}
     }
-    let rows = active.iter().map(|v| v.rows).min().unwrap();
-    let cols = active.iter().map(|v| v.cols).min().unwrap();
+    let rows = active.iter().map(|v| v.rows).min()?;
+    let cols = active.iter().map(|v| v.cols).min()?;
     Some((rows, cols))
 }
 
@@ -707,7 +1084,10 @@ mod tests {
     #[test]
     fn test_calculate_effective_dims_empty() {
         let viewports = HashMap::new();
-        assert_eq!(calculate_effective_dims(&viewports), None);
+        assert_eq!(
+            calculate_effective_dims(&viewports, SizePolicy::Smallest, None),
+            None
+        );
     }
 
     #[test]
@@ -722,7 +1102,69 @@ mod tests {
                 is_active: false,
             },
         );
-        assert_eq!(calculate_effective_dims(&viewports), None);
+        assert_eq!(
+            calculate_effective_dims(&viewports, SizePolicy::Smallest, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spectator_never_shrinks_terminal() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
+        vt.update_viewport("cli", 40, 120, ClientType::Terminal);
+        let result = vt.update_viewport("watcher", 10, 40, ClientType::Spectator);
+        assert_eq!(result, None);
+        assert_eq!(vt.effective_dims(), (40, 120));
+
+        // A lone spectator keeps the current dims
+        vt.remove_client("cli");
+        assert_eq!(vt.effective_dims(), (40, 120));
+    }
+
+    #[test]
+    fn test_largest_policy() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
+        vt.update_viewport("web", 40, 100, ClientType::Web);
+        vt.update_viewport("cli", 30, 120, ClientType::Terminal);
+        assert_eq!(vt.effective_dims(), (30, 100));
+        let result = vt.set_size_policy(SizePolicy::Largest);
+        assert_eq!(result, Some((40, 120)));
+        assert_eq!(vt.size_policy(), SizePolicy::Largest);
+    }
+
+    #[test]
+    fn test_lock_holder_policy() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
+        vt.set_size_policy(SizePolicy::LockHolder);
+        vt.update_viewport("web", 40, 100, ClientType::Web);
+        vt.update_viewport("cli", 30, 120, ClientType::Terminal);
+        // No owner yet: smallest
+        assert_eq!(vt.effective_dims(), (30, 100));
+
+        assert_eq!(vt.set_size_owner(Some("web")), Some((40, 100)));
+        assert_eq!(vt.set_size_owner(Some("cli")), Some((30, 120)));
+
+        // Owner hides its terminal: back to smallest of the rest
+        assert_eq!(vt.set_active("cli", false), Some((40, 100)));
+    }
+
+    #[test]
+    fn test_fixed_policy_ignores_clients() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
+        vt.update_viewport("web", 40, 100, ClientType::Web);
+        let result = vt.set_size_policy(SizePolicy::Fixed {
+            rows: 50,
+            cols: 132,
+        });
+        assert_eq!(result, Some((50, 132)));
+        assert_eq!(
+            vt.update_viewport("cli", 20, 60, ClientType::Terminal),
+            None
+        );
+        assert_eq!(vt.effective_dims(), (50, 132));
+
+        // Back to negotiating
+        assert_eq!(vt.set_size_policy(SizePolicy::Smallest), Some((20, 60)));
     }
 
     #[test]
@@ -1194,7 +1636,10 @@ mod tests {
     }
 
     /// Extract all scrollback lines from a parser (oldest first).
-    fn scrollback_lines(parser: &mut vt100::Parser, cols: u16) -> Vec<String> {
+    fn scrollback_lines<CB: vt100::Callbacks>(
+        parser: &mut vt100::Parser<CB>,
+        cols: u16,
+    ) -> Vec<String> {
         let depth = scrollback_depth(parser);
         let mut lines = Vec::new();
         for offset in (1..=depth).rev() {
@@ -1205,6 +1650,74 @@ mod tests {
         lines
     }
 
+    /// Describe a row as runs of identically-styled text, e.g.
+    /// `[bold ul=Curly ulc=Idx(9)]"todo"`. Unstyled runs print bare.
+    fn attr_runs(screen: &vt100::Screen, row: u16, cols: u16) -> String {
+        let mut out = String::new();
+        let mut run: Option<(String, String)> = None;
+        for cell in walk_row(screen, row, cols) {
+            let style = cell_style(&cell);
+            let text = if cell.contents.is_empty() {
+                " "
+            } else {
+                cell.contents
+            };
+            match &mut run {
+                Some((s, t)) if *s == style => t.push_str(text),
+                _ => {
+                    if let Some((s, t)) = run.take() {
+                        push_run(&mut out, &s, &t);
+                    }
+                    run = Some((style, text.to_string()));
+                }
+            }
+        }
+        if let Some((s, t)) = run {
+            push_run(&mut out, &s, t.trim_end());
+        }
+        out
+    }
+
+    fn cell_style(cell: &CellInfo<'_>) -> String {
+        let mut parts = Vec::new();
+        for (on, name) in [
+            (cell.bold, "bold"),
+            (cell.dim, "dim"),
+            (cell.italic, "italic"),
+            (cell.inverse, "inverse"),
+            (cell.strikethrough, "strike"),
+            (cell.blink, "blink"),
+        ] {
+            if on {
+                parts.push(name.to_string());
+            }
+        }
+        if cell.underline != vt100::UnderlineStyle::None {
+            parts.push(format!("ul={:?}", cell.underline));
+        }
+        if cell.underline_color != vt100::Color::Default {
+            parts.push(format!("ulc={:?}", cell.underline_color));
+        }
+        if cell.fg != vt100::Color::Default {
+            parts.push(format!("fg={:?}", cell.fg));
+        }
+        if cell.bg != vt100::Color::Default {
+            parts.push(format!("bg={:?}", cell.bg));
+        }
+        parts.join(" ")
+    }
+
+    fn push_run(out: &mut String, style: &str, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if style.is_empty() {
+            out.push_str(text);
+        } else {
+            out.push_str(&format!("[{}]{:?}", style, text));
+        }
+    }
+
     // ── Replay rendering TDD tests ──────────────────────────────────
 
     /// Cell-level round-trip verification.
@@ -1252,6 +1765,55 @@ mod tests {
         );
     }
 
+    /// Every attribute vt100 tracks survives the keyframe: the client cell
+    /// matches the server cell field for field.
+    #[test]
+    fn test_replay_preserves_extended_attributes() {
+        let mut vt = VirtualTerminal::new(4, 40, 4096, 100);
+        vt.process_output(
+            b"\x1b[2mdim\x1b[0m \x1b[9mstrike\x1b[0m \x1b[5mblink\x1b[0m\r\n\
+              \x1b[4:3;58:2::255:0:0mcurly\x1b[0m \x1b[21mdouble\x1b[0m \
+              \x1b[4:4;58;5;12mdotted\x1b[0m \x1b[4:5mdashed\x1b[0m",
+        );
+
+        let client = replay_roundtrip(&mut vt, 4, 40);
+        for row in 0..2 {
+            let server: Vec<String> = walk_row(vt.screen(), row, 40)
+                .map(|c| cell_style(&c))
+                .collect();
+            let replayed: Vec<String> = walk_row(client.screen(), row, 40)
+                .map(|c| cell_style(&c))
+                .collect();
+            assert_eq!(server, replayed, "row {row} attributes");
+        }
+
+        let row1 = attr_runs(client.screen(), 1, 40);
+        assert!(
+            row1.contains(r#"[ul=Curly ulc=Rgb(255, 0, 0)]"curly""#),
+            "{row1}"
+        );
+        assert!(row1.contains(r#"[ul=Double]"double""#), "{row1}");
+        assert!(
+            row1.contains(r#"[ul=Dotted ulc=Idx(12)]"dotted""#),
+            "{row1}"
+        );
+        assert!(row1.contains(r#"[ul=Dashed]"dashed""#), "{row1}");
+    }
+
+    /// Turning off one attribute must not leak the others: `22` ends dim
+    /// but leaves the following text's underline colour alone.
+    #[test]
+    fn test_replay_attribute_removal_resets_cleanly() {
+        let mut vt = VirtualTerminal::new(2, 40, 4096, 100);
+        vt.process_output(b"\x1b[2;9;58;5;3mab\x1b[22;29mcd\x1b[59mef");
+
+        let client = replay_roundtrip(&mut vt, 2, 40);
+        assert_eq!(
+            attr_runs(client.screen(), 0, 40),
+            r#"[dim strike ulc=Idx(3)]"ab"[ulc=Idx(3)]"cd"ef"#
+        );
+    }
+
     /// Regression test: no CUP sequences in the scrollback portion of replay.
     #[test]
     fn test_replay_no_cup_in_scrollback() {
@@ -3595,4 +4157,38 @@ mod tests {
         // Snapshot the full rendered output for regression detection
         insta::assert_snapshot!(all_lines.join("\n"));
     }
+
+    /// Golden: a recorded session exercising dim, strikethrough, blink and
+    /// styled/coloured underlines (colon and semicolon forms, across a
+    /// resize) renders identically on a client fed the keyframe.
+    #[test]
+    fn test_styled_attrs_golden_recording() {
+        let fixture =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/styled_attrs.vtr");
+        let recording = VtRecording::from_file(&fixture).unwrap();
+        let mut vt = recording.replay(64 * 1024);
+
+        let (rows, cols) = vt.debug_state().screen_size;
+        let mut client = vt100::Parser::new(rows, cols, 10_000);
+        client.process(&vt.replay(rows));
+
+        for row in 0..rows {
+            assert_eq!(
+                attr_runs(vt.screen(), row, cols),
+                attr_runs(client.screen(), row, cols),
+                "visible row {row}"
+            );
+        }
+
+        let depth = scrollback_depth(&mut client);
+        let mut all_lines = Vec::new();
+        for offset in (1..=depth).rev() {
+            client.screen_mut().set_scrollback(offset);
+            all_lines.push(attr_runs(client.screen(), 0, cols));
+        }
+        client.screen_mut().set_scrollback(0);
+        all_lines.extend((0..rows).map(|row| attr_runs(client.screen(), row, cols)));
+
+        insta::assert_snapshot!(all_lines.join("\n"));
+    }
 }
@@ -8,7 +8,8 @@
 //!
 //! The file is a sequence of self-delimiting CBOR values:
 //!
-//! 1. One `VtRecordingHeader` (initial terminal dimensions + scrollback config)
+//! 1. One `VtRecordingHeader` (initial terminal dimensions, scrollback config,
+//!    and the wall-clock start time that event timestamps are relative to)
 //! 2. Zero or more `VtEvent` values (output/input/resize with timestamps)
 //!
 //! Reading stops at EOF. A partial trailing CBOR value (from a crash) is
@@ -16,7 +17,7 @@
 
 use std::io::{self, Read, Write};
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
@@ -28,18 +29,26 @@ pub struct VtRecordingHeader {
     pub rows: u16,
     pub cols: u16,
     pub scrollback: u32,
+    /// Unix time (milliseconds) when recording started. Event `timestamp_us`
+    /// values are offsets from this instant. `None` for recordings written
+    /// before the field existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at_ms: Option<i64>,
 }
 
 /// A single recorded event.
+///
+/// Timestamps were `u32` in early recordings; CBOR integers are
+/// variable-width, so those files still decode into `u64`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VtEvent {
     /// PTY output bytes.
-    Output { timestamp_us: u32, data: Vec<u8> },
+    Output { timestamp_us: u64, data: Vec<u8> },
     /// Input sent to PTY.
-    Input { timestamp_us: u32, data: Vec<u8> },
+    Input { timestamp_us: u64, data: Vec<u8> },
     /// Terminal resize.
     Resize {
-        timestamp_us: u32,
+        timestamp_us: u64,
         rows: u16,
         cols: u16,
     },
@@ -56,9 +65,10 @@ pub struct VtRecorder<W: Write> {
 }
 
 impl VtRecorder<std::fs::File> {
-    /// Open a recording file, write the header, and return the recorder.
+    /// Create a recording file, write the header, and return the recorder.
+    /// Fails if the file exists: a recording is never overwritten.
     pub fn open(path: &Path, rows: u16, cols: u16, scrollback: u32) -> io::Result<Self> {
-        let f = std::fs::File::create(path)?;
+        let f = std::fs::File::create_new(path)?;
         Self::new(f, rows, cols, scrollback)
     }
 }
@@ -67,10 +77,15 @@ impl<W: Write> VtRecorder<W> {
     /// Create a recorder that streams to `writer`. Writes the header immediately.
     pub fn new(writer: W, rows: u16, cols: u16, scrollback: u32) -> io::Result<Self> {
         let mut w = io::BufWriter::new(writer);
+        let started_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .ok();
         let header = VtRecordingHeader {
             rows,
             cols,
             scrollback,
+            started_at_ms,
         };
         ciborium::into_writer(&header, &mut w).map_err(cbor_to_io)?;
         w.flush()?;
@@ -116,8 +131,8 @@ impl<W: Write> VtRecorder<W> {
             .map_err(|e| io::Error::other(e.to_string()))
     }
 
-    fn elapsed_us(&self) -> u32 {
-        self.start.elapsed().as_micros() as u32
+    fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
     }
 
     fn write_event(&mut self, event: &VtEvent) -> io::Result<()> {
@@ -167,6 +182,12 @@ impl VtRecording {
     ///
     /// `max_delta_bytes` controls the VT's auto-compaction threshold.
     pub fn replay(&self, max_delta_bytes: usize) -> VirtualTerminal {
+        self.replay_until(max_delta_bytes, u64::MAX)
+    }
+
+    /// Replay every event with `timestamp_us <= until_us`, returning the VT
+    /// state as it was at that moment.
+    pub fn replay_until(&self, max_delta_bytes: usize, until_us: u64) -> VirtualTerminal {
         let mut vt = VirtualTerminal::new(
             self.header.rows,
             self.header.cols,
@@ -174,7 +195,11 @@ impl VtRecording {
             self.header.scrollback as usize,
         );
 
-        for event in &self.events {
+        for event in self
+            .events
+            .iter()
+            .take_while(|e| e.timestamp_us() <= until_us)
+        {
             match event {
                 VtEvent::Output { data, .. } => {
                     vt.process_output(data);
@@ -190,13 +215,75 @@ impl VtRecording {
 
         vt
     }
+
+    /// Find seek points in the recording.
+    ///
+    /// A keyframe is emitted for the first output event, for every resize,
+    /// for the first output after `idle_gap_us` of silence (the start of a new
+    /// burst), and whenever `min_output_bytes` of output have accumulated
+    /// since the previous keyframe. Consumers replay up to a keyframe's
+    /// timestamp with [`replay_until`](Self::replay_until) to render it.
+    pub fn keyframes(&self, min_output_bytes: usize, idle_gap_us: u64) -> Vec<VtKeyframe> {
+        let mut keyframes: Vec<VtKeyframe> = Vec::new();
+        let mut bytes_since = 0usize;
+        let mut last_output_us: Option<u64> = None;
+
+        for (event_index, event) in self.events.iter().enumerate() {
+            let timestamp_us = event.timestamp_us();
+            let is_keyframe = match event {
+                VtEvent::Output { data, .. } => {
+                    bytes_since += data.len();
+                    let after_gap = last_output_us
+                        .is_none_or(|prev| timestamp_us.saturating_sub(prev) >= idle_gap_us);
+                    last_output_us = Some(timestamp_us);
+                    after_gap || bytes_since >= min_output_bytes
+                }
+                VtEvent::Resize { .. } => true,
+                VtEvent::Input { .. } => false,
+            };
+            if is_keyframe {
+                bytes_since = 0;
+                keyframes.push(VtKeyframe {
+                    event_index,
+                    timestamp_us,
+                });
+            }
+        }
+
+        keyframes
+    }
+
+    /// Timestamp of the last event, or 0 for an empty recording.
+    pub fn duration_us(&self) -> u64 {
+        self.events.last().map_or(0, VtEvent::timestamp_us)
+    }
+}
+
+/// A seek point in a recording (see [`VtRecording::keyframes`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VtKeyframe {
+    /// Index into `VtRecording::events`.
+    pub event_index: usize,
+    /// Offset from the start of the recording, in microseconds.
+    pub timestamp_us: u64,
+}
+
+impl VtEvent {
+    /// Offset of this event from the start of the recording, in microseconds.
+    pub fn timestamp_us(&self) -> u64 {
+        match self {
+            VtEvent::Output { timestamp_us, .. }
+            | VtEvent::Input { timestamp_us, .. }
+            | VtEvent::Resize { timestamp_us, .. } => *timestamp_us,
+        }
+    }
 }
 
-fn cbor_to_io<T: std::fmt::Debug>(e: ciborium::ser::Error<T>) -> io::Error {
+pub(crate) fn cbor_to_io<T: std::fmt::Debug>(e: ciborium::ser::Error<T>) -> io::Error {
     io::Error::other(format!("{e:?}"))
 }
 
-fn cbor_de_to_io<T: std::fmt::Debug>(e: ciborium::de::Error<T>) -> io::Error {
+pub(crate) fn cbor_de_to_io<T: std::fmt::Debug>(e: ciborium::de::Error<T>) -> io::Error {
     io::Error::other(format!("{e:?}"))
 }
 
@@ -218,9 +305,35 @@ mod tests {
         assert_eq!(parsed.header.rows, 24);
         assert_eq!(parsed.header.cols, 80);
         assert_eq!(parsed.header.scrollback, 10_000);
+        assert!(parsed.header.started_at_ms.is_some());
         assert!(parsed.events.is_empty());
     }
 
+    #[test]
+    fn header_without_start_time_parses() {
+        // Recordings written before `started_at_ms` existed have no such key.
+        #[derive(Serialize)]
+        struct LegacyHeader {
+            rows: u16,
+            cols: u16,
+            scrollback: u32,
+        }
+        let mut buf = Vec::new();
+        ciborium::into_writer(
+            &LegacyHeader {
+                rows: 24,
+                cols: 80,
+                scrollback: 0,
+            },
+            &mut buf,
+        )
+        .unwrap();
+
+        let parsed = VtRecording::parse(&buf[..]).unwrap();
+        assert_eq!(parsed.header.rows, 24);
+        assert!(parsed.header.started_at_ms.is_none());
+    }
+
     #[test]
     fn roundtrip_events() {
         let buf = record_to_bytes(|rec| {
@@ -311,6 +424,73 @@ mod tests {
         assert!(t1 >= t0);
     }
 
+    #[test]
+    fn replay_until_stops_at_timestamp() {
+        let recording = VtRecording {
+            header: VtRecordingHeader {
+                rows: 24,
+                cols: 80,
+                scrollback: 0,
+                started_at_ms: None,
+            },
+            events: vec![
+                VtEvent::Output {
+                    timestamp_us: 100,
+                    data: b"first".to_vec(),
+                },
+                VtEvent::Output {
+                    timestamp_us: 200,
+                    data: b" second".to_vec(),
+                },
+            ],
+        };
+
+        let vt = recording.replay_until(4096, 150);
+        let row = crate::read_row_text(vt.screen(), 0, 80);
+        assert_eq!(row, "first");
+
+        let vt = recording.replay_until(4096, 200);
+        let row = crate::read_row_text(vt.screen(), 0, 80);
+        assert_eq!(row, "first second");
+    }
+
+    #[test]
+    fn keyframes_on_gap_resize_and_volume() {
+        let out = |timestamp_us: u64, n: usize| VtEvent::Output {
+            timestamp_us,
+            data: vec![b'x'; n],
+        };
+        let recording = VtRecording {
+            header: VtRecordingHeader {
+                rows: 24,
+                cols: 80,
+                scrollback: 0,
+                started_at_ms: None,
+            },
+            events: vec![
+                out(0, 10),     // 0: first output
+                out(1_000, 10), // 1: same burst
+                VtEvent::Input {
+                    timestamp_us: 1_500,
+                    data: b"a".to_vec(),
+                }, // 2: input never a keyframe
+                out(5_000_000, 10), // 3: after idle gap
+                out(5_001_000, 200), // 4: volume threshold crossed
+                VtEvent::Resize {
+                    timestamp_us: 5_002_000,
+                    rows: 40,
+                    cols: 120,
+                }, // 5: resize
+            ],
+        };
+
+        let keyframes = recording.keyframes(100, 2_000_000);
+        let indices: Vec<usize> = keyframes.iter().map(|k| k.event_index).collect();
+        assert_eq!(indices, vec![0, 3, 4, 5]);
+        assert_eq!(keyframes[1].timestamp_us, 5_000_000);
+        assert_eq!(recording.duration_us(), 5_002_000);
+    }
+
     #[test]
     fn truncated_event_survives() {
         let mut buf = record_to_bytes(|rec| {
@@ -331,6 +511,7 @@ mod tests {
         let dir = std::env::temp_dir().join("vt_recorder_test");
         let _ = std::fs::create_dir_all(&dir);
         let path = dir.join("test.vtr");
+        let _ = std::fs::remove_file(&path);
 
         {
             let mut rec = VtRecorder::open(&path, 24, 80, 5000).unwrap();
@@ -354,6 +535,10 @@ mod tests {
             }
         ));
 
+        // An existing recording is never truncated
+        assert!(VtRecorder::open(&path, 24, 80, 5000).is_err());
+        assert_eq!(VtRecording::from_file(&path).unwrap().events.len(), 3);
+
         let _ = std::fs::remove_dir_all(&dir);
     }
 }
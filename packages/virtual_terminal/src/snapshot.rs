@@ -0,0 +1,132 @@
+//! Point-in-time VT snapshots for persisting scrollback across restarts.
+//!
+//! A snapshot captures everything a fresh `VirtualTerminal` needs to show
+//! what was on screen before: the replay stream (scrollback + visible
+//! screen, as produced by [`VirtualTerminal::replay`]), the dimensions it
+//! was rendered at, and a bounded tail of raw PTY output.
+//!
+//! Snapshots are stored as a single CBOR value. [`VtSnapshot::write_file`]
+//! writes to a temporary sibling and renames it into place so a crash
+//! mid-write never leaves a truncated snapshot behind.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::{cbor_de_to_io, cbor_to_io};
+
+/// Serialized VT state — see the module docs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VtSnapshot {
+    pub rows: u16,
+    pub cols: u16,
+    /// Scrollback capacity of the terminal the snapshot was taken from.
+    pub scrollback: u32,
+    /// Replay stream rendered for a `rows`-tall client.
+    pub screen: Vec<u8>,
+    /// Most recent raw PTY output, oldest byte first.
+    pub raw_tail: Vec<u8>,
+    /// Unix time (milliseconds) when the snapshot was taken.
+    pub saved_at_ms: i64,
+    /// Window title at snapshot time.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+impl VtSnapshot {
+    /// Encode the snapshot as CBOR into `writer`.
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        ciborium::into_writer(self, writer).map_err(cbor_to_io)
+    }
+
+    /// Decode a snapshot previously written with [`write_to`](Self::write_to).
+    pub fn read_from(reader: impl Read) -> io::Result<Self> {
+        ciborium::from_reader(reader).map_err(cbor_de_to_io)
+    }
+
+    /// Atomically write the snapshot to `path`.
+    pub fn write_file(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        {
+            let mut w = io::BufWriter::new(std::fs::File::create(&tmp)?);
+            self.write_to(&mut w)?;
+            w.flush()?;
+        }
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Read a snapshot from `path`.
+    pub fn read_file(path: &Path) -> io::Result<Self> {
+        let f = std::fs::File::open(path)?;
+        Self::read_from(io::BufReader::new(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VirtualTerminal;
+
+    #[test]
+    fn snapshot_restores_scrollback_and_screen() {
+        let mut vt = VirtualTerminal::new(5, 20, 4096, 100);
+        for i in 0..12 {
+            vt.process_output(format!("line {i}\r\n").as_bytes());
+        }
+        vt.process_output(b"$ ");
+        let expected = vt.lines();
+
+        let snap = vt.snapshot();
+        assert_eq!((snap.rows, snap.cols), (5, 20));
+        assert!(snap.raw_tail.ends_with(b"line 11\r\n$ "));
+
+        let mut restored = VirtualTerminal::new(24, 80, 4096, 100);
+        restored.restore(&snap);
+        assert_eq!(restored.effective_dims(), (5, 20));
+        assert_eq!(restored.lines(), expected);
+        assert_eq!(restored.cursor_position(), vt.cursor_position());
+    }
+
+    #[test]
+    fn snapshot_cbor_roundtrip() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(b"\x1b[1;31mred\x1b[0m plain");
+        let snap = vt.snapshot();
+
+        let mut buf = Vec::new();
+        snap.write_to(&mut buf).unwrap();
+        assert_eq!(VtSnapshot::read_from(&buf[..]).unwrap(), snap);
+    }
+
+    #[test]
+    fn snapshot_write_file_replaces_atomically() {
+        let dir = std::env::temp_dir().join(format!("vt-snap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.vts");
+
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 100);
+        vt.process_output(b"first");
+        vt.snapshot().write_file(&path).unwrap();
+        vt.process_output(b" second");
+        let snap = vt.snapshot();
+        snap.write_file(&path).unwrap();
+
+        assert_eq!(VtSnapshot::read_file(&path).unwrap(), snap);
+        assert!(!path.with_extension("tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn raw_tail_is_bounded() {
+        let mut vt = VirtualTerminal::new(24, 80, 4096, 0);
+        let chunk = vec![b'x'; 1000];
+        for _ in 0..(crate::RAW_TAIL_BYTES / 1000 + 10) {
+            vt.process_output(&chunk);
+        }
+        vt.process_output(b"end");
+        let snap = vt.snapshot();
+        assert!(snap.raw_tail.len() <= crate::RAW_TAIL_BYTES);
+        assert!(snap.raw_tail.ends_with(b"end"));
+    }
+}
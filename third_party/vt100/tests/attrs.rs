@@ -0,0 +1,138 @@
+// Attributes added by the fork: dim, strikethrough, blink, underline style
+// (SGR 4:n, 21) and underline colour (SGR 58/59).
+
+use vt100::{Color, UnderlineStyle};
+
+fn cell(input: &[u8]) -> vt100::Cell {
+    let mut parser = vt100::Parser::new(24, 80, 0);
+    parser.process(input);
+    parser.screen().cell(0, 0).unwrap().clone()
+}
+
+#[test]
+fn underline_styles() {
+    assert_eq!(cell(b"\x1b[4mx").underline_style(), UnderlineStyle::Single);
+    assert_eq!(
+        cell(b"\x1b[4:1mx").underline_style(),
+        UnderlineStyle::Single
+    );
+    assert_eq!(
+        cell(b"\x1b[4:2mx").underline_style(),
+        UnderlineStyle::Double
+    );
+    assert_eq!(cell(b"\x1b[4:3mx").underline_style(), UnderlineStyle::Curly);
+    assert_eq!(
+        cell(b"\x1b[4:4mx").underline_style(),
+        UnderlineStyle::Dotted
+    );
+    assert_eq!(
+        cell(b"\x1b[4:5mx").underline_style(),
+        UnderlineStyle::Dashed
+    );
+    assert_eq!(cell(b"\x1b[21mx").underline_style(), UnderlineStyle::Double);
+    assert!(cell(b"\x1b[4:3mx").underline());
+
+    assert_eq!(
+        cell(b"\x1b[4:3;4:0mx").underline_style(),
+        UnderlineStyle::None
+    );
+    assert_eq!(
+        cell(b"\x1b[21;24mx").underline_style(),
+        UnderlineStyle::None
+    );
+    assert!(!cell(b"\x1b[4;24mx").underline());
+
+    // unknown styles leave the current one alone
+    assert_eq!(
+        cell(b"\x1b[4:3;4:9mx").underline_style(),
+        UnderlineStyle::Curly
+    );
+}
+
+#[test]
+fn underline_colour() {
+    assert_eq!(cell(b"\x1b[58;5;196mx").underline_color(), Color::Idx(196));
+    assert_eq!(cell(b"\x1b[58:5:196mx").underline_color(), Color::Idx(196));
+    assert_eq!(
+        cell(b"\x1b[58;2;10;20;30mx").underline_color(),
+        Color::Rgb(10, 20, 30)
+    );
+    assert_eq!(
+        cell(b"\x1b[58:2:10:20:30mx").underline_color(),
+        Color::Rgb(10, 20, 30)
+    );
+    // colon form with the (ignored) colour space id
+    assert_eq!(
+        cell(b"\x1b[58:2::10:20:30mx").underline_color(),
+        Color::Rgb(10, 20, 30)
+    );
+    assert_eq!(
+        cell(b"\x1b[58;5;196;59mx").underline_color(),
+        Color::Default
+    );
+    assert_eq!(cell(b"\x1b[58;5;196;0mx").underline_color(), Color::Default);
+
+    // the underline colour is separate from the foreground
+    let c = cell(b"\x1b[31;58;5;2mx");
+    assert_eq!(c.fgcolor(), Color::Idx(1));
+    assert_eq!(c.underline_color(), Color::Idx(2));
+}
+
+#[test]
+fn strikethrough_blink_and_dim() {
+    assert!(cell(b"\x1b[9mx").strikethrough());
+    assert!(!cell(b"\x1b[9;29mx").strikethrough());
+
+    assert!(cell(b"\x1b[5mx").blink());
+    assert!(cell(b"\x1b[6mx").blink());
+    assert!(!cell(b"\x1b[5;25mx").blink());
+
+    let c = cell(b"\x1b[2mx");
+    assert!(c.dim());
+    assert!(!c.bold());
+    // bold and dim share the intensity: the last one wins, 22 clears both
+    assert!(cell(b"\x1b[2;1mx").bold());
+    assert!(!cell(b"\x1b[2;1mx").dim());
+    let c = cell(b"\x1b[1;2;22mx");
+    assert!(!c.bold() && !c.dim());
+
+    let c = cell(b"\x1b[9;5;4:3;58;5;1;0mx");
+    assert!(!c.strikethrough() && !c.blink() && !c.underline());
+    assert_eq!(c.underline_color(), Color::Default);
+}
+
+#[test]
+fn formatted_output_round_trips() {
+    let mut parser = vt100::Parser::new(24, 80, 0);
+    parser.process(
+        b"\x1b[2;9mA\x1b[0;5mB\x1b[0;4:3;58:2::1:2:3mC\x1b[0;21;58;5;9mD\x1b[0mE",
+    );
+    let formatted = parser.screen().contents_formatted();
+
+    let mut replayed = vt100::Parser::new(24, 80, 0);
+    replayed.process(&formatted);
+    for col in 0..5 {
+        assert_eq!(
+            parser.screen().cell(0, col),
+            replayed.screen().cell(0, col),
+            "column {col}"
+        );
+    }
+    assert_eq!(
+        replayed.screen().cell(0, 2).unwrap().underline_color(),
+        Color::Rgb(1, 2, 3)
+    );
+}
+
+#[test]
+fn combining_marks_keep_upstream_capacity() {
+    // a base character and eight combining marks: 17 bytes
+    let cluster = "e\u{301}\u{302}\u{303}\u{304}\u{305}\u{306}\u{307}\u{308}";
+    assert_eq!(cell(cluster.as_bytes()).contents(), cluster);
+
+    // past 22 bytes further marks are dropped, as upstream
+    let long = format!("{cluster}\u{309}\u{30a}\u{30b}");
+    let kept = cell(long.as_bytes()).contents().to_string();
+    assert_eq!(kept, format!("{cluster}\u{309}"));
+    assert!(kept.len() <= 22);
+}